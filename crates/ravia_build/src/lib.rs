@@ -1,29 +1,74 @@
-use std::{fs, path::Path};
-
+use std::{
+    fs,
+    hash::{DefaultHasher, Hash, Hasher},
+    io::Write,
+    path::{Path, PathBuf},
+};
+
+use flate2::{write::GzEncoder, Compression};
 use fs_extra::dir::CopyOptions;
 
+mod mesh;
+mod texture;
+
+pub use mesh::MeshOptimization;
+
 /// Build result type.
 pub type Result<T> = anyhow::Result<T>;
 
 /// Build error type.
 pub type Error = anyhow::Error;
 
+/// Build configuration.
+#[derive(Debug, Clone, Default)]
+pub struct BuildConfig {
+    /// If set, resources larger than this many bytes are gzip-compressed during the copy step.
+    ///
+    /// The engine decompresses them transparently, so they are still requested by their
+    /// original path.
+    pub compress_threshold: Option<u64>,
+
+    /// If true, OBJ meshes are converted to the engine binary mesh format during the copy step.
+    ///
+    /// The converted mesh is written next to the original with the `rmesh` extension, e.g.
+    /// `model/cube.obj` becomes `model/cube.rmesh`, which loads much faster than parsing OBJ text.
+    pub convert_meshes: bool,
+
+    /// If set, converted meshes are optimized for the vertex cache, overdraw and vertex fetch,
+    /// and optionally simplified. Statistics before and after are reported as cargo warnings, so
+    /// that they are shown in the build log.
+    pub optimize_meshes: Option<MeshOptimization>,
+
+    /// If true, PNG and JPEG images are converted to the engine binary texture format, with their
+    /// full mip chain generated, during the copy step.
+    ///
+    /// The converted texture is written next to the original with the `rtex` extension, e.g.
+    /// `texture/grass.png` becomes `texture/grass.rtex`.
+    pub convert_textures: bool,
+}
+
 /// Build the project.
 pub fn build() -> Result<()> {
-    copy_resources()?;
+    build_with_config(BuildConfig::default())
+}
+
+/// Build the project with the given configuration.
+pub fn build_with_config(config: BuildConfig) -> Result<()> {
+    copy_resources(&config)?;
     set_log_level()?;
 
     Ok(())
 }
 
 /// Copy resources from the engine and user directories to the output directory.
-fn copy_resources() -> Result<()> {
+fn copy_resources(config: &BuildConfig) -> Result<()> {
     println!("cargo::rerun-if-env-changed=CARGO_MANIFEST_DIR");
     println!("cargo::rerun-if-env-changed=PROFILE");
 
     let target_arch = std::env::var("CARGO_CFG_TARGET_ARCH")?;
     let working_dir = std::env::var("CARGO_MANIFEST_DIR")?;
-    let default_engine_res_dir = Path::new(&working_dir).join("../../crates/ravia_res");
+    // engine resources live next to this crate, wherever the project being built is.
+    let default_engine_res_dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("../ravia_res");
     let default_user_res_dir = Path::new(&working_dir).join("res");
 
     println!(
@@ -70,6 +115,17 @@ fn copy_resources() -> Result<()> {
         )?;
     }
 
+    if config.convert_meshes {
+        convert_meshes(&assets_out_dir, config.optimize_meshes)?;
+    }
+    if config.convert_textures {
+        convert_textures(&assets_out_dir)?;
+    }
+    if let Some(threshold) = config.compress_threshold {
+        compress_resources(&assets_out_dir, threshold)?;
+    }
+    write_manifest(&assets_out_dir)?;
+
     println!(
         "cargo:rustc-env=RAVIA_RES={}",
         assets_out_dir.to_string_lossy()
@@ -78,6 +134,99 @@ fn copy_resources() -> Result<()> {
     Ok(())
 }
 
+/// Convert every OBJ mesh to the engine binary mesh format.
+fn convert_meshes(assets_out_dir: &Path, optimization: Option<MeshOptimization>) -> Result<()> {
+    for path in resource_files(assets_out_dir)? {
+        if path.extension().and_then(|ext| ext.to_str()) != Some("obj") {
+            continue;
+        }
+
+        let name = path.strip_prefix(assets_out_dir)?.to_string_lossy();
+        let binary = mesh::convert_obj(&name, &fs::read(&path)?, optimization)?;
+        fs::write(path.with_extension(mesh::BINARY_MESH_EXTENSION), binary)?;
+    }
+
+    Ok(())
+}
+
+/// Convert every image to the engine binary texture format.
+fn convert_textures(assets_out_dir: &Path) -> Result<()> {
+    for path in resource_files(assets_out_dir)? {
+        let is_image = path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .is_some_and(|ext| texture::IMAGE_EXTENSIONS.contains(&ext.to_lowercase().as_str()));
+        if !is_image {
+            continue;
+        }
+
+        let binary = texture::convert_image(&fs::read(&path)?)?;
+        fs::write(
+            path.with_extension(texture::BINARY_TEXTURE_EXTENSION),
+            binary,
+        )?;
+    }
+
+    Ok(())
+}
+
+/// Gzip-compress every resource larger than `threshold` bytes, replacing the original file.
+fn compress_resources(assets_out_dir: &Path, threshold: u64) -> Result<()> {
+    for path in resource_files(assets_out_dir)? {
+        let is_compressed = matches!(
+            path.extension().and_then(|ext| ext.to_str()),
+            Some("gz" | "zst")
+        );
+        if is_compressed || fs::metadata(&path)?.len() <= threshold {
+            continue;
+        }
+
+        let mut compressed_path = path.clone().into_os_string();
+        compressed_path.push(".gz");
+
+        let mut encoder = GzEncoder::new(fs::File::create(&compressed_path)?, Compression::best());
+        encoder.write_all(&fs::read(&path)?)?;
+        encoder.finish()?;
+        fs::remove_file(&path)?;
+    }
+
+    Ok(())
+}
+
+/// List every resource file in the output directory, in a stable order.
+fn resource_files(assets_out_dir: &Path) -> Result<Vec<PathBuf>> {
+    let pattern = assets_out_dir.join("**/*");
+    let mut paths = glob::glob(&pattern.to_string_lossy())?
+        .filter_map(|entry| entry.ok())
+        .filter(|path| path.is_file() && !path.ends_with(MANIFEST_FILE))
+        .collect::<Vec<_>>();
+    paths.sort();
+
+    Ok(paths)
+}
+
+/// Name of the manifest file written at the root of the resource output directory.
+const MANIFEST_FILE: &str = "manifest";
+
+/// Write a manifest containing a hash of every copied resource.
+///
+/// The web runtime keys its resource cache by this hash, so that stale resources are never
+/// served after a rebuild.
+fn write_manifest(assets_out_dir: &Path) -> Result<()> {
+    let mut hasher = DefaultHasher::new();
+    for path in resource_files(assets_out_dir)? {
+        path.strip_prefix(assets_out_dir)?.hash(&mut hasher);
+        fs::read(&path)?.hash(&mut hasher);
+    }
+
+    fs::write(
+        assets_out_dir.join(MANIFEST_FILE),
+        format!("{:016x}", hasher.finish()),
+    )?;
+
+    Ok(())
+}
+
 /// Set the log level based on the build profile.
 fn set_log_level() -> Result<()> {
     println!("cargo::rerun-if-env-changed=PROFILE");
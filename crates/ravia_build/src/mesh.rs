@@ -0,0 +1,200 @@
+use std::io::BufReader;
+
+use crate::Result;
+
+/// File extension of binary meshes, matching `ravia_engine::graphics::BINARY_MESH_EXTENSION`.
+pub const BINARY_MESH_EXTENSION: &str = "rmesh";
+
+const MAGIC: &[u8; 4] = b"RMSH";
+const VERSION: u32 = 1;
+
+/// Vertex layouts of the binary mesh format, matching the engine vertex types.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum VertexLayout {
+    /// `Vertex3DStandard`: position, uv, normal.
+    Standard = 0,
+    /// `Vertex3DStandardColored`: position, uv, normal, color.
+    StandardColored = 1,
+}
+
+impl VertexLayout {
+    fn vertex_size(self) -> usize {
+        match self {
+            Self::Standard => 32,
+            Self::StandardColored => 44,
+        }
+    }
+}
+
+/// Convert an OBJ-formatted buffer to the engine binary mesh format, optimizing it if requested.
+///
+/// Like the engine OBJ loader, only the first model is converted, and vertex colors are kept if
+/// present. `name` is only used for the build log.
+pub fn convert_obj(
+    name: &str,
+    data: &[u8],
+    optimization: Option<MeshOptimization>,
+) -> Result<Vec<u8>> {
+    let mut buf = BufReader::new(data);
+    let (models, _) = tobj::load_obj_buf(
+        &mut buf,
+        &tobj::LoadOptions {
+            single_index: true,
+            triangulate: true,
+            ..Default::default()
+        },
+        |_| Err(tobj::LoadError::GenericFailure),
+    )?;
+
+    let Some(model) = models.first() else {
+        anyhow::bail!("No models found in the OBJ file");
+    };
+    let mesh = &model.mesh;
+    let num_vertices = mesh.positions.len() / 3;
+
+    let layout = if mesh.vertex_color.is_empty() {
+        VertexLayout::Standard
+    } else {
+        VertexLayout::StandardColored
+    };
+
+    let attribute = |values: &[f32], i: usize, n: usize| -> Vec<f32> {
+        values
+            .get(n * i..n * i + n)
+            .map(|v| v.to_vec())
+            .unwrap_or_else(|| vec![0.0; n])
+    };
+
+    let mut vertices = Vec::new();
+    for i in 0..num_vertices {
+        let mut vertex = attribute(&mesh.positions, i, 3);
+        vertex.extend(attribute(&mesh.texcoords, i, 2));
+        vertex.extend(attribute(&mesh.normals, i, 3));
+        if layout == VertexLayout::StandardColored {
+            vertex.extend(attribute(&mesh.vertex_color, i, 3));
+        }
+
+        for value in vertex {
+            vertices.extend_from_slice(&value.to_le_bytes());
+        }
+    }
+    let mut indices = mesh.indices.clone();
+
+    if let Some(optimization) = optimization {
+        (vertices, indices) = optimize(
+            name,
+            &vertices,
+            layout.vertex_size(),
+            &indices,
+            optimization,
+        )?;
+    }
+
+    let mut out = Vec::new();
+    out.extend_from_slice(MAGIC);
+    out.extend_from_slice(&VERSION.to_le_bytes());
+    out.extend_from_slice(&(layout as u32).to_le_bytes());
+    out.extend_from_slice(&((vertices.len() / layout.vertex_size()) as u32).to_le_bytes());
+    out.extend_from_slice(&(indices.len() as u32).to_le_bytes());
+    out.extend_from_slice(&vertices);
+    for index in &indices {
+        out.extend_from_slice(&index.to_le_bytes());
+    }
+
+    Ok(out)
+}
+
+/// Optimization applied to converted meshes.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MeshOptimization {
+    /// If set, meshes are simplified to roughly this fraction of their triangles, e.g. `0.5`.
+    pub simplify_ratio: Option<f32>,
+}
+
+/// Statistics of a mesh, printed before and after optimization.
+struct MeshStats {
+    triangles: usize,
+    vertices: usize,
+    acmr: f32,
+    overfetch: f32,
+    overdraw: f32,
+}
+
+impl MeshStats {
+    fn analyze(vertices: &[u8], stride: usize, indices: &[u32]) -> Result<Self> {
+        let vertex_count = vertices.len() / stride;
+        let adapter = meshopt::VertexDataAdapter::new(vertices, stride, 0)?;
+        Ok(Self {
+            triangles: indices.len() / 3,
+            vertices: vertex_count,
+            acmr: meshopt::analyze_vertex_cache(indices, vertex_count, 16, 0, 0).acmr,
+            overfetch: meshopt::analyze_vertex_fetch(indices, vertex_count, stride).overfetch,
+            overdraw: meshopt::analyze_overdraw(indices, &adapter).overdraw,
+        })
+    }
+}
+
+impl std::fmt::Display for MeshStats {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} triangles, {} vertices, acmr {:.3}, overfetch {:.3}, overdraw {:.3}",
+            self.triangles, self.vertices, self.acmr, self.overfetch, self.overdraw
+        )
+    }
+}
+
+/// Optimize a mesh for the vertex cache, overdraw and vertex fetch, optionally simplifying it
+/// first. Positions are expected in the first 12 bytes of each vertex.
+fn optimize(
+    name: &str,
+    vertices: &[u8],
+    stride: usize,
+    indices: &[u32],
+    optimization: MeshOptimization,
+) -> Result<(Vec<u8>, Vec<u32>)> {
+    let vertex_count = vertices.len() / stride;
+    let adapter = meshopt::VertexDataAdapter::new(vertices, stride, 0)?;
+    let before = MeshStats::analyze(vertices, stride, indices)?;
+
+    let mut indices = match optimization.simplify_ratio {
+        Some(ratio) => {
+            let target_count = (indices.len() as f32 * ratio) as usize / 3 * 3;
+            meshopt::simplify(
+                indices,
+                &adapter,
+                target_count,
+                f32::MAX,
+                meshopt::SimplifyOptions::None,
+                None,
+            )
+        }
+        None => indices.to_vec(),
+    };
+
+    meshopt::optimize_vertex_cache_in_place(&mut indices, vertex_count);
+    meshopt::optimize_overdraw_in_place(&mut indices, &adapter, 1.05);
+
+    // reorder vertices in the order they are first referenced, dropping unused ones.
+    let remap = meshopt::optimize_vertex_fetch_remap(&indices, vertex_count);
+    let unique_vertices = remap.iter().filter(|&&index| index != u32::MAX).count();
+    let mut optimized_vertices = vec![0; unique_vertices * stride];
+    for (old, &new) in remap.iter().enumerate() {
+        if new != u32::MAX {
+            let new = new as usize;
+            optimized_vertices[new * stride..(new + 1) * stride]
+                .copy_from_slice(&vertices[old * stride..(old + 1) * stride]);
+        }
+    }
+    for index in indices.iter_mut() {
+        *index = remap[*index as usize];
+    }
+
+    let after = MeshStats::analyze(&optimized_vertices, stride, &indices)?;
+    // cargo only shows the warnings of build scripts in the build log, not their output.
+    println!("cargo::warning=optimized mesh {}", name);
+    println!("cargo::warning=  before: {}", before);
+    println!("cargo::warning=  after:  {}", after);
+
+    Ok((optimized_vertices, indices))
+}
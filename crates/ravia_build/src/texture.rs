@@ -0,0 +1,54 @@
+use image::{imageops::FilterType, RgbaImage};
+
+use crate::Result;
+
+/// File extension of binary textures, matching `ravia_engine::graphics::BINARY_TEXTURE_EXTENSION`.
+pub const BINARY_TEXTURE_EXTENSION: &str = "rtex";
+
+/// Extensions of the image files converted to binary textures.
+pub const IMAGE_EXTENSIONS: &[&str] = &["png", "jpg", "jpeg"];
+
+const MAGIC: &[u8; 4] = b"RTEX";
+const VERSION: u32 = 1;
+
+/// Convert an image to the engine binary texture format, with its full mip chain.
+///
+/// The texture is stored as RGBA8, with every mip level down to 1x1 generated by downsampling the
+/// previous level.
+pub fn convert_image(data: &[u8]) -> Result<Vec<u8>> {
+    let image = image::load_from_memory(data)?.to_rgba8();
+    let mips = mip_chain(image);
+
+    let mut out = Vec::new();
+    out.extend_from_slice(MAGIC);
+    out.extend_from_slice(&VERSION.to_le_bytes());
+    out.extend_from_slice(&mips[0].width().to_le_bytes());
+    out.extend_from_slice(&mips[0].height().to_le_bytes());
+    out.extend_from_slice(&(mips.len() as u32).to_le_bytes());
+    for mip in mips {
+        out.extend_from_slice(mip.as_raw());
+    }
+
+    Ok(out)
+}
+
+/// Generate the mip chain of an image, starting with the image itself.
+fn mip_chain(image: RgbaImage) -> Vec<RgbaImage> {
+    let mut mips = vec![image];
+    loop {
+        let last = mips.last().unwrap();
+        if last.width() == 1 && last.height() == 1 {
+            break;
+        }
+
+        let width = (last.width() / 2).max(1);
+        let height = (last.height() / 2).max(1);
+        mips.push(image::imageops::resize(
+            last,
+            width,
+            height,
+            FilterType::Triangle,
+        ));
+    }
+    mips
+}
@@ -0,0 +1,120 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{anyhow, bail, Context};
+
+/// CLI result type.
+type Result<T> = anyhow::Result<T>;
+
+const USAGE: &str = "\
+Usage: ravia <command>
+
+Commands:
+  new <name> [--engine-path <path>]  Create a new project in the directory <name>
+";
+
+/// Files of a new project, as (path, template) pairs.
+const TEMPLATES: &[(&str, &str)] = &[
+    (
+        "Cargo.toml",
+        include_str!("../templates/Cargo.toml.template"),
+    ),
+    ("build.rs", include_str!("../templates/build.rs.template")),
+    (
+        "index.html",
+        include_str!("../templates/index.html.template"),
+    ),
+    (
+        ".gitignore",
+        include_str!("../templates/gitignore.template"),
+    ),
+    ("src/lib.rs", include_str!("../templates/lib.rs.template")),
+    ("src/main.rs", include_str!("../templates/main.rs.template")),
+    (
+        "src/triangle.wgsl",
+        include_str!("../templates/triangle.wgsl.template"),
+    ),
+    ("res/.gitkeep", ""),
+];
+
+fn main() {
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+    if let Err(e) = run(&args) {
+        eprintln!("error: {:#}", e);
+        std::process::exit(1);
+    }
+}
+
+fn run(args: &[String]) -> Result<()> {
+    match args.first().map(String::as_str) {
+        Some("new") => new_project(&args[1..]),
+        Some("help" | "--help" | "-h") | None => {
+            print!("{}", USAGE);
+            Ok(())
+        }
+        Some(command) => bail!("unknown command `{}`\n\n{}", command, USAGE),
+    }
+}
+
+/// Generates a new project from the templates.
+fn new_project(args: &[String]) -> Result<()> {
+    let mut name = None;
+    // by default, the project depends on the engine this CLI was built from.
+    let mut engine_path = Path::new(env!("CARGO_MANIFEST_DIR")).join("../..");
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--engine-path" => {
+                let path = args
+                    .next()
+                    .ok_or_else(|| anyhow!("--engine-path requires a value"))?;
+                engine_path = PathBuf::from(path);
+            }
+            _ if name.is_none() => name = Some(arg.as_str()),
+            _ => bail!("unexpected argument `{}`\n\n{}", arg, USAGE),
+        }
+    }
+
+    let name = name.ok_or_else(|| anyhow!("missing project name\n\n{}", USAGE))?;
+    let is_valid_name = !name.is_empty()
+        && !name.starts_with(|c: char| c.is_ascii_digit())
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-');
+    if !is_valid_name {
+        bail!("`{}` is not a valid crate name", name);
+    }
+
+    let project_dir = Path::new(name);
+    if project_dir.exists() {
+        bail!("destination `{}` already exists", project_dir.display());
+    }
+
+    let engine_path = fs::canonicalize(&engine_path)
+        .with_context(|| format!("engine not found at `{}`", engine_path.display()))?;
+    let crate_name = name.replace('-', "_");
+
+    for (path, template) in TEMPLATES {
+        let contents = template
+            .replace("{{name}}", name)
+            .replace("{{crate_name}}", &crate_name)
+            .replace("{{engine_path}}", &engine_path.to_string_lossy());
+
+        let path = project_dir.join(path);
+        fs::create_dir_all(path.parent().unwrap())?;
+        fs::write(&path, contents)
+            .with_context(|| format!("failed to write {}", path.display()))?;
+    }
+
+    println!("Created project `{}`", name);
+    println!();
+    println!("Run it natively with:");
+    println!("  cd {} && cargo run", name);
+    println!("Or build it for the web with:");
+    println!("  cd {} && wasm-pack build --target web", name);
+
+    Ok(())
+}
@@ -0,0 +1,213 @@
+//! Renderer benchmarks, running on a headless [`Gpu`].
+//!
+//! Run with `cargo bench -p ravia_engine`. The benchmarks are skipped if no GPU adapter is
+//! available.
+
+use std::sync::Arc;
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use ravia_engine::prelude::*;
+
+const SIZE: UVec2 = UVec2::new(1280, 720);
+
+fn context() -> Option<EngineContext> {
+    match pollster::block_on(Gpu::new_headless(SIZE, GpuConfig::default())) {
+        Ok(gpu) => Some(EngineContext::new(
+            Arc::new(gpu),
+            Arc::new(ResourceManager::new()),
+            ScreenSize {
+                size: SIZE,
+                scale_factor: 1.0,
+            },
+        )),
+        Err(e) => {
+            eprintln!("skipping renderer benchmarks: {}", e);
+            None
+        }
+    }
+}
+
+fn transform_flush(c: &mut Criterion, ctx: &EngineContext) {
+    const ENTITIES: usize = 10_000;
+
+    let mut transforms = (0..ENTITIES)
+        .map(|_| Transform::identity(ctx))
+        .collect::<Vec<_>>();
+
+    c.bench_function("transform_flush_10k", |b| {
+        let mut frame = 0.0;
+        b.iter(|| {
+            frame += 1.0;
+            for (i, transform) in transforms.iter_mut().enumerate() {
+                transform.set_position(vec3(i as f32, frame, 0.0));
+                transform.flush(ctx);
+            }
+            ctx.gpu.queue.submit([]);
+            ctx.gpu.device.poll(wgpu::Maintain::Wait);
+        });
+    });
+}
+
+/// Model matrix of an instance.
+#[repr(C)]
+#[derive(Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+struct Instance {
+    model: Mat4,
+}
+
+impl Vertex for Instance {
+    const ATTRIBUTE_FORMATS: &[wgpu::VertexFormat] = &[
+        wgpu::VertexFormat::Float32x4,
+        wgpu::VertexFormat::Float32x4,
+        wgpu::VertexFormat::Float32x4,
+        wgpu::VertexFormat::Float32x4,
+    ];
+}
+
+const INSTANCED_SHADER: &str = "
+struct CameraUniform {
+  projection: mat4x4<f32>,
+};
+
+struct TransformUniform {
+  transform: mat4x4<f32>,
+  transform_inv: mat4x4<f32>,
+};
+
+@group(0) @binding(0) var<uniform> camera: CameraUniform;
+@group(1) @binding(0) var<uniform> camera_transform: TransformUniform;
+
+struct VertexInput {
+  @location(0) position: vec3<f32>,
+  @location(1) uv: vec2<f32>,
+  @location(2) normal: vec3<f32>,
+  @location(3) model_0: vec4<f32>,
+  @location(4) model_1: vec4<f32>,
+  @location(5) model_2: vec4<f32>,
+  @location(6) model_3: vec4<f32>,
+};
+
+struct VertexOutput {
+  @builtin(position) position: vec4<f32>,
+  @location(0) normal: vec3<f32>,
+};
+
+@vertex
+fn vs_main(in: VertexInput) -> VertexOutput {
+  let model = mat4x4<f32>(in.model_0, in.model_1, in.model_2, in.model_3);
+  var out: VertexOutput;
+  out.position = camera.projection * camera_transform.transform_inv * model * vec4<f32>(in.position, 1.0);
+  out.normal = in.normal;
+  return out;
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+  return vec4<f32>(in.normal * 0.5 + 0.5, 1.0);
+}
+";
+
+fn cube(ctx: &EngineContext) -> Mesh {
+    let mut vertices = vec![];
+    let mut indices = vec![];
+    for normal in [
+        Vec3::X,
+        Vec3::NEG_X,
+        Vec3::Y,
+        Vec3::NEG_Y,
+        Vec3::Z,
+        Vec3::NEG_Z,
+    ] {
+        let tangent = normal.any_orthonormal_vector();
+        let bitangent = normal.cross(tangent);
+        let base = vertices.len() as u32;
+        for (u, v) in [(0.0, 0.0), (1.0, 0.0), (1.0, 1.0), (0.0, 1.0)] {
+            vertices.push(Vertex3DStandard {
+                position: (normal + tangent * (u * 2.0 - 1.0) + bitangent * (v * 2.0 - 1.0)) * 0.5,
+                data: VertexStandardData {
+                    uv: vec2(u, v),
+                    normal,
+                },
+            });
+        }
+        indices.extend([base, base + 1, base + 2, base, base + 2, base + 3]);
+    }
+    Mesh::new_indexed(ctx, &vertices, &indices)
+}
+
+fn instanced_cubes(c: &mut Criterion, ctx: &EngineContext) {
+    const SIDE: usize = 100;
+
+    let instances = (0..SIDE * SIDE)
+        .map(|i| Instance {
+            model: Mat4::from_translation(vec3(
+                (i % SIDE) as f32 * 2.0 - SIDE as f32,
+                (i / SIDE) as f32 * 2.0 - SIDE as f32,
+                0.0,
+            )),
+        })
+        .collect::<Vec<_>>();
+
+    let mut mesh = cube(ctx);
+    mesh.add_vertex_buffer(ctx, &instances, wgpu::VertexStepMode::Instance);
+
+    let vertex_buffers = [VertexBufferLayout::per_instance::<Instance>()];
+    let material = Material::new(
+        ctx,
+        &ShaderConfig::new(INSTANCED_SHADER)
+            .with_label("instanced_cubes")
+            .with_vertex_type::<Vertex3DStandard>()
+            .with_vertex_buffers(&vertex_buffers)
+            .with_uniforms(&[UniformType::Camera, UniformType::CameraTransform]),
+    );
+
+    let mut world = World::default();
+    world.push((
+        Camera::perspective(ctx, 1.0, SIZE.x as f32 / SIZE.y as f32, 0.1, 1000.0),
+        Transform::new(ctx, vec3(0.0, 0.0, 150.0), Quat::IDENTITY, Vec3::ONE),
+    ));
+    world.push((mesh, material, Transform::identity(ctx)));
+
+    for transform in <&mut Transform>::query().iter_mut(&mut world) {
+        transform.flush(ctx);
+    }
+
+    c.bench_function("instanced_cubes_10k", |b| {
+        b.iter(|| {
+            ctx.gpu.render(&world, &Time::ZERO);
+            ctx.gpu.device.poll(wgpu::Maintain::Wait);
+        });
+    });
+}
+
+fn texture_upload(c: &mut Criterion, ctx: &EngineContext) {
+    const TEXTURE_SIZE: u32 = 4096;
+
+    let data = vec![128u8; (TEXTURE_SIZE * TEXTURE_SIZE * 4) as usize];
+
+    c.bench_function("texture_upload_4096", |b| {
+        b.iter(|| {
+            let texture = Texture::new_2d(
+                ctx,
+                uvec2(TEXTURE_SIZE, TEXTURE_SIZE),
+                data.as_slice(),
+                TextureFilterMode::Bilinear,
+            );
+            ctx.gpu.device.poll(wgpu::Maintain::Wait);
+            texture
+        });
+    });
+}
+
+fn renderer(c: &mut Criterion) {
+    let Some(ctx) = context() else {
+        return;
+    };
+
+    transform_flush(c, &ctx);
+    instanced_cubes(c, &ctx);
+    texture_upload(c, &ctx);
+}
+
+criterion_group!(benches, renderer);
+criterion_main!(benches);
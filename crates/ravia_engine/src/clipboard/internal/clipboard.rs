@@ -0,0 +1,109 @@
+#[cfg(not(target_arch = "wasm32"))]
+use std::sync::Mutex;
+#[cfg(target_arch = "wasm32")]
+use std::sync::{Arc, Mutex};
+
+use crate::ecs;
+
+/// [`Clipboard`] provides access to the system clipboard.
+///
+/// In web platforms, the clipboard can only be read asynchronously through the Clipboard API.
+/// Reading the clipboard requests its contents in the background and returns the contents
+/// fetched by the previous request.
+pub struct Clipboard {
+    #[cfg(not(target_arch = "wasm32"))]
+    inner: Mutex<Option<arboard::Clipboard>>,
+
+    #[cfg(target_arch = "wasm32")]
+    contents: Arc<Mutex<Option<String>>>,
+}
+
+assert_impl_all!(Clipboard: ecs::systems::Resource);
+
+impl std::fmt::Debug for Clipboard {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Clipboard")
+    }
+}
+
+impl Default for Clipboard {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Clipboard {
+    /// Creates a new [`Clipboard`].
+    ///
+    /// If the system clipboard is not available, reading and writing the clipboard is a no-op.
+    pub fn new() -> Self {
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            let inner = match arboard::Clipboard::new() {
+                Ok(clipboard) => Some(clipboard),
+                Err(e) => {
+                    log::warn!(target: "ravia_engine::clipboard", "Clipboard not available: {}", e);
+                    None
+                }
+            };
+
+            Self {
+                inner: Mutex::new(inner),
+            }
+        }
+        #[cfg(target_arch = "wasm32")]
+        {
+            Self {
+                contents: Arc::new(Mutex::new(None)),
+            }
+        }
+    }
+
+    /// Returns the text contents of the clipboard.
+    pub fn text(&self) -> Option<String> {
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            let mut inner = self.inner.lock().unwrap();
+            inner.as_mut()?.get_text().ok()
+        }
+        #[cfg(target_arch = "wasm32")]
+        {
+            let clipboard = web_sys::window()?.navigator().clipboard();
+            let contents = self.contents.clone();
+            wasm_bindgen_futures::spawn_local(async move {
+                let text = wasm_bindgen_futures::JsFuture::from(clipboard.read_text()).await;
+                if let Some(text) = text.ok().and_then(|text| text.as_string()) {
+                    *contents.lock().unwrap() = Some(text);
+                }
+            });
+
+            self.contents.lock().unwrap().clone()
+        }
+    }
+
+    /// Sets the text contents of the clipboard.
+    pub fn set_text(&self, text: &str) {
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            let mut inner = self.inner.lock().unwrap();
+            if let Some(clipboard) = inner.as_mut() {
+                if let Err(e) = clipboard.set_text(text) {
+                    log::warn!(target: "ravia_engine::clipboard", "Failed to write clipboard: {}", e);
+                }
+            }
+        }
+        #[cfg(target_arch = "wasm32")]
+        {
+            *self.contents.lock().unwrap() = Some(text.to_string());
+
+            if let Some(window) = web_sys::window() {
+                let promise = window.navigator().clipboard().write_text(text);
+                wasm_bindgen_futures::spawn_local(async move {
+                    if wasm_bindgen_futures::JsFuture::from(promise).await.is_err() {
+                        log::warn!(target: "ravia_engine::clipboard", "Failed to write clipboard");
+                    }
+                });
+            }
+        }
+    }
+}
@@ -1,51 +1,194 @@
 use core::fmt;
-use std::{future::Future, sync::Arc};
+use std::{
+    future::Future,
+    sync::{Arc, Mutex, RwLock, RwLockReadGuard, RwLockWriteGuard},
+    time::Duration,
+};
+
+#[cfg(not(target_arch = "wasm32"))]
+use std::time::Instant;
+#[cfg(target_arch = "wasm32")]
+use web_time::Instant;
 
-use log::{debug, info, trace};
+use log::{debug, error, info, trace, warn};
 use winit::{
     application::ApplicationHandler,
     dpi::LogicalSize,
-    event::WindowEvent,
+    event::{DeviceEvent, DeviceId, Ime, WindowEvent},
     event_loop::{ActiveEventLoop, ControlFlow, EventLoop, EventLoopProxy},
-    window::Window,
+    window::{CursorGrabMode, Window},
 };
 
-use crate::{ecs, graphics, math, resource, time};
+use crate::{
+    clipboard, ecs, event, graphics, input, math, nav, net, resource, spatial, streaming, time, ui,
+    window,
+};
+
+/// Interval between the updates of the frame rate in the window title, see
+/// [`EngineConfig::title_stats`].
+pub const TITLE_STATS_INTERVAL: Duration = Duration::from_millis(500);
 
 /// World initializer.
 pub type InitWorld = fn(&mut ecs::World, &EngineContext);
 
 /// User system initializer.
+///
+/// In native mode, the systems run in parallel unless they access the same components or
+/// resources mutably. Use [`ecs::systems::Builder::flush`] to add a barrier between the systems,
+/// which also applies their command buffers.
 pub type InitSystem = fn(&mut ecs::systems::Builder);
 
+/// Error handler.
+pub type OnError = fn(&EngineError);
+
+/// Possible errors while booting or running the engine.
+#[derive(Debug, Clone, PartialEq)]
+pub enum EngineError {
+    /// Failed to create or run the event loop.
+    EventLoop(String),
+    /// Failed to create the window.
+    Window(String),
+    /// Failed to create a WebGPU surface for the window.
+    Surface(String),
+    /// No suitable WebGPU adapter is available, e.g. the browser does not support WebGPU.
+    AdapterNotFound,
+    /// Failed to request a WebGPU device.
+    Device(String),
+}
+
+impl fmt::Display for EngineError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let msg = match self {
+            EngineError::EventLoop(e) => format!("failed to run event loop: {}", e),
+            EngineError::Window(e) => format!("failed to create window: {}", e),
+            EngineError::Surface(e) => format!("failed to create surface: {}", e),
+            EngineError::AdapterNotFound => "no suitable WebGPU adapter found".to_string(),
+            EngineError::Device(e) => format!("failed to request WebGPU device: {}", e),
+        };
+        write!(f, "{}", msg)
+    }
+}
+
+impl std::error::Error for EngineError {}
+
 /// Engine configuration.
 #[derive(Clone, Copy, Debug)]
 pub struct EngineConfig {
-    /// Window title.
+    /// Window title. The title can be changed at runtime with [`window::WindowCommands::set_title`].
     pub window_title: &'static str,
+    /// Appends the frame rate and the average frame time of the [`time::FrameStats`] to the
+    /// window title, updated every [`TITLE_STATS_INTERVAL`].
+    pub title_stats: bool,
     /// Display size. Only effective in native mode.
     pub display_size: math::UVec2,
     /// World initializer.
     pub init_world: InitWorld,
     /// User system initializer.
     pub init_system: InitSystem,
+    /// Pauses the [`time::Time`] while the window is not focused.
+    pub pause_on_focus_lost: bool,
+    /// Creates the window with a transparent background, e.g. for desktop widgets and streaming
+    /// overlays. The frame is blended with the desktop according to [`graphics::GpuConfig::alpha_mode`],
+    /// which defaults to a premultiplied alpha mode for transparent windows.
+    ///
+    /// Only effective in native mode, on platforms supporting transparent windows.
+    pub transparent_window: bool,
+    /// Allows input methods (IME) to compose text, e.g. for non-Latin scripts.
+    pub ime_allowed: bool,
+    /// Paths of the resources to load before the first frame.
+    ///
+    /// The systems are not executed until all the resources are either loaded or failed to load,
+    /// and the progress is reported by the [`resource::PreloadProgress`] resource.
+    pub preload: &'static [&'static str],
+    /// Number of threads to execute the systems in parallel. Only effective in native mode.
+    ///
+    /// If not set, the systems run in the global thread pool, which has a thread per CPU core.
+    /// Set to 1 to execute the systems sequentially, e.g. for debugging.
+    pub ecs_threads: Option<usize>,
+    /// Renderer configuration.
+    pub gpu: graphics::GpuConfig,
+    /// Retry policy for the failed resource loads.
+    pub resource_retry: resource::RetryPolicy,
+    /// Runs the simulation deterministically, for replays and lockstep experiments.
+    ///
+    /// Time advances by [`EngineConfig::fixed_timestep`] every frame regardless of the wall time,
+    /// the [`math::Random`] resource is seeded with [`EngineConfig::random_seed`], and the systems
+    /// are executed sequentially so that queries iterate entities in a stable order.
+    pub deterministic: bool,
+    /// Time step of every frame in deterministic mode.
+    pub fixed_timestep: Duration,
+    /// Seed of the [`math::Random`] resource. If not set, the resource is seeded from the clock,
+    /// or with zero in deterministic mode.
+    pub random_seed: Option<u64>,
+    /// World streaming configuration, used by the [`streaming::WorldStreaming`] resource.
+    pub streaming: streaming::StreamingConfig,
+    /// Navigation mesh configuration, used by the [`nav::NavMesh`] resource.
+    pub nav: nav::NavMeshConfig,
+    /// Spatial index configuration, used by the [`spatial::SpatialIndex`] resource.
+    pub spatial: spatial::SpatialConfig,
+    /// Initial [`graphics::RenderBudget`] resource, limiting the extra passes of the renderer.
+    pub render_budget: graphics::RenderBudget,
+    /// Frame statistics configuration, used by the [`time::FrameStats`] resource.
+    pub frame_stats: time::FrameStatsConfig,
+    /// System timing configuration, used by the [`time::SystemTimings`] resource.
+    pub system_timings: time::SystemTimingsConfig,
+    /// Error handler, called when the engine fails to boot.
+    ///
+    /// Applications may use this to inform the user, e.g. that WebGPU is not supported.
+    pub on_error: OnError,
 }
 
 impl Default for EngineConfig {
     fn default() -> Self {
         Self {
             window_title: "",
+            title_stats: false,
             display_size: math::uvec2(1024, 720),
             init_world: |_, _| {},
             init_system: |_| {},
+            pause_on_focus_lost: false,
+            transparent_window: false,
+            ime_allowed: false,
+            preload: &[],
+            ecs_threads: None,
+            gpu: graphics::GpuConfig::default(),
+            resource_retry: resource::RetryPolicy::NEVER,
+            deterministic: false,
+            fixed_timestep: Duration::from_secs(1) / 60,
+            random_seed: None,
+            streaming: streaming::StreamingConfig::default(),
+            nav: nav::NavMeshConfig::default(),
+            spatial: spatial::SpatialConfig::default(),
+            render_budget: graphics::RenderBudget::default(),
+            frame_stats: time::FrameStatsConfig::default(),
+            system_timings: time::SystemTimingsConfig::default(),
+            on_error: |e| error!(target: "ravia_engine::engine", "{}", e),
+        }
+    }
+}
+
+impl EngineConfig {
+    /// Returns the renderer configuration, with a transparent alpha mode for a transparent
+    /// window unless specified otherwise.
+    fn gpu_config(&self) -> graphics::GpuConfig {
+        let mut gpu = self.gpu;
+        if self.transparent_window && gpu.alpha_mode == wgpu::CompositeAlphaMode::Auto {
+            gpu.alpha_mode = wgpu::CompositeAlphaMode::PreMultiplied;
         }
+        gpu
     }
 }
 
 /// Engine events to work with the winit event loop.
 #[derive(Debug)]
 enum EngineEvent {
-    Initialized(Engine),
+    Initialized(Box<Engine>),
+    Failed(EngineError),
+    DeviceRecreated(Result<Box<graphics::Gpu>, EngineError>),
+    #[cfg_attr(not(target_arch = "wasm32"), allow(dead_code))]
+    ContainerResized(math::Vec2),
+    #[cfg_attr(not(target_arch = "wasm32"), allow(dead_code))]
+    FileDropped(event::DroppedFile),
 }
 
 #[derive(Debug, Default)]
@@ -56,7 +199,11 @@ enum EngineState {
         config: EngineConfig,
         proxy: EventLoopProxy<EngineEvent>,
     },
-    Running(Engine),
+    Initializing {
+        config: EngineConfig,
+    },
+    Running(Box<Engine>),
+    Failed(EngineError),
 }
 
 impl EngineState {
@@ -65,30 +212,85 @@ impl EngineState {
     fn initialize(&mut self, event_loop: &ActiveEventLoop) {
         match std::mem::take(self) {
             EngineState::Created { config, proxy } => {
-                let window = Engine::new_window(event_loop, config);
+                let window = match Engine::new_window(event_loop, config) {
+                    Ok(window) => window,
+                    Err(e) => {
+                        *self = EngineState::Initializing { config };
+                        self.fail(event_loop, e);
+                        return;
+                    }
+                };
+                #[cfg(target_arch = "wasm32")]
+                {
+                    Engine::watch_dropped_files(&window, proxy.clone());
+                    Engine::watch_container_size(proxy.clone());
+                }
+
+                *self = EngineState::Initializing { config };
                 resolve_future(async move {
-                    let engine = Engine::new(window, config).await;
+                    let event = match Engine::new(window, config, proxy.clone()).await {
+                        Ok(engine) => EngineEvent::Initialized(Box::new(engine)),
+                        Err(e) => EngineEvent::Failed(e),
+                    };
                     proxy
-                        .send_event(EngineEvent::Initialized(engine))
+                        .send_event(event)
                         .expect("Failed to send initialized event");
                 })
             }
-            EngineState::Running(_) => panic!("Engine already initialized"),
+            EngineState::Initializing { .. } | EngineState::Running(_) => {
+                panic!("Engine already initialized")
+            }
+            EngineState::Failed(_) => panic!("Engine has failed"),
             EngineState::Uninitialized => panic!("Engine not initialized"),
         }
     }
+
+    /// Reports the error to the error handler and stops the engine, while initializing or
+    /// running.
+    fn fail(&mut self, event_loop: &ActiveEventLoop, e: EngineError) {
+        match self {
+            EngineState::Initializing { config } => (config.on_error)(&e),
+            EngineState::Running(engine) => (engine.config.on_error)(&e),
+            _ => (),
+        }
+
+        event_loop.exit();
+        *self = EngineState::Failed(e);
+    }
 }
 
 impl ApplicationHandler<EngineEvent> for EngineState {
     fn resumed(&mut self, event_loop: &winit::event_loop::ActiveEventLoop) {
         debug!(target: "ravia_engine::engine_state", "Engine resumed, engine = {:?}", self);
 
-        if let EngineState::Created { .. } = self {
-            self.initialize(event_loop);
+        match self {
+            EngineState::Created { .. } => self.initialize(event_loop),
+            EngineState::Running(engine) => {
+                if let Err(e) = engine.ctx.gpu.recreate_surface() {
+                    error!(target: "ravia_engine::engine_state", "{}", e);
+                }
+                engine.resume(event_loop);
+            }
+            _ => (),
+        }
+    }
+
+    fn suspended(&mut self, event_loop: &ActiveEventLoop) {
+        debug!(target: "ravia_engine::engine_state", "Engine suspended, engine = {:?}", self);
+
+        if let EngineState::Running(engine) = self {
+            engine.suspend(event_loop);
+            engine.ctx.gpu.save_pipeline_cache();
         }
     }
 
-    fn user_event(&mut self, _event_loop: &ActiveEventLoop, event: EngineEvent) {
+    fn exiting(&mut self, _event_loop: &ActiveEventLoop) {
+        if let EngineState::Running(engine) = self {
+            engine.ctx.gpu.save_pipeline_cache();
+        }
+    }
+
+    fn user_event(&mut self, event_loop: &ActiveEventLoop, event: EngineEvent) {
         trace!(target: "ravia_engine::engine_state", "User event: {:?}", event);
 
         match event {
@@ -96,6 +298,46 @@ impl ApplicationHandler<EngineEvent> for EngineState {
                 engine.request_frame();
                 *self = EngineState::Running(engine);
             }
+            EngineEvent::Failed(e) => self.fail(event_loop, e),
+            EngineEvent::DeviceRecreated(result) => match (&mut *self, result) {
+                (EngineState::Running(engine), Ok(gpu)) => engine.restore_device(*gpu),
+                // the device is lost for good, so the engine stops with the error.
+                (EngineState::Running(_), Err(e)) => self.fail(event_loop, e),
+                _ => (),
+            },
+            EngineEvent::ContainerResized(size) => {
+                if let EngineState::Running(engine) = self {
+                    let _ = engine
+                        .window
+                        .request_inner_size(LogicalSize::new(size.x, size.y));
+                }
+            }
+            EngineEvent::FileDropped(file) => {
+                if let EngineState::Running(engine) = self {
+                    engine.send_event(event::Event::FileDropped(file));
+                }
+            }
+        }
+    }
+
+    fn device_event(
+        &mut self,
+        _event_loop: &ActiveEventLoop,
+        _device_id: DeviceId,
+        event: DeviceEvent,
+    ) {
+        let engine = match self {
+            EngineState::Running(engine) => engine,
+            _ => return,
+        };
+
+        // device events are not associated with a window, so they are ignored when unfocused.
+        if !engine.focused {
+            return;
+        }
+
+        if let DeviceEvent::MouseMotion { delta: (x, y) } = event {
+            engine.send_event(event::Event::MouseMotion(math::vec2(x as f32, y as f32)));
         }
     }
 
@@ -118,12 +360,117 @@ impl ApplicationHandler<EngineEvent> for EngineState {
 
         match event {
             WindowEvent::RedrawRequested => {
+                if engine.suspended {
+                    return;
+                }
+
                 engine.request_frame();
                 engine.frame();
             }
             WindowEvent::Resized(physical_size) => {
                 engine.resize(math::uvec2(physical_size.width, physical_size.height));
             }
+            WindowEvent::ScaleFactorChanged { .. } => {
+                engine.resize(graphics::Gpu::window_size(&engine.window));
+            }
+            WindowEvent::DroppedFile(path) => {
+                engine.send_event(event::Event::FileDropped(event::DroppedFile {
+                    path,
+                    data: None,
+                }));
+            }
+            WindowEvent::HoveredFile(path) => {
+                engine.send_event(event::Event::FileHovered(path));
+            }
+            WindowEvent::HoveredFileCancelled => {
+                engine.send_event(event::Event::FileHoverCancelled);
+            }
+            WindowEvent::KeyboardInput {
+                event: key_event, ..
+            } if key_event.state.is_pressed() => {
+                engine.record_key(&key_event);
+
+                if !key_event.repeat
+                    && engine.ctx.gpu.gizmo_toggle_key().is_some_and(|key| {
+                        key_event.physical_key == winit::keyboard::PhysicalKey::Code(key)
+                    })
+                {
+                    engine
+                        .ctx
+                        .gpu
+                        .set_gizmos_visible(!engine.ctx.gpu.gizmos_visible());
+                }
+
+                if !key_event.repeat
+                    && engine.ctx.gpu.frame_stats_toggle_key().is_some_and(|key| {
+                        key_event.physical_key == winit::keyboard::PhysicalKey::Code(key)
+                    })
+                {
+                    engine
+                        .ctx
+                        .gpu
+                        .set_frame_stats_visible(!engine.ctx.gpu.frame_stats_visible());
+                }
+
+                if !key_event.repeat
+                    && engine.ctx.gpu.frame_graph_key().is_some_and(|key| {
+                        key_event.physical_key == winit::keyboard::PhysicalKey::Code(key)
+                    })
+                {
+                    info!(target: "ravia_engine::engine", "{}", engine.ctx.gpu.frame_graph());
+                }
+
+                if !key_event.repeat
+                    && engine.ctx.gpu.debug_view_key().is_some_and(|key| {
+                        key_event.physical_key == winit::keyboard::PhysicalKey::Code(key)
+                    })
+                {
+                    let view = engine.ctx.gpu.debug_view().next();
+                    info!(target: "ravia_engine::engine", "Debug view: {:?}", view);
+                    engine.ctx.gpu.set_debug_view(view);
+                }
+
+                if let Some(text) = key_event.text {
+                    engine.send_event(event::Event::TextInput(text.to_string()));
+                }
+            }
+            WindowEvent::KeyboardInput {
+                event: key_event, ..
+            } => {
+                engine.record_key(&key_event);
+            }
+            WindowEvent::CursorMoved { position, .. } => {
+                let position = math::vec2(position.x as f32, position.y as f32);
+                engine.ctx.mouse_mut().set_position(Some(position));
+            }
+            WindowEvent::CursorLeft { .. } => {
+                engine.ctx.mouse_mut().set_position(None);
+            }
+            WindowEvent::MouseInput { state, button, .. } => {
+                engine.send_event(event::Event::MouseButton {
+                    button: button.into(),
+                    pressed: state.is_pressed(),
+                });
+            }
+            WindowEvent::Ime(ime) => {
+                engine.send_event(match ime {
+                    Ime::Enabled => event::Event::ImeEnabled,
+                    Ime::Preedit(text, cursor) => event::Event::ImePreedit { text, cursor },
+                    Ime::Commit(text) => event::Event::ImeCommit(text),
+                    Ime::Disabled => event::Event::ImeDisabled,
+                });
+            }
+            WindowEvent::Focused(focused) => {
+                engine.focus(focused);
+            }
+            WindowEvent::Occluded(true) => {
+                debug!(target: "ravia_engine::engine_state", "Window occluded, suspending.");
+                engine.suspend(event_loop);
+            }
+            WindowEvent::Occluded(false) => {
+                debug!(target: "ravia_engine::engine_state", "Window visible, resuming.");
+                engine.resume(event_loop);
+            }
             WindowEvent::CloseRequested => {
                 info!(target: "ravia_engine::engine_state", "Window close requested, exiting.");
                 event_loop.exit();
@@ -142,19 +489,41 @@ pub struct Engine {
     world: ecs::World,
     resources: ecs::Resources,
     schedule: ecs::Schedule,
+    #[cfg(not(target_arch = "wasm32"))]
+    thread_pool: Option<rayon::ThreadPool>,
 
     window: Arc<Window>,
-    gpu: Arc<graphics::Gpu>,
-    resource_manager: Arc<resource::ResourceManager>,
+    ctx: EngineContext,
+    preloads: Vec<resource::Resource>,
     timer: time::Timer,
+    /// End of the last frame, to measure the frame times from.
+    last_frame: Option<Instant>,
+    /// Last update of the frame rate in the window title.
+    title_stats_updated: Option<Instant>,
+    config: EngineConfig,
+    proxy: EventLoopProxy<EngineEvent>,
+    suspended: bool,
+    focused: bool,
+    recovering: bool,
+    /// Screenshots of the [`graphics::PhotoMode`] being read back, with their size and whether
+    /// their texels are in BGRA order.
+    photo_captures: Vec<(math::UVec2, bool, graphics::Readback)>,
 }
 
 impl Engine {
     /// Initializes and runs the main event loop for the engine.    
-    pub fn run(config: EngineConfig) {
-        let event_loop = EventLoop::<EngineEvent>::with_user_event()
-            .build()
-            .expect("Failed to create event loop");
+    ///
+    /// Returns the error which stopped the engine, if any. In web platforms, the event loop runs
+    /// asynchronously, so the errors are only reported through [`EngineConfig::on_error`].
+    pub fn run(config: EngineConfig) -> Result<(), EngineError> {
+        let event_loop = match EventLoop::<EngineEvent>::with_user_event().build() {
+            Ok(event_loop) => event_loop,
+            Err(e) => {
+                let e = EngineError::EventLoop(e.to_string());
+                (config.on_error)(&e);
+                return Err(e);
+            }
+        };
         event_loop.set_control_flow(ControlFlow::Poll);
 
         let engine_state = EngineState::Created {
@@ -166,67 +535,150 @@ impl Engine {
         {
             use winit::platform::web::EventLoopExtWebSys;
             event_loop.spawn_app(engine_state);
+            Ok(())
         }
         #[cfg(not(target_arch = "wasm32"))]
         {
             let mut engine_state = engine_state;
-            event_loop
-                .run_app(&mut engine_state)
-                .expect("Failed to run main event loop");
+            if let Err(e) = event_loop.run_app(&mut engine_state) {
+                let e = EngineError::EventLoop(e.to_string());
+                (config.on_error)(&e);
+                return Err(e);
+            }
+
+            match engine_state {
+                EngineState::Failed(e) => Err(e),
+                _ => Ok(()),
+            }
         }
     }
 
     /// Creates a new [`Engine`].
-    async fn new(window: Window, config: EngineConfig) -> Self {
+    async fn new(
+        window: Window,
+        config: EngineConfig,
+        proxy: EventLoopProxy<EngineEvent>,
+    ) -> Result<Self, EngineError> {
         let window = Arc::new(window);
 
         debug!(target: "ravia_engine::engine", "Initializing WebGPU resources");
-        let gpu = graphics::Gpu::new(window.clone()).await;
+        let gpu = graphics::Gpu::new(window.clone(), config.gpu_config()).await?;
         let gpu = Arc::new(gpu);
 
-        let resource_manager = resource::ResourceManager::new();
+        let resource_manager = resource::ResourceManager::with_retry_policy(config.resource_retry);
         let resource_manager = Arc::new(resource_manager);
 
-        let timer = time::Timer::new();
+        let preloads = config
+            .preload
+            .iter()
+            .map(|path| {
+                let mut res = resource::Resource::new(path);
+                resource_manager.request(&mut res);
+                res
+            })
+            .collect::<Vec<_>>();
+
+        let timer = if config.deterministic {
+            time::Timer::with_fixed_timestep(config.fixed_timestep)
+        } else {
+            time::Timer::new()
+        };
+        let random = match (config.random_seed, config.deterministic) {
+            (Some(seed), _) => math::Random::new(seed),
+            (None, true) => math::Random::new(0),
+            (None, false) => math::Random::from_clock(),
+        };
 
+        let screen_size = window::ScreenSize {
+            size: graphics::Gpu::window_size(&window),
+            scale_factor: window.scale_factor(),
+        };
+        let ctx = EngineContext::new(gpu, resource_manager, screen_size);
         let mut world = ecs::World::default();
 
         let mut resources = ecs::Resources::default();
-        resources.insert(EngineContext {
-            gpu: gpu.clone(),
-            resource_manager: resource_manager.clone(),
+        resources.insert(ctx.clone());
+        resources.insert(ctx.gpu.capabilities().clone());
+        resources.insert(event::Events::new());
+        resources.insert(clipboard::Clipboard::new());
+        resources.insert(window::WindowCommands::new().with_title(config.window_title));
+        resources.insert(screen_size);
+        resources.insert(resource::PreloadProgress {
+            settled: 0,
+            total: preloads.len(),
         });
+        resources.insert(streaming::WorldStreaming::new(config.streaming));
+        resources.insert(graphics::PhotoMode::new());
+        resources.insert(config.render_budget);
+        resources.insert(time::FrameStats::new(config.frame_stats));
+        resources.insert(nav::NavMesh::new(config.nav));
+        resources.insert(spatial::SpatialIndex::new(config.spatial));
+        resources.insert(net::NetworkClient::new());
+        resources.insert(random);
 
         let mut schedule_builder = ecs::Schedule::builder();
         graphics::system(&mut schedule_builder);
         resource::system(&mut schedule_builder);
+        streaming::system(&mut schedule_builder);
+        nav::system(&mut schedule_builder);
+        spatial::system(&mut schedule_builder);
+        net::system(&mut schedule_builder);
+        ui::system(&mut schedule_builder);
         (config.init_system)(&mut schedule_builder);
-        let schedule = schedule_builder.build();
+        let mut system_timings = time::SystemTimings::new(config.system_timings);
+        let schedule = system_timings.instrument(schedule_builder.build());
+        resources.insert(system_timings);
 
-        (config.init_world)(
-            &mut world,
-            &EngineContext {
-                gpu: gpu.clone(),
-                resource_manager: resource_manager.clone(),
-            },
-        );
+        #[cfg(not(target_arch = "wasm32"))]
+        let ecs_threads = if config.deterministic {
+            Some(1)
+        } else {
+            config.ecs_threads
+        };
+        #[cfg(not(target_arch = "wasm32"))]
+        let thread_pool = ecs_threads.and_then(|threads| {
+            rayon::ThreadPoolBuilder::new()
+                .num_threads(threads)
+                .thread_name(|i| format!("ravia_engine::ecs::{}", i))
+                .build()
+                .inspect_err(|e| {
+                    warn!(target: "ravia_engine::engine", "Failed to build ECS thread pool: {}", e)
+                })
+                .ok()
+        });
 
-        Self {
+        (config.init_world)(&mut world, &ctx);
+
+        Ok(Self {
             world,
             resources,
             schedule,
+            #[cfg(not(target_arch = "wasm32"))]
+            thread_pool,
 
             window,
-            gpu,
-            resource_manager,
+            ctx,
+            preloads,
             timer,
-        }
+            last_frame: None,
+            title_stats_updated: None,
+            config,
+            proxy,
+            suspended: false,
+            focused: true,
+            recovering: false,
+            photo_captures: vec![],
+        })
     }
 
     /// Creates a new [`Window`].
-    fn new_window(event_loop: &ActiveEventLoop, config: EngineConfig) -> Window {
+    fn new_window(
+        event_loop: &ActiveEventLoop,
+        config: EngineConfig,
+    ) -> Result<Window, EngineError> {
         let window_attrs = Window::default_attributes()
             .with_title(config.window_title)
+            .with_transparent(config.transparent_window)
             .with_inner_size(LogicalSize::new(
                 config.display_size.x,
                 config.display_size.y,
@@ -234,7 +686,8 @@ impl Engine {
 
         let window = event_loop
             .create_window(window_attrs)
-            .expect("Failed to create window");
+            .map_err(|e| EngineError::Window(e.to_string()))?;
+        window.set_ime_allowed(config.ime_allowed);
 
         #[cfg(target_arch = "wasm32")]
         {
@@ -248,15 +701,213 @@ impl Engine {
                     root.append_child(&canvas).ok()?;
                     Some(())
                 })
-                .expect("Failed to append canvas to root element");
+                .ok_or_else(|| {
+                    EngineError::Window("failed to append canvas to root element".to_string())
+                })?;
         }
 
-        window
+        Ok(window)
+    }
+
+    /// Forwards the files dropped onto the canvas to the event loop.
+    ///
+    /// winit does not support file drops in web platforms, so the canvas listens to the HTML
+    /// drag and drop events and reads the file contents instead.
+    #[cfg(target_arch = "wasm32")]
+    fn watch_dropped_files(window: &Window, proxy: EventLoopProxy<EngineEvent>) {
+        use wasm_bindgen::{closure::Closure, JsCast};
+        use winit::platform::web::WindowExtWebSys;
+
+        let Some(canvas) = window.canvas() else {
+            return;
+        };
+
+        let on_drag_over =
+            Closure::<dyn FnMut(web_sys::DragEvent)>::new(|event: web_sys::DragEvent| {
+                // the drop event is only fired if the default drag over behavior is prevented.
+                event.prevent_default();
+            });
+
+        let on_drop =
+            Closure::<dyn FnMut(web_sys::DragEvent)>::new(move |event: web_sys::DragEvent| {
+                event.prevent_default();
+
+                let Some(files) = event.data_transfer().and_then(|dt| dt.files()) else {
+                    return;
+                };
+
+                for i in 0..files.length() {
+                    let Some(file) = files.get(i) else {
+                        continue;
+                    };
+
+                    let proxy = proxy.clone();
+                    wasm_bindgen_futures::spawn_local(async move {
+                        let Ok(buffer) =
+                            wasm_bindgen_futures::JsFuture::from(file.array_buffer()).await
+                        else {
+                            return;
+                        };
+
+                        let data = js_sys::Uint8Array::new(&buffer).to_vec();
+                        let _ = proxy.send_event(EngineEvent::FileDropped(event::DroppedFile {
+                            path: file.name().into(),
+                            data: Some(data),
+                        }));
+                    });
+                }
+            });
+
+        canvas
+            .add_event_listener_with_callback("dragover", on_drag_over.as_ref().unchecked_ref())
+            .expect("Failed to add dragover listener");
+        canvas
+            .add_event_listener_with_callback("drop", on_drop.as_ref().unchecked_ref())
+            .expect("Failed to add drop listener");
+
+        // the listeners live during the whole engine lifetime.
+        on_drag_over.forget();
+        on_drop.forget();
+    }
+
+    /// Resizes the canvas to fill its container element whenever the container is resized.
+    ///
+    /// The canvas is sized by its style, so it does not follow the page layout by itself.
+    #[cfg(target_arch = "wasm32")]
+    fn watch_container_size(proxy: EventLoopProxy<EngineEvent>) {
+        use wasm_bindgen::{closure::Closure, JsCast};
+
+        let Some(root) = web_sys::window()
+            .and_then(|win| win.document())
+            .and_then(|doc| doc.get_element_by_id("root"))
+        else {
+            return;
+        };
+
+        let on_resize = Closure::<dyn FnMut(js_sys::Array)>::new(move |entries: js_sys::Array| {
+            let Ok(entry) = entries.get(0).dyn_into::<web_sys::ResizeObserverEntry>() else {
+                return;
+            };
+
+            let rect = entry.content_rect();
+            let _ = proxy.send_event(EngineEvent::ContainerResized(math::vec2(
+                rect.width() as f32,
+                rect.height() as f32,
+            )));
+        });
+
+        let Ok(observer) = web_sys::ResizeObserver::new(on_resize.as_ref().unchecked_ref()) else {
+            warn!(target: "ravia_engine::engine", "ResizeObserver is not supported");
+            return;
+        };
+        observer.observe(&root);
+
+        // the observer lives during the whole engine lifetime.
+        on_resize.forget();
+        std::mem::forget(observer);
     }
 
     /// Handles the display resize.
-    fn resize(&self, size: math::UVec2) {
-        self.gpu.resize(size);
+    fn resize(&mut self, size: math::UVec2) {
+        self.ctx.gpu.resize(size);
+        let screen_size = window::ScreenSize {
+            size,
+            scale_factor: self.window.scale_factor(),
+        };
+        self.ctx.set_screen_size(screen_size);
+        self.resources.insert(screen_size);
+    }
+
+    /// Suspends the frame loop, e.g. when the application is backgrounded or hidden.
+    ///
+    /// The timer is paused and no further frames are requested until [`Engine::resume`].
+    fn suspend(&mut self, event_loop: &ActiveEventLoop) {
+        if self.suspended {
+            return;
+        }
+
+        info!(target: "ravia_engine::engine", "Suspending frame loop");
+        self.suspended = true;
+        self.last_frame = None;
+        self.update_timer();
+        event_loop.set_control_flow(ControlFlow::Wait);
+    }
+
+    /// Resumes the frame loop suspended by [`Engine::suspend`].
+    fn resume(&mut self, event_loop: &ActiveEventLoop) {
+        if !self.suspended {
+            return;
+        }
+
+        info!(target: "ravia_engine::engine", "Resuming frame loop");
+        self.suspended = false;
+        self.update_timer();
+        event_loop.set_control_flow(ControlFlow::Poll);
+        self.request_frame();
+    }
+
+    /// Handles the window focus change.
+    fn focus(&mut self, focused: bool) {
+        if self.focused == focused {
+            return;
+        }
+
+        self.focused = focused;
+        self.update_timer();
+        if !focused {
+            // the releases of the held keys are not received while unfocused.
+            self.ctx.keyboard_mut().release_all();
+        }
+        self.send_event(if focused {
+            event::Event::WindowFocused
+        } else {
+            event::Event::WindowUnfocused
+        });
+    }
+
+    /// Pauses or resumes the timer according to the engine state.
+    fn update_timer(&mut self) {
+        let photo_mode = self
+            .resources
+            .get::<graphics::PhotoMode>()
+            .is_some_and(|photo_mode| photo_mode.is_active());
+        let paused =
+            self.suspended || photo_mode || (self.config.pause_on_focus_lost && !self.focused);
+        if paused {
+            self.timer.pause();
+        } else {
+            self.timer.resume();
+        }
+    }
+
+    /// Sends an engine event to the systems.
+    fn send_event(&mut self, event: event::Event) {
+        if let Some(mut events) = self.resources.get_mut::<event::Events>() {
+            events.send(event);
+        }
+    }
+
+    /// Records a key press or release into the [`input::Keyboard`] of the context.
+    fn record_key(&mut self, key_event: &winit::event::KeyEvent) {
+        let winit::keyboard::PhysicalKey::Code(code) = key_event.physical_key else {
+            return;
+        };
+        let mut keyboard = self.ctx.keyboard_mut();
+
+        if !key_event.state.is_pressed() {
+            keyboard.release(code);
+            return;
+        }
+
+        // the layout is learned from the keys without modifiers, where the platform supports it.
+        #[cfg(not(target_arch = "wasm32"))]
+        let key = {
+            use winit::platform::modifier_supplement::KeyEventExtModifierSupplement;
+            key_event.key_without_modifiers()
+        };
+        #[cfg(target_arch = "wasm32")]
+        let key = key_event.logical_key.clone();
+        keyboard.press(code, key);
     }
 
     /// Requests a new frame.
@@ -266,12 +917,315 @@ impl Engine {
 
     /// Handles the single frame update.
     fn frame(&mut self) {
+        if self.ctx.gpu.is_lost() {
+            self.recover_device();
+            return;
+        }
+
+        if !self.preload() {
+            return;
+        }
+
+        self.update_timer();
         self.timer.frame();
         let time = self.timer.time();
         self.resources.insert(time);
+        for e in self.ctx.gpu.take_errors() {
+            self.send_event(event::Event::GpuError(e));
+        }
+        if let Some(mut events) = self.resources.get_mut::<event::Events>() {
+            // the events sent through the context are visible from this frame on, like the
+            // events sent by the systems of the previous frame.
+            for e in self.ctx.take_events() {
+                events.send(e);
+            }
+            events.update();
+        }
+        self.ctx.keyboard_mut().update();
+
+        self.resources.insert(self.ctx.gpu.memory_stats());
+
+        let frame_start = Instant::now();
+        self.execute_schedule();
+        let systems_end = Instant::now();
+        self.record_system_timings();
+        if let Some(stats) = self.resources.get::<time::FrameStats>() {
+            self.ctx.gpu.write_frame_stats(&stats);
+        }
+        self.ctx.gpu.render(&self.world, &time);
+        self.finish_photo_captures();
+        self.capture_photo(&time);
+        let render_end = Instant::now();
+
+        self.apply_window_commands();
+        self.record_frame_stats(
+            &time,
+            vec![
+                time::FrameSpan {
+                    name: "systems",
+                    duration: systems_end - frame_start,
+                },
+                time::FrameSpan {
+                    name: "render",
+                    duration: render_end - systems_end,
+                },
+            ],
+            frame_start,
+            render_end,
+        );
+    }
+
+    /// Records the time since the end of the last frame into the [`time::FrameStats`], and logs
+    /// the frame if it is a hitch.
+    ///
+    /// The time spent between the frames, e.g. in the event loop, is recorded as the `wait` span.
+    fn record_frame_stats(
+        &mut self,
+        time: &time::Time,
+        mut spans: Vec<time::FrameSpan>,
+        frame_start: Instant,
+        frame_end: Instant,
+    ) {
+        let Some(last_frame) = self.last_frame.replace(frame_end) else {
+            return;
+        };
+        let Some(mut stats) = self.resources.get_mut::<time::FrameStats>() else {
+            return;
+        };
+
+        spans.push(time::FrameSpan {
+            name: "wait",
+            duration: frame_start.saturating_duration_since(last_frame),
+        });
+        if let Some(hitch) = stats.record(time.frames, frame_end - last_frame, spans) {
+            let spans = hitch
+                .spans
+                .iter()
+                .map(|span| format!("{} {:.1?}", span.name, span.duration))
+                .collect::<Vec<_>>()
+                .join(", ");
+            let slowest_system = self
+                .resources
+                .get::<time::SystemTimings>()
+                .and_then(|timings| {
+                    timings
+                        .iter()
+                        .max_by_key(|system| system.last())
+                        .map(|system| {
+                            format!(", slowest system {} {:.1?}", system.name(), system.last())
+                        })
+                });
+            warn!(
+                target: "ravia_engine::engine",
+                "Frame {} hitched: {:.1?} ({}{})",
+                hitch.frame,
+                hitch.duration,
+                spans,
+                slowest_system.unwrap_or_default()
+            );
+        }
+    }
+
+    /// Records the execution times of the systems into the [`time::SystemTimings`], and logs the
+    /// systems exceeding the budget.
+    fn record_system_timings(&mut self) {
+        let Some(mut timings) = self.resources.get_mut::<time::SystemTimings>() else {
+            return;
+        };
+
+        let budget = timings.config().budget.unwrap_or_default();
+        for system in timings.record() {
+            warn!(
+                target: "ravia_engine::engine",
+                "System {} took {:.1?}, over the budget of {:.1?}", system.name(), system.last(), budget
+            );
+        }
+    }
+
+    /// Captures the screenshot requested through the [`graphics::PhotoMode`], if any.
+    ///
+    /// The screenshot is read back without blocking the frame, and sent as
+    /// [`event::Event::PhotoCaptured`] from a later frame, see [`Engine::finish_photo_captures`].
+    fn capture_photo(&mut self, time: &time::Time) {
+        let Some(scale) = self
+            .resources
+            .get_mut::<graphics::PhotoMode>()
+            .and_then(|mut photo_mode| photo_mode.take_capture())
+        else {
+            return;
+        };
+
+        let size = graphics::Gpu::window_size(&self.window);
+        let texture = self
+            .ctx
+            .gpu
+            .render_offscreen(&self.world, time, size, scale);
+
+        let size = math::uvec2(texture.width(), texture.height());
+        let swizzle = matches!(
+            texture.format(),
+            wgpu::TextureFormat::Bgra8Unorm | wgpu::TextureFormat::Bgra8UnormSrgb
+        );
+        match self.ctx.gpu.read_texture(&texture) {
+            Ok(readback) => self.photo_captures.push((size, swizzle, readback)),
+            Err(e) => warn!(target: "ravia_engine::engine", "Failed to capture photo: {}", e),
+        }
+    }
+
+    /// Sends the screenshots whose readback has finished since the last frame.
+    fn finish_photo_captures(&mut self) {
+        let mut captured = vec![];
+        self.photo_captures
+            .retain_mut(|(size, swizzle, readback)| match readback.try_take() {
+                None => true,
+                Some(Ok(mut data)) => {
+                    if *swizzle {
+                        data.chunks_exact_mut(4).for_each(|texel| texel.swap(0, 2));
+                    }
+                    captured.push(event::Event::PhotoCaptured { size: *size, data });
+                    false
+                }
+                Some(Err(e)) => {
+                    warn!(target: "ravia_engine::engine", "Failed to capture photo: {}", e);
+                    false
+                }
+            });
+        for e in captured {
+            self.send_event(e);
+        }
+    }
+
+    /// Executes the systems.
+    fn execute_schedule(&mut self) {
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Some(thread_pool) = &self.thread_pool {
+            self.schedule
+                .execute_in_thread_pool(&mut self.world, &mut self.resources, thread_pool);
+            return;
+        }
 
         self.schedule.execute(&mut self.world, &mut self.resources);
-        self.gpu.render(&self.world);
+    }
+
+    /// Recreates the GPU device after it has been lost.
+    ///
+    /// The world is kept, and its components are recreated for the new device once ready, see
+    /// [`event::Event::DeviceRestored`].
+    fn recover_device(&mut self) {
+        if self.recovering {
+            return;
+        }
+
+        warn!(target: "ravia_engine::engine", "GPU device lost, recreating");
+        self.recovering = true;
+
+        let window = self.window.clone();
+        let config = self.config.gpu_config();
+        let proxy = self.proxy.clone();
+        resolve_future(async move {
+            let result = graphics::Gpu::new(window, config).await.map(Box::new);
+            proxy
+                .send_event(EngineEvent::DeviceRecreated(result))
+                .expect("Failed to send device recreated event");
+        });
+    }
+
+    /// Replaces the lost GPU device with the recreated one, and recreates the components of the
+    /// world for it.
+    fn restore_device(&mut self, gpu: graphics::Gpu) {
+        info!(target: "ravia_engine::engine", "GPU device recreated, restoring world");
+        gpu.restore_settings(&self.ctx.gpu);
+        self.ctx = self.ctx.with_gpu(Arc::new(gpu));
+
+        self.resources.insert(self.ctx.clone());
+        self.resources.insert(self.ctx.gpu.capabilities().clone());
+        resource::restore_world(&mut self.world);
+        graphics::restore_world(&mut self.world, &self.ctx);
+
+        self.recovering = false;
+        self.send_event(event::Event::DeviceRestored);
+    }
+
+    /// Updates the preload progress, and returns true if all the preloads are settled.
+    fn preload(&mut self) -> bool {
+        if self.preloads.is_empty() {
+            return true;
+        }
+
+        let progress = resource::PreloadProgress {
+            settled: self
+                .preloads
+                .iter()
+                .filter(|res| !self.ctx.resource_manager.is_loading(res))
+                .count(),
+            total: self.preloads.len(),
+        };
+        self.resources.insert(progress);
+
+        if !progress.is_done() {
+            trace!(target: "ravia_engine::engine", "Preloading resources: {:?}", progress);
+            return false;
+        }
+
+        debug!(target: "ravia_engine::engine", "Preloaded {} resources", progress.total);
+        self.preloads.clear();
+        true
+    }
+
+    /// Applies the window commands queued by the systems.
+    fn apply_window_commands(&mut self) {
+        let Some(mut commands) = self.resources.get_mut::<window::WindowCommands>() else {
+            return;
+        };
+
+        for command in commands.drain() {
+            match command {
+                window::WindowCommand::SetRelativeMouseMode(true) => {
+                    // not every platform supports locking the cursor, so fall back to confining.
+                    if let Err(e) = self
+                        .window
+                        .set_cursor_grab(CursorGrabMode::Locked)
+                        .or_else(|_| self.window.set_cursor_grab(CursorGrabMode::Confined))
+                    {
+                        warn!(target: "ravia_engine::engine", "Failed to grab cursor: {}", e);
+                    }
+                    self.window.set_cursor_visible(false);
+                }
+                window::WindowCommand::SetRelativeMouseMode(false) => {
+                    if let Err(e) = self.window.set_cursor_grab(CursorGrabMode::None) {
+                        warn!(target: "ravia_engine::engine", "Failed to release cursor: {}", e);
+                    }
+                    self.window.set_cursor_visible(true);
+                }
+                // with the frame rate, the title is updated below.
+                window::WindowCommand::SetTitle(title) if !self.config.title_stats => {
+                    self.window.set_title(&title);
+                }
+                window::WindowCommand::SetTitle(_) => self.title_stats_updated = None,
+            }
+        }
+
+        if self.config.title_stats
+            && self
+                .title_stats_updated
+                .is_none_or(|updated| updated.elapsed() >= TITLE_STATS_INTERVAL)
+        {
+            let average = self
+                .resources
+                .get::<time::FrameStats>()
+                .map(|stats| stats.average())
+                .unwrap_or_default();
+            let stats = format!(
+                "{:.0} FPS ({:.2} ms)",
+                1.0 / average.as_secs_f64().max(f64::EPSILON),
+                average.as_secs_f64() * 1000.0
+            );
+            match commands.title() {
+                "" => self.window.set_title(&stats),
+                title => self.window.set_title(&format!("{} - {}", title, stats)),
+            }
+            self.title_stats_updated = Some(Instant::now());
+        }
     }
 }
 
@@ -283,10 +1237,93 @@ impl fmt::Debug for Engine {
 
 /// [`EngineContext`] contains the reference for the global resources, which can be then accessed
 /// by the system update loop.
-#[derive(Debug)]
+///
+/// The same context is given to [`EngineConfig::init_world`] and inserted as a resource for the
+/// systems, so that the world is built and updated with the same means.
+///
+/// Cloning an [`EngineContext`] is cheap, and refers to the same resources.
+#[derive(Debug, Clone)]
 pub struct EngineContext {
     pub gpu: Arc<graphics::Gpu>,
     pub resource_manager: Arc<resource::ResourceManager>,
+    /// Materials shared between entities through [`graphics::MaterialInstance`]s.
+    ///
+    /// The materials are recreated along with the GPU device when it is lost, under the same
+    /// names.
+    pub materials: Arc<resource::Assets<graphics::Material>>,
+    screen_size: Arc<RwLock<window::ScreenSize>>,
+    keyboard: Arc<RwLock<input::Keyboard>>,
+    mouse: Arc<RwLock<input::Mouse>>,
+    /// Events sent through the context, moved to the [`event::Events`] resource every frame.
+    events: Arc<Mutex<Vec<event::Event>>>,
+}
+
+assert_impl_all!(EngineContext: ecs::systems::Resource);
+
+impl EngineContext {
+    /// Creates a new [`EngineContext`], e.g. to use the renderer on a headless [`graphics::Gpu`].
+    pub fn new(
+        gpu: Arc<graphics::Gpu>,
+        resource_manager: Arc<resource::ResourceManager>,
+        screen_size: window::ScreenSize,
+    ) -> Self {
+        Self {
+            gpu,
+            resource_manager,
+            materials: Arc::new(resource::Assets::new()),
+            screen_size: Arc::new(RwLock::new(screen_size)),
+            keyboard: Arc::new(RwLock::new(input::Keyboard::new())),
+            mouse: Arc::new(RwLock::new(input::Mouse::new())),
+            events: Arc::new(Mutex::new(vec![])),
+        }
+    }
+
+    /// Returns the context with a recreated GPU device.
+    fn with_gpu(&self, gpu: Arc<graphics::Gpu>) -> Self {
+        Self {
+            gpu,
+            ..self.clone()
+        }
+    }
+
+    /// Returns the size of the window surface, also available as the [`window::ScreenSize`]
+    /// resource.
+    pub fn screen_size(&self) -> window::ScreenSize {
+        *self.screen_size.read().unwrap()
+    }
+
+    /// Returns the state of the keyboard.
+    pub fn keyboard(&self) -> RwLockReadGuard<'_, input::Keyboard> {
+        self.keyboard.read().unwrap()
+    }
+
+    /// Returns the state of the mouse.
+    pub fn mouse(&self) -> RwLockReadGuard<'_, input::Mouse> {
+        self.mouse.read().unwrap()
+    }
+
+    /// Sends an event to the systems through the [`event::Events`] resource, visible from the
+    /// next frame on. Unlike [`event::Events::send`], events can be sent outside of the systems,
+    /// e.g. from the world initializer.
+    pub fn send_event(&self, event: event::Event) {
+        self.events.lock().unwrap().push(event);
+    }
+
+    fn set_screen_size(&self, screen_size: window::ScreenSize) {
+        *self.screen_size.write().unwrap() = screen_size;
+    }
+
+    fn keyboard_mut(&self) -> RwLockWriteGuard<'_, input::Keyboard> {
+        self.keyboard.write().unwrap()
+    }
+
+    fn mouse_mut(&self) -> RwLockWriteGuard<'_, input::Mouse> {
+        self.mouse.write().unwrap()
+    }
+
+    fn take_events(&self) -> Vec<event::Event> {
+        std::mem::take(&mut *self.events.lock().unwrap())
+    }
 }
 
 fn resolve_future<F: Future<Output = ()> + 'static>(f: F) {
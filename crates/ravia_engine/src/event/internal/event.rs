@@ -0,0 +1,104 @@
+use std::path::PathBuf;
+
+use crate::{math, net, resource};
+
+/// An event emitted by the engine, which can be read by the systems through [`super::events::Events`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum Event {
+    /// The window has gained focus.
+    WindowFocused,
+    /// The window has lost focus.
+    WindowUnfocused,
+    /// A file has been dropped onto the window.
+    FileDropped(DroppedFile),
+    /// A file is being hovered over the window. Only emitted in native mode.
+    FileHovered(PathBuf),
+    /// The hovered file has left the window without being dropped. Only emitted in native mode.
+    FileHoverCancelled,
+    /// Text has been typed with the keyboard.
+    TextInput(String),
+    /// The input method has been enabled. Only emitted if IME is allowed in the engine config.
+    ImeEnabled,
+    /// The input method is composing text, which is not committed yet.
+    ///
+    /// The cursor is the byte range of the composed text to highlight, if any.
+    /// An empty text means the composition has been cleared.
+    ImePreedit {
+        text: String,
+        cursor: Option<(usize, usize)>,
+    },
+    /// The input method has committed the composed text.
+    ImeCommit(String),
+    /// The input method has been disabled.
+    ImeDisabled,
+    /// The mouse has moved, in raw device units.
+    ///
+    /// Unlike the cursor position, the motion is not limited by the window edges, which makes it
+    /// suitable for camera controls with [`crate::window::WindowCommands::set_relative_mouse_mode`].
+    MouseMotion(math::Vec2),
+    /// A mouse button has been pressed or released over the window.
+    MouseButton { button: MouseButton, pressed: bool },
+    /// The GPU device has been lost and recreated, and the components of the world have been
+    /// recreated for the new device.
+    ///
+    /// The components which cannot be recreated from what the engine keeps on the CPU are removed
+    /// for the application to create them again: the meshes it has created rather than loaded
+    /// from a [`crate::resource::Resource`], the lightmaps, and the skyboxes of cubemaps it has
+    /// created. The textures it has set on the materials are replaced by neutral textures, and its
+    /// [`crate::graphics::CustomPasses`] are removed.
+    DeviceRestored,
+    /// A WebGPU validation error has occurred, e.g. while creating a GPU resource.
+    GpuError(String),
+    /// A screenshot requested with [`crate::graphics::PhotoMode::capture`] has been captured, as
+    /// tightly packed rows of RGBA8 texels in sRGB.
+    PhotoCaptured { size: math::UVec2, data: Vec<u8> },
+    /// The [`crate::net::NetworkClient`] has connected to the server.
+    NetworkConnected,
+    /// A message has been received from the server.
+    NetworkMessage(net::NetworkMessage),
+    /// The connection of the [`crate::net::NetworkClient`] has been closed by the server or has
+    /// failed, with the error if any.
+    NetworkDisconnected { error: Option<String> },
+    /// A resource has failed to load.
+    ResourceFailed {
+        path: String,
+        error: resource::Error,
+    },
+    /// An asset loaded from a resource has been uploaded to the GPU in the background, and bound
+    /// to its entity.
+    UploadCompleted { path: String },
+}
+
+/// A file dropped onto the window.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DroppedFile {
+    /// Path of the file. In web platforms, this only contains the file name.
+    pub path: PathBuf,
+    /// Contents of the file. Only available in web platforms, where the file cannot be accessed
+    /// through its path.
+    pub data: Option<Vec<u8>>,
+}
+
+/// A button of the mouse.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MouseButton {
+    Left,
+    Right,
+    Middle,
+    Back,
+    Forward,
+    Other(u16),
+}
+
+impl From<winit::event::MouseButton> for MouseButton {
+    fn from(button: winit::event::MouseButton) -> Self {
+        match button {
+            winit::event::MouseButton::Left => Self::Left,
+            winit::event::MouseButton::Right => Self::Right,
+            winit::event::MouseButton::Middle => Self::Middle,
+            winit::event::MouseButton::Back => Self::Back,
+            winit::event::MouseButton::Forward => Self::Forward,
+            winit::event::MouseButton::Other(id) => Self::Other(id),
+        }
+    }
+}
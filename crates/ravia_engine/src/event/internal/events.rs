@@ -0,0 +1,38 @@
+use super::event::Event;
+
+/// [`Events`] buffers the engine events emitted during a frame.
+///
+/// Events sent during a frame are visible to the systems from the next frame on, and are
+/// discarded after a single frame.
+#[derive(Debug, Default)]
+pub struct Events {
+    current: Vec<Event>,
+    pending: Vec<Event>,
+}
+
+impl Events {
+    /// Creates a new empty [`Events`].
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sends an event, which will be visible from the next frame.
+    pub fn send(&mut self, event: Event) {
+        self.pending.push(event);
+    }
+
+    /// Returns an iterator over the events of the current frame.
+    pub fn iter(&self) -> impl Iterator<Item = &Event> {
+        self.current.iter()
+    }
+
+    /// Returns true if there are no events in the current frame.
+    pub fn is_empty(&self) -> bool {
+        self.current.is_empty()
+    }
+
+    /// Promotes the pending events to the current frame, discarding the previous ones.
+    pub(crate) fn update(&mut self) {
+        self.current = std::mem::take(&mut self.pending);
+    }
+}
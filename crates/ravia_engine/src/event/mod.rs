@@ -0,0 +1,7 @@
+// implementation module
+mod internal;
+
+pub use internal::{
+    event::{DroppedFile, Event, MouseButton},
+    events::Events,
+};
@@ -0,0 +1,261 @@
+use crate::engine::EngineContext;
+
+use super::{
+    coordinates::MeshImportOptions,
+    mesh::{
+        Mesh, Vertex, Vertex3DSkinned, Vertex3DStandard, Vertex3DStandardColored,
+        Vertex3DStandardUv2,
+    },
+};
+
+/// File extension of binary meshes.
+pub const BINARY_MESH_EXTENSION: &str = "rmesh";
+
+const MAGIC: &[u8; 4] = b"RMSH";
+const VERSION: u32 = 1;
+const HEADER_SIZE: usize = 20;
+
+/// Vertex layouts a binary mesh can be stored in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum VertexLayout {
+    /// [`Vertex3DStandard`].
+    Standard = 0,
+    /// [`Vertex3DStandardColored`].
+    StandardColored = 1,
+    /// [`Vertex3DStandardUv2`].
+    StandardUv2 = 2,
+    /// [`Vertex3DSkinned`].
+    Skinned = 3,
+}
+
+impl VertexLayout {
+    fn from_u32(value: u32) -> Option<Self> {
+        match value {
+            0 => Some(Self::Standard),
+            1 => Some(Self::StandardColored),
+            2 => Some(Self::StandardUv2),
+            3 => Some(Self::Skinned),
+            _ => None,
+        }
+    }
+
+    fn vertex_size(self) -> u64 {
+        match self {
+            Self::Standard => Vertex3DStandard::SIZE,
+            Self::StandardColored => Vertex3DStandardColored::SIZE,
+            Self::StandardUv2 => Vertex3DStandardUv2::SIZE,
+            Self::Skinned => Vertex3DSkinned::SIZE,
+        }
+    }
+}
+
+/// The parts of a binary mesh, borrowed from its buffer.
+#[derive(Debug, PartialEq, Eq)]
+struct BinaryMesh<'a> {
+    layout: VertexLayout,
+    num_vertices: u32,
+    vertices: &'a [u8],
+    num_indices: u32,
+    indices: &'a [u8],
+}
+
+/// Loads a mesh from a buffer containing a binary mesh.
+///
+/// Binary meshes are produced from OBJ files by `ravia_build`, and are uploaded to the GPU without
+/// any parsing. All values are little-endian: a header of magic `b"RMSH"`, format version,
+/// vertex layout, number of vertices and number of indices (each `u32`), followed by the vertex
+/// blob laid out as the engine vertex type and the `u32` index blob.
+pub fn load_mesh_from_binary(ctx: &EngineContext, data: &[u8]) -> Result<Mesh, anyhow::Error> {
+    load_mesh_from_binary_with_label(ctx, data, "ravia_engine::mesh")
+}
+
+/// Loads a mesh from a buffer containing a binary mesh, with a debug label for its buffers.
+pub fn load_mesh_from_binary_with_label(
+    ctx: &EngineContext,
+    data: &[u8],
+    label: &str,
+) -> Result<Mesh, anyhow::Error> {
+    load_mesh_from_binary_with_options(ctx, data, label, MeshImportOptions::default())
+}
+
+/// Loads a mesh from a buffer containing a binary mesh, converting it with the import options,
+/// e.g. when authored in another coordinate system or unit.
+pub fn load_mesh_from_binary_with_options(
+    ctx: &EngineContext,
+    data: &[u8],
+    label: &str,
+    options: MeshImportOptions,
+) -> Result<Mesh, anyhow::Error> {
+    let BinaryMesh {
+        layout,
+        num_vertices,
+        vertices,
+        num_indices,
+        indices,
+    } = parse_binary_mesh(data)?;
+
+    let units_per_meter = ctx.gpu.units_per_meter();
+    let mesh = if options.is_identity(units_per_meter) {
+        Mesh::from_bytes(ctx, vertices, num_vertices, indices, num_indices, label)
+    } else {
+        let mut vertices = vertices.to_vec();
+        let mut indices = indices
+            .chunks_exact(4)
+            .map(|index| u32::from_le_bytes(index.try_into().unwrap()))
+            .collect::<Vec<_>>();
+        options.apply(
+            units_per_meter,
+            &mut vertices,
+            layout.vertex_size() as usize,
+            &mut indices,
+        );
+        Mesh::from_bytes(
+            ctx,
+            &vertices,
+            num_vertices,
+            bytemuck::cast_slice(&indices),
+            num_indices,
+            label,
+        )
+    }
+    .with_import_scale(options.scale(units_per_meter));
+    Ok(if layout == VertexLayout::StandardColored {
+        mesh.with_vertex_colors()
+    } else {
+        mesh
+    })
+}
+
+/// Parses a binary mesh into its vertex layout, vertices and indices, checking its header.
+fn parse_binary_mesh(data: &[u8]) -> Result<BinaryMesh<'_>, anyhow::Error> {
+    if data.len() < HEADER_SIZE || &data[0..4] != MAGIC {
+        return Err(anyhow::anyhow!("Not a binary mesh"));
+    }
+
+    let read_u32 = |offset: usize| u32::from_le_bytes(data[offset..offset + 4].try_into().unwrap());
+
+    let version = read_u32(4);
+    if version != VERSION {
+        return Err(anyhow::anyhow!(
+            "Unsupported binary mesh version {}",
+            version
+        ));
+    }
+
+    let layout = VertexLayout::from_u32(read_u32(8))
+        .ok_or_else(|| anyhow::anyhow!("Unknown vertex layout {}", read_u32(8)))?;
+    let num_vertices = read_u32(12);
+    let num_indices = read_u32(16);
+
+    // the sizes are checked, since the counts in the header can overflow `usize` on wasm32.
+    let size_mismatch = || anyhow::anyhow!("Binary mesh size does not match its header");
+    let vertices_size = (num_vertices as usize)
+        .checked_mul(layout.vertex_size() as usize)
+        .ok_or_else(size_mismatch)?;
+    let indices_size = (num_indices as usize)
+        .checked_mul(std::mem::size_of::<u32>())
+        .ok_or_else(size_mismatch)?;
+    let total_size = HEADER_SIZE
+        .checked_add(vertices_size)
+        .and_then(|size| size.checked_add(indices_size));
+    if total_size != Some(data.len()) {
+        return Err(size_mismatch());
+    }
+
+    let (vertices, indices) = data[HEADER_SIZE..].split_at(vertices_size);
+    Ok(BinaryMesh {
+        layout,
+        num_vertices,
+        vertices,
+        num_indices,
+        indices,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn header(version: u32, layout: u32, num_vertices: u32, num_indices: u32) -> Vec<u8> {
+        let mut data = MAGIC.to_vec();
+        for value in [version, layout, num_vertices, num_indices] {
+            data.extend_from_slice(&value.to_le_bytes());
+        }
+        data
+    }
+
+    fn triangle(layout: VertexLayout) -> Vec<u8> {
+        let mut data = header(VERSION, layout as u32, 3, 3);
+        data.extend((0..3 * layout.vertex_size()).map(|i| i as u8));
+        for index in [0u32, 1, 2] {
+            data.extend_from_slice(&index.to_le_bytes());
+        }
+        data
+    }
+
+    #[test]
+    fn parses_every_layout() {
+        for layout in [
+            VertexLayout::Standard,
+            VertexLayout::StandardColored,
+            VertexLayout::StandardUv2,
+            VertexLayout::Skinned,
+        ] {
+            let data = triangle(layout);
+            let mesh = parse_binary_mesh(&data).unwrap();
+            assert_eq!(mesh.layout, layout);
+            assert_eq!((mesh.num_vertices, mesh.num_indices), (3, 3));
+            assert_eq!(mesh.vertices.len() as u64, 3 * layout.vertex_size());
+            assert_eq!(mesh.vertices[1], 1);
+            assert_eq!(mesh.indices, bytemuck::cast_slice::<u32, u8>(&[0, 1, 2]));
+        }
+    }
+
+    #[test]
+    fn parses_empty_mesh() {
+        let data = header(VERSION, VertexLayout::Standard as u32, 0, 0);
+        let mesh = parse_binary_mesh(&data).unwrap();
+        assert!(mesh.vertices.is_empty() && mesh.indices.is_empty());
+    }
+
+    #[test]
+    fn rejects_invalid_headers() {
+        let error = |data: &[u8]| parse_binary_mesh(data).unwrap_err().to_string();
+
+        let data = triangle(VertexLayout::Standard);
+        assert_eq!(error(&data[..HEADER_SIZE - 1]), "Not a binary mesh");
+        assert_eq!(error(b"RTEX0000000000000000"), "Not a binary mesh");
+        assert_eq!(
+            error(&header(VERSION + 1, 0, 0, 0)),
+            format!("Unsupported binary mesh version {}", VERSION + 1)
+        );
+        assert_eq!(error(&header(VERSION, 4, 0, 0)), "Unknown vertex layout 4");
+    }
+
+    #[test]
+    fn rejects_size_mismatch() {
+        let data = triangle(VertexLayout::Standard);
+        let expected = "Binary mesh size does not match its header";
+        assert_eq!(
+            parse_binary_mesh(&data[..data.len() - 1])
+                .unwrap_err()
+                .to_string(),
+            expected
+        );
+
+        let mut trailing = data.clone();
+        trailing.push(0);
+        assert_eq!(
+            parse_binary_mesh(&trailing).unwrap_err().to_string(),
+            expected
+        );
+
+        // the same bytes read with a larger vertex layout.
+        let mut skinned = data;
+        skinned[8..12].copy_from_slice(&(VertexLayout::Skinned as u32).to_le_bytes());
+        assert!(parse_binary_mesh(&skinned).is_err());
+
+        let huge = header(VERSION, VertexLayout::Skinned as u32, u32::MAX, u32::MAX);
+        assert_eq!(parse_binary_mesh(&huge).unwrap_err().to_string(), expected);
+    }
+}
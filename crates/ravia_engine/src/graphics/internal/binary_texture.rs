@@ -0,0 +1,70 @@
+use crate::{engine::EngineContext, math};
+
+use super::texture::{Texture, TextureFilterMode};
+
+/// File extension of binary textures.
+pub const BINARY_TEXTURE_EXTENSION: &str = "rtex";
+
+const MAGIC: &[u8; 4] = b"RTEX";
+const VERSION: u32 = 1;
+const HEADER_SIZE: usize = 20;
+
+/// Loads a texture from a buffer containing a binary texture.
+///
+/// Binary textures are produced from images by `ravia_build`, with their mip chain generated at
+/// build time. All values are little-endian: a header of magic `b"RTEX"`, format version, width,
+/// height and number of mip levels (each `u32`), followed by every mip level in RGBA8.
+pub fn load_texture_from_binary(
+    ctx: &EngineContext,
+    data: &[u8],
+    filter_mode: TextureFilterMode,
+) -> Result<Texture, anyhow::Error> {
+    let (size, mip_level_count, mips) = parse_binary_texture(data)?;
+    Ok(Texture::new_2d_with_mips(
+        ctx,
+        size,
+        mip_level_count,
+        mips,
+        filter_mode,
+    ))
+}
+
+/// Parses a binary texture into its size, number of mip levels, and the data of every mip level.
+pub(super) fn parse_binary_texture(
+    data: &[u8],
+) -> Result<(math::UVec2, u32, &[u8]), anyhow::Error> {
+    if data.len() < HEADER_SIZE || &data[0..4] != MAGIC {
+        return Err(anyhow::anyhow!("Not a binary texture"));
+    }
+
+    let read_u32 = |offset: usize| u32::from_le_bytes(data[offset..offset + 4].try_into().unwrap());
+
+    let version = read_u32(4);
+    if version != VERSION {
+        return Err(anyhow::anyhow!(
+            "Unsupported binary texture version {}",
+            version
+        ));
+    }
+
+    let size = math::uvec2(read_u32(8), read_u32(12));
+    let mip_level_count = read_u32(16);
+
+    let expected_size: u64 = (0..mip_level_count)
+        .map(|level| mip_level_bytes(size, level))
+        .sum();
+    if (data.len() - HEADER_SIZE) as u64 != expected_size {
+        return Err(anyhow::anyhow!(
+            "Binary texture size does not match its header"
+        ));
+    }
+
+    Ok((size, mip_level_count, &data[HEADER_SIZE..]))
+}
+
+/// Returns the size in bytes of a mip level of an RGBA8 texture.
+pub(super) fn mip_level_bytes(size: math::UVec2, level: u32) -> u64 {
+    let width = (size.x >> level).max(1) as u64;
+    let height = (size.y >> level).max(1) as u64;
+    4 * width * height
+}
@@ -0,0 +1,136 @@
+use crate::ecs;
+
+/// [`RenderBudget`] is a resource limiting the work of the renderer features drawing extra passes
+/// every frame, so that enabling every feature degrades gracefully on weak GPUs.
+///
+/// Over the budget, the features keep their most important work, e.g. the shadow maps of the
+/// lights nearest to the camera, and skip the rest. The budget may be changed at runtime, e.g.
+/// from a graphics options menu.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RenderBudget {
+    /// Maximum number of shadow map passes rendered per frame. The shadow map of a directional
+    /// light is rendered in a single pass, and the shadow map of a point light in six passes, one
+    /// per face of its cube.
+    pub max_shadow_passes: usize,
+    /// GPU memory available to textures, in bytes, if limited.
+    ///
+    /// [`super::texture_streaming::StreamedTexture`]s are streamed within the memory left by the
+    /// other textures, in addition to [`super::texture_streaming::TextureStreamingConfig::budget_bytes`].
+    pub texture_memory_bytes: Option<u64>,
+}
+
+assert_impl_all!(RenderBudget: ecs::systems::Resource);
+
+impl Default for RenderBudget {
+    fn default() -> Self {
+        Self {
+            // a directional light and three point lights.
+            max_shadow_passes: 19,
+            texture_memory_bytes: None,
+        }
+    }
+}
+
+impl RenderBudget {
+    /// Returns the shadow casters to render shadow maps for this frame, by descending priority,
+    /// given with their priority and number of passes.
+    pub fn select_shadow_maps<T>(
+        &self,
+        candidates: impl IntoIterator<Item = (T, f32, usize)>,
+    ) -> Vec<T> {
+        Self::select(candidates, self.max_shadow_passes)
+    }
+
+    /// Returns the texture memory available to streamed textures, given the memory used by the
+    /// other textures.
+    pub(super) fn streaming_bytes(&self, streaming_budget: u64, other_texture_bytes: u64) -> u64 {
+        match self.texture_memory_bytes {
+            Some(limit) => streaming_budget.min(limit.saturating_sub(other_texture_bytes)),
+            None => streaming_budget,
+        }
+    }
+
+    /// Returns the candidates with the highest priority whose costs add up to at most `max`,
+    /// highest first. A candidate over the remaining budget is skipped for the cheaper ones
+    /// after it. Candidates of equal priority keep their order.
+    fn select<T>(candidates: impl IntoIterator<Item = (T, f32, usize)>, max: usize) -> Vec<T> {
+        let mut candidates = candidates.into_iter().collect::<Vec<_>>();
+        candidates.sort_by(|a, b| b.1.total_cmp(&a.1));
+
+        let mut remaining = max;
+        candidates
+            .into_iter()
+            .filter(|(_, _, cost)| {
+                let selected = *cost <= remaining;
+                if selected {
+                    remaining -= cost;
+                }
+                selected
+            })
+            .map(|(item, _, _)| item)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn budget(max_shadow_passes: usize) -> RenderBudget {
+        RenderBudget {
+            max_shadow_passes,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn selects_by_descending_priority() {
+        let candidates = [("far", 1.0, 1), ("near", 3.0, 1), ("middle", 2.0, 1)];
+        assert_eq!(budget(2).select_shadow_maps(candidates), ["near", "middle"]);
+        assert_eq!(
+            budget(10).select_shadow_maps(candidates),
+            ["near", "middle", "far"]
+        );
+        assert!(budget(0).select_shadow_maps(candidates).is_empty());
+    }
+
+    #[test]
+    fn skips_candidates_over_the_remaining_budget() {
+        // the directional light always comes first, and a point light costs six passes.
+        let candidates = [
+            ("point near", 3.0, 6),
+            ("directional", f32::INFINITY, 1),
+            ("point far", 1.0, 6),
+            ("cheap", 2.0, 1),
+        ];
+        assert_eq!(
+            budget(8).select_shadow_maps(candidates),
+            ["directional", "point near", "cheap"]
+        );
+        // the point light does not fit after the directional light, but the cheaper one does.
+        assert_eq!(
+            budget(6).select_shadow_maps(candidates),
+            ["directional", "cheap"]
+        );
+        assert_eq!(budget(19).select_shadow_maps(candidates).len(), 4);
+    }
+
+    #[test]
+    fn keeps_the_order_of_equal_priorities() {
+        let candidates = [(0, 1.0, 1), (1, 1.0, 1), (2, 1.0, 1), (3, 2.0, 1)];
+        assert_eq!(budget(3).select_shadow_maps(candidates), [3, 0, 1]);
+    }
+
+    #[test]
+    fn limits_streaming_to_the_texture_memory_left() {
+        assert_eq!(budget(0).streaming_bytes(100, 50), 100);
+
+        let limited = RenderBudget {
+            texture_memory_bytes: Some(120),
+            ..Default::default()
+        };
+        assert_eq!(limited.streaming_bytes(100, 50), 70);
+        assert_eq!(limited.streaming_bytes(100, 10), 100);
+        assert_eq!(limited.streaming_bytes(100, 200), 0);
+    }
+}
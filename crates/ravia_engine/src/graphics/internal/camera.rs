@@ -1,16 +1,66 @@
 use wgpu::util::DeviceExt;
 
-use crate::{ecs, engine::EngineContext, math};
+use crate::{
+    ecs::{self, IntoQuery},
+    engine::EngineContext,
+    math,
+};
 
-use super::uniform::Uniform;
+use super::{
+    memory::{MemoryAllocation, MemoryCategory},
+    overlay::Overlay,
+    render_texture::RenderTexture,
+    transform::Transform,
+    uniform::Uniform,
+    viewport::Viewport,
+};
+
+/// How a [`Camera`] clears the frame before drawing the scene.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CameraClear {
+    /// Clears the frame to a solid color, in linear RGBA.
+    Color(math::Vec4),
+    /// Draws over the frame drawn by the previous cameras, e.g. for UI overlays.
+    Nothing,
+}
+
+impl Default for CameraClear {
+    fn default() -> Self {
+        Self::Color(math::Vec4::W)
+    }
+}
+
+impl CameraClear {
+    pub(super) fn load_op(&self) -> wgpu::LoadOp<wgpu::Color> {
+        match self {
+            Self::Color(color) => wgpu::LoadOp::Clear(wgpu::Color {
+                r: color.x as f64,
+                g: color.y as f64,
+                b: color.z as f64,
+                a: color.w as f64,
+            }),
+            Self::Nothing => wgpu::LoadOp::Load,
+        }
+    }
+}
 
 /// A [`Camera`] is used to render the scene from a specific point of view.
+///
+/// When the world has several cameras, each camera draws the scene in ascending
+/// [`Camera::order`], after clearing the frame as specified by its [`CameraClear`]. A camera with
+/// a [`Camera::target`] draws into its [`RenderTexture`] instead of the frame. A camera with a
+/// [`Camera::viewport`] draws into a rectangle of the frame, e.g. for split-screen.
 #[derive(Debug)]
 pub struct Camera {
     projection: math::Mat4,
+    clear: CameraClear,
+    order: i32,
+    target: Option<RenderTexture>,
+    viewport: Viewport,
 
     _buffer: wgpu::Buffer,
     bind_group: wgpu::BindGroup,
+    _allocation: MemoryAllocation,
 }
 
 assert_impl_all!(Camera: ecs::storage::Component);
@@ -21,7 +71,7 @@ impl Camera {
             .gpu
             .device
             .create_buffer_init(&wgpu::util::BufferInitDescriptor {
-                label: None,
+                label: Some("ravia_engine::camera"),
                 contents: bytemuck::cast_slice(&[projection]),
                 usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
             });
@@ -30,7 +80,7 @@ impl Camera {
             .gpu
             .device
             .create_bind_group(&wgpu::BindGroupDescriptor {
-                label: None,
+                label: Some("ravia_engine::camera"),
                 layout: &ctx.gpu.default_bind_group_layouts.camera,
                 entries: &[wgpu::BindGroupEntry {
                     binding: 0,
@@ -40,17 +90,34 @@ impl Camera {
 
         Self {
             projection,
+            clear: CameraClear::default(),
+            order: 0,
+            target: None,
+            viewport: Viewport::FULL,
+            _allocation: ctx.gpu.track_memory(MemoryCategory::Uniform, buffer.size()),
             _buffer: buffer,
             bind_group,
         }
     }
 
+    /// Returns a copy of the camera for the device recreated after a device loss, rendering into
+    /// the given target.
+    pub(super) fn restored(&self, ctx: &EngineContext, target: Option<RenderTexture>) -> Self {
+        Self {
+            clear: self.clear,
+            order: self.order,
+            target,
+            viewport: self.viewport,
+            ..Self::new(ctx, self.projection)
+        }
+    }
+
     /// Creates a no-op [`Camera`].
     pub fn noop(ctx: &EngineContext) -> Self {
         Self::new(ctx, math::Mat4::IDENTITY)
     }
 
-    /// Creates a perspective [`Camera`].
+    /// Creates a perspective [`Camera`], with the handedness of [`super::gpu::Gpu::handedness`].
     pub fn perspective(
         ctx: &EngineContext,
         fov_y: f32,
@@ -58,24 +125,124 @@ impl Camera {
         z_near: f32,
         z_far: f32,
     ) -> Self {
-        Self::new(
-            ctx,
-            math::Mat4::perspective_rh(fov_y, aspect_ratio, z_near, z_far),
-        )
+        let projection = ctx
+            .gpu
+            .handedness()
+            .perspective(fov_y, aspect_ratio, z_near, z_far);
+        Self::new(ctx, projection)
     }
 
-    /// Creates a perspective [`Camera`] with the default parameters.
+    /// Creates an orthographic [`Camera`], with the handedness of
+    /// [`super::gpu::Gpu::handedness`].
+    pub fn orthographic(
+        ctx: &EngineContext,
+        left: f32,
+        right: f32,
+        bottom: f32,
+        top: f32,
+        z_near: f32,
+        z_far: f32,
+    ) -> Self {
+        let projection = ctx
+            .gpu
+            .handedness()
+            .orthographic(left, right, bottom, top, z_near, z_far);
+        Self::new(ctx, projection)
+    }
+
+    /// Creates a perspective [`Camera`] with the default parameters, clipping from 10 cm to
+    /// 100 m.
     pub fn perspective_with_defaults(ctx: &EngineContext) -> Self {
         let surface_config = ctx.gpu.surface_config.lock().unwrap();
         let width = surface_config.width as f32;
         let height = surface_config.height as f32;
-        Self::perspective(ctx, 45.0, width / height, 0.1, 100.0)
+        let units_per_meter = ctx.gpu.units_per_meter();
+        Self::perspective(
+            ctx,
+            45.0,
+            width / height,
+            0.1 * units_per_meter,
+            100.0 * units_per_meter,
+        )
+    }
+
+    /// Sets how the camera clears the frame.
+    pub fn with_clear(mut self, clear: CameraClear) -> Self {
+        self.clear = clear;
+        self
+    }
+
+    /// Sets the order in which the camera draws, relative to the other cameras.
+    pub fn with_order(mut self, order: i32) -> Self {
+        self.order = order;
+        self
+    }
+
+    /// Sets the texture the camera renders into, instead of the frame.
+    pub fn with_target(mut self, target: &RenderTexture) -> Self {
+        self.target = Some(target.clone());
+        self
+    }
+
+    /// Sets the rectangle of the frame, or of the target, the camera draws into.
+    pub fn with_viewport(mut self, viewport: Viewport) -> Self {
+        self.viewport = viewport;
+        self
+    }
+
+    /// Returns how the camera clears the frame.
+    pub fn clear(&self) -> CameraClear {
+        self.clear
+    }
+
+    /// Sets how the camera clears the frame.
+    pub fn set_clear(&mut self, clear: CameraClear) {
+        self.clear = clear;
+    }
+
+    /// Returns the order in which the camera draws. Cameras of lower order draw first.
+    pub fn order(&self) -> i32 {
+        self.order
+    }
+
+    /// Sets the order in which the camera draws.
+    pub fn set_order(&mut self, order: i32) {
+        self.order = order;
+    }
+
+    /// Returns the texture the camera renders into, or `None` if it renders into the frame.
+    pub fn target(&self) -> Option<&RenderTexture> {
+        self.target.as_ref()
+    }
+
+    /// Sets the texture the camera renders into, or `None` to render into the frame.
+    pub fn set_target(&mut self, target: Option<&RenderTexture>) {
+        self.target = target.cloned();
+    }
+
+    /// Returns the rectangle of the frame, or of the target, the camera draws into. The camera
+    /// clears only its viewport.
+    pub fn viewport(&self) -> Viewport {
+        self.viewport
+    }
+
+    /// Sets the rectangle of the frame, or of the target, the camera draws into.
+    pub fn set_viewport(&mut self, viewport: Viewport) {
+        self.viewport = viewport;
     }
 
     /// Returns the projection matrix of the camera.
     pub fn projection(&self) -> &math::Mat4 {
         &self.projection
     }
+
+    /// Sets the projection matrix of the camera.
+    pub fn set_projection(&mut self, ctx: &EngineContext, projection: math::Mat4) {
+        self.projection = projection;
+        ctx.gpu
+            .queue
+            .write_buffer(&self._buffer, 0, bytemuck::cast_slice(&[projection]));
+    }
 }
 
 impl Uniform for Camera {
@@ -83,3 +250,16 @@ impl Uniform for Camera {
         &self.bind_group
     }
 }
+
+/// Returns the position of the primary camera of the world, i.e. the scene camera drawn first
+/// into the frame, other than the [`Overlay`] cameras and the cameras rendering into textures.
+///
+/// Within a system, the system should read [`Camera`], [`Overlay`] and [`Transform`].
+pub(crate) fn primary_camera_position<W: ecs::EntityStore>(world: &W) -> Option<math::Vec3> {
+    <(&Camera, &Transform)>::query()
+        .filter(!ecs::component::<Overlay>())
+        .iter(world)
+        .filter(|(camera, _)| camera.target().is_none())
+        .min_by_key(|(camera, _)| camera.order())
+        .map(|(_, transform)| *transform.position())
+}
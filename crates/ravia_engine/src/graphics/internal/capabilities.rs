@@ -0,0 +1,181 @@
+use log::warn;
+
+use crate::ecs;
+
+use super::{gpu::GpuConfig, pipeline_cache::PipelineCache, present::DITHERING_FORMAT};
+
+/// [`GpuCapabilities`] is a resource reporting what the GPU adapter supports, and which engine
+/// features have been adjusted or disabled on startup because the adapter cannot run them.
+///
+/// The adapter is probed once when the [`super::gpu::Gpu`] is created: the device is requested
+/// with the best limits the adapter supports, e.g. the downlevel limits on WebGL2 or old GL
+/// drivers, rather than failing to boot or panicking mid-frame. The decisions are logged, and
+/// listed by [`GpuCapabilities::decisions`].
+#[derive(Debug, Clone)]
+pub struct GpuCapabilities {
+    adapter: wgpu::AdapterInfo,
+    limits: wgpu::Limits,
+    features: wgpu::Features,
+    downlevel: wgpu::DownlevelCapabilities,
+    dithering: bool,
+    decisions: Vec<String>,
+}
+
+assert_impl_all!(GpuCapabilities: ecs::systems::Resource);
+
+impl GpuCapabilities {
+    /// Probes the adapter, choosing the limits and features to request the device with.
+    pub(super) fn probe(adapter: &wgpu::Adapter, config: &GpuConfig) -> Self {
+        let adapter_limits = adapter.limits();
+        let downlevel = adapter.get_downlevel_capabilities();
+        let mut decisions = vec![];
+
+        let tiers = [
+            ("default", wgpu::Limits::default()),
+            ("downlevel", wgpu::Limits::downlevel_defaults()),
+            ("WebGL2", wgpu::Limits::downlevel_webgl2_defaults()),
+        ];
+        let limits = match tiers
+            .iter()
+            .position(|(_, limits)| limits.check_limits(&adapter_limits))
+        {
+            Some(0) => tiers[0].1.clone(),
+            Some(i) => {
+                decisions.push(format!(
+                    "limits lowered to the {} limits, as the adapter does not support the default limits",
+                    tiers[i].0
+                ));
+                tiers[i].1.clone().using_resolution(adapter_limits.clone())
+            }
+            None => {
+                decisions.push(
+                    "limits lowered to the limits of the adapter, below the WebGL2 limits"
+                        .to_string(),
+                );
+                adapter_limits.clone()
+            }
+        };
+
+        let mut features = PipelineCache::required_features(adapter, config.pipeline_cache_path);
+        if config.pipeline_cache_path.is_some()
+            && !features.contains(wgpu::Features::PIPELINE_CACHE)
+        {
+            decisions
+                .push("pipeline cache disabled, as the adapter does not support it".to_string());
+        }
+
+        if config.polygon_modes {
+            let polygon_modes =
+                wgpu::Features::POLYGON_MODE_LINE | wgpu::Features::POLYGON_MODE_POINT;
+            features |= adapter.features() & polygon_modes;
+            let missing = polygon_modes - adapter.features();
+            if !missing.is_empty() {
+                decisions.push(format!(
+                    "polygon modes {:?} disabled, as the adapter does not support them",
+                    missing
+                ));
+            }
+        }
+
+        let dithering = config.dithering
+            && adapter
+                .get_texture_format_features(DITHERING_FORMAT)
+                .allowed_usages
+                .contains(
+                    wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING,
+                );
+        if config.dithering && !dithering {
+            decisions.push(format!(
+                "dithering disabled, as the adapter cannot render into {:?}",
+                DITHERING_FORMAT
+            ));
+        }
+
+        if !downlevel.is_webgpu_compliant() {
+            decisions.push(format!(
+                "adapter is not WebGPU compliant, missing {:?}",
+                wgpu::DownlevelFlags::compliant() - downlevel.flags
+            ));
+        }
+
+        let mut capabilities = Self {
+            adapter: adapter.get_info(),
+            limits,
+            features,
+            downlevel,
+            dithering,
+            decisions,
+        };
+        if !capabilities.compute() {
+            capabilities
+                .decisions
+                .push("compute shaders disabled, as the adapter does not support them".to_string());
+        }
+
+        for decision in &capabilities.decisions {
+            warn!(target: "ravia_engine::graphics::gpu", "{}: {}", capabilities.adapter.name, decision);
+        }
+        capabilities
+    }
+
+    /// Returns the information of the adapter, e.g. its name and backend.
+    pub fn adapter(&self) -> &wgpu::AdapterInfo {
+        &self.adapter
+    }
+
+    /// Returns the limits the device has been requested with.
+    pub fn limits(&self) -> &wgpu::Limits {
+        &self.limits
+    }
+
+    /// Returns the features the device has been requested with.
+    pub fn features(&self) -> wgpu::Features {
+        self.features
+    }
+
+    /// Returns the capabilities of the adapter below the WebGPU specification, e.g. on GL.
+    pub fn downlevel(&self) -> &wgpu::DownlevelCapabilities {
+        &self.downlevel
+    }
+
+    /// Returns true if compute shaders can be dispatched.
+    pub fn compute(&self) -> bool {
+        self.downlevel
+            .flags
+            .contains(wgpu::DownlevelFlags::COMPUTE_SHADERS)
+            && self.limits.max_compute_workgroups_per_dimension > 0
+    }
+
+    /// Returns true if the scene is rendered into [`DITHERING_FORMAT`] and dithered when
+    /// presented, see [`GpuConfig::dithering`].
+    pub fn dithering(&self) -> bool {
+        self.dithering
+    }
+
+    /// Returns true if pipelines can be created with the polygon mode, see
+    /// [`GpuConfig::polygon_modes`].
+    pub fn polygon_mode(&self, polygon_mode: wgpu::PolygonMode) -> bool {
+        match polygon_mode {
+            wgpu::PolygonMode::Fill => true,
+            wgpu::PolygonMode::Line => self.features.contains(wgpu::Features::POLYGON_MODE_LINE),
+            wgpu::PolygonMode::Point => self.features.contains(wgpu::Features::POLYGON_MODE_POINT),
+        }
+    }
+
+    /// Returns the largest width or height of a 2D texture.
+    ///
+    /// Larger textures are created without their largest mip levels, if they have a mip chain.
+    pub fn max_texture_size(&self) -> u32 {
+        self.limits.max_texture_dimension_2d
+    }
+
+    /// Returns the largest number of layers of a texture array.
+    pub fn max_texture_array_layers(&self) -> u32 {
+        self.limits.max_texture_array_layers
+    }
+
+    /// Returns the engine features adjusted or disabled on startup, in plain words.
+    pub fn decisions(&self) -> &[String] {
+        &self.decisions
+    }
+}
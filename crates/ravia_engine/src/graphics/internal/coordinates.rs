@@ -0,0 +1,157 @@
+use crate::math;
+
+/// Handedness of a coordinate system, where x points right and y points up.
+///
+/// The engine is right-handed by default: cameras look towards -z, as in OpenGL, glTF and most
+/// modeling tools. In a left-handed coordinate system, cameras look towards +z, as in Direct3D
+/// and Unity.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum Handedness {
+    /// z points towards the viewer.
+    #[default]
+    Right,
+    /// z points away from the viewer.
+    Left,
+}
+
+impl Handedness {
+    /// Returns the direction a camera looks towards, in its local space.
+    pub fn forward(&self) -> math::Vec3 {
+        match self {
+            Self::Right => math::Vec3::NEG_Z,
+            Self::Left => math::Vec3::Z,
+        }
+    }
+
+    /// Returns a view matrix of the handedness, looking from `eye` towards `direction`.
+    pub fn look_to(&self, eye: math::Vec3, direction: math::Vec3, up: math::Vec3) -> math::Mat4 {
+        match self {
+            Self::Right => math::Mat4::look_to_rh(eye, direction, up),
+            Self::Left => math::Mat4::look_to_lh(eye, direction, up),
+        }
+    }
+
+    /// Returns a perspective projection of the handedness.
+    pub fn perspective(
+        &self,
+        fov_y: f32,
+        aspect_ratio: f32,
+        z_near: f32,
+        z_far: f32,
+    ) -> math::Mat4 {
+        match self {
+            Self::Right => math::Mat4::perspective_rh(fov_y, aspect_ratio, z_near, z_far),
+            Self::Left => math::Mat4::perspective_lh(fov_y, aspect_ratio, z_near, z_far),
+        }
+    }
+
+    /// Returns an orthographic projection of the handedness.
+    pub fn orthographic(
+        &self,
+        left: f32,
+        right: f32,
+        bottom: f32,
+        top: f32,
+        z_near: f32,
+        z_far: f32,
+    ) -> math::Mat4 {
+        match self {
+            Self::Right => math::Mat4::orthographic_rh(left, right, bottom, top, z_near, z_far),
+            Self::Left => math::Mat4::orthographic_lh(left, right, bottom, top, z_near, z_far),
+        }
+    }
+}
+
+/// [`MeshImportOptions`] converts meshes authored in another coordinate system or unit when they
+/// are loaded, so that they are neither mirrored, inside-out, nor a hundred times too large.
+///
+/// Flipping z mirrors the mesh, which also reverses the winding of its triangles as seen by the
+/// renderer; flip both to convert between handedness, see [`MeshImportOptions::converting`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MeshImportOptions {
+    /// Negates the z coordinate of the positions and normals.
+    pub flip_z: bool,
+    /// Reverses the winding of the triangles, turning back faces into front faces.
+    pub flip_winding: bool,
+    /// Length of a unit of the mesh in meters, e.g. 0.01 for meshes authored in centimeters. The
+    /// positions are scaled into [`super::gpu::GpuConfig::units_per_meter`].
+    pub meters_per_unit: f32,
+}
+
+impl Default for MeshImportOptions {
+    fn default() -> Self {
+        Self {
+            flip_z: false,
+            flip_winding: false,
+            meters_per_unit: 1.0,
+        }
+    }
+}
+
+impl MeshImportOptions {
+    /// Returns the options converting meshes authored with the handedness `from` into `to`.
+    pub fn converting(from: Handedness, to: Handedness) -> Self {
+        let flip = from != to;
+        Self {
+            flip_z: flip,
+            flip_winding: flip,
+            ..Default::default()
+        }
+    }
+
+    /// Specifies the length of a unit of the mesh in meters.
+    pub fn with_meters_per_unit(mut self, meters_per_unit: f32) -> Self {
+        self.meters_per_unit = meters_per_unit;
+        self
+    }
+
+    /// Returns the factor the positions are scaled by, into the given units per meter.
+    pub fn scale(&self, units_per_meter: f32) -> f32 {
+        self.meters_per_unit * units_per_meter
+    }
+
+    /// Returns true if the options leave the meshes unchanged.
+    pub(super) fn is_identity(&self, units_per_meter: f32) -> bool {
+        !self.flip_z && !self.flip_winding && self.scale(units_per_meter) == 1.0
+    }
+
+    /// Applies the options to the vertex data of a standard vertex type, i.e. whose every vertex
+    /// starts with a position, a texture coordinate and a normal, followed by other attributes.
+    pub(super) fn apply(
+        &self,
+        units_per_meter: f32,
+        vertices: &mut [u8],
+        stride: usize,
+        indices: &mut [u32],
+    ) {
+        /// Offsets of the z coordinate of the position and of the normal.
+        const Z_OFFSETS: [usize; 2] = [8, 28];
+
+        let scale = self.scale(units_per_meter);
+        if scale != 1.0 {
+            for vertex in vertices.chunks_exact_mut(stride) {
+                for offset in [0, 4, 8] {
+                    let coordinate = &mut vertex[offset..offset + 4];
+                    let scaled = f32::from_le_bytes(coordinate.try_into().unwrap()) * scale;
+                    coordinate.copy_from_slice(&scaled.to_le_bytes());
+                }
+            }
+        }
+
+        if self.flip_z {
+            for vertex in vertices.chunks_exact_mut(stride) {
+                for offset in Z_OFFSETS {
+                    let z = &mut vertex[offset..offset + 4];
+                    let flipped = -f32::from_le_bytes(z.try_into().unwrap());
+                    z.copy_from_slice(&flipped.to_le_bytes());
+                }
+            }
+        }
+
+        if self.flip_winding {
+            for triangle in indices.chunks_exact_mut(3) {
+                triangle.swap(1, 2);
+            }
+        }
+    }
+}
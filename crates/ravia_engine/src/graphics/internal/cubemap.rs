@@ -0,0 +1,507 @@
+use wgpu::util::DeviceExt;
+
+use crate::{engine::EngineContext, math};
+
+use super::{
+    binary_texture::parse_binary_texture,
+    memory::{MemoryAllocation, MemoryCategory},
+};
+
+/// File extension of Radiance HDR images.
+pub const HDR_EXTENSION: &str = "hdr";
+
+/// Format of the faces of a [`Cubemap`] projected from an HDR image, keeping the high dynamic
+/// range of the source.
+const CUBEMAP_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba16Float;
+
+/// Format of the faces of a [`Cubemap`] built from six images.
+const IMAGE_CUBEMAP_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba8UnormSrgb;
+
+const EQUIRECT_SOURCE: &str = "
+@group(0) @binding(0) var equirect: texture_2d<f32>;
+
+struct VertexOutput {
+  @builtin(position) position: vec4<f32>,
+  @location(0) @interpolate(flat) face: u32,
+};
+
+@vertex
+fn vs_main(@builtin(vertex_index) index: u32, @builtin(instance_index) face: u32) -> VertexOutput {
+  // a single triangle covering the whole face.
+  let ndc = vec2<f32>(f32(index / 2u) * 4.0 - 1.0, f32(index % 2u) * 4.0 - 1.0);
+  return VertexOutput(vec4<f32>(ndc, 0.0, 1.0), face);
+}
+
+fn face_direction(face: u32, uv: vec2<f32>) -> vec3<f32> {
+  // faces in the order +x, -x, +y, -y, +z, -z, with v pointing down.
+  switch face {
+    case 0u: { return vec3<f32>(1.0, -uv.y, -uv.x); }
+    case 1u: { return vec3<f32>(-1.0, -uv.y, uv.x); }
+    case 2u: { return vec3<f32>(uv.x, 1.0, uv.y); }
+    case 3u: { return vec3<f32>(uv.x, -1.0, -uv.y); }
+    case 4u: { return vec3<f32>(uv.x, -uv.y, 1.0); }
+    default: { return vec3<f32>(-uv.x, -uv.y, -1.0); }
+  }
+}
+
+fn load(texel: vec2<i32>, size: vec2<i32>) -> vec4<f32> {
+  // wraps around horizontally, and clamps at the poles.
+  let x = ((texel.x % size.x) + size.x) % size.x;
+  let y = clamp(texel.y, 0, size.y - 1);
+  return textureLoad(equirect, vec2<i32>(x, y), 0);
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+  let face_size = f32(FACE_SIZE);
+  let uv = in.position.xy / face_size * 2.0 - 1.0;
+  let direction = normalize(face_direction(in.face, uv));
+
+  // the center of the image faces -z, the forward direction of the cameras.
+  let pi = 3.14159265359;
+  let equirect_uv = vec2<f32>(
+    0.5 + atan2(direction.x, -direction.z) / (2.0 * pi),
+    acos(clamp(direction.y, -1.0, 1.0)) / pi,
+  );
+
+  // bilinear filtering, as float textures may not be filterable.
+  let size = vec2<i32>(textureDimensions(equirect));
+  let position = equirect_uv * vec2<f32>(size) - 0.5;
+  let base = vec2<i32>(floor(position));
+  let t = fract(position);
+  let top = mix(load(base, size), load(base + vec2<i32>(1, 0), size), t.x);
+  let bottom = mix(load(base + vec2<i32>(0, 1), size), load(base + vec2<i32>(1, 1), size), t.x);
+  return vec4<f32>(mix(top, bottom, t.y).rgb, 1.0);
+}
+";
+
+/// A [`Cubemap`] holds a cube texture with six square faces, e.g. an environment for a skybox
+/// or image based lighting.
+///
+/// The faces are in the order +x, -x, +y, -y, +z, -z. A cubemap projected from an HDR image
+/// keeps its high dynamic range in a half float format, while a cubemap built from six images
+/// keeps their 8-bit sRGB format.
+#[derive(Debug)]
+pub struct Cubemap {
+    texture: wgpu::Texture,
+    view: wgpu::TextureView,
+    _allocation: MemoryAllocation,
+}
+
+impl Cubemap {
+    /// Creates a new [`Cubemap`] by projecting an equirectangular (latitude-longitude) image
+    /// onto its faces on the GPU.
+    ///
+    /// `data` contains the pixels of the image in linear RGBA, row by row from the top. The
+    /// center of the image faces -z.
+    pub fn from_equirect(
+        ctx: &EngineContext,
+        size: math::UVec2,
+        data: &[f32],
+        face_size: u32,
+    ) -> Self {
+        const LABEL: &str = "ravia_engine::cubemap";
+
+        let gpu = &ctx.gpu;
+        let face_size = face_size.clamp(1, gpu.capabilities().max_texture_size());
+
+        let equirect = gpu.error_scope(LABEL, |device| {
+            device.create_texture_with_data(
+                &gpu.queue,
+                &wgpu::TextureDescriptor {
+                    label: Some(LABEL),
+                    size: wgpu::Extent3d {
+                        width: size.x,
+                        height: size.y,
+                        depth_or_array_layers: 1,
+                    },
+                    mip_level_count: 1,
+                    sample_count: 1,
+                    dimension: wgpu::TextureDimension::D2,
+                    format: wgpu::TextureFormat::Rgba32Float,
+                    usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+                    view_formats: &[],
+                },
+                Default::default(),
+                bytemuck::cast_slice(data),
+            )
+        });
+
+        let texture = gpu.device.create_texture(&wgpu::TextureDescriptor {
+            label: Some(LABEL),
+            size: wgpu::Extent3d {
+                width: face_size,
+                height: face_size,
+                depth_or_array_layers: 6,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: CUBEMAP_FORMAT,
+            usage: wgpu::TextureUsages::TEXTURE_BINDING
+                | wgpu::TextureUsages::RENDER_ATTACHMENT
+                | wgpu::TextureUsages::COPY_SRC,
+            view_formats: &[],
+        });
+
+        let bind_group_layout =
+            gpu.device
+                .create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                    label: Some(LABEL),
+                    entries: &[wgpu::BindGroupLayoutEntry {
+                        binding: 0,
+                        visibility: wgpu::ShaderStages::FRAGMENT,
+                        ty: wgpu::BindingType::Texture {
+                            sample_type: wgpu::TextureSampleType::Float { filterable: false },
+                            view_dimension: wgpu::TextureViewDimension::D2,
+                            multisampled: false,
+                        },
+                        count: None,
+                    }],
+                });
+        let bind_group = gpu.device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some(LABEL),
+            layout: &bind_group_layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: wgpu::BindingResource::TextureView(
+                    &equirect.create_view(&wgpu::TextureViewDescriptor::default()),
+                ),
+            }],
+        });
+
+        let shader_module = gpu
+            .device
+            .create_shader_module(wgpu::ShaderModuleDescriptor {
+                label: Some(LABEL),
+                source: wgpu::ShaderSource::Wgsl(
+                    EQUIRECT_SOURCE
+                        .replace("FACE_SIZE", &face_size.to_string())
+                        .into(),
+                ),
+            });
+        let pipeline_layout = gpu
+            .device
+            .create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                label: Some(LABEL),
+                bind_group_layouts: &[&bind_group_layout],
+                push_constant_ranges: &[],
+            });
+        let pipeline = gpu
+            .device
+            .create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+                label: Some(LABEL),
+                layout: Some(&pipeline_layout),
+                vertex: wgpu::VertexState {
+                    module: &shader_module,
+                    entry_point: Some("vs_main"),
+                    buffers: &[],
+                    compilation_options: wgpu::PipelineCompilationOptions::default(),
+                },
+                fragment: Some(wgpu::FragmentState {
+                    module: &shader_module,
+                    entry_point: Some("fs_main"),
+                    targets: &[Some(wgpu::ColorTargetState {
+                        format: CUBEMAP_FORMAT,
+                        blend: None,
+                        write_mask: wgpu::ColorWrites::ALL,
+                    })],
+                    compilation_options: wgpu::PipelineCompilationOptions::default(),
+                }),
+                primitive: wgpu::PrimitiveState::default(),
+                depth_stencil: None,
+                multisample: wgpu::MultisampleState::default(),
+                multiview: None,
+                cache: None,
+            });
+
+        let mut command_encoder = gpu
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor { label: Some(LABEL) });
+        for face in 0..6 {
+            let face_view = texture.create_view(&wgpu::TextureViewDescriptor {
+                label: Some(LABEL),
+                dimension: Some(wgpu::TextureViewDimension::D2),
+                base_array_layer: face,
+                array_layer_count: Some(1),
+                ..Default::default()
+            });
+            let mut render_pass = command_encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some(LABEL),
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view: &face_view,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(wgpu::Color::BLACK),
+                        store: wgpu::StoreOp::Store,
+                    },
+                })],
+                depth_stencil_attachment: None,
+                occlusion_query_set: None,
+                timestamp_writes: None,
+            });
+            render_pass.set_pipeline(&pipeline);
+            render_pass.set_bind_group(0, &bind_group, &[]);
+            render_pass.draw(0..3, face..face + 1);
+        }
+        gpu.queue.submit(std::iter::once(command_encoder.finish()));
+
+        let view = texture.create_view(&wgpu::TextureViewDescriptor {
+            label: Some(LABEL),
+            dimension: Some(wgpu::TextureViewDimension::Cube),
+            ..Default::default()
+        });
+
+        // the faces are created with an 8 bytes per pixel format.
+        let bytes = face_size as u64 * face_size as u64 * 6 * 8;
+        Self {
+            _allocation: gpu.track_memory(MemoryCategory::Texture, bytes),
+            texture,
+            view,
+        }
+    }
+
+    /// Creates a new [`Cubemap`] from the mip chains of six square faces of the same size, in
+    /// RGBA8 sRGB, see [`load_cubemap_from_binary`].
+    fn from_faces(
+        ctx: &EngineContext,
+        face_size: u32,
+        mip_level_count: u32,
+        faces: [&[u8]; 6],
+    ) -> Self {
+        const LABEL: &str = "ravia_engine::cubemap";
+
+        let max_size = ctx.gpu.capabilities().max_texture_size();
+        if face_size > max_size {
+            ctx.gpu.report_error(format!(
+                "{}: face size {} exceeds the maximum texture size {}",
+                LABEL, face_size, max_size
+            ));
+        }
+
+        // the faces are uploaded layer by layer, each with its whole mip chain.
+        let data = faces.concat();
+        let texture = ctx.gpu.error_scope(LABEL, |device| {
+            device.create_texture_with_data(
+                &ctx.gpu.queue,
+                &wgpu::TextureDescriptor {
+                    label: Some(LABEL),
+                    size: wgpu::Extent3d {
+                        width: face_size,
+                        height: face_size,
+                        depth_or_array_layers: 6,
+                    },
+                    mip_level_count,
+                    sample_count: 1,
+                    dimension: wgpu::TextureDimension::D2,
+                    format: IMAGE_CUBEMAP_FORMAT,
+                    usage: wgpu::TextureUsages::TEXTURE_BINDING
+                        | wgpu::TextureUsages::COPY_DST
+                        | wgpu::TextureUsages::COPY_SRC,
+                    view_formats: &[],
+                },
+                wgpu::util::TextureDataOrder::LayerMajor,
+                &data,
+            )
+        });
+
+        let view = texture.create_view(&wgpu::TextureViewDescriptor {
+            label: Some(LABEL),
+            dimension: Some(wgpu::TextureViewDimension::Cube),
+            ..Default::default()
+        });
+
+        Self {
+            _allocation: ctx
+                .gpu
+                .track_memory(MemoryCategory::Texture, data.len() as u64),
+            texture,
+            view,
+        }
+    }
+
+    /// Returns the width and height of a face.
+    pub fn face_size(&self) -> u32 {
+        self.texture.width()
+    }
+
+    /// Returns the underlying [`wgpu::Texture`], with a layer per face.
+    pub fn texture(&self) -> &wgpu::Texture {
+        &self.texture
+    }
+
+    /// Returns the cube view of the texture.
+    pub fn view(&self) -> &wgpu::TextureView {
+        &self.view
+    }
+}
+
+/// Loads a cubemap from a buffer containing an equirectangular Radiance HDR image, e.g. an
+/// environment map downloaded as `.hdr`.
+///
+/// Only the common format of run-length encoded RGBE pixels, stored top to bottom, is supported.
+pub fn load_cubemap_from_hdr(
+    ctx: &EngineContext,
+    data: &[u8],
+    face_size: u32,
+) -> Result<Cubemap, anyhow::Error> {
+    let (size, pixels) = parse_radiance_hdr(data)?;
+    Ok(Cubemap::from_equirect(ctx, size, &pixels, face_size))
+}
+
+/// Loads a cubemap from six buffers containing binary textures, one per face in the order +x,
+/// -x, +y, -y, +z, -z, e.g. the faces of a skybox.
+///
+/// The faces must be square textures of the same size and number of mip levels, with their rows
+/// from the top as seen from the inside of the cube, i.e. the common layout of cubemap faces.
+pub fn load_cubemap_from_binary(
+    ctx: &EngineContext,
+    faces: [&[u8]; 6],
+) -> Result<Cubemap, anyhow::Error> {
+    let (size, mip_level_count, _) = parse_binary_texture(faces[0])?;
+    if size.x != size.y {
+        return Err(anyhow::anyhow!(
+            "Cubemap faces must be square, got {}x{}",
+            size.x,
+            size.y
+        ));
+    }
+
+    let mut data = [&[][..]; 6];
+    for (i, face) in faces.iter().enumerate() {
+        let (face_size, face_mip_level_count, mips) = parse_binary_texture(face)?;
+        if face_size != size || face_mip_level_count != mip_level_count {
+            return Err(anyhow::anyhow!(
+                "Cubemap face {} is {}x{} with {} mip levels, expected {}x{} with {}",
+                i,
+                face_size.x,
+                face_size.y,
+                face_mip_level_count,
+                size.x,
+                size.y,
+                mip_level_count
+            ));
+        }
+        data[i] = mips;
+    }
+
+    Ok(Cubemap::from_faces(ctx, size.x, mip_level_count, data))
+}
+
+/// Parses a Radiance HDR image into its size and its pixels in linear RGBA.
+fn parse_radiance_hdr(data: &[u8]) -> Result<(math::UVec2, Vec<f32>), anyhow::Error> {
+    if !data.starts_with(b"#?") {
+        return Err(anyhow::anyhow!("Not a Radiance HDR image"));
+    }
+
+    let mut offset = 0;
+    let mut next_line = || -> Result<&[u8], anyhow::Error> {
+        let end = data[offset..]
+            .iter()
+            .position(|b| *b == b'\n')
+            .ok_or_else(|| anyhow::anyhow!("Unexpected end of HDR header"))?;
+        let line = &data[offset..offset + end];
+        offset += end + 1;
+        Ok(line)
+    };
+
+    // the header ends with an empty line.
+    loop {
+        let line = next_line()?;
+        if line.is_empty() {
+            break;
+        }
+        if let Some(format) = line.strip_prefix(b"FORMAT=") {
+            if format != b"32-bit_rle_rgbe" {
+                return Err(anyhow::anyhow!(
+                    "Unsupported HDR format {}",
+                    String::from_utf8_lossy(format)
+                ));
+            }
+        }
+    }
+
+    let resolution = String::from_utf8_lossy(next_line()?).to_string();
+    let size = match resolution.split_whitespace().collect::<Vec<_>>()[..] {
+        ["-Y", height, "+X", width] => math::uvec2(width.parse()?, height.parse()?),
+        _ => {
+            return Err(anyhow::anyhow!(
+                "Unsupported HDR orientation {}",
+                resolution
+            ))
+        }
+    };
+
+    let width = size.x as usize;
+    let mut rgbe = vec![0u8; width * size.y as usize * 4];
+    let mut input = &data[offset..];
+    for scanline in rgbe.chunks_exact_mut(width * 4) {
+        input = read_scanline(input, scanline)?;
+    }
+
+    let pixels = rgbe
+        .chunks_exact(4)
+        .flat_map(|p| {
+            let scale = if p[3] == 0 {
+                0.0
+            } else {
+                2f32.powi(p[3] as i32 - 136)
+            };
+            [
+                p[0] as f32 * scale,
+                p[1] as f32 * scale,
+                p[2] as f32 * scale,
+                1.0,
+            ]
+        })
+        .collect();
+    Ok((size, pixels))
+}
+
+/// Reads a scanline of RGBE pixels, either run-length encoded or flat, and returns the rest of the
+/// input.
+fn read_scanline<'a>(input: &'a [u8], scanline: &mut [u8]) -> Result<&'a [u8], anyhow::Error> {
+    let unexpected_end = || anyhow::anyhow!("Unexpected end of HDR data");
+    let width = scanline.len() / 4;
+
+    let encoded = (8..=0x7fff).contains(&width)
+        && input.len() >= 4
+        && input[0] == 2
+        && input[1] == 2
+        && ((input[2] as usize) << 8 | input[3] as usize) == width;
+    if !encoded {
+        let flat = input.get(..scanline.len()).ok_or_else(unexpected_end)?;
+        scanline.copy_from_slice(flat);
+        return Ok(&input[scanline.len()..]);
+    }
+
+    // every channel is encoded separately, as runs of a value or literal values.
+    let mut input = &input[4..];
+    for channel in 0..4 {
+        let mut x = 0;
+        while x < width {
+            let (&count, rest) = input.split_first().ok_or_else(unexpected_end)?;
+            let run = count > 128;
+            let count = if run { count - 128 } else { count } as usize;
+            if count == 0 || x + count > width {
+                return Err(anyhow::anyhow!("Invalid HDR run length"));
+            }
+
+            if run {
+                let (&value, rest) = rest.split_first().ok_or_else(unexpected_end)?;
+                for i in x..x + count {
+                    scanline[i * 4 + channel] = value;
+                }
+                input = rest;
+            } else {
+                let values = rest.get(..count).ok_or_else(unexpected_end)?;
+                for (i, value) in values.iter().enumerate() {
+                    scanline[(x + i) * 4 + channel] = *value;
+                }
+                input = &rest[count..];
+            }
+            x += count;
+        }
+    }
+    Ok(input)
+}
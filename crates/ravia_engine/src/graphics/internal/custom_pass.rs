@@ -0,0 +1,150 @@
+use core::fmt;
+use std::sync::{Arc, Mutex};
+
+use crate::{ecs, math, time};
+
+use super::gpu::Gpu;
+
+/// The point of the frame where a [`CustomPass`] is recorded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CustomPassStage {
+    /// After every scene camera, before the [`super::post_process::PostProcessStack`] and the
+    /// [`super::overlay::Overlay`] cameras. The depth view holds the depth of the last scene
+    /// camera.
+    AfterScene,
+    /// After every camera, into the output of the [`super::post_process::PostProcessStack`]. The
+    /// depth view holds the depth of the last camera.
+    AfterOverlays,
+}
+
+/// What a [`CustomPass`] records its commands with.
+pub struct CustomPassContext<'a> {
+    pub gpu: &'a Gpu,
+    pub world: &'a ecs::World,
+    pub time: &'a time::Time,
+    /// The encoder of the frame, recording after the passes of the engine.
+    pub encoder: &'a mut wgpu::CommandEncoder,
+    /// The view the scene is rendered into at the stage of the pass. It is not the target itself
+    /// while a display adjustment or post-processing effect is applied.
+    pub color: &'a wgpu::TextureView,
+    pub color_format: wgpu::TextureFormat,
+    /// The depth buffer of the scene passes.
+    pub depth: &'a wgpu::TextureView,
+    pub depth_format: wgpu::TextureFormat,
+    /// Size of the views, in pixels.
+    pub size: math::UVec2,
+    /// The projection and view of the primary camera, or the identity without one.
+    pub view_projection: math::Mat4,
+    /// Number of draw calls of the pass, reported in the [`super::frame_graph::FrameGraph`].
+    pub draw_calls: u32,
+}
+
+/// A [`CustomPass`] records render passes of the application into the frame, e.g. an effect the
+/// engine does not provide, without forking [`Gpu::render`]. See [`Gpu::custom_passes`].
+///
+/// Closures taking a [`CustomPassContext`] are custom passes:
+///
+/// ```ignore
+/// ctx.gpu.custom_passes().add("outline", CustomPassStage::AfterScene, |ctx: &mut CustomPassContext| {
+///     let mut pass = ctx.encoder.begin_render_pass(&wgpu::RenderPassDescriptor { .. });
+///     ..
+/// });
+/// ```
+///
+/// The color view is loaded and stored by the passes of the engine, so the pass draws over the
+/// frame with [`wgpu::LoadOp::Load`]. Pipelines target [`CustomPassContext::color_format`].
+pub trait CustomPass: Send + Sync {
+    fn record(&self, ctx: &mut CustomPassContext);
+}
+
+impl<F: Fn(&mut CustomPassContext) + Send + Sync> CustomPass for F {
+    fn record(&self, ctx: &mut CustomPassContext) {
+        self(ctx)
+    }
+}
+
+/// Identifies a pass added to the [`CustomPasses`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct CustomPassId(u64);
+
+struct CustomPassState {
+    id: CustomPassId,
+    label: String,
+    stage: CustomPassStage,
+    pass: Arc<dyn CustomPass>,
+}
+
+impl fmt::Debug for CustomPassState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CustomPassState")
+            .field("id", &self.id)
+            .field("label", &self.label)
+            .field("stage", &self.stage)
+            .finish_non_exhaustive()
+    }
+}
+
+/// [`CustomPasses`] holds the [`CustomPass`]es recorded into every frame, by stage in the order
+/// they are added.
+#[derive(Debug, Default)]
+pub struct CustomPasses {
+    passes: Mutex<Vec<CustomPassState>>,
+    next_id: Mutex<u64>,
+}
+
+impl CustomPasses {
+    /// Adds a pass recorded at the stage of every frame.
+    pub fn add(
+        &self,
+        label: &str,
+        stage: CustomPassStage,
+        pass: impl CustomPass + 'static,
+    ) -> CustomPassId {
+        let mut next_id = self.next_id.lock().unwrap();
+        let id = CustomPassId(*next_id);
+        *next_id += 1;
+
+        self.passes.lock().unwrap().push(CustomPassState {
+            id,
+            label: label.to_string(),
+            stage,
+            pass: Arc::new(pass),
+        });
+        id
+    }
+
+    /// Removes the pass. Returns false if it has already been removed.
+    pub fn remove(&self, id: CustomPassId) -> bool {
+        let mut passes = self.passes.lock().unwrap();
+        let len = passes.len();
+        passes.retain(|state| state.id != id);
+        passes.len() != len
+    }
+
+    /// Removes every pass.
+    pub fn clear(&self) {
+        self.passes.lock().unwrap().clear();
+    }
+
+    /// Returns the number of passes.
+    pub fn len(&self) -> usize {
+        self.passes.lock().unwrap().len()
+    }
+
+    /// Returns true if there is no pass.
+    pub fn is_empty(&self) -> bool {
+        self.passes.lock().unwrap().is_empty()
+    }
+
+    /// Returns the passes of the stage with their labels, so that they are recorded without
+    /// holding the lock, e.g. while a pass adds another.
+    pub(super) fn stage(&self, stage: CustomPassStage) -> Vec<(String, Arc<dyn CustomPass>)> {
+        self.passes
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|state| state.stage == stage)
+            .map(|state| (state.label.clone(), state.pass.clone()))
+            .collect()
+    }
+}
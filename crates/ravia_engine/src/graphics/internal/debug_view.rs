@@ -0,0 +1,334 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+use super::{
+    depth::depth_stencil_state,
+    gpu::Gpu,
+    preprocessor::preprocess,
+    shader::{strip_index_format, PlaceholderKey},
+};
+
+/// An intermediate value of the scene rendered in place of the materials, to verify the
+/// attributes and color spaces of assets and shaders. See [`Gpu::set_debug_view`].
+///
+/// The values are written into the frame as they are, without the sRGB encoding of the frame, so
+/// that a pixel reads as the value itself.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DebugView {
+    /// Renders the materials.
+    #[default]
+    Off,
+    /// World space normals, mapped from `[-1, 1]` to `[0, 1]`.
+    Normals,
+    /// Texture coordinates in red and green, repeating beyond `[0, 1]`.
+    Uvs,
+    /// The texture multiplied by the vertex color, in linear RGB. sRGB textures appear darker
+    /// than in the material, as their values decoded into linear space.
+    Albedo,
+    /// Distance from the camera, from black at the near plane to white at the far plane.
+    Depth,
+}
+
+impl DebugView {
+    /// Returns the view after this one, cycling back to [`DebugView::Off`].
+    pub fn next(self) -> Self {
+        match self {
+            Self::Off => Self::Normals,
+            Self::Normals => Self::Uvs,
+            Self::Uvs => Self::Albedo,
+            Self::Albedo => Self::Depth,
+            Self::Depth => Self::Off,
+        }
+    }
+
+    fn define(self) -> &'static str {
+        match self {
+            Self::Off => "DEBUG_OFF",
+            Self::Normals => "DEBUG_NORMALS",
+            Self::Uvs => "DEBUG_UVS",
+            Self::Albedo => "DEBUG_ALBEDO",
+            Self::Depth => "DEBUG_DEPTH",
+        }
+    }
+}
+
+/// Vertex layout of a debug pipeline: the layout of the position, and the offsets of the
+/// attributes recognized by the order of the engine vertex types, i.e. a texture coordinate
+/// following the position, then a normal, then a color. Missing attributes render flat.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub(super) struct DebugLayout {
+    position: PlaceholderKey,
+    uv: Option<u64>,
+    normal: Option<u64>,
+    color: Option<u64>,
+}
+
+impl DebugLayout {
+    /// Returns the layout of the vertex attributes, or `None` if the position is not made of
+    /// floats.
+    pub fn new(formats: &[wgpu::VertexFormat]) -> Option<Self> {
+        let position = PlaceholderKey::new(formats)?;
+        let mut layout = Self {
+            position,
+            uv: None,
+            normal: None,
+            color: None,
+        };
+
+        let mut offset = formats[0].size();
+        for (i, format) in formats.iter().enumerate().skip(1) {
+            match (i, format) {
+                (1, wgpu::VertexFormat::Float32x2) => layout.uv = Some(offset),
+                // 2D vertices are colored rather than lit.
+                (1, wgpu::VertexFormat::Float32x3)
+                    if formats[0] == wgpu::VertexFormat::Float32x2 =>
+                {
+                    layout.color = Some(offset)
+                }
+                (2, wgpu::VertexFormat::Float32x3) if layout.uv.is_some() => {
+                    layout.normal = Some(offset)
+                }
+                (3, wgpu::VertexFormat::Float32x3) if layout.normal.is_some() => {
+                    layout.color = Some(offset)
+                }
+                _ => {}
+            }
+            offset += format.size();
+        }
+        Some(layout)
+    }
+
+    /// Returns the layout with the topology of the drawn mesh.
+    pub fn with_topology(mut self, topology: wgpu::PrimitiveTopology) -> Self {
+        self.position = self.position.with_topology(topology);
+        self
+    }
+}
+
+const DEBUG_SOURCE: &str = "
+struct CameraUniform {
+  projection: mat4x4<f32>,
+};
+
+struct TransformUniform {
+  transform: mat4x4<f32>,
+  transform_inv: mat4x4<f32>,
+};
+
+@group(0) @binding(0) var<uniform> camera: CameraUniform;
+@group(1) @binding(0) var<uniform> camera_transform: TransformUniform;
+@group(2) @binding(0) var<uniform> model_transform: TransformUniform;
+#ifdef TEXTURED
+@group(3) @binding(0) var tex: texture_2d<f32>;
+@group(3) @binding(1) var tex_sampler: sampler;
+#endif
+
+struct VertexInput {
+  @location(0) position: POSITION_TYPE,
+#ifdef HAS_UV
+  @location(1) uv: vec2<f32>,
+#endif
+#ifdef HAS_NORMAL
+  @location(2) normal: vec3<f32>,
+#endif
+#ifdef HAS_COLOR
+  @location(3) color: vec3<f32>,
+#endif
+};
+
+struct VertexOutput {
+  @builtin(position) position: vec4<f32>,
+  @location(0) value: vec3<f32>,
+  @location(1) uv: vec2<f32>,
+};
+
+// Returns the view space depth of the camera projection at the normalized device depth.
+fn view_depth(depth: f32) -> f32 {
+  let p = camera.projection;
+  return (p[3][2] - depth * p[3][3]) / (depth * p[2][3] - p[2][2]);
+}
+
+@vertex
+fn vs_main(in: VertexInput) -> VertexOutput {
+  let position = in.position;
+  let view_position = camera_transform.transform_inv * model_transform.transform * POSITION;
+
+  var out: VertexOutput;
+  out.position = camera.projection * view_position;
+  out.value = vec3<f32>(0.5);
+  out.uv = vec2<f32>(0.0);
+#ifdef HAS_UV
+  out.uv = in.uv;
+#endif
+
+#ifdef DEBUG_NORMALS
+#ifdef HAS_NORMAL
+  let normal_matrix = transpose(model_transform.transform_inv);
+  let normal = normalize((normal_matrix * vec4<f32>(in.normal, 0.0)).xyz);
+  out.value = normal * 0.5 + 0.5;
+#endif
+#endif
+
+#ifdef DEBUG_ALBEDO
+  out.value = vec3<f32>(1.0);
+#ifdef HAS_COLOR
+  out.value = in.color;
+#endif
+#endif
+
+#ifdef DEBUG_DEPTH
+  let near = view_depth(0.0);
+  let far = view_depth(1.0);
+  out.value = vec3<f32>((view_position.z - near) / (far - near));
+#endif
+  return out;
+}
+
+// Returns the linear value which the sRGB encoding of the frame turns into the value.
+fn srgb_to_linear(value: vec3<f32>) -> vec3<f32> {
+  let low = value / 12.92;
+  let high = pow((value + 0.055) / 1.055, vec3<f32>(2.4));
+  return select(high, low, value <= vec3<f32>(0.04045));
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+  var value = in.value;
+#ifdef DEBUG_UVS
+#ifdef HAS_UV
+  value = vec3<f32>(fract(in.uv), 0.0);
+#endif
+#endif
+#ifdef DEBUG_ALBEDO
+#ifdef TEXTURED
+  value *= textureSample(tex, tex_sampler, in.uv).rgb;
+#endif
+#endif
+  return vec4<f32>(srgb_to_linear(clamp(value, vec3<f32>(0.0), vec3<f32>(1.0))), 1.0);
+}
+";
+
+/// Caches the debug pipelines drawn in place of the materials while a [`DebugView`] is active,
+/// one per vertex layout, view and whether a texture is bound.
+#[derive(Debug, Default)]
+pub(super) struct DebugPipelines {
+    pipelines: Mutex<HashMap<(DebugLayout, DebugView, bool), Arc<wgpu::RenderPipeline>>>,
+}
+
+impl DebugPipelines {
+    /// Bind group index of the texture of the material, if bound.
+    pub const TEXTURE_INDEX: u32 = 3;
+
+    /// Returns the debug pipeline, creating it if necessary.
+    pub fn get(
+        &self,
+        gpu: &Gpu,
+        layout: DebugLayout,
+        view: DebugView,
+        textured: bool,
+    ) -> Arc<wgpu::RenderPipeline> {
+        self.pipelines
+            .lock()
+            .unwrap()
+            .entry((layout, view, textured))
+            .or_insert_with(|| Arc::new(Self::create(gpu, layout, view, textured)))
+            .clone()
+    }
+
+    fn create(
+        gpu: &Gpu,
+        layout: DebugLayout,
+        view: DebugView,
+        textured: bool,
+    ) -> wgpu::RenderPipeline {
+        const LABEL: &str = "ravia_engine::debug_view";
+
+        let mut defines = vec![view.define()];
+        let mut attributes = vec![layout.position.attribute()];
+        for (define, offset, format, location) in [
+            ("HAS_UV", layout.uv, wgpu::VertexFormat::Float32x2, 1),
+            (
+                "HAS_NORMAL",
+                layout.normal,
+                wgpu::VertexFormat::Float32x3,
+                2,
+            ),
+            ("HAS_COLOR", layout.color, wgpu::VertexFormat::Float32x3, 3),
+        ] {
+            if let Some(offset) = offset {
+                defines.push(define);
+                attributes.push(wgpu::VertexAttribute {
+                    format,
+                    offset,
+                    shader_location: location,
+                });
+            }
+        }
+        if textured {
+            defines.push("TEXTURED");
+        }
+
+        let (position_type, position) = layout.position.position_type();
+        let (source, _) = preprocess(DEBUG_SOURCE, &defines);
+        let source = source
+            .replace("POSITION_TYPE", position_type)
+            .replace("POSITION;", &format!("{};", position));
+        let shader_module = gpu
+            .device
+            .create_shader_module(wgpu::ShaderModuleDescriptor {
+                label: Some(LABEL),
+                source: wgpu::ShaderSource::Wgsl(source.into()),
+            });
+
+        let layouts = &gpu.default_bind_group_layouts;
+        let mut bind_group_layouts = vec![&layouts.camera, &layouts.transform, &layouts.transform];
+        if textured {
+            bind_group_layouts.push(&layouts.texture_2d);
+        }
+        let pipeline_layout = gpu
+            .device
+            .create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                label: Some(LABEL),
+                bind_group_layouts: &bind_group_layouts,
+                push_constant_ranges: &[],
+            });
+
+        gpu.device
+            .create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+                label: Some(LABEL),
+                layout: Some(&pipeline_layout),
+                vertex: wgpu::VertexState {
+                    module: &shader_module,
+                    entry_point: Some("vs_main"),
+                    buffers: &[wgpu::VertexBufferLayout {
+                        array_stride: layout.position.array_stride(),
+                        step_mode: wgpu::VertexStepMode::Vertex,
+                        attributes: &attributes,
+                    }],
+                    compilation_options: wgpu::PipelineCompilationOptions::default(),
+                },
+                fragment: Some(wgpu::FragmentState {
+                    module: &shader_module,
+                    entry_point: Some("fs_main"),
+                    targets: &[Some(wgpu::ColorTargetState {
+                        format: gpu.scene_format(),
+                        blend: Some(wgpu::BlendState::REPLACE),
+                        write_mask: wgpu::ColorWrites::ALL,
+                    })],
+                    compilation_options: wgpu::PipelineCompilationOptions::default(),
+                }),
+                // both faces are drawn, so that flipped normals and windings show up.
+                primitive: wgpu::PrimitiveState {
+                    topology: layout.position.topology(),
+                    strip_index_format: strip_index_format(layout.position.topology()),
+                    ..Default::default()
+                },
+                depth_stencil: Some(depth_stencil_state(true)),
+                multisample: wgpu::MultisampleState::default(),
+                multiview: None,
+                cache: gpu.pipeline_cache(),
+            })
+    }
+}
@@ -0,0 +1,119 @@
+use crate::{ecs, engine::EngineContext, event, math};
+
+use super::{camera::Camera, coordinates::Handedness, transform::Transform};
+
+/// A [`DemoCamera`] component makes the [`super::transform::Transform`] of the same entity orbit
+/// around a target, so that a model can be shown from all sides without any camera code.
+///
+/// The camera slowly turns around the target on its own. While the left mouse button is held, the
+/// mouse motion rotates the camera instead, and the automatic orbit resumes after
+/// [`DemoCamera::idle_delay`] seconds without dragging.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DemoCamera {
+    /// The point the camera orbits around and looks at.
+    pub target: math::Vec3,
+    /// Distance from the target.
+    pub distance: f32,
+    /// Rotation around the y axis, in radians.
+    pub yaw: f32,
+    /// Elevation above the horizontal plane, in radians.
+    pub pitch: f32,
+    /// Speed of the automatic orbit, in radians per second.
+    pub orbit_speed: f32,
+    /// Rotation per unit of mouse motion while dragging, in radians.
+    pub drag_sensitivity: f32,
+    /// Seconds after the last drag before the automatic orbit resumes.
+    pub idle_delay: f32,
+
+    dragging: bool,
+    idle: f32,
+}
+
+assert_impl_all!(DemoCamera: ecs::storage::Component);
+
+impl DemoCamera {
+    /// Limit of the pitch, keeping the camera from flipping over the poles.
+    const MAX_PITCH: f32 = std::f32::consts::FRAC_PI_2 - 0.01;
+
+    /// Creates a new [`DemoCamera`] orbiting the target at the given distance.
+    pub fn new(target: math::Vec3, distance: f32) -> Self {
+        Self {
+            target,
+            distance,
+            yaw: 0.0,
+            pitch: 0.3,
+            orbit_speed: 0.3,
+            drag_sensitivity: 0.005,
+            idle_delay: 2.0,
+            dragging: false,
+            idle: f32::INFINITY,
+        }
+    }
+
+    /// Creates the components of a perspective camera entity orbiting the target.
+    ///
+    /// ```ignore
+    /// world.push(DemoCamera::bundle(ctx, Vec3::ZERO, 5.0));
+    /// ```
+    pub fn bundle(
+        ctx: &EngineContext,
+        target: math::Vec3,
+        distance: f32,
+    ) -> (Camera, Transform, DemoCamera) {
+        let demo_camera = Self::new(target, distance);
+        let mut transform = Transform::identity(ctx);
+        demo_camera.apply(ctx.gpu.handedness(), &mut transform);
+        (
+            Camera::perspective_with_defaults(ctx),
+            transform,
+            demo_camera,
+        )
+    }
+
+    /// Returns true if the camera is being dragged with the mouse.
+    pub fn is_dragging(&self) -> bool {
+        self.dragging
+    }
+
+    /// Advances the camera by a frame, reading the mouse events.
+    pub(super) fn update(&mut self, delta_seconds: f32, events: &event::Events) {
+        for event in events.iter() {
+            match event {
+                event::Event::MouseButton {
+                    button: event::MouseButton::Left,
+                    pressed,
+                } => {
+                    self.dragging = *pressed;
+                }
+                event::Event::WindowUnfocused => {
+                    self.dragging = false;
+                }
+                event::Event::MouseMotion(delta) if self.dragging => {
+                    self.yaw -= delta.x * self.drag_sensitivity;
+                    self.pitch += delta.y * self.drag_sensitivity;
+                    self.idle = 0.0;
+                }
+                _ => (),
+            }
+        }
+
+        if self.dragging {
+            self.idle = 0.0;
+        } else {
+            self.idle += delta_seconds;
+            if self.idle >= self.idle_delay {
+                self.yaw += self.orbit_speed * delta_seconds;
+            }
+        }
+
+        self.yaw %= std::f32::consts::TAU;
+        self.pitch = self.pitch.clamp(-Self::MAX_PITCH, Self::MAX_PITCH);
+    }
+
+    /// Places the transform on the orbit, looking at the target.
+    pub(super) fn apply(&self, handedness: Handedness, transform: &mut Transform) {
+        let rotation = math::Quat::from_euler(math::EulerRot::YXZ, self.yaw, -self.pitch, 0.0);
+        transform.set_position(self.target - rotation * handedness.forward() * self.distance);
+        transform.set_rotation(rotation);
+    }
+}
@@ -0,0 +1,109 @@
+use std::sync::{Arc, Mutex};
+
+use crate::math;
+
+use super::memory::{MemoryAllocation, MemoryCategory, MemoryTracker};
+
+/// Format of the depth buffer, which every pipeline drawn in the scene passes is created with.
+pub(super) const DEPTH_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Depth32Float;
+
+/// Returns the depth state of a pipeline drawn in the scene passes.
+///
+/// Pipelines without depth test are drawn over the previous draws, in the order they are drawn,
+/// e.g. for 2D content.
+pub(super) fn depth_stencil_state(depth_test: bool) -> wgpu::DepthStencilState {
+    wgpu::DepthStencilState {
+        format: DEPTH_FORMAT,
+        depth_write_enabled: depth_test,
+        depth_compare: if depth_test {
+            wgpu::CompareFunction::Less
+        } else {
+            wgpu::CompareFunction::Always
+        },
+        stencil: wgpu::StencilState::default(),
+        bias: wgpu::DepthBiasState::default(),
+    }
+}
+
+/// Returns the depth state of a pipeline drawing into shadow maps, biased against shadow acne.
+pub(super) fn shadow_depth_stencil_state() -> wgpu::DepthStencilState {
+    wgpu::DepthStencilState {
+        bias: wgpu::DepthBiasState {
+            constant: 2,
+            slope_scale: 2.0,
+            clamp: 0.0,
+        },
+        ..depth_stencil_state(true)
+    }
+}
+
+#[derive(Debug)]
+struct DepthTexture {
+    texture: wgpu::Texture,
+    _allocation: MemoryAllocation,
+}
+
+/// The depth buffer of the scene passes, matching the size of the frame target.
+#[derive(Debug)]
+pub(super) struct DepthBuffer {
+    texture: Mutex<Option<DepthTexture>>,
+    memory: Arc<MemoryTracker>,
+}
+
+impl DepthBuffer {
+    const LABEL: &str = "ravia_engine::depth";
+
+    pub fn new(memory: &Arc<MemoryTracker>) -> Self {
+        Self {
+            texture: Mutex::new(None),
+            memory: memory.clone(),
+        }
+    }
+
+    /// Recreates the depth texture with the given size.
+    pub fn resize(&self, device: &wgpu::Device, size: math::UVec2) {
+        *self.texture.lock().unwrap() = Some(self.create_texture(device, size));
+    }
+
+    /// Returns the view of the depth texture, recreating it if the size does not match the
+    /// target.
+    pub fn view(&self, device: &wgpu::Device, size: math::UVec2) -> wgpu::TextureView {
+        let mut texture = self.texture.lock().unwrap();
+        let outdated = texture.as_ref().is_none_or(|depth| {
+            depth.texture.width() != size.x || depth.texture.height() != size.y
+        });
+        if outdated {
+            *texture = Some(self.create_texture(device, size));
+        }
+        texture
+            .as_ref()
+            .expect("depth texture is created")
+            .texture
+            .create_view(&wgpu::TextureViewDescriptor::default())
+    }
+
+    fn create_texture(&self, device: &wgpu::Device, size: math::UVec2) -> DepthTexture {
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some(Self::LABEL),
+            size: wgpu::Extent3d {
+                width: size.x.max(1),
+                height: size.y.max(1),
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: DEPTH_FORMAT,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+            view_formats: &[],
+        });
+
+        let bytes = texture.width() as u64
+            * texture.height() as u64
+            * DEPTH_FORMAT.block_copy_size(None).unwrap_or(4) as u64;
+        DepthTexture {
+            _allocation: self.memory.allocate(MemoryCategory::Texture, bytes),
+            texture,
+        }
+    }
+}
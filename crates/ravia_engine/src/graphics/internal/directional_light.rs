@@ -0,0 +1,434 @@
+use wgpu::util::DeviceExt;
+
+use crate::{ecs, engine::EngineContext, math};
+
+use super::{
+    coordinates::Handedness,
+    depth::DEPTH_FORMAT,
+    gpu::{Gpu, GpuDefaultBindGroupLayouts},
+    memory::{MemoryAllocation, MemoryCategory, MemoryTracker},
+};
+
+/// WGSL declarations of the shadow map bound as [`super::uniform::UniformType::ShadowMap`], with
+/// percentage-closer filtering (PCF) helpers. Prepend it to the source of a shader sampling the
+/// shadow map:
+///
+/// ```ignore
+/// let source = format!("{}{}", SHADOW_WGSL, SOURCE);
+/// ```
+///
+/// The shader declares the bindings of the uniform itself, at the group of the uniform:
+///
+/// ```wgsl
+/// @group(4) @binding(0) var shadow_map: texture_depth_2d;
+/// @group(4) @binding(1) var shadow_sampler: sampler_comparison;
+/// @group(4) @binding(2) var<uniform> shadow: Shadow;
+///
+/// let lit = shadow_pcf(shadow_map, shadow_sampler, shadow, world_position);
+/// ```
+pub const SHADOW_WGSL: &str = "
+struct Shadow {
+  view_projection: mat4x4<f32>,
+  direction: vec3<f32>,
+  bias: f32,
+  texel_size: f32,
+  enabled: u32,
+};
+
+// Returns the shadow map coordinates of the world position: the uv in xy, and the depth in z.
+fn shadow_coords(shadow: Shadow, world_position: vec3<f32>) -> vec3<f32> {
+  let clip = shadow.view_projection * vec4<f32>(world_position, 1.0);
+  let ndc = clip.xyz / clip.w;
+  return vec3<f32>(ndc.xy * vec2<f32>(0.5, -0.5) + 0.5, ndc.z);
+}
+
+// Returns the fraction of the light reaching the world position, from 0 in full shadow to 1 when
+// lit, filtered over 3x3 texels. Positions outside of the shadow map are lit.
+fn shadow_pcf(
+  map: texture_depth_2d,
+  compare: sampler_comparison,
+  shadow: Shadow,
+  world_position: vec3<f32>,
+) -> f32 {
+  let coords = shadow_coords(shadow, world_position);
+  if shadow.enabled == 0u || any(coords.xy < vec2<f32>(0.0)) || any(coords.xy > vec2<f32>(1.0)) || coords.z > 1.0 {
+    return 1.0;
+  }
+
+  var lit = 0.0;
+  for (var y = -1; y <= 1; y++) {
+    for (var x = -1; x <= 1; x++) {
+      let offset = vec2<f32>(f32(x), f32(y)) * shadow.texel_size;
+      lit += textureSampleCompareLevel(map, compare, coords.xy + offset, coords.z - shadow.bias);
+    }
+  }
+  return lit / 9.0;
+}
+";
+
+/// [`ShadowConfig`] configures the shadow map of a [`DirectionalLight`].
+///
+/// The shadow map covers a box around the primary camera, aligned with the light, so that the
+/// shadows follow the camera through large worlds.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ShadowConfig {
+    /// Width and height of the shadow map, in texels.
+    pub resolution: u32,
+    /// Half of the width and height of the box covered by the shadow map, in world units.
+    pub extent: f32,
+    /// Depth of the box covered by the shadow map along the light, in world units, centered on
+    /// the camera.
+    pub depth: f32,
+    /// Depth bias subtracted when comparing against the shadow map, against shadow acne.
+    pub bias: f32,
+}
+
+impl Default for ShadowConfig {
+    fn default() -> Self {
+        Self {
+            resolution: 2048,
+            extent: 20.0,
+            depth: 100.0,
+            bias: 0.001,
+        }
+    }
+}
+
+/// Values of the shadow map, bound with its texture.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+struct ShadowUniform {
+    view_projection: [[f32; 4]; 4],
+    direction: [f32; 3],
+    bias: f32,
+    texel_size: f32,
+    enabled: u32,
+    _padding: [f32; 2],
+}
+
+/// A [`DirectionalLight`] component lights the world from a direction, e.g. the sun. It is bound
+/// to the shaders with the other lights as [`super::uniform::UniformType::Lights`].
+///
+/// A light with a shadow map renders the meshes from its direction before the frame, and binds
+/// the depth as [`super::uniform::UniformType::ShadowMap`] to the shaders declaring it, which
+/// sample it with the helpers of [`SHADOW_WGSL`]. Only the brightest light with a shadow map
+/// within the [`super::budget::RenderBudget`] renders it; shaders are bound an empty shadow map
+/// when there is none, where everything is lit.
+#[derive(Debug)]
+pub struct DirectionalLight {
+    /// Direction the light travels towards, in world space.
+    pub direction: math::Vec3,
+    /// Color of the light, in linear RGB.
+    pub color: math::Vec3,
+    pub intensity: f32,
+
+    shadow: Option<ShadowMap>,
+    /// Whether the shadow map is rendered this frame, selected within the render budget.
+    pub(super) casts_shadow: bool,
+}
+
+assert_impl_all!(DirectionalLight: ecs::storage::Component);
+
+impl DirectionalLight {
+    /// Creates a new [`DirectionalLight`] without shadows.
+    pub fn new(direction: math::Vec3, color: math::Vec3, intensity: f32) -> Self {
+        Self {
+            direction,
+            color,
+            intensity,
+            shadow: None,
+            casts_shadow: false,
+        }
+    }
+
+    /// Specifies the shadow map of the light.
+    pub fn with_shadow(mut self, ctx: &EngineContext, config: ShadowConfig) -> Self {
+        self.set_shadow(ctx, Some(config));
+        self
+    }
+
+    /// Sets or removes the shadow map of the light.
+    pub fn set_shadow(&mut self, ctx: &EngineContext, config: Option<ShadowConfig>) {
+        self.shadow = config.map(|config| ShadowMap::new(&ctx.gpu, config));
+        if self.shadow.is_none() {
+            self.casts_shadow = false;
+        }
+    }
+
+    /// Returns the configuration of the shadow map, if any.
+    pub fn shadow_config(&self) -> Option<&ShadowConfig> {
+        self.shadow.as_ref().map(|shadow| &shadow.config)
+    }
+
+    /// Returns true if the shadow map of the light is rendered this frame.
+    pub fn casts_shadow(&self) -> bool {
+        self.casts_shadow
+    }
+
+    /// Returns the shadow map, if rendered this frame.
+    pub(super) fn shadow_map(&self) -> Option<&ShadowMap> {
+        self.shadow.as_ref().filter(|_| self.casts_shadow)
+    }
+}
+
+/// The depth texture of a [`DirectionalLight`] with its camera, and the bind group sampling it.
+#[derive(Debug)]
+pub(super) struct ShadowMap {
+    config: ShadowConfig,
+
+    view: wgpu::TextureView,
+    _texture: wgpu::Texture,
+    camera_buffer: wgpu::Buffer,
+    /// The projection of the light, bound as a camera in the shadow pass.
+    pub camera: wgpu::BindGroup,
+    transform_buffer: wgpu::Buffer,
+    /// The view of the light, bound as a camera transform in the shadow pass.
+    pub transform: wgpu::BindGroup,
+    uniform_buffer: wgpu::Buffer,
+    /// The bind group of [`super::uniform::UniformType::ShadowMap`].
+    pub bind_group: wgpu::BindGroup,
+    _allocation: MemoryAllocation,
+}
+
+impl ShadowMap {
+    const LABEL: &str = "ravia_engine::shadow_map";
+
+    fn new(gpu: &Gpu, config: ShadowConfig) -> Self {
+        let max_size = gpu.capabilities().max_texture_size();
+        let resolution = config.resolution.clamp(1, max_size);
+        let layouts = &gpu.default_bind_group_layouts;
+
+        let texture = gpu.device.create_texture(&wgpu::TextureDescriptor {
+            label: Some(Self::LABEL),
+            size: wgpu::Extent3d {
+                width: resolution,
+                height: resolution,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: DEPTH_FORMAT,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING,
+            view_formats: &[],
+        });
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+
+        let camera_buffer = gpu.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some(Self::LABEL),
+            size: std::mem::size_of::<math::Mat4>() as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let camera = uniform_bind_group(&gpu.device, &layouts.camera, &camera_buffer);
+
+        let transform_buffer = gpu.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some(Self::LABEL),
+            size: 2 * std::mem::size_of::<math::Mat4>() as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let transform = uniform_bind_group(&gpu.device, &layouts.transform, &transform_buffer);
+
+        let uniform_buffer = gpu.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some(Self::LABEL),
+            size: std::mem::size_of::<ShadowUniform>() as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let bind_group =
+            shadow_bind_group(&gpu.device, &layouts.shadow_map, &view, &uniform_buffer);
+
+        let bytes = resolution as u64
+            * resolution as u64
+            * DEPTH_FORMAT.block_copy_size(None).unwrap_or(4) as u64;
+        let allocation = gpu.track_memory(MemoryCategory::Texture, bytes);
+
+        Self {
+            config: ShadowConfig {
+                resolution,
+                ..config
+            },
+            view,
+            _texture: texture,
+            camera_buffer,
+            camera,
+            transform_buffer,
+            transform,
+            uniform_buffer,
+            bind_group,
+            _allocation: allocation,
+        }
+    }
+
+    /// Returns the view of the depth texture to render into.
+    pub fn view(&self) -> &wgpu::TextureView {
+        &self.view
+    }
+
+    /// Returns the size of the depth texture.
+    pub fn size(&self) -> math::UVec2 {
+        math::UVec2::splat(self.config.resolution)
+    }
+
+    /// Places the camera of the light around the center, and uploads it.
+    pub fn update(
+        &self,
+        queue: &wgpu::Queue,
+        handedness: Handedness,
+        direction: math::Vec3,
+        center: math::Vec3,
+    ) {
+        let direction = direction.try_normalize().unwrap_or(math::Vec3::NEG_Y);
+        let up = if direction.y.abs() > 0.99 {
+            math::Vec3::Z
+        } else {
+            math::Vec3::Y
+        };
+
+        // snap the center to the texels, so that the shadows do not shimmer as the camera moves.
+        let texel = 2.0 * self.config.extent / self.config.resolution as f32;
+        let rotation = handedness.look_to(math::Vec3::ZERO, direction, up);
+        let local = rotation.transform_point3(center);
+        let snapped = math::vec3(
+            (local.x / texel).floor() * texel,
+            (local.y / texel).floor() * texel,
+            local.z,
+        );
+        let center = rotation.inverse().transform_point3(snapped);
+
+        let half_depth = self.config.depth * 0.5;
+        let view = handedness.look_to(center - direction * half_depth, direction, up);
+        let extent = self.config.extent;
+        let projection =
+            handedness.orthographic(-extent, extent, -extent, extent, 0.0, self.config.depth);
+
+        queue.write_buffer(&self.camera_buffer, 0, bytemuck::cast_slice(&[projection]));
+        queue.write_buffer(
+            &self.transform_buffer,
+            0,
+            bytemuck::cast_slice(&[view.inverse(), view]),
+        );
+
+        let uniform = ShadowUniform {
+            view_projection: (projection * view).to_cols_array_2d(),
+            direction: direction.to_array(),
+            bias: self.config.bias,
+            texel_size: 1.0 / self.config.resolution as f32,
+            enabled: 1,
+            _padding: [0.0; 2],
+        };
+        queue.write_buffer(&self.uniform_buffer, 0, bytemuck::cast_slice(&[uniform]));
+    }
+}
+
+/// The bind group of [`super::uniform::UniformType::ShadowMap`] when no shadow map is rendered,
+/// where everything is lit.
+#[derive(Debug)]
+pub(super) struct ShadowFallback {
+    _texture: wgpu::Texture,
+    _buffer: wgpu::Buffer,
+    pub bind_group: wgpu::BindGroup,
+    _allocation: MemoryAllocation,
+}
+
+impl ShadowFallback {
+    pub fn new(
+        device: &wgpu::Device,
+        layouts: &GpuDefaultBindGroupLayouts,
+        memory: &std::sync::Arc<MemoryTracker>,
+    ) -> Self {
+        const LABEL: &str = "ravia_engine::shadow_fallback";
+
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some(LABEL),
+            size: wgpu::Extent3d {
+                width: 1,
+                height: 1,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: DEPTH_FORMAT,
+            usage: wgpu::TextureUsages::TEXTURE_BINDING,
+            view_formats: &[],
+        });
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+
+        let buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some(LABEL),
+            contents: bytemuck::cast_slice(&[ShadowUniform {
+                view_projection: math::Mat4::IDENTITY.to_cols_array_2d(),
+                direction: math::Vec3::NEG_Y.to_array(),
+                bias: 0.0,
+                texel_size: 1.0,
+                enabled: 0,
+                _padding: [0.0; 2],
+            }]),
+            usage: wgpu::BufferUsages::UNIFORM,
+        });
+        let bind_group = shadow_bind_group(device, &layouts.shadow_map, &view, &buffer);
+
+        Self {
+            _allocation: memory.allocate(MemoryCategory::Uniform, buffer.size()),
+            _texture: texture,
+            _buffer: buffer,
+            bind_group,
+        }
+    }
+}
+
+/// Creates the bind group of a uniform buffer of a shadow map, e.g. its camera.
+pub(super) fn uniform_bind_group(
+    device: &wgpu::Device,
+    layout: &wgpu::BindGroupLayout,
+    buffer: &wgpu::Buffer,
+) -> wgpu::BindGroup {
+    device.create_bind_group(&wgpu::BindGroupDescriptor {
+        label: Some(ShadowMap::LABEL),
+        layout,
+        entries: &[wgpu::BindGroupEntry {
+            binding: 0,
+            resource: buffer.as_entire_binding(),
+        }],
+    })
+}
+
+/// Creates the bind group sampling a shadow map, with the layout of its uniform type.
+pub(super) fn shadow_bind_group(
+    device: &wgpu::Device,
+    layout: &wgpu::BindGroupLayout,
+    view: &wgpu::TextureView,
+    buffer: &wgpu::Buffer,
+) -> wgpu::BindGroup {
+    let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+        label: Some(ShadowMap::LABEL),
+        address_mode_u: wgpu::AddressMode::ClampToEdge,
+        address_mode_v: wgpu::AddressMode::ClampToEdge,
+        address_mode_w: wgpu::AddressMode::ClampToEdge,
+        mag_filter: wgpu::FilterMode::Linear,
+        min_filter: wgpu::FilterMode::Linear,
+        compare: Some(wgpu::CompareFunction::LessEqual),
+        ..Default::default()
+    });
+
+    device.create_bind_group(&wgpu::BindGroupDescriptor {
+        label: Some(ShadowMap::LABEL),
+        layout,
+        entries: &[
+            wgpu::BindGroupEntry {
+                binding: 0,
+                resource: wgpu::BindingResource::TextureView(view),
+            },
+            wgpu::BindGroupEntry {
+                binding: 1,
+                resource: wgpu::BindingResource::Sampler(&sampler),
+            },
+            wgpu::BindGroupEntry {
+                binding: 2,
+                resource: buffer.as_entire_binding(),
+            },
+        ],
+    })
+}
@@ -0,0 +1,180 @@
+use std::sync::Mutex;
+
+use wgpu::util::DeviceExt;
+
+use super::gpu::Gpu;
+
+const DOWNSAMPLE_SOURCE: &str = "
+@group(0) @binding(0) var source: texture_2d<f32>;
+// the factor in x, padded to the alignment of uniforms.
+@group(0) @binding(1) var<uniform> factor: vec4<u32>;
+
+@vertex
+fn vs_main(@builtin(vertex_index) index: u32) -> @builtin(position) vec4<f32> {
+  // a single triangle covering the whole screen.
+  let ndc = vec2<f32>(f32(index / 2u) * 4.0 - 1.0, f32(index % 2u) * 4.0 - 1.0);
+  return vec4<f32>(ndc, 0.0, 1.0);
+}
+
+@fragment
+fn fs_main(@builtin(position) position: vec4<f32>) -> @location(0) vec4<f32> {
+  let origin = vec2<u32>(position.xy) * factor.x;
+  var sum = vec4<f32>(0.0);
+  for (var y = 0u; y < factor.x; y++) {
+    for (var x = 0u; x < factor.x; x++) {
+      sum += textureLoad(source, origin + vec2<u32>(x, y), 0);
+    }
+  }
+  return sum / f32(factor.x * factor.x);
+}
+";
+
+/// Averages the blocks of texels of a supersampled frame into the texels of a texture of a lower
+/// resolution, e.g. for [`Gpu::render_offscreen`].
+#[derive(Debug)]
+pub(super) struct DownsamplePass {
+    bind_group_layout: wgpu::BindGroupLayout,
+    pipeline: Mutex<Option<(wgpu::TextureFormat, wgpu::RenderPipeline)>>,
+}
+
+impl DownsamplePass {
+    const LABEL: &str = "ravia_engine::downsample";
+
+    pub fn new(device: &wgpu::Device) -> Self {
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some(Self::LABEL),
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        sample_type: wgpu::TextureSampleType::Float { filterable: false },
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        multisampled: false,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+            ],
+        });
+
+        Self {
+            bind_group_layout,
+            pipeline: Mutex::new(None),
+        }
+    }
+
+    /// Records the pass writing the average of every block of `factor` by `factor` texels of the
+    /// source into a texel of the target.
+    pub fn draw(
+        &self,
+        gpu: &Gpu,
+        encoder: &mut wgpu::CommandEncoder,
+        source: &wgpu::Texture,
+        target: &wgpu::Texture,
+        factor: u32,
+    ) {
+        let buffer = gpu
+            .device
+            .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some(Self::LABEL),
+                contents: bytemuck::cast_slice(&[factor, 0, 0, 0]),
+                usage: wgpu::BufferUsages::UNIFORM,
+            });
+        let source_view = source.create_view(&wgpu::TextureViewDescriptor::default());
+        let bind_group = gpu.device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some(Self::LABEL),
+            layout: &self.bind_group_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(&source_view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: buffer.as_entire_binding(),
+                },
+            ],
+        });
+
+        let mut pipeline = self.pipeline.lock().unwrap();
+        if pipeline
+            .as_ref()
+            .is_none_or(|(format, _)| *format != target.format())
+        {
+            *pipeline = Some((target.format(), self.create_pipeline(gpu, target.format())));
+        }
+        let (_, pipeline) = pipeline.as_ref().expect("pipeline is created");
+
+        let target_view = target.create_view(&wgpu::TextureViewDescriptor::default());
+        let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some(Self::LABEL),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view: &target_view,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(wgpu::Color::BLACK),
+                    store: wgpu::StoreOp::Store,
+                },
+            })],
+            depth_stencil_attachment: None,
+            occlusion_query_set: None,
+            timestamp_writes: None,
+        });
+        render_pass.set_pipeline(pipeline);
+        render_pass.set_bind_group(0, &bind_group, &[]);
+        render_pass.draw(0..3, 0..1);
+    }
+
+    fn create_pipeline(&self, gpu: &Gpu, format: wgpu::TextureFormat) -> wgpu::RenderPipeline {
+        let shader_module = gpu
+            .device
+            .create_shader_module(wgpu::ShaderModuleDescriptor {
+                label: Some(Self::LABEL),
+                source: wgpu::ShaderSource::Wgsl(DOWNSAMPLE_SOURCE.into()),
+            });
+        let pipeline_layout = gpu
+            .device
+            .create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                label: Some(Self::LABEL),
+                bind_group_layouts: &[&self.bind_group_layout],
+                push_constant_ranges: &[],
+            });
+
+        gpu.device
+            .create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+                label: Some(Self::LABEL),
+                layout: Some(&pipeline_layout),
+                vertex: wgpu::VertexState {
+                    module: &shader_module,
+                    entry_point: Some("vs_main"),
+                    buffers: &[],
+                    compilation_options: wgpu::PipelineCompilationOptions::default(),
+                },
+                fragment: Some(wgpu::FragmentState {
+                    module: &shader_module,
+                    entry_point: Some("fs_main"),
+                    targets: &[Some(wgpu::ColorTargetState {
+                        format,
+                        blend: None,
+                        write_mask: wgpu::ColorWrites::ALL,
+                    })],
+                    compilation_options: wgpu::PipelineCompilationOptions::default(),
+                }),
+                primitive: wgpu::PrimitiveState::default(),
+                depth_stencil: None,
+                multisample: wgpu::MultisampleState::default(),
+                multiview: None,
+                cache: gpu.pipeline_cache(),
+            })
+    }
+}
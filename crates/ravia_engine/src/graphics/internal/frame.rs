@@ -0,0 +1,90 @@
+use std::sync::Arc;
+
+use crate::math;
+
+use super::{
+    gpu::GpuDefaultBindGroupLayouts,
+    memory::{MemoryAllocation, MemoryCategory, MemoryTracker},
+};
+
+/// Per-frame values bound as [`super::uniform::UniformType::Frame`].
+///
+/// In WGSL, the uniform is declared as:
+///
+/// ```wgsl
+/// struct Frame {
+///   time: f32,
+///   delta: f32,
+///   resolution: vec2<f32>,
+///   camera_position: vec3<f32>,
+/// };
+/// ```
+#[repr(C)]
+#[derive(Debug, Default, Clone, Copy, PartialEq, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct FrameUniform {
+    /// Elapsed time since the engine started, in seconds.
+    pub time: f32,
+    /// Time since the previous frame, in seconds.
+    pub delta: f32,
+    /// Size of the surface in physical pixels.
+    pub resolution: [f32; 2],
+    /// World position of the camera.
+    pub camera_position: [f32; 3],
+    _padding: f32,
+}
+
+impl FrameUniform {
+    /// Creates a new [`FrameUniform`].
+    pub fn new(time: f32, delta: f32, resolution: math::Vec2, camera_position: math::Vec3) -> Self {
+        Self {
+            time,
+            delta,
+            resolution: resolution.to_array(),
+            camera_position: camera_position.to_array(),
+            _padding: 0.0,
+        }
+    }
+}
+
+/// The engine-managed buffer holding the [`FrameUniform`].
+#[derive(Debug)]
+pub(super) struct FrameBuffer {
+    buffer: wgpu::Buffer,
+    pub bind_group: wgpu::BindGroup,
+    _allocation: MemoryAllocation,
+}
+
+impl FrameBuffer {
+    pub fn new(
+        device: &wgpu::Device,
+        layouts: &GpuDefaultBindGroupLayouts,
+        memory: &Arc<MemoryTracker>,
+    ) -> Self {
+        let buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("ravia_engine::frame"),
+            size: std::mem::size_of::<FrameUniform>() as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("ravia_engine::frame"),
+            layout: &layouts.frame,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: buffer.as_entire_binding(),
+            }],
+        });
+
+        Self {
+            _allocation: memory.allocate(MemoryCategory::Uniform, buffer.size()),
+            buffer,
+            bind_group,
+        }
+    }
+
+    /// Uploads the values of the current frame.
+    pub fn write(&self, queue: &wgpu::Queue, frame: FrameUniform) {
+        queue.write_buffer(&self.buffer, 0, bytemuck::cast_slice(&[frame]));
+    }
+}
@@ -0,0 +1,182 @@
+use std::fmt::{self, Write};
+
+use crate::math;
+
+/// The texture a pass of the [`FrameGraph`] renders into.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FrameAttachment {
+    /// The texture the frame is rendered into, i.e. the window surface or the headless texture.
+    Target,
+    /// The intermediate texture the scene is rendered into, when the display adjustment is
+    /// applied to the frame.
+    Frame,
+    /// A [`super::render_texture::RenderTexture`] a camera renders into.
+    RenderTexture,
+    /// An intermediate texture of the [`super::post_process::PostProcessStack`], which the scene
+    /// is rendered into while an effect is enabled.
+    PostProcess,
+    /// The shadow map of a [`super::directional_light::DirectionalLight`], or a face of the cube
+    /// shadow map of a [`super::point_light::PointLight`].
+    ShadowMap,
+}
+
+impl FrameAttachment {
+    fn name(&self) -> &'static str {
+        match self {
+            Self::Target => "target",
+            Self::Frame => "frame",
+            Self::RenderTexture => "render_texture",
+            Self::PostProcess => "post_process",
+            Self::ShadowMap => "shadow_map",
+        }
+    }
+}
+
+/// The kind of a pass of the [`FrameGraph`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FramePassKind {
+    /// Draws the depth of the scene from a light into its shadow map, or a face of it.
+    Shadow,
+    /// Draws the scene from a camera.
+    Scene,
+    /// Draws the [`super::overlay::Overlay`] entities from an overlay camera.
+    Overlay,
+    /// Clears the frame, when the world has no camera.
+    Clear,
+    /// Records a [`super::custom_pass::CustomPass`] of the application.
+    Custom,
+    /// Applies an effect of the [`super::post_process::PostProcessStack`].
+    PostProcess,
+    /// Copies the frame into the target, applying the display adjustment.
+    Present,
+    /// Draws the frame time graph over the target.
+    FrameStats,
+}
+
+impl FramePassKind {
+    fn name(&self) -> &'static str {
+        match self {
+            Self::Shadow => "shadow",
+            Self::Scene => "scene",
+            Self::Overlay => "overlay",
+            Self::Clear => "clear",
+            Self::Custom => "custom",
+            Self::PostProcess => "post_process",
+            Self::Present => "present",
+            Self::FrameStats => "frame_stats",
+        }
+    }
+}
+
+/// A render pass of the [`FrameGraph`], with its statistics.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FramePass {
+    pub kind: FramePassKind,
+    /// The order of the camera drawing the pass, if any.
+    pub camera_order: Option<i32>,
+    pub attachment: FrameAttachment,
+    pub size: math::UVec2,
+    pub format: wgpu::TextureFormat,
+    /// Whether the attachment is cleared before the pass, rather than drawn over.
+    pub clear: bool,
+    /// Whether the pass has a depth attachment.
+    pub depth: bool,
+    /// Number of draw calls, including the gizmos and the placeholders.
+    pub draw_calls: u32,
+    /// Number of draw calls of placeholders, drawn in place of shaders which are still compiling.
+    pub placeholder_draws: u32,
+    /// Whether the gizmos are drawn in the pass.
+    pub gizmos: bool,
+}
+
+/// [`FrameGraph`] describes the render passes scheduled by the [`super::gpu::Gpu`] for the last
+/// frame, in the order they are executed.
+///
+/// It is meant for debugging the renderer: see [`super::gpu::Gpu::frame_graph`]. The graph can be
+/// printed as text through [`fmt::Display`], or as JSON with [`FrameGraph::to_json`].
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct FrameGraph {
+    passes: Vec<FramePass>,
+}
+
+impl FrameGraph {
+    /// Returns the passes, in the order they are executed.
+    pub fn passes(&self) -> &[FramePass] {
+        &self.passes
+    }
+
+    /// Returns the total number of draw calls of the frame.
+    pub fn draw_calls(&self) -> u32 {
+        self.passes.iter().map(|pass| pass.draw_calls).sum()
+    }
+
+    pub(super) fn push(&mut self, pass: FramePass) {
+        self.passes.push(pass);
+    }
+
+    /// Returns the graph as a JSON document.
+    pub fn to_json(&self) -> String {
+        let mut json = String::from("{\"passes\":[");
+        for (i, pass) in self.passes.iter().enumerate() {
+            if i > 0 {
+                json.push(',');
+            }
+            let camera_order = pass
+                .camera_order
+                .map_or_else(|| "null".to_string(), |order| order.to_string());
+            let _ = write!(
+                json,
+                "{{\"kind\":\"{}\",\"camera_order\":{},\"attachment\":\"{}\",\"width\":{},\"height\":{},\"format\":\"{:?}\",\"clear\":{},\"depth\":{},\"draw_calls\":{},\"placeholder_draws\":{},\"gizmos\":{}}}",
+                pass.kind.name(),
+                camera_order,
+                pass.attachment.name(),
+                pass.size.x,
+                pass.size.y,
+                pass.format,
+                pass.clear,
+                pass.depth,
+                pass.draw_calls,
+                pass.placeholder_draws,
+                pass.gizmos,
+            );
+        }
+        let _ = write!(json, "],\"draw_calls\":{}}}", self.draw_calls());
+        json
+    }
+}
+
+impl fmt::Display for FrameGraph {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "frame graph: {} passes, {} draw calls",
+            self.passes.len(),
+            self.draw_calls()
+        )?;
+        for (i, pass) in self.passes.iter().enumerate() {
+            write!(f, "  #{} {}", i, pass.kind.name())?;
+            if let Some(order) = pass.camera_order {
+                write!(f, " (camera order {})", order)?;
+            }
+            write!(
+                f,
+                ": {} {}x{} {:?}, {}, {}, {} draws",
+                pass.attachment.name(),
+                pass.size.x,
+                pass.size.y,
+                pass.format,
+                if pass.clear { "clear" } else { "load" },
+                if pass.depth { "depth" } else { "no depth" },
+                pass.draw_calls,
+            )?;
+            if pass.placeholder_draws > 0 {
+                write!(f, " ({} placeholders)", pass.placeholder_draws)?;
+            }
+            if pass.gizmos {
+                write!(f, ", gizmos")?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
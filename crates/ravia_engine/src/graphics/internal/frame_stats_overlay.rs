@@ -0,0 +1,312 @@
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, OnceLock,
+    },
+    time::Duration,
+};
+
+use crate::{math, time::FrameStats};
+
+use super::{
+    gpu::Gpu,
+    memory::{MemoryAllocation, MemoryCategory, MemoryTracker},
+};
+
+/// [`FrameStatsOverlayConfig`] configures the graph of the recent frame times drawn over the
+/// frame, from the [`FrameStats`] resource.
+///
+/// Frames within the budget are drawn in green, frames over the budget in yellow, and hitches in
+/// red, see [`crate::time::FrameStatsConfig::hitch_threshold`].
+#[derive(Debug, Clone, Copy)]
+pub struct FrameStatsOverlayConfig {
+    /// Whether the graph is drawn from the start. See [`Gpu::set_frame_stats_visible`].
+    pub visible: bool,
+    /// Frame time budget, drawn as a line across the graph.
+    pub budget: Duration,
+    /// Frame time at the top of the graph. Longer frames are clipped.
+    pub scale: Duration,
+    /// Position of the top-left corner of the graph, in pixels from the top-left corner of the
+    /// frame.
+    pub position: math::Vec2,
+    /// Size of the graph, in pixels.
+    pub size: math::Vec2,
+    /// Key showing and hiding the graph at runtime, if any.
+    pub toggle_key: Option<winit::keyboard::KeyCode>,
+}
+
+impl Default for FrameStatsOverlayConfig {
+    fn default() -> Self {
+        Self {
+            visible: false,
+            budget: Duration::from_micros(16_667),
+            scale: Duration::from_millis(100),
+            position: math::vec2(8.0, 8.0),
+            size: math::vec2(256.0, 96.0),
+            toggle_key: None,
+        }
+    }
+}
+
+/// Number of frames drawn by the graph.
+const GRAPH_FRAMES: usize = 256;
+
+/// Values of the graph shader, written every frame.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+struct FrameStatsUniform {
+    frame_times: [[f32; 4]; GRAPH_FRAMES / 4],
+    rect: [f32; 4],
+    resolution: [f32; 2],
+    count: u32,
+    scale_ms: f32,
+    budget_ms: f32,
+    hitch_ms: f32,
+    _padding: [f32; 2],
+}
+
+const FRAME_STATS_SOURCE: &str = "
+struct FrameStats {
+  frame_times: array<vec4<f32>, 64>,
+  rect: vec4<f32>,
+  resolution: vec2<f32>,
+  count: u32,
+  scale_ms: f32,
+  budget_ms: f32,
+  hitch_ms: f32,
+};
+
+@group(0) @binding(0) var<uniform> stats: FrameStats;
+
+struct VertexOutput {
+  @builtin(position) position: vec4<f32>,
+  @location(0) color: vec4<f32>,
+};
+
+fn frame_time(index: u32) -> f32 {
+  return stats.frame_times[index / 4u][index % 4u];
+}
+
+@vertex
+fn vs_main(@builtin(vertex_index) index: u32) -> VertexOutput {
+  // six vertices per quad: the background, a bar per frame, then the budget line.
+  let quad = index / 6u;
+  let corner_index = index % 6u;
+  let corner = vec2<f32>(
+    select(0.0, 1.0, corner_index == 1u || corner_index == 2u || corner_index == 4u),
+    select(0.0, 1.0, corner_index == 2u || corner_index == 4u || corner_index == 5u),
+  );
+
+  // the rectangle of the quad within the graph, with y pointing up from the bottom.
+  var lower = vec2<f32>(0.0);
+  var upper = vec2<f32>(1.0);
+  var color = vec4<f32>(0.0, 0.0, 0.0, 0.5);
+  if quad == 257u {
+    let y = stats.budget_ms / stats.scale_ms;
+    lower = vec2<f32>(0.0, y);
+    upper = vec2<f32>(1.0, y + 1.0 / stats.rect.w);
+    color = vec4<f32>(1.0, 1.0, 1.0, 0.8);
+  } else if quad > 0u {
+    // the latest frame is drawn on the right.
+    let slot = quad - 1u;
+    let first = 256u - stats.count;
+    if slot < first {
+      return VertexOutput(vec4<f32>(0.0), vec4<f32>(0.0));
+    }
+    let ms = frame_time(slot - first);
+    lower = vec2<f32>(f32(slot) / 256.0, 0.0);
+    upper = vec2<f32>(f32(slot + 1u) / 256.0, clamp(ms / stats.scale_ms, 0.0, 1.0));
+    if ms > stats.hitch_ms {
+      color = vec4<f32>(0.9, 0.2, 0.2, 0.9);
+    } else if ms > stats.budget_ms {
+      color = vec4<f32>(0.9, 0.8, 0.2, 0.9);
+    } else {
+      color = vec4<f32>(0.2, 0.8, 0.3, 0.9);
+    }
+  }
+
+  let graph = mix(lower, upper, corner);
+  let pixel = stats.rect.xy + vec2<f32>(graph.x, 1.0 - graph.y) * stats.rect.zw;
+  let ndc = vec2<f32>(pixel.x / stats.resolution.x * 2.0 - 1.0, 1.0 - pixel.y / stats.resolution.y * 2.0);
+  return VertexOutput(vec4<f32>(ndc, 0.0, 1.0), color);
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+  return in.color;
+}
+";
+
+/// Draws the graph configured by [`FrameStatsOverlayConfig`].
+#[derive(Debug)]
+pub(super) struct FrameStatsOverlay {
+    config: FrameStatsOverlayConfig,
+    visible: AtomicBool,
+
+    buffer: wgpu::Buffer,
+    bind_group_layout: wgpu::BindGroupLayout,
+    bind_group: wgpu::BindGroup,
+    _allocation: MemoryAllocation,
+
+    /// Pipeline of the graph, created when the graph is first drawn.
+    pipeline: OnceLock<wgpu::RenderPipeline>,
+}
+
+impl FrameStatsOverlay {
+    const LABEL: &str = "ravia_engine::frame_stats";
+
+    pub fn new(
+        device: &wgpu::Device,
+        config: FrameStatsOverlayConfig,
+        memory: &Arc<MemoryTracker>,
+    ) -> Self {
+        let buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some(Self::LABEL),
+            size: std::mem::size_of::<FrameStatsUniform>() as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some(Self::LABEL),
+            entries: &[wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::VERTEX,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            }],
+        });
+
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some(Self::LABEL),
+            layout: &bind_group_layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: buffer.as_entire_binding(),
+            }],
+        });
+
+        Self {
+            config,
+            visible: AtomicBool::new(config.visible),
+            _allocation: memory.allocate(MemoryCategory::Uniform, buffer.size()),
+            buffer,
+            bind_group_layout,
+            bind_group,
+            pipeline: OnceLock::new(),
+        }
+    }
+
+    pub fn config(&self) -> &FrameStatsOverlayConfig {
+        &self.config
+    }
+
+    pub fn is_visible(&self) -> bool {
+        self.visible.load(Ordering::Relaxed)
+    }
+
+    pub fn set_visible(&self, visible: bool) {
+        self.visible.store(visible, Ordering::Relaxed);
+    }
+
+    /// Uploads the recent frame times of the stats.
+    pub fn write(&self, queue: &wgpu::Queue, stats: &FrameStats, resolution: math::UVec2) {
+        let frame_times = stats.frame_times().collect::<Vec<_>>();
+        let recent = &frame_times[frame_times.len().saturating_sub(GRAPH_FRAMES)..];
+
+        let mut uniform = FrameStatsUniform {
+            frame_times: [[0.0; 4]; GRAPH_FRAMES / 4],
+            rect: [
+                self.config.position.x,
+                self.config.position.y,
+                self.config.size.x,
+                self.config.size.y,
+            ],
+            resolution: resolution.as_vec2().to_array(),
+            count: recent.len() as u32,
+            scale_ms: duration_ms(self.config.scale),
+            budget_ms: duration_ms(self.config.budget),
+            hitch_ms: duration_ms(stats.config().hitch_threshold),
+            _padding: [0.0; 2],
+        };
+        for (i, frame_time) in recent.iter().enumerate() {
+            uniform.frame_times[i / 4][i % 4] = duration_ms(*frame_time);
+        }
+        queue.write_buffer(&self.buffer, 0, bytemuck::cast_slice(&[uniform]));
+    }
+
+    /// Draws the graph over the frame.
+    pub fn draw(&self, gpu: &Gpu, encoder: &mut wgpu::CommandEncoder, view: &wgpu::TextureView) {
+        let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some(Self::LABEL),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Load,
+                    store: wgpu::StoreOp::Store,
+                },
+            })],
+            depth_stencil_attachment: None,
+            occlusion_query_set: None,
+            timestamp_writes: None,
+        });
+        render_pass.set_pipeline(self.pipeline(gpu));
+        render_pass.set_bind_group(0, &self.bind_group, &[]);
+        render_pass.draw(0..(GRAPH_FRAMES as u32 + 2) * 6, 0..1);
+    }
+
+    fn pipeline(&self, gpu: &Gpu) -> &wgpu::RenderPipeline {
+        self.pipeline.get_or_init(|| {
+            let surface_format = gpu.surface_config.lock().unwrap().format;
+            let shader_module = gpu
+                .device
+                .create_shader_module(wgpu::ShaderModuleDescriptor {
+                    label: Some(Self::LABEL),
+                    source: wgpu::ShaderSource::Wgsl(FRAME_STATS_SOURCE.into()),
+                });
+            let pipeline_layout =
+                gpu.device
+                    .create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                        label: Some(Self::LABEL),
+                        bind_group_layouts: &[&self.bind_group_layout],
+                        push_constant_ranges: &[],
+                    });
+
+            gpu.device
+                .create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+                    label: Some(Self::LABEL),
+                    layout: Some(&pipeline_layout),
+                    vertex: wgpu::VertexState {
+                        module: &shader_module,
+                        entry_point: Some("vs_main"),
+                        buffers: &[],
+                        compilation_options: wgpu::PipelineCompilationOptions::default(),
+                    },
+                    fragment: Some(wgpu::FragmentState {
+                        module: &shader_module,
+                        entry_point: Some("fs_main"),
+                        targets: &[Some(wgpu::ColorTargetState {
+                            format: surface_format,
+                            blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+                            write_mask: wgpu::ColorWrites::ALL,
+                        })],
+                        compilation_options: wgpu::PipelineCompilationOptions::default(),
+                    }),
+                    primitive: wgpu::PrimitiveState::default(),
+                    depth_stencil: None,
+                    multisample: wgpu::MultisampleState::default(),
+                    multiview: None,
+                    cache: gpu.pipeline_cache(),
+                })
+        })
+    }
+}
+
+fn duration_ms(duration: Duration) -> f32 {
+    duration.as_secs_f32() * 1000.0
+}
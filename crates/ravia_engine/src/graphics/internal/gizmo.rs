@@ -0,0 +1,306 @@
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc, OnceLock,
+};
+
+use crate::math;
+
+use super::{
+    depth::depth_stencil_state,
+    gpu::Gpu,
+    memory::{MemoryAllocation, MemoryCategory, MemoryTracker},
+};
+
+/// [`GizmoConfig`] configures the orientation aids drawn by the renderer: an infinite grid on the
+/// ground (xz) plane, and the x, y and z axes through the origin in red, green and blue.
+#[derive(Debug, Clone, Copy)]
+pub struct GizmoConfig {
+    /// Whether the gizmos are drawn from the start. See [`Gpu::set_gizmos_visible`].
+    pub visible: bool,
+    /// Draws the ground grid.
+    pub grid: bool,
+    /// Draws the axes.
+    pub axes: bool,
+    /// Distance between grid lines, in world units. Every tenth line is emphasized.
+    pub grid_spacing: f32,
+    /// Length of the axes, in world units.
+    pub axes_length: f32,
+    /// Key showing and hiding the gizmos at runtime, if any.
+    pub toggle_key: Option<winit::keyboard::KeyCode>,
+}
+
+impl Default for GizmoConfig {
+    fn default() -> Self {
+        Self {
+            visible: false,
+            grid: true,
+            axes: true,
+            grid_spacing: 1.0,
+            axes_length: 1.0,
+            toggle_key: None,
+        }
+    }
+}
+
+/// Values of the gizmo shaders, written every frame.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+struct GizmoUniform {
+    view_projection: [[f32; 4]; 4],
+    view_projection_inv: [[f32; 4]; 4],
+    grid_spacing: f32,
+    axes_length: f32,
+    _padding: [f32; 2],
+}
+
+const GIZMO_SOURCE: &str = "
+struct Gizmo {
+  view_projection: mat4x4<f32>,
+  view_projection_inv: mat4x4<f32>,
+  grid_spacing: f32,
+  axes_length: f32,
+};
+
+@group(0) @binding(0) var<uniform> gizmo: Gizmo;
+
+struct GridOutput {
+  @builtin(position) position: vec4<f32>,
+  @location(0) ndc: vec2<f32>,
+};
+
+@vertex
+fn vs_grid(@builtin(vertex_index) index: u32) -> GridOutput {
+  // a single triangle covering the whole screen.
+  let ndc = vec2<f32>(f32(index / 2u) * 4.0 - 1.0, f32(index % 2u) * 4.0 - 1.0);
+  return GridOutput(vec4<f32>(ndc, 0.0, 1.0), ndc);
+}
+
+fn unproject(ndc: vec2<f32>, depth: f32) -> vec3<f32> {
+  let p = gizmo.view_projection_inv * vec4<f32>(ndc, depth, 1.0);
+  return p.xyz / p.w;
+}
+
+fn grid_lines(coord: vec2<f32>, derivative: vec2<f32>) -> f32 {
+  let distance = abs(fract(coord - 0.5) - 0.5) / derivative;
+  return 1.0 - min(min(distance.x, distance.y), 1.0);
+}
+
+@fragment
+fn fs_grid(in: GridOutput) -> @location(0) vec4<f32> {
+  // intersect the view ray of the pixel with the ground plane.
+  let near = unproject(in.ndc, 0.0);
+  let far = unproject(in.ndc, 0.5);
+  let t = -near.y / (far.y - near.y);
+  let p = near + t * (far - near);
+
+  // derivatives are computed before any discard, while the control flow is uniform.
+  let coord = p.xz / gizmo.grid_spacing;
+  let derivative = fwidth(coord);
+  let minor = grid_lines(coord, derivative);
+  let major = grid_lines(coord / 10.0, derivative / 10.0);
+  var color = vec4<f32>(vec3<f32>(0.5), max(minor * 0.3, major * 0.6));
+
+  let axis = abs(coord) / derivative;
+  if axis.y < 1.0 {
+    color = vec4<f32>(0.9, 0.2, 0.2, 1.0 - axis.y);
+  }
+  if axis.x < 1.0 {
+    color = vec4<f32>(0.2, 0.2, 0.9, 1.0 - axis.x);
+  }
+
+  // fade out towards the horizon, where the lines get denser than the pixels.
+  color.a *= 1.0 - smoothstep(0.2, 1.0, max(derivative.x, derivative.y));
+
+  // keep the intersection only if it lies within the view volume.
+  let clip = gizmo.view_projection * vec4<f32>(p, 1.0);
+  let depth = clip.z / clip.w;
+  if clip.w <= 0.0 || depth < 0.0 || depth > 1.0 || color.a <= 0.0 {
+    discard;
+  }
+  return color;
+}
+
+struct AxisOutput {
+  @builtin(position) position: vec4<f32>,
+  @location(0) color: vec3<f32>,
+};
+
+@vertex
+fn vs_axes(@builtin(vertex_index) index: u32) -> AxisOutput {
+  // two vertices per axis, from the origin to the length of the axis.
+  var direction = vec3<f32>(0.0);
+  direction[index / 2u] = 1.0;
+  let position = direction * gizmo.axes_length * f32(index % 2u);
+  return AxisOutput(gizmo.view_projection * vec4<f32>(position, 1.0), direction);
+}
+
+@fragment
+fn fs_axes(in: AxisOutput) -> @location(0) vec4<f32> {
+  return vec4<f32>(in.color, 1.0);
+}
+";
+
+/// Draws the gizmos configured by [`GizmoConfig`].
+#[derive(Debug)]
+pub(super) struct Gizmos {
+    config: GizmoConfig,
+    visible: AtomicBool,
+
+    buffer: wgpu::Buffer,
+    bind_group_layout: wgpu::BindGroupLayout,
+    bind_group: wgpu::BindGroup,
+    _allocation: MemoryAllocation,
+
+    /// Pipelines of the grid and the axes, created when the gizmos are first drawn.
+    pipelines: OnceLock<(wgpu::RenderPipeline, wgpu::RenderPipeline)>,
+}
+
+impl Gizmos {
+    const LABEL: &str = "ravia_engine::gizmo";
+
+    pub fn new(device: &wgpu::Device, config: GizmoConfig, memory: &Arc<MemoryTracker>) -> Self {
+        let buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some(Self::LABEL),
+            size: std::mem::size_of::<GizmoUniform>() as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some(Self::LABEL),
+            entries: &[wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::VERTEX_FRAGMENT,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            }],
+        });
+
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some(Self::LABEL),
+            layout: &bind_group_layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: buffer.as_entire_binding(),
+            }],
+        });
+
+        Self {
+            config,
+            visible: AtomicBool::new(config.visible),
+            _allocation: memory.allocate(MemoryCategory::Uniform, buffer.size()),
+            buffer,
+            bind_group_layout,
+            bind_group,
+            pipelines: OnceLock::new(),
+        }
+    }
+
+    pub fn config(&self) -> &GizmoConfig {
+        &self.config
+    }
+
+    pub fn is_visible(&self) -> bool {
+        self.visible.load(Ordering::Relaxed)
+    }
+
+    pub fn set_visible(&self, visible: bool) {
+        self.visible.store(visible, Ordering::Relaxed);
+    }
+
+    /// Uploads the camera of the current frame.
+    pub fn write(&self, queue: &wgpu::Queue, view_projection: math::Mat4) {
+        let uniform = GizmoUniform {
+            view_projection: view_projection.to_cols_array_2d(),
+            view_projection_inv: view_projection.inverse().to_cols_array_2d(),
+            grid_spacing: self.config.grid_spacing,
+            axes_length: self.config.axes_length,
+            _padding: [0.0; 2],
+        };
+        queue.write_buffer(&self.buffer, 0, bytemuck::cast_slice(&[uniform]));
+    }
+
+    /// Draws the ground grid, if enabled.
+    pub fn draw_grid(&self, gpu: &Gpu, render_pass: &mut wgpu::RenderPass) {
+        if !self.config.grid {
+            return;
+        }
+
+        let (grid, _) = self.pipelines(gpu);
+        render_pass.set_pipeline(grid);
+        render_pass.set_bind_group(0, &self.bind_group, &[]);
+        render_pass.draw(0..3, 0..1);
+    }
+
+    /// Draws the axes, if enabled.
+    pub fn draw_axes(&self, gpu: &Gpu, render_pass: &mut wgpu::RenderPass) {
+        if !self.config.axes {
+            return;
+        }
+
+        let (_, axes) = self.pipelines(gpu);
+        render_pass.set_pipeline(axes);
+        render_pass.set_bind_group(0, &self.bind_group, &[]);
+        render_pass.draw(0..6, 0..1);
+    }
+
+    fn pipelines(&self, gpu: &Gpu) -> &(wgpu::RenderPipeline, wgpu::RenderPipeline) {
+        self.pipelines.get_or_init(|| {
+            let scene_format = gpu.scene_format();
+            let shader_module = gpu
+                .device
+                .create_shader_module(wgpu::ShaderModuleDescriptor {
+                    label: Some(Self::LABEL),
+                    source: wgpu::ShaderSource::Wgsl(GIZMO_SOURCE.into()),
+                });
+            let pipeline_layout =
+                gpu.device
+                    .create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                        label: Some(Self::LABEL),
+                        bind_group_layouts: &[&self.bind_group_layout],
+                        push_constant_ranges: &[],
+                    });
+
+            let create = |vs: &str, fs: &str, topology: wgpu::PrimitiveTopology| {
+                gpu.device
+                    .create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+                        label: Some(Self::LABEL),
+                        layout: Some(&pipeline_layout),
+                        vertex: wgpu::VertexState {
+                            module: &shader_module,
+                            entry_point: Some(vs),
+                            buffers: &[],
+                            compilation_options: wgpu::PipelineCompilationOptions::default(),
+                        },
+                        fragment: Some(wgpu::FragmentState {
+                            module: &shader_module,
+                            entry_point: Some(fs),
+                            targets: &[Some(wgpu::ColorTargetState {
+                                format: scene_format,
+                                blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+                                write_mask: wgpu::ColorWrites::ALL,
+                            })],
+                            compilation_options: wgpu::PipelineCompilationOptions::default(),
+                        }),
+                        primitive: wgpu::PrimitiveState {
+                            topology,
+                            ..Default::default()
+                        },
+                        depth_stencil: Some(depth_stencil_state(false)),
+                        multisample: wgpu::MultisampleState::default(),
+                        multiview: None,
+                        cache: gpu.pipeline_cache(),
+                    })
+            };
+
+            (
+                create("vs_grid", "fs_grid", wgpu::PrimitiveTopology::TriangleList),
+                create("vs_axes", "fs_axes", wgpu::PrimitiveTopology::LineList),
+            )
+        })
+    }
+}
@@ -1,20 +1,158 @@
-use std::sync::{Arc, Mutex};
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc, Mutex, MutexGuard,
+};
 
 use log::{error, info, warn};
+use wgpu::util::DeviceExt;
 
 use crate::{
     ecs::{self, IntoQuery},
-    math,
+    engine::EngineError,
+    math, time,
 };
 
 use super::{
     camera::Camera,
-    material::Material,
-    mesh::Mesh,
+    capabilities::GpuCapabilities,
+    coordinates::{Handedness, MeshImportOptions},
+    custom_pass::{CustomPassContext, CustomPassStage, CustomPasses},
+    debug_view::{DebugPipelines, DebugView},
+    depth::{DepthBuffer, DEPTH_FORMAT},
+    directional_light::{DirectionalLight, ShadowFallback},
+    downsample::DownsamplePass,
+    frame::{FrameBuffer, FrameUniform},
+    frame_graph::{FrameAttachment, FrameGraph, FramePass, FramePassKind},
+    frame_stats_overlay::{FrameStatsOverlay, FrameStatsOverlayConfig},
+    gizmo::{GizmoConfig, Gizmos},
+    instances::Instances,
+    lightmap::Lightmap,
+    lights::LightsBuffer,
+    material::{Material, MaterialInstance},
+    memory::{MemoryAllocation, MemoryCategory, MemoryStats, MemoryTracker},
+    mesh::{Mesh, Vertex, Vertex3DStandard},
+    overlay::Overlay,
+    pipeline_cache::PipelineCache,
+    pipeline_compiler::PipelineCompiler,
+    point_light::{PointLight, PointShadowFallback},
+    post_process::{PostProcessStack, PostProcessTargets},
+    present::{DisplayAdjustment, PresentPass, PresentTargets, DITHERING_FORMAT},
+    render_order::{RenderLayer, RenderOrder},
+    sampler::{SamplerCache, SamplerKey},
+    shader::{
+        PlaceholderKey, PlaceholderPipelines, PLACEHOLDER_CAMERA_INDEX,
+        PLACEHOLDER_CAMERA_TRANSFORM_INDEX, PLACEHOLDER_MODEL_TRANSFORM_INDEX,
+    },
+    skin::Skin,
+    skybox::{Skybox, SkyboxPipeline},
+    texture_streaming::TextureStreamingConfig,
     transform::Transform,
     uniform::{Uniform, UniformType},
+    viewport::{Viewport, ViewportClearPipelines},
+    visibility::{is_rendered, ComputedVisibility, Visibility},
 };
 
+/// Describes how the frame is rendered when the world has no [`Camera`].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum CameraFallback {
+    /// Skips the frame without presenting it.
+    Skip,
+    /// Presents a cleared frame without rendering the scene.
+    #[default]
+    Clear,
+    /// Renders the scene with an identity projection and camera transform.
+    Identity,
+}
+
+/// [`GpuConfig`] holds the configuration of the renderer.
+#[derive(Debug, Clone, Copy)]
+pub struct GpuConfig {
+    /// Behavior when the world has no [`Camera`].
+    pub camera_fallback: CameraFallback,
+    /// Inserts debug groups around the render passes and draw calls, so that frame captures
+    /// (e.g. RenderDoc or browser devtools) are easier to navigate.
+    pub debug_markers: bool,
+    /// Directory to record a replayable WebGPU API trace into. Only effective in native mode,
+    /// with the `trace` feature of the engine enabled.
+    pub trace_path: Option<&'static str>,
+    /// Directory to persist compiled pipelines into, reused by later runs to cut shader warm-up
+    /// time. Only effective in native mode, on backends supporting pipeline caches.
+    pub pipeline_cache_path: Option<&'static str>,
+    /// Configuration of [`StreamedTexture`](super::texture_streaming::StreamedTexture)s.
+    pub texture_streaming: TextureStreamingConfig,
+    /// Configuration of the ground grid and axes gizmos.
+    pub gizmos: GizmoConfig,
+    /// Configuration of the frame time graph, drawn from the [`crate::time::FrameStats`].
+    pub frame_stats: FrameStatsOverlayConfig,
+    /// Gamma and brightness applied to the frame when presented. See
+    /// [`Gpu::set_display_adjustment`].
+    pub display_adjustment: DisplayAdjustment,
+    /// Renders the scene with 16 bits per channel, and adds blue noise to the frame when it is
+    /// quantized into the target, against the banding of smooth gradients and dark colors with
+    /// 8 bits per channel. Costs the memory and bandwidth of an intermediate texture. Disabled
+    /// on adapters which cannot render into 16-bit float textures.
+    pub dithering: bool,
+    /// Requests the line and point polygon modes of the adapter, so that materials can be drawn
+    /// in wireframe or as points for debugging, see [`super::shader::ShaderConfig::with_polygon_mode`].
+    /// Not supported in web platforms.
+    pub polygon_modes: bool,
+    /// How the window compositor blends the frame with what is behind the window. A non-opaque
+    /// mode combined with [`crate::engine::EngineConfig::transparent_window`] renders with a
+    /// transparent background, where the cameras clear the frame to a color with zero alpha.
+    ///
+    /// [`wgpu::CompositeAlphaMode::Auto`] picks the first mode supported by the surface. A mode
+    /// which is not supported falls back to another supported mode.
+    pub alpha_mode: wgpu::CompositeAlphaMode,
+    /// How frames are presented to the window, trading latency for tearing and power. The
+    /// automatic modes pick the best supported mode, and a mode which is not supported falls
+    /// back to [`wgpu::PresentMode::Fifo`]. See [`Gpu::present_mode`] for the mode in use.
+    pub present_mode: wgpu::PresentMode,
+    /// Maximum number of frames queued for presentation. Lower values reduce the input latency,
+    /// e.g. 1 for latency-sensitive applications, while higher values improve the throughput.
+    pub frame_latency: u32,
+    /// Key logging the [`FrameGraph`] of the last frame, if any. See [`Gpu::frame_graph`].
+    pub frame_graph_key: Option<winit::keyboard::KeyCode>,
+    /// Intermediate value rendered in place of the materials from the start. See
+    /// [`Gpu::set_debug_view`].
+    pub debug_view: DebugView,
+    /// Key cycling through the [`DebugView`]s at runtime, if any.
+    pub debug_view_key: Option<winit::keyboard::KeyCode>,
+    /// Handedness of the world coordinates, which the [`Camera`] projections are created with.
+    pub handedness: Handedness,
+    /// Conversion applied to the meshes loaded from resources, e.g.
+    /// [`MeshImportOptions::converting`] for assets authored with another handedness.
+    pub mesh_import: MeshImportOptions,
+    /// Number of world units in a meter, which the loaded meshes are scaled into and the default
+    /// camera clipping planes are measured in.
+    pub units_per_meter: f32,
+}
+
+impl Default for GpuConfig {
+    fn default() -> Self {
+        Self {
+            camera_fallback: CameraFallback::default(),
+            debug_markers: cfg!(debug_assertions),
+            trace_path: None,
+            pipeline_cache_path: None,
+            texture_streaming: TextureStreamingConfig::default(),
+            gizmos: GizmoConfig::default(),
+            frame_stats: FrameStatsOverlayConfig::default(),
+            display_adjustment: DisplayAdjustment::default(),
+            dithering: false,
+            polygon_modes: false,
+            alpha_mode: wgpu::CompositeAlphaMode::Auto,
+            present_mode: wgpu::PresentMode::AutoVsync,
+            frame_latency: 2,
+            frame_graph_key: None,
+            debug_view: DebugView::Off,
+            debug_view_key: None,
+            handedness: Handedness::default(),
+            mesh_import: MeshImportOptions::default(),
+            units_per_meter: 1.0,
+        }
+    }
+}
+
 /// [`Gpu`] holds the WebGPU device and its resources.
 #[derive(Debug)]
 pub struct Gpu {
@@ -28,26 +166,120 @@ pub struct Gpu {
     ///
     /// A surface corresponds to a platform-specific window (e.g. a canvas in web platforms).
     /// The window lives during the whole engine lifetime, so it holds a static lifetime.
-    pub surface: wgpu::Surface<'static>,
+    /// The surface may be recreated when the application resumes from the background.
+    /// A headless [`Gpu`] has no surface.
+    pub surface: Mutex<Option<wgpu::Surface<'static>>>,
 
-    /// A WebGPU surface configuration.
+    /// A WebGPU surface configuration. A headless [`Gpu`] uses its size and format for the
+    /// offscreen texture.
     pub surface_config: Mutex<wgpu::SurfaceConfiguration>,
 
-    /// A window handle.
-    pub window: Arc<winit::window::Window>,
+    /// A window handle, or `None` for a headless [`Gpu`].
+    pub window: Option<Arc<winit::window::Window>>,
+
+    /// The texture which a headless [`Gpu`] renders into.
+    offscreen: Mutex<Option<wgpu::Texture>>,
 
     /// A collection of default bind group layouts.
     pub(super) default_bind_group_layouts: GpuDefaultBindGroupLayouts,
+
+    /// The renderer configuration.
+    config: GpuConfig,
+
+    /// An identity camera, used as a fallback when the world has no camera.
+    identity_camera: IdentityCamera,
+
+    /// The buffer of [`super::uniform::UniformType::Frame`].
+    frame: FrameBuffer,
+
+    /// The buffer of [`super::uniform::UniformType::Lights`].
+    lights: LightsBuffer,
+
+    /// Samplers shared between textures.
+    samplers: SamplerCache,
+
+    /// Pipelines drawn in place of shaders which are still compiling.
+    placeholders: PlaceholderPipelines,
+
+    /// The intermediate value rendered in place of the materials.
+    debug_view: Mutex<DebugView>,
+    /// Pipelines drawn in place of the materials while a [`DebugView`] is active.
+    debug_pipelines: DebugPipelines,
+
+    /// The pipeline drawing the [`Skybox`] of the world.
+    pub(super) skybox: SkyboxPipeline,
+
+    /// Pipelines clearing the viewports of the cameras which do not cover the frame.
+    viewport_clears: ViewportClearPipelines,
+
+    /// The ground grid and axes gizmos.
+    gizmos: Gizmos,
+
+    /// The frame time graph.
+    frame_stats: FrameStatsOverlay,
+
+    /// The final pass applying the display adjustment.
+    present: PresentPass,
+    post_process: PostProcessStack,
+
+    /// The pass averaging the supersampled frames of [`Gpu::render_offscreen`].
+    downsample: DownsamplePass,
+
+    /// The render passes of the application.
+    custom_passes: CustomPasses,
+
+    /// The intermediate textures of the frames rendered into the window.
+    targets: FrameTargets,
+
+    /// The shadow map bound when no light renders one.
+    shadow_fallback: ShadowFallback,
+
+    /// The cube shadow map bound to the meshes out of the range of every point light rendering
+    /// one.
+    point_shadow_fallback: PointShadowFallback,
+
+    /// Whether the cameras of the overlay pass are drawn.
+    overlays_visible: AtomicBool,
+
+    /// The passes scheduled for the last frame.
+    frame_graph: Mutex<FrameGraph>,
+
+    /// Compiled pipelines persisted on disk, if enabled.
+    pipeline_cache: Option<PipelineCache>,
+
+    /// Compiles the pipelines of [`Shader::new_async`](super::shader::Shader::new_async).
+    pub(super) pipeline_compiler: PipelineCompiler,
+    /// Held while an error scope is pushed, as the scopes of the device capture the errors of
+    /// every thread.
+    error_scopes: Mutex<()>,
+
+    /// A WebGPU instance, kept to recreate the surface.
+    instance: wgpu::Instance,
+
+    /// Set when the device has been lost, e.g. by a driver reset.
+    lost: Arc<AtomicBool>,
+
+    /// WebGPU errors captured since the last frame.
+    errors: Arc<Mutex<Vec<String>>>,
+
+    /// GPU memory allocated by the engine.
+    memory: Arc<MemoryTracker>,
+
+    /// What the adapter supports, probed on creation.
+    capabilities: GpuCapabilities,
 }
 
 impl Gpu {
     /// Creates a new [`Gpu`] and initializes its resources.
-    pub async fn new(window: Arc<winit::window::Window>) -> Self {
+    pub async fn new(
+        window: Arc<winit::window::Window>,
+        config: GpuConfig,
+    ) -> Result<Self, EngineError> {
         let instance = wgpu::Instance::new(Default::default());
 
         let surface = instance
             .create_surface(window.clone())
-            .expect("Failed to create wgpu surface");
+            .map_err(|e| EngineError::Surface(e.to_string()))?;
 
         let adapter = instance
             .request_adapter(&wgpu::RequestAdapterOptions {
@@ -56,20 +288,9 @@ impl Gpu {
                 compatible_surface: Some(&surface),
             })
             .await
-            .expect("Failed to request wgpu adapter");
+            .ok_or(EngineError::AdapterNotFound)?;
 
-        let (device, queue) = adapter
-            .request_device(
-                &wgpu::DeviceDescriptor {
-                    label: Some("ravia_engine"),
-                    required_features: wgpu::Features::empty(),
-                    required_limits: wgpu::Limits::default(),
-                    memory_hints: wgpu::MemoryHints::default(),
-                },
-                None,
-            )
-            .await
-            .expect("Failed to request wgpu device");
+        let (device, queue, capabilities) = Self::request_device(&adapter, &config).await?;
 
         let surface_capabilities = surface.get_capabilities(&adapter);
         let surface_format = surface_capabilities
@@ -84,26 +305,574 @@ impl Gpu {
             format: surface_format,
             width: size.x,
             height: size.y,
-            present_mode: surface_capabilities.present_modes[0],
-            alpha_mode: surface_capabilities.alpha_modes[0],
+            present_mode: Self::select_present_mode(
+                config.present_mode,
+                &surface_capabilities.present_modes,
+            ),
+            alpha_mode: Self::select_alpha_mode(
+                config.alpha_mode,
+                &surface_capabilities.alpha_modes,
+            ),
             view_formats: vec![],
-            desired_maximum_frame_latency: 2,
+            desired_maximum_frame_latency: config.frame_latency.max(1),
         };
 
         surface.configure(&device, &surface_config);
 
+        Ok(Self::with_device(
+            instance,
+            capabilities,
+            device,
+            queue,
+            Some((window, surface)),
+            surface_config,
+            config,
+        ))
+    }
+
+    /// Creates a new [`Gpu`] without a window, rendering frames into an offscreen texture of the
+    /// given size, e.g. for benchmarks and tests.
+    pub async fn new_headless(size: math::UVec2, config: GpuConfig) -> Result<Self, EngineError> {
+        let instance = wgpu::Instance::new(Default::default());
+
+        let adapter = instance
+            .request_adapter(&wgpu::RequestAdapterOptions {
+                power_preference: wgpu::PowerPreference::HighPerformance,
+                force_fallback_adapter: false,
+                compatible_surface: None,
+            })
+            .await
+            .ok_or(EngineError::AdapterNotFound)?;
+
+        let (device, queue, capabilities) = Self::request_device(&adapter, &config).await?;
+
+        let surface_config = wgpu::SurfaceConfiguration {
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+            format: wgpu::TextureFormat::Rgba8UnormSrgb,
+            width: size.x.max(1),
+            height: size.y.max(1),
+            present_mode: wgpu::PresentMode::Fifo,
+            alpha_mode: wgpu::CompositeAlphaMode::Opaque,
+            view_formats: vec![],
+            desired_maximum_frame_latency: config.frame_latency.max(1),
+        };
+
+        Ok(Self::with_device(
+            instance,
+            capabilities,
+            device,
+            queue,
+            None,
+            surface_config,
+            config,
+        ))
+    }
+
+    /// Returns the requested alpha mode if supported by the surface, or the closest supported mode.
+    fn select_alpha_mode(
+        requested: wgpu::CompositeAlphaMode,
+        supported: &[wgpu::CompositeAlphaMode],
+    ) -> wgpu::CompositeAlphaMode {
+        if requested == wgpu::CompositeAlphaMode::Auto {
+            return supported[0];
+        }
+        if supported.contains(&requested) {
+            return requested;
+        }
+
+        // a transparent mode falls back to another transparent mode, if any.
+        let fallback = match requested {
+            wgpu::CompositeAlphaMode::Opaque => None,
+            _ => supported
+                .iter()
+                .copied()
+                .find(|mode| *mode != wgpu::CompositeAlphaMode::Opaque),
+        }
+        .unwrap_or(supported[0]);
+        warn!(target: "ravia_engine::graphics::gpu", "Alpha mode {:?} is not supported, using {:?}", requested, fallback);
+        fallback
+    }
+
+    /// Returns the requested present mode if supported by the surface, resolving the automatic
+    /// modes into the mode they select.
+    fn select_present_mode(
+        requested: wgpu::PresentMode,
+        supported: &[wgpu::PresentMode],
+    ) -> wgpu::PresentMode {
+        let candidates: &[wgpu::PresentMode] = match requested {
+            wgpu::PresentMode::AutoVsync => {
+                &[wgpu::PresentMode::FifoRelaxed, wgpu::PresentMode::Fifo]
+            }
+            wgpu::PresentMode::AutoNoVsync => &[
+                wgpu::PresentMode::Immediate,
+                wgpu::PresentMode::Mailbox,
+                wgpu::PresentMode::Fifo,
+            ],
+            _ => &[requested],
+        };
+
+        // fifo is supported by every surface.
+        candidates
+            .iter()
+            .copied()
+            .find(|mode| supported.contains(mode))
+            .unwrap_or_else(|| {
+                warn!(target: "ravia_engine::graphics::gpu", "Present mode {:?} is not supported, using Fifo", requested);
+                wgpu::PresentMode::Fifo
+            })
+    }
+
+    /// Requests the device with the limits and features supported by the adapter.
+    async fn request_device(
+        adapter: &wgpu::Adapter,
+        config: &GpuConfig,
+    ) -> Result<(wgpu::Device, wgpu::Queue, GpuCapabilities), EngineError> {
+        let capabilities = GpuCapabilities::probe(adapter, config);
+        let (device, queue) = adapter
+            .request_device(
+                &wgpu::DeviceDescriptor {
+                    label: Some("ravia_engine"),
+                    required_features: capabilities.features(),
+                    required_limits: capabilities.limits().clone(),
+                    memory_hints: wgpu::MemoryHints::default(),
+                },
+                Self::trace_path(config),
+            )
+            .await
+            .map_err(|e| EngineError::Device(e.to_string()))?;
+        Ok((device, queue, capabilities))
+    }
+
+    /// Initializes the resources of the engine on the device, rendering to the surface of the
+    /// window if given, or offscreen otherwise.
+    fn with_device(
+        instance: wgpu::Instance,
+        capabilities: GpuCapabilities,
+        device: wgpu::Device,
+        queue: wgpu::Queue,
+        window_surface: Option<(Arc<winit::window::Window>, wgpu::Surface<'static>)>,
+        surface_config: wgpu::SurfaceConfiguration,
+        config: GpuConfig,
+    ) -> Self {
+        let lost = Arc::new(AtomicBool::new(false));
+        {
+            let lost = lost.clone();
+            device.set_device_lost_callback(move |reason, message| {
+                // the callback is also invoked when the device is dropped or the callback is replaced.
+                if reason == wgpu::DeviceLostReason::Unknown {
+                    error!(target: "ravia_engine::graphics::gpu", "Device lost: {}", message);
+                    lost.store(true, Ordering::SeqCst);
+                }
+            });
+        }
+
+        let errors = Arc::new(Mutex::new(vec![]));
+        {
+            let errors = errors.clone();
+            // by default, uncaptured errors panic in native platforms.
+            device.on_uncaptured_error(Box::new(move |e| {
+                error!(target: "ravia_engine::graphics::gpu", "Uncaptured WebGPU error: {}", e);
+                errors.lock().unwrap().push(e.to_string());
+            }));
+        }
+
+        let (window, surface) = window_surface.unzip();
+        let offscreen = match surface {
+            Some(_) => None,
+            None => Some(Self::create_offscreen_texture(&device, &surface_config)),
+        };
+
+        let pipeline_cache =
+            PipelineCache::load(&device, capabilities.adapter(), config.pipeline_cache_path);
+
+        let memory = Arc::new(MemoryTracker::default());
         let default_bind_group_layouts = GpuDefaultBindGroupLayouts::new(&device);
+        let identity_camera = IdentityCamera::new(&device, &default_bind_group_layouts, &memory);
+        let frame = FrameBuffer::new(&device, &default_bind_group_layouts, &memory);
+        let lights = LightsBuffer::new(&device, &default_bind_group_layouts, &memory);
+        let skybox = SkyboxPipeline::new(&device);
+        let gizmos = Gizmos::new(&device, config.gizmos, &memory);
+        let frame_stats = FrameStatsOverlay::new(&device, config.frame_stats, &memory);
+        let present = PresentPass::new(
+            &device,
+            config.display_adjustment,
+            capabilities.dithering(),
+            &memory,
+        );
+        let post_process = PostProcessStack::new(&device, &memory);
+        let downsample = DownsamplePass::new(&device);
+        let targets = FrameTargets::new(&memory);
+        let shadow_fallback = ShadowFallback::new(&device, &default_bind_group_layouts, &memory);
+        let point_shadow_fallback =
+            PointShadowFallback::new(&device, &default_bind_group_layouts, &memory);
 
         Self {
             device,
             queue,
-            surface,
+            surface: Mutex::new(surface),
             surface_config: Mutex::new(surface_config),
             window,
+            offscreen: Mutex::new(offscreen),
             default_bind_group_layouts,
+            config,
+            identity_camera,
+            frame,
+            lights,
+            samplers: SamplerCache::default(),
+            placeholders: PlaceholderPipelines::default(),
+            debug_view: Mutex::new(config.debug_view),
+            debug_pipelines: DebugPipelines::default(),
+            skybox,
+            viewport_clears: ViewportClearPipelines::default(),
+            gizmos,
+            frame_stats,
+            present,
+            post_process,
+            downsample,
+            custom_passes: CustomPasses::default(),
+            targets,
+            shadow_fallback,
+            point_shadow_fallback,
+            overlays_visible: AtomicBool::new(true),
+            frame_graph: Mutex::default(),
+            pipeline_cache,
+            pipeline_compiler: PipelineCompiler::default(),
+            error_scopes: Mutex::default(),
+            instance,
+            lost,
+            errors,
+            memory,
+            capabilities,
+        }
+    }
+
+    /// Creates the texture which headless frames are rendered into.
+    fn create_offscreen_texture(
+        device: &wgpu::Device,
+        surface_config: &wgpu::SurfaceConfiguration,
+    ) -> wgpu::Texture {
+        device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("ravia_engine::offscreen"),
+            size: wgpu::Extent3d {
+                width: surface_config.width,
+                height: surface_config.height,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: surface_config.format,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC,
+            view_formats: &[],
+        })
+    }
+
+    /// Returns true if the [`Gpu`] renders offscreen, without a window.
+    pub fn is_headless(&self) -> bool {
+        self.window.is_none()
+    }
+
+    /// Returns the shared sampler with the given state.
+    pub(super) fn sampler(&self, key: SamplerKey) -> Arc<wgpu::Sampler> {
+        self.samplers.get(&self.device, key)
+    }
+
+    /// Returns true if the gizmos configured by [`GpuConfig::gizmos`] are drawn.
+    pub fn gizmos_visible(&self) -> bool {
+        self.gizmos.is_visible()
+    }
+
+    /// Shows or hides the gizmos configured by [`GpuConfig::gizmos`].
+    pub fn set_gizmos_visible(&self, visible: bool) {
+        self.gizmos.set_visible(visible);
+    }
+
+    /// Returns true if the frame time graph configured by [`GpuConfig::frame_stats`] is drawn.
+    pub fn frame_stats_visible(&self) -> bool {
+        self.frame_stats.is_visible()
+    }
+
+    /// Shows or hides the frame time graph configured by [`GpuConfig::frame_stats`].
+    pub fn set_frame_stats_visible(&self, visible: bool) {
+        self.frame_stats.set_visible(visible);
+    }
+
+    /// Uploads the recent frame times drawn by the frame time graph, if visible.
+    pub(crate) fn write_frame_stats(&self, stats: &time::FrameStats) {
+        if !self.frame_stats.is_visible() {
+            return;
+        }
+        let resolution = {
+            let surface_config = self.surface_config.lock().unwrap();
+            math::uvec2(surface_config.width, surface_config.height)
+        };
+        self.frame_stats.write(&self.queue, stats, resolution);
+    }
+
+    /// Returns how the window compositor blends the frame, as configured for the surface.
+    pub fn alpha_mode(&self) -> wgpu::CompositeAlphaMode {
+        self.surface_config.lock().unwrap().alpha_mode
+    }
+
+    /// Returns how frames are presented to the window, as configured for the surface. Automatic
+    /// modes of [`GpuConfig::present_mode`] are resolved into the selected mode.
+    pub fn present_mode(&self) -> wgpu::PresentMode {
+        self.surface_config.lock().unwrap().present_mode
+    }
+
+    /// Returns the maximum number of frames queued for presentation.
+    pub fn frame_latency(&self) -> u32 {
+        self.surface_config
+            .lock()
+            .unwrap()
+            .desired_maximum_frame_latency
+    }
+
+    /// Returns the format the cameras render the scene in, which the pipelines of the scene
+    /// target: the format of the surface, or a 16-bit float format while dithering.
+    pub fn scene_format(&self) -> wgpu::TextureFormat {
+        match self.capabilities.dithering() {
+            true => DITHERING_FORMAT,
+            false => self.surface_config.lock().unwrap().format,
+        }
+    }
+
+    /// Returns the gamma and brightness applied to the frame.
+    pub fn display_adjustment(&self) -> DisplayAdjustment {
+        self.present.adjustment()
+    }
+
+    /// Sets the gamma and brightness applied to the frame, e.g. from an options menu.
+    ///
+    /// Unless the adjustment is the identity, frames are rendered into an intermediate texture
+    /// which is then copied to the window with the adjustment applied.
+    pub fn set_display_adjustment(&self, adjustment: DisplayAdjustment) {
+        self.present.set_adjustment(adjustment);
+    }
+
+    /// Returns the chain of full-screen effects applied to the scene before it is presented.
+    ///
+    /// ```ignore
+    /// let grayscale = ctx.gpu.post_process().push(PostProcessEffect::new("grayscale", SOURCE));
+    /// ```
+    pub fn post_process(&self) -> &PostProcessStack {
+        &self.post_process
+    }
+
+    /// Returns the render passes of the application, recorded into every frame along with the
+    /// passes of the engine.
+    ///
+    /// ```ignore
+    /// ctx.gpu.custom_passes().add("outline", CustomPassStage::AfterScene, outline_pass);
+    /// ```
+    pub fn custom_passes(&self) -> &CustomPasses {
+        &self.custom_passes
+    }
+
+    /// Takes over the settings of the application from the [`Gpu`] of a lost device: the
+    /// visibility of the gizmos, the frame time graph and the overlays, the display adjustment,
+    /// the debug view and the post-process effects.
+    ///
+    /// The [`CustomPasses`] are not taken over, as they may hold resources of the lost device.
+    pub(crate) fn restore_settings(&self, lost: &Gpu) {
+        self.set_gizmos_visible(lost.gizmos_visible());
+        self.set_frame_stats_visible(lost.frame_stats_visible());
+        self.set_overlays_visible(lost.overlays_visible());
+        self.set_display_adjustment(lost.display_adjustment());
+        self.set_debug_view(lost.debug_view());
+        self.post_process.restore_from(&lost.post_process);
+    }
+
+    /// Returns true if the [`Overlay`] cameras are drawn.
+    pub fn overlays_visible(&self) -> bool {
+        self.overlays_visible.load(Ordering::Relaxed)
+    }
+
+    /// Shows or hides every [`Overlay`] camera, e.g. to take a screenshot without the UI.
+    pub fn set_overlays_visible(&self, visible: bool) {
+        self.overlays_visible.store(visible, Ordering::Relaxed);
+    }
+
+    /// Returns the key toggling the gizmos, if any.
+    pub(crate) fn gizmo_toggle_key(&self) -> Option<winit::keyboard::KeyCode> {
+        self.gizmos.config().toggle_key
+    }
+
+    /// Returns the key toggling the frame time graph, if any.
+    pub(crate) fn frame_stats_toggle_key(&self) -> Option<winit::keyboard::KeyCode> {
+        self.frame_stats.config().toggle_key
+    }
+
+    /// Returns the key logging the frame graph, if any.
+    pub(crate) fn frame_graph_key(&self) -> Option<winit::keyboard::KeyCode> {
+        self.config.frame_graph_key
+    }
+
+    pub(crate) fn debug_view_key(&self) -> Option<winit::keyboard::KeyCode> {
+        self.config.debug_view_key
+    }
+
+    /// Returns the intermediate value rendered in place of the materials.
+    pub fn debug_view(&self) -> DebugView {
+        *self.debug_view.lock().unwrap()
+    }
+
+    /// Renders an intermediate value of the scene in place of the materials, e.g. the normals
+    /// to check the meshes, or [`DebugView::Off`] to render the materials again.
+    ///
+    /// Meshes are drawn with a debug shader reading the attributes of their vertices, without
+    /// their skins and instances. The overlay cameras are drawn as usual.
+    pub fn set_debug_view(&self, view: DebugView) {
+        *self.debug_view.lock().unwrap() = view;
+    }
+
+    /// Returns the render passes scheduled for the last frame, with their attachments and draw
+    /// calls, e.g. to check what the renderer actually draws after extending it.
+    pub fn frame_graph(&self) -> FrameGraph {
+        self.frame_graph.lock().unwrap().clone()
+    }
+
+    /// Returns the handedness of the world coordinates.
+    pub fn handedness(&self) -> Handedness {
+        self.config.handedness
+    }
+
+    /// Returns the number of world units in a meter.
+    pub fn units_per_meter(&self) -> f32 {
+        self.config.units_per_meter
+    }
+
+    /// Returns the conversion applied to the meshes loaded from resources.
+    pub fn mesh_import_options(&self) -> MeshImportOptions {
+        self.config.mesh_import
+    }
+
+    /// Returns the configuration of texture streaming.
+    pub(super) fn texture_streaming_config(&self) -> &TextureStreamingConfig {
+        &self.config.texture_streaming
+    }
+
+    /// Returns the pipeline cache to compile pipelines with, if enabled.
+    pub(super) fn pipeline_cache(&self) -> Option<&wgpu::PipelineCache> {
+        self.pipeline_cache.as_ref().map(PipelineCache::cache)
+    }
+
+    /// Writes the compiled pipelines to the pipeline cache directory, if enabled.
+    ///
+    /// The engine saves the cache when the application is suspended or exits.
+    pub fn save_pipeline_cache(&self) {
+        if let Some(pipeline_cache) = &self.pipeline_cache {
+            pipeline_cache.save();
+        }
+    }
+
+    /// Returns the directory to record the API trace into, creating it if necessary.
+    fn trace_path(config: &GpuConfig) -> Option<&'static std::path::Path> {
+        #[cfg(target_arch = "wasm32")]
+        {
+            let _ = config;
+            None
+        }
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            let path = std::path::Path::new(config.trace_path?);
+            if let Err(e) = std::fs::create_dir_all(path) {
+                warn!(target: "ravia_engine::graphics::gpu", "Failed to create trace directory: {}", e);
+                return None;
+            }
+
+            if !cfg!(feature = "trace") {
+                warn!(target: "ravia_engine::graphics::gpu", "Trace path is set, but the trace feature is disabled");
+            }
+
+            info!(target: "ravia_engine::graphics::gpu", "Recording WebGPU trace into {}", path.display());
+            Some(path)
         }
     }
 
+    /// Returns true if the device has been lost, and the [`Gpu`] should be recreated.
+    pub fn is_lost(&self) -> bool {
+        self.lost.load(Ordering::SeqCst)
+    }
+
+    /// Runs the closure creating GPU resources within a validation error scope.
+    ///
+    /// The validation errors are logged with the label, and reported as
+    /// [`crate::event::Event::GpuError`] events.
+    pub fn error_scope<T>(&self, label: &str, f: impl FnOnce(&wgpu::Device) -> T) -> T {
+        self.flagged_error_scope(label, None, f)
+    }
+
+    /// Runs the closure like [`Gpu::error_scope`], also raising the flag on a validation error.
+    ///
+    /// The flag is raised once the error is known, which may be after returning on the web.
+    pub(super) fn flagged_error_scope<T>(
+        &self,
+        label: &str,
+        flag: Option<Arc<AtomicBool>>,
+        f: impl FnOnce(&wgpu::Device) -> T,
+    ) -> T {
+        let (result, error) = {
+            let _scopes = self.error_scopes.lock().unwrap();
+            self.device.push_error_scope(wgpu::ErrorFilter::Validation);
+            let result = f(&self.device);
+            (result, self.device.pop_error_scope())
+        };
+
+        let label = label.to_string();
+        let errors = self.errors.clone();
+        let report = async move {
+            if let Some(e) = error.await {
+                error!(target: "ravia_engine::graphics::gpu", "WebGPU error in {}: {}", label, e);
+                errors.lock().unwrap().push(format!("{}: {}", label, e));
+                if let Some(flag) = flag {
+                    flag.store(true, Ordering::SeqCst);
+                }
+            }
+        };
+
+        #[cfg(target_arch = "wasm32")]
+        wasm_bindgen_futures::spawn_local(report);
+        #[cfg(not(target_arch = "wasm32"))]
+        pollster::block_on(report);
+
+        result
+    }
+
+    /// Runs the closure while no error scope is pushed, so that its validation errors are
+    /// reported as uncaptured errors rather than captured by the scope of another thread.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub(super) fn outside_error_scopes<T>(&self, f: impl FnOnce(&wgpu::Device) -> T) -> T {
+        let _scopes = self.error_scopes.lock().unwrap();
+        f(&self.device)
+    }
+
+    /// Logs an error detected by the engine, and reports it like a captured WebGPU error.
+    pub(super) fn report_error(&self, message: String) {
+        error!(target: "ravia_engine::graphics::gpu", "{}", message);
+        self.errors.lock().unwrap().push(message);
+    }
+
+    /// Takes the WebGPU errors captured since the last call.
+    pub fn take_errors(&self) -> Vec<String> {
+        std::mem::take(&mut *self.errors.lock().unwrap())
+    }
+
+    /// Returns what the adapter supports, and the engine features adjusted on startup.
+    pub fn capabilities(&self) -> &GpuCapabilities {
+        &self.capabilities
+    }
+
+    /// Returns the GPU memory allocated by the engine.
+    pub fn memory_stats(&self) -> MemoryStats {
+        self.memory.stats()
+    }
+
+    /// Records a GPU memory allocation, released when the returned allocation is dropped.
+    pub(super) fn track_memory(&self, category: MemoryCategory, bytes: u64) -> MemoryAllocation {
+        self.memory.allocate(category, bytes)
+    }
+
     /// Retrieves the current display size from the window.
     pub fn window_size(window: &winit::window::Window) -> math::UVec2 {
         let winit::dpi::PhysicalSize { width, height } = window.inner_size();
@@ -115,103 +884,1078 @@ impl Gpu {
         let mut surface_config = self.surface_config.lock().unwrap();
         surface_config.width = size.x.max(1);
         surface_config.height = size.y.max(1);
-        self.surface.configure(&self.device, &surface_config);
+        match self.surface.lock().unwrap().as_ref() {
+            Some(surface) => surface.configure(&self.device, &surface_config),
+            None => {
+                *self.offscreen.lock().unwrap() = Some(Self::create_offscreen_texture(
+                    &self.device,
+                    &surface_config,
+                ));
+            }
+        }
+        self.targets.depth.resize(
+            &self.device,
+            math::uvec2(surface_config.width, surface_config.height),
+        );
     }
 
-    /// Renders the current frame.
+    /// Recreates the surface from the window.
     ///
-    /// For now, this procedure contains all the details about wgpu render pipeline specific to
-    /// surface texture. We hope to move this to a separate module in the future.
-    pub fn render(&self, world: &ecs::World) {
-        let surface_texture = match self.surface.get_current_texture() {
+    /// Some platforms (e.g. Android) invalidate the surface while the application is suspended,
+    /// so it should be recreated when the application resumes.
+    pub fn recreate_surface(&self) -> Result<(), EngineError> {
+        let Some(window) = &self.window else {
+            return Ok(());
+        };
+
+        let surface = self
+            .instance
+            .create_surface(window.clone())
+            .map_err(|e| EngineError::Surface(e.to_string()))?;
+
+        let mut surface_config = self.surface_config.lock().unwrap();
+        let size = Self::window_size(window);
+        surface_config.width = size.x;
+        surface_config.height = size.y;
+        surface.configure(&self.device, &surface_config);
+
+        *self.surface.lock().unwrap() = Some(surface);
+        Ok(())
+    }
+
+    /// Acquires the texture to render the frame into, or `None` if the frame should be skipped.
+    fn acquire_frame_target(&self) -> Option<FrameTarget<'_>> {
+        let Some(window) = &self.window else {
+            return Some(FrameTarget::Offscreen(self.offscreen.lock().unwrap()));
+        };
+
+        let surface_texture = self.current_surface_texture()?;
+        let surface_texture = match surface_texture {
             Ok(surface_texture) => surface_texture,
             Err(wgpu::SurfaceError::Lost | wgpu::SurfaceError::Outdated) => {
                 info!(target: "ravia_engine::graphics::gpu", "Surface lost or outdated, resizing");
 
-                self.resize(Self::window_size(&self.window));
-                self.surface
-                    .get_current_texture()
-                    .expect("Failed to get current surface texture")
+                self.resize(Self::window_size(window));
+                let surface_texture = self.current_surface_texture()?;
+                match surface_texture {
+                    Ok(surface_texture) => surface_texture,
+                    Err(e) => {
+                        error!(target: "ravia_engine::graphics::gpu", "Failed to reconfigure surface, skipping frame: {}", e);
+                        return None;
+                    }
+                }
             }
             Err(wgpu::SurfaceError::Timeout) => {
                 error!(target: "ravia_engine::graphics::gpu", "Surface timeout, skipping frame");
-                return;
+                return None;
             }
             Err(wgpu::SurfaceError::OutOfMemory) => {
                 error!(target: "ravia_engine::graphics::gpu", "Out of memory, skipping frame");
-                return;
+                return None;
             }
         };
 
-        let target_view = surface_texture
-            .texture
-            .create_view(&wgpu::TextureViewDescriptor::default());
+        Some(FrameTarget::Surface(surface_texture))
+    }
+
+    fn current_surface_texture(&self) -> Option<Result<wgpu::SurfaceTexture, wgpu::SurfaceError>> {
+        self.surface
+            .lock()
+            .unwrap()
+            .as_ref()
+            .map(wgpu::Surface::get_current_texture)
+    }
+
+    /// Renders the current frame.
+    ///
+    /// For now, this procedure contains all the details about wgpu render pipeline specific to
+    /// surface texture. We hope to move this to a separate module in the future.
+    pub fn render(&self, world: &ecs::World, time: &time::Time) {
+        if self.is_lost() {
+            return;
+        }
+
+        let Some(frame_target) = self.acquire_frame_target() else {
+            return;
+        };
+
+        let frame_graph =
+            self.render_into(world, time, frame_target.texture(), &self.targets, true);
+        if let Some(frame_graph) = frame_graph {
+            *self.frame_graph.lock().unwrap() = frame_graph;
+            frame_target.present();
+        }
+    }
+
+    /// Renders the world into a new texture of the given size, e.g. to capture a screenshot.
+    ///
+    /// The frame is rendered at `supersampling` times the size, and every texel of the texture
+    /// averages a block of `supersampling` by `supersampling` samples. The supersampling is
+    /// limited by the maximum texture size of the device.
+    ///
+    /// The frame is rendered with its own depth buffer and intermediate textures, leaving the
+    /// ones of the window and the [`Gpu::frame_graph`] of the last frame unchanged.
+    ///
+    /// The texture has the format of the surface, and can be read back with
+    /// [`Gpu::read_texture`]. The display adjustment is not applied.
+    pub fn render_offscreen(
+        &self,
+        world: &ecs::World,
+        time: &time::Time,
+        size: math::UVec2,
+        supersampling: u32,
+    ) -> wgpu::Texture {
+        let max_size = self.device.limits().max_texture_dimension_2d;
+        let size = size.clamp(math::UVec2::ONE, math::UVec2::splat(max_size));
+        let supersampling = supersampling.clamp(1, max_size / size.max_element());
+        let format = self.surface_config.lock().unwrap().format;
+        let create_texture = |size: math::UVec2| {
+            self.device.create_texture(&wgpu::TextureDescriptor {
+                label: Some("ravia_engine::offscreen"),
+                size: wgpu::Extent3d {
+                    width: size.x,
+                    height: size.y,
+                    depth_or_array_layers: 1,
+                },
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: wgpu::TextureDimension::D2,
+                format,
+                usage: wgpu::TextureUsages::RENDER_ATTACHMENT
+                    | wgpu::TextureUsages::TEXTURE_BINDING
+                    | wgpu::TextureUsages::COPY_SRC,
+                view_formats: &[],
+            })
+        };
+
+        let texture = create_texture(size);
+        if self.is_lost() {
+            return texture;
+        }
+
+        let targets = FrameTargets::new(&self.memory);
+        if supersampling == 1 {
+            self.render_into(world, time, &texture, &targets, false);
+            return texture;
+        }
+
+        let supersampled = create_texture(size * supersampling);
+        if self
+            .render_into(world, time, &supersampled, &targets, false)
+            .is_some()
+        {
+            let mut command_encoder =
+                self.device
+                    .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                        label: Some("ravia_engine::downsample"),
+                    });
+            self.downsample.draw(
+                self,
+                &mut command_encoder,
+                &supersampled,
+                &texture,
+                supersampling,
+            );
+            self.queue.submit(std::iter::once(command_encoder.finish()));
+        }
+        texture
+    }
+
+    /// Renders the world into the target texture with the intermediate textures of the target,
+    /// applying the display adjustment if presenting to the window.
+    ///
+    /// Returns the passes of the frame, or `None` if the frame has been skipped.
+    fn render_into(
+        &self,
+        world: &ecs::World,
+        time: &time::Time,
+        target_texture: &wgpu::Texture,
+        targets: &FrameTargets,
+        presenting: bool,
+    ) -> Option<FrameGraph> {
+        // resolves the readbacks whose copy has finished, without waiting for the others.
+        #[cfg(not(target_arch = "wasm32"))]
+        self.device.poll(wgpu::Maintain::Poll);
+        self.pipeline_compiler.poll(self);
+
+        // cameras are drawn by their order, each over the frame drawn by the previous ones.
+        let overlays_visible = self.overlays_visible();
+        let mut cameras = <(&Camera, &Transform, Option<&Overlay>)>::query()
+            .iter(world)
+            .filter(|(_, _, overlay)| overlays_visible || overlay.is_none())
+            .collect::<Vec<_>>();
+        // overlay cameras are drawn after every scene camera.
+        cameras.sort_by_key(|(camera, _, overlay)| (overlay.is_some(), camera.order()));
+        // cameras rendering into textures are drawn before the frame, which may sample them.
+        let (texture_cameras, cameras): (Vec<_>, Vec<_>) = cameras
+            .into_iter()
+            .partition(|(camera, _, _)| camera.target().is_some());
+
+        let primary = cameras.first();
+        let camera_position =
+            primary.map_or(math::Vec3::ZERO, |(_, transform, _)| *transform.position());
+        let view_projection = primary.map_or(math::Mat4::IDENTITY, |(camera, transform, _)| {
+            *camera.projection() * *transform.transform_inv()
+        });
+
+        // a transparent window shows through the frame cleared without a camera.
+        let clear_black = wgpu::LoadOp::Clear(match self.alpha_mode() {
+            wgpu::CompositeAlphaMode::Opaque => wgpu::Color::BLACK,
+            _ => wgpu::Color::TRANSPARENT,
+        });
+        let passes = match (cameras.is_empty(), self.config.camera_fallback) {
+            (false, _) => cameras
+                .iter()
+                .map(|(camera, transform, overlay)| {
+                    let load = match overlay {
+                        Some(_) => wgpu::LoadOp::Load,
+                        None => camera.clear().load_op(),
+                    };
+                    (
+                        Some((
+                            camera.bind_group(),
+                            transform.bind_group(),
+                            overlay.is_some(),
+                        )),
+                        load,
+                        Some(camera.order()),
+                        camera.viewport(),
+                    )
+                })
+                .collect::<Vec<_>>(),
+            (true, CameraFallback::Skip) => {
+                warn!(target: "ravia_engine::graphics::gpu", "No camera found, skipping frame");
+                return None;
+            }
+            (true, CameraFallback::Clear) => vec![(None, clear_black, None, Viewport::FULL)],
+            (true, CameraFallback::Identity) => vec![(
+                Some((
+                    &self.identity_camera.camera,
+                    &self.identity_camera.transform,
+                    false,
+                )),
+                clear_black,
+                None,
+                Viewport::FULL,
+            )],
+        };
+
+        let resolution = math::uvec2(target_texture.width(), target_texture.height());
+        self.frame.write(
+            &self.queue,
+            FrameUniform::new(
+                time.time.as_secs_f32(),
+                time.delta.as_secs_f32(),
+                resolution.as_vec2(),
+                camera_position,
+            ),
+        );
+        self.lights.write(&self.queue, world, camera_position);
+
+        let target_view = target_texture.create_view(&wgpu::TextureViewDescriptor::default());
+        let frame_view =
+            self.present
+                .frame_view(self, &targets.present, target_texture, presenting);
+        let post_process_view =
+            self.post_process
+                .input_view(self, &targets.post_process, target_texture);
+        let scene_view = post_process_view
+            .as_ref()
+            .or(frame_view.as_ref())
+            .unwrap_or(&target_view);
+        let scene_format = self.scene_format();
+        let depth_view = targets.depth.view(&self.device, resolution);
         let mut command_encoder =
             self.device
                 .create_command_encoder(&wgpu::CommandEncoderDescriptor {
                     label: Some("ravia_engine"),
                 });
 
-        'render_pass: {
+        let gizmos_visible = self.gizmos.is_visible();
+        if gizmos_visible {
+            self.gizmos.write(&self.queue, view_projection);
+        }
+
+        let debug_markers = self.config.debug_markers;
+        let mut frame_graph = FrameGraph::default();
+        let skybox = <&Skybox>::query().iter(world).next();
+
+        // the shadow maps are rendered before every camera, which may sample them.
+        let shadow_light = <&DirectionalLight>::query()
+            .iter(world)
+            .find_map(|light| light.shadow_map().map(|shadow| (light, shadow)));
+        let directional = match shadow_light {
+            Some((light, shadow)) => {
+                shadow.update(
+                    &self.queue,
+                    self.handedness(),
+                    light.direction,
+                    camera_position,
+                );
+                frame_graph.push(self.draw_shadow_map(
+                    world,
+                    &mut command_encoder,
+                    shadow.view(),
+                    shadow.size(),
+                    (&shadow.camera, &shadow.transform),
+                ));
+                &shadow.bind_group
+            }
+            None => &self.shadow_fallback.bind_group,
+        };
+
+        let mut points = vec![];
+        for (light, transform) in <(&PointLight, &Transform)>::query().iter(world) {
+            let Some(shadow) = light.shadow_map() else {
+                continue;
+            };
+
+            let position = *transform.position();
+            shadow.update(&self.queue, position, light.range);
+            for face in shadow.faces() {
+                frame_graph.push(self.draw_shadow_map(
+                    world,
+                    &mut command_encoder,
+                    &face.view,
+                    shadow.size(),
+                    (&face.camera, &face.transform),
+                ));
+            }
+            points.push((position, light.range, &shadow.bind_group));
+        }
+        let shadows = SceneShadows {
+            directional,
+            points,
+            point_fallback: &self.point_shadow_fallback.bind_group,
+        };
+
+        for (camera, transform, overlay) in texture_cameras {
+            let target = camera.target().expect("texture cameras have a target");
+            let (view, depth_view) = target.views();
+            let load = camera.clear().load_op();
+            let viewport = camera.viewport();
+            let (pass_load, viewport_clear) = viewport.load_op(load);
+            let mut pass = FramePass {
+                kind: match overlay {
+                    Some(_) => FramePassKind::Overlay,
+                    None => FramePassKind::Scene,
+                },
+                camera_order: Some(camera.order()),
+                attachment: FrameAttachment::RenderTexture,
+                size: target.size(),
+                format: target.texture().format(),
+                clear: matches!(load, wgpu::LoadOp::Clear(_)),
+                depth: true,
+                draw_calls: 0,
+                placeholder_draws: 0,
+                gizmos: false,
+            };
+            if debug_markers {
+                command_encoder.push_debug_group("ravia_engine::render_texture_pass");
+            }
+
+            let mut render_pass = command_encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("ravia_engine::render_texture"),
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view: &view,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: pass_load,
+                        store: wgpu::StoreOp::Store,
+                    },
+                })],
+                depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
+                    view: &depth_view,
+                    depth_ops: Some(wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(1.0),
+                        store: wgpu::StoreOp::Discard,
+                    }),
+                    stencil_ops: None,
+                }),
+                occlusion_query_set: None,
+                timestamp_writes: None,
+            });
+            if viewport != Viewport::FULL {
+                let target = (target.size(), target.texture().format());
+                self.viewport_clears.apply(
+                    self,
+                    &mut render_pass,
+                    viewport,
+                    target,
+                    viewport_clear,
+                );
+            }
+            if let Some(skybox) = skybox.filter(|_| overlay.is_none()) {
+                let camera = (camera.bind_group(), transform.bind_group());
+                if self.skybox.draw(self, &mut render_pass, skybox, camera) {
+                    pass.draw_calls += 1;
+                }
+            }
+            self.draw_scene(
+                world,
+                &mut render_pass,
+                (camera.bind_group(), transform.bind_group()),
+                &shadows,
+                overlay.is_some(),
+                &mut pass,
+            );
+
+            drop(render_pass);
+            frame_graph.push(pass);
+            if debug_markers {
+                command_encoder.pop_debug_group();
+            }
+        }
+
+        if debug_markers {
+            command_encoder.push_debug_group("ravia_engine::main_pass");
+        }
+
+        let output_attachment = match frame_view {
+            Some(_) => FrameAttachment::Frame,
+            None => FrameAttachment::Target,
+        };
+        let scene_attachment = match post_process_view {
+            Some(_) => FrameAttachment::PostProcess,
+            None => output_attachment,
+        };
+
+        // the depth buffer is kept for the custom passes, which may test against it.
+        let depth_store = match self.custom_passes.is_empty() {
+            true => wgpu::StoreOp::Discard,
+            false => wgpu::StoreOp::Store,
+        };
+        let record_custom_passes =
+            |stage: CustomPassStage,
+             encoder: &mut wgpu::CommandEncoder,
+             frame_graph: &mut FrameGraph,
+             (color, attachment): (&wgpu::TextureView, FrameAttachment)| {
+                for (label, custom_pass) in self.custom_passes.stage(stage) {
+                    if debug_markers {
+                        encoder.push_debug_group(&label);
+                    }
+                    let mut ctx = CustomPassContext {
+                        gpu: self,
+                        world,
+                        time,
+                        encoder,
+                        color,
+                        color_format: scene_format,
+                        depth: &depth_view,
+                        depth_format: DEPTH_FORMAT,
+                        size: resolution,
+                        view_projection,
+                        draw_calls: 0,
+                    };
+                    custom_pass.record(&mut ctx);
+                    let draw_calls = ctx.draw_calls;
+                    if debug_markers {
+                        encoder.pop_debug_group();
+                    }
+
+                    frame_graph.push(FramePass {
+                        kind: FramePassKind::Custom,
+                        camera_order: None,
+                        attachment,
+                        size: resolution,
+                        format: scene_format,
+                        clear: false,
+                        depth: true,
+                        draw_calls,
+                        placeholder_draws: 0,
+                        gizmos: false,
+                    });
+                }
+            };
+
+        // the scene effects apply before the overlays, which are drawn into the output of the
+        // effects.
+        let output_view = frame_view.as_ref().unwrap_or(&target_view);
+        let finish_scene = |encoder: &mut wgpu::CommandEncoder, frame_graph: &mut FrameGraph| {
+            record_custom_passes(
+                CustomPassStage::AfterScene,
+                encoder,
+                frame_graph,
+                (scene_view, scene_attachment),
+            );
+            if post_process_view.is_some() {
+                self.post_process.draw(
+                    self,
+                    &targets.post_process,
+                    encoder,
+                    output_view,
+                    output_attachment,
+                    frame_graph,
+                );
+            }
+        };
+
+        let mut after_scene = false;
+        for (i, (camera, load, camera_order, viewport)) in passes.into_iter().enumerate() {
+            let overlay = camera.is_some_and(|(_, _, overlay)| overlay);
+            if overlay && !after_scene {
+                finish_scene(&mut command_encoder, &mut frame_graph);
+                after_scene = true;
+            }
+            let (color_view, attachment) = match after_scene {
+                true => (output_view, output_attachment),
+                false => (scene_view, scene_attachment),
+            };
+            let mut pass = FramePass {
+                kind: match camera {
+                    Some((_, _, true)) => FramePassKind::Overlay,
+                    Some(_) => FramePassKind::Scene,
+                    None => FramePassKind::Clear,
+                },
+                camera_order,
+                attachment,
+                size: resolution,
+                format: scene_format,
+                clear: matches!(load, wgpu::LoadOp::Clear(_)),
+                depth: true,
+                draw_calls: 0,
+                placeholder_draws: 0,
+                gizmos: false,
+            };
+            if debug_markers && overlay {
+                command_encoder.push_debug_group("ravia_engine::overlay_pass");
+            }
+            let (load, viewport_clear) = viewport.load_op(load);
+
             let mut render_pass = command_encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
                 label: Some("ravia_engine"),
                 color_attachments: &[Some(wgpu::RenderPassColorAttachment {
-                    view: &target_view,
+                    view: color_view,
                     resolve_target: None,
                     ops: wgpu::Operations {
-                        load: wgpu::LoadOp::Clear(wgpu::Color::BLACK),
+                        load,
                         store: wgpu::StoreOp::Store,
                     },
                 })],
-                depth_stencil_attachment: None,
+                // every camera draws the scene over a cleared depth buffer.
+                depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
+                    view: &depth_view,
+                    depth_ops: Some(wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(1.0),
+                        store: depth_store,
+                    }),
+                    stencil_ops: None,
+                }),
                 occlusion_query_set: None,
                 timestamp_writes: None,
             });
+            if viewport != Viewport::FULL {
+                let target = (resolution, scene_format);
+                self.viewport_clears.apply(
+                    self,
+                    &mut render_pass,
+                    viewport,
+                    target,
+                    viewport_clear,
+                );
+            }
 
-            let mut camera_query = <(&Camera, &Transform)>::query();
-            let (camera, camera_transform) = if let Some(entity) = camera_query.iter(world).next() {
-                entity
-            } else {
-                warn!(target: "ravia_engine::graphics::gpu", "No camera found, skipping frame");
-                break 'render_pass;
+            if let Some((camera, camera_transform, overlay)) = camera {
+                // the skybox is drawn first, behind the whole scene.
+                if let Some(skybox) = skybox.filter(|_| !overlay) {
+                    let camera = (camera, camera_transform);
+                    if self.skybox.draw(self, &mut render_pass, skybox, camera) {
+                        pass.draw_calls += 1;
+                    }
+                }
+
+                // gizmos are drawn from the first camera, the grid below the scene and the axes
+                // above it.
+                let draw_gizmos = gizmos_visible && i == 0 && !overlay;
+                if draw_gizmos {
+                    self.gizmos.draw_grid(self, &mut render_pass);
+                }
+
+                self.draw_scene(
+                    world,
+                    &mut render_pass,
+                    (camera, camera_transform),
+                    &shadows,
+                    overlay,
+                    &mut pass,
+                );
+
+                if draw_gizmos {
+                    self.gizmos.draw_axes(self, &mut render_pass);
+                    let config = self.gizmos.config();
+                    pass.gizmos = true;
+                    pass.draw_calls += config.grid as u32 + config.axes as u32;
+                }
+            }
+
+            drop(render_pass);
+            frame_graph.push(pass);
+            if debug_markers && overlay {
+                command_encoder.pop_debug_group();
+            }
+        }
+
+        if !after_scene {
+            finish_scene(&mut command_encoder, &mut frame_graph);
+        }
+        record_custom_passes(
+            CustomPassStage::AfterOverlays,
+            &mut command_encoder,
+            &mut frame_graph,
+            (output_view, output_attachment),
+        );
+
+        if debug_markers {
+            command_encoder.pop_debug_group();
+        }
+
+        if frame_view.is_some() {
+            self.present.draw(
+                self,
+                &targets.present,
+                &mut command_encoder,
+                &target_view,
+                target_texture.format(),
+                presenting,
+            );
+            frame_graph.push(FramePass {
+                kind: FramePassKind::Present,
+                camera_order: None,
+                attachment: FrameAttachment::Target,
+                size: resolution,
+                format: target_texture.format(),
+                clear: true,
+                depth: false,
+                draw_calls: 1,
+                placeholder_draws: 0,
+                gizmos: false,
+            });
+        }
+
+        // the frame time graph is drawn over the presented frame, but not into screenshots.
+        if presenting && self.frame_stats.is_visible() {
+            self.frame_stats
+                .draw(self, &mut command_encoder, &target_view);
+            frame_graph.push(FramePass {
+                kind: FramePassKind::FrameStats,
+                camera_order: None,
+                attachment: FrameAttachment::Target,
+                size: resolution,
+                format: target_texture.format(),
+                clear: false,
+                depth: false,
+                draw_calls: 1,
+                placeholder_draws: 0,
+                gizmos: false,
+            });
+        }
+
+        self.queue.submit(std::iter::once(command_encoder.finish()));
+        Some(frame_graph)
+    }
+
+    /// Draws the depth of the renderable entities of the world into the view of a shadow map,
+    /// from the camera of its light, returning the pass of the frame graph.
+    ///
+    /// The meshes are drawn with the depth-only placeholder pipeline of their vertex layout, so
+    /// skinned meshes cast the shadow of their bind pose, and instanced meshes of their first
+    /// instance.
+    fn draw_shadow_map(
+        &self,
+        world: &ecs::World,
+        command_encoder: &mut wgpu::CommandEncoder,
+        view: &wgpu::TextureView,
+        size: math::UVec2,
+        (camera, camera_transform): (&wgpu::BindGroup, &wgpu::BindGroup),
+    ) -> FramePass {
+        let mut pass = FramePass {
+            kind: FramePassKind::Shadow,
+            camera_order: None,
+            attachment: FrameAttachment::ShadowMap,
+            size,
+            format: DEPTH_FORMAT,
+            clear: true,
+            depth: true,
+            draw_calls: 0,
+            placeholder_draws: 0,
+            gizmos: false,
+        };
+        if self.config.debug_markers {
+            command_encoder.push_debug_group("ravia_engine::shadow_pass");
+        }
+
+        let mut render_pass = command_encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("ravia_engine::shadow_map"),
+            color_attachments: &[],
+            depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
+                view,
+                depth_ops: Some(wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(1.0),
+                    store: wgpu::StoreOp::Store,
+                }),
+                stencil_ops: None,
+            }),
+            occlusion_query_set: None,
+            timestamp_writes: None,
+        });
+        render_pass.set_bind_group(PLACEHOLDER_CAMERA_INDEX, camera, &[]);
+        render_pass.set_bind_group(PLACEHOLDER_CAMERA_TRANSFORM_INDEX, camera_transform, &[]);
+
+        let mut casters_query = <(
+            &Mesh,
+            Option<&Material>,
+            Option<&MaterialInstance>,
+            &Transform,
+            (
+                Option<&Visibility>,
+                Option<&ComputedVisibility>,
+                Option<&Overlay>,
+            ),
+        )>::query();
+        for (
+            mesh,
+            material,
+            material_instance,
+            model_transform,
+            (visibility, computed_visibility, overlay),
+        ) in casters_query.iter(world)
+        {
+            if overlay.is_some() || !is_rendered(visibility, computed_visibility) {
+                continue;
+            }
+
+            let shader = match (material, material_instance) {
+                (Some(material), _) => &material.shader,
+                (None, Some(instance)) => instance.shader(),
+                (None, None) => continue,
+            };
+            let Some(key) = shader.placeholder_layout() else {
+                continue;
             };
 
-            let mut renderables_query = <(&Mesh, &Material, &Transform)>::query();
-            for (mesh, material, model_transform) in renderables_query.iter(world) {
-                render_pass.set_pipeline(material.shader.pipeline());
+            let key = key.with_topology(mesh.topology());
+            render_pass.set_pipeline(&self.placeholders.get_depth_only(self, key));
+            render_pass.set_vertex_buffer(0, mesh.vertex_slice());
+            render_pass.set_index_buffer(mesh.index_slice(), wgpu::IndexFormat::Uint32);
+            render_pass.set_bind_group(
+                PLACEHOLDER_MODEL_TRANSFORM_INDEX,
+                model_transform.bind_group(),
+                &[],
+            );
+            render_pass.draw_indexed(mesh.indices(), 0, mesh.instances());
+            pass.draw_calls += 1;
+        }
+
+        drop(render_pass);
+        if self.config.debug_markers {
+            command_encoder.pop_debug_group();
+        }
+        pass
+    }
+
+    /// Draws the renderable entities of the world from the camera and its transform, either the
+    /// [`Overlay`] entities or the others, counting the draw calls into the pass of the frame graph.
+    fn draw_scene(
+        &self,
+        world: &ecs::World,
+        render_pass: &mut wgpu::RenderPass,
+        (camera, camera_transform): (&wgpu::BindGroup, &wgpu::BindGroup),
+        shadows: &SceneShadows,
+        overlay: bool,
+        pass: &mut FramePass,
+    ) {
+        let debug_markers = self.config.debug_markers;
+        let debug_view = Some(self.debug_view()).filter(|view| !overlay && *view != DebugView::Off);
+
+        let mut renderables_query = <(
+            &Mesh,
+            Option<&Material>,
+            Option<&MaterialInstance>,
+            &Transform,
+            Option<&Skin>,
+            Option<&Lightmap>,
+            Option<&Instances>,
+            (
+                Option<&Visibility>,
+                Option<&ComputedVisibility>,
+                Option<&Overlay>,
+                Option<&RenderLayer>,
+                Option<&RenderOrder>,
+            ),
+        )>::query();
+
+        // the layers are drawn from the lowest, then the entities by their order within a layer.
+        let mut renderables = renderables_query.iter(world).collect::<Vec<_>>();
+        renderables.sort_by_key(|(.., (_, _, _, layer, order))| {
+            (
+                layer.copied().unwrap_or_default(),
+                order.copied().unwrap_or_default(),
+            )
+        });
+
+        for (
+            mesh,
+            material,
+            material_instance,
+            model_transform,
+            skin,
+            lightmap,
+            instances,
+            (visibility, computed_visibility, entity_overlay, _, _),
+        ) in renderables
+        {
+            if entity_overlay.is_some() != overlay || !is_rendered(visibility, computed_visibility)
+            {
+                continue;
+            }
+
+            // the instances are bound after the vertex buffers of the mesh.
+            let instance_buffer = match instances {
+                Some(instances) => match instances.vertex_slice() {
+                    Some(slice) => Some((slice, instances.instances())),
+                    None => continue,
+                },
+                None => None,
+            };
+
+            // an own material takes precedence over an instance of a shared one.
+            let (shader, texture, normal_map) = match (material, material_instance) {
+                (Some(material), _) => (
+                    &material.shader,
+                    material.texture.as_ref(),
+                    material.normal_map.as_ref(),
+                ),
+                (None, Some(instance)) => {
+                    (instance.shader(), instance.texture(), instance.normal_map())
+                }
+                (None, None) => continue,
+            };
+
+            if debug_markers {
+                render_pass.push_debug_group(shader.label());
+            }
+
+            // error meshes, and the meshes of shaders which have failed to compile, are drawn in
+            // the flat pink of the error shader.
+            if mesh.is_error() || shader.has_failed() {
+                let layout = match mesh.is_error() {
+                    true => PlaceholderKey::new(Vertex3DStandard::ATTRIBUTE_FORMATS),
+                    false => shader.placeholder_layout(),
+                };
+                if let Some(key) = layout {
+                    let key = key.with_topology(mesh.topology());
+                    let pipeline = self.placeholders.get_error(self, key);
+                    render_pass.set_pipeline(&pipeline);
+                    render_pass.set_vertex_buffer(0, mesh.vertex_slice());
+                    render_pass.set_index_buffer(mesh.index_slice(), wgpu::IndexFormat::Uint32);
+                    render_pass.set_bind_group(PLACEHOLDER_CAMERA_INDEX, camera, &[]);
+                    render_pass.set_bind_group(
+                        PLACEHOLDER_CAMERA_TRANSFORM_INDEX,
+                        camera_transform,
+                        &[],
+                    );
+                    render_pass.set_bind_group(
+                        PLACEHOLDER_MODEL_TRANSFORM_INDEX,
+                        model_transform.bind_group(),
+                        &[],
+                    );
+                    render_pass.draw_indexed(mesh.indices(), 0, mesh.instances());
+                    pass.draw_calls += 1;
+                }
+
+                if debug_markers {
+                    render_pass.pop_debug_group();
+                }
+                continue;
+            }
+
+            // the debug view replaces the material, whether its pipeline is compiled or not.
+            if let Some((view, layout)) = debug_view.zip(shader.debug_layout()) {
+                let texture =
+                    texture.filter(|_| shader.bind_group_index(UniformType::Texture2D).is_some());
+                let layout = layout.with_topology(mesh.topology());
+                let pipeline = self
+                    .debug_pipelines
+                    .get(self, layout, view, texture.is_some());
+                render_pass.set_pipeline(&pipeline);
                 render_pass.set_vertex_buffer(0, mesh.vertex_slice());
                 render_pass.set_index_buffer(mesh.index_slice(), wgpu::IndexFormat::Uint32);
+                render_pass.set_bind_group(PLACEHOLDER_CAMERA_INDEX, camera, &[]);
+                render_pass.set_bind_group(
+                    PLACEHOLDER_CAMERA_TRANSFORM_INDEX,
+                    camera_transform,
+                    &[],
+                );
+                render_pass.set_bind_group(
+                    PLACEHOLDER_MODEL_TRANSFORM_INDEX,
+                    model_transform.bind_group(),
+                    &[],
+                );
+                if let Some(texture) = texture {
+                    render_pass.set_bind_group(
+                        DebugPipelines::TEXTURE_INDEX,
+                        texture.bind_group(),
+                        &[],
+                    );
+                }
+                render_pass.draw_indexed(mesh.indices(), 0, mesh.instances());
+                pass.draw_calls += 1;
 
-                if let Some(index) = material.shader.bind_group_index(UniformType::Texture2D) {
-                    if let Some(texture) = &material.texture {
-                        render_pass.set_bind_group(index, texture.bind_group(), &[]);
-                    }
+                if debug_markers {
+                    render_pass.pop_debug_group();
+                }
+                continue;
+            }
+
+            let Some(pipeline) = shader.pipeline() else {
+                // draw a placeholder until the pipeline of the shader is compiled.
+                if let Some(key) = shader.placeholder_layout() {
+                    let key = key.with_topology(mesh.topology());
+                    let placeholder = self.placeholders.get(self, key);
+                    render_pass.set_pipeline(&placeholder);
+                    render_pass.set_vertex_buffer(0, mesh.vertex_slice());
+                    render_pass.set_index_buffer(mesh.index_slice(), wgpu::IndexFormat::Uint32);
+                    render_pass.set_bind_group(PLACEHOLDER_CAMERA_INDEX, camera, &[]);
+                    render_pass.set_bind_group(
+                        PLACEHOLDER_CAMERA_TRANSFORM_INDEX,
+                        camera_transform,
+                        &[],
+                    );
+                    render_pass.set_bind_group(
+                        PLACEHOLDER_MODEL_TRANSFORM_INDEX,
+                        model_transform.bind_group(),
+                        &[],
+                    );
+                    render_pass.draw_indexed(mesh.indices(), 0, mesh.instances());
+                    pass.draw_calls += 1;
+                    pass.placeholder_draws += 1;
+                }
+
+                if debug_markers {
+                    render_pass.pop_debug_group();
                 }
+                continue;
+            };
+
+            render_pass.set_pipeline(pipeline);
+            render_pass.set_vertex_buffer(0, mesh.vertex_slice());
+            let mut slot = 1;
+            for slice in mesh.extra_vertex_slices() {
+                render_pass.set_vertex_buffer(slot, slice);
+                slot += 1;
+            }
+            let instance_range = match instance_buffer {
+                Some((slice, range)) => {
+                    render_pass.set_vertex_buffer(slot, slice);
+                    range
+                }
+                None => mesh.instances(),
+            };
+            render_pass.set_index_buffer(mesh.index_slice(), wgpu::IndexFormat::Uint32);
 
-                if let Some(index) = material.shader.bind_group_index(UniformType::Camera) {
-                    render_pass.set_bind_group(index, camera.bind_group(), &[]);
+            if let Some(index) = shader.bind_group_index(UniformType::Texture2D) {
+                if let Some(texture) = texture {
+                    render_pass.set_bind_group(index, texture.bind_group(), &[]);
                 }
+            }
 
-                if let Some(index) = material
-                    .shader
-                    .bind_group_index(UniformType::CameraTransform)
-                {
-                    render_pass.set_bind_group(index, camera_transform.bind_group(), &[]);
+            if let Some(index) = shader.bind_group_index(UniformType::NormalMap) {
+                if let Some(normal_map) = normal_map {
+                    render_pass.set_bind_group(index, normal_map.bind_group(), &[]);
                 }
+            }
+
+            if let Some(index) = shader.bind_group_index(UniformType::Camera) {
+                render_pass.set_bind_group(index, camera, &[]);
+            }
+
+            if let Some(index) = shader.bind_group_index(UniformType::CameraTransform) {
+                render_pass.set_bind_group(index, camera_transform, &[]);
+            }
+
+            if let Some(index) = shader.bind_group_index(UniformType::ModelTransform) {
+                render_pass.set_bind_group(index, model_transform.bind_group(), &[]);
+            }
+
+            if let Some(index) = shader.bind_group_index(UniformType::Frame) {
+                render_pass.set_bind_group(index, &self.frame.bind_group, &[]);
+            }
+
+            if let Some(index) = shader.bind_group_index(UniformType::Lights) {
+                render_pass.set_bind_group(index, &self.lights.bind_group, &[]);
+            }
 
-                if let Some(index) = material
-                    .shader
-                    .bind_group_index(UniformType::ModelTransform)
-                {
-                    render_pass.set_bind_group(index, model_transform.bind_group(), &[]);
+            if let Some(index) = shader.bind_group_index(UniformType::Lightmap) {
+                if let Some(lightmap) = lightmap {
+                    render_pass.set_bind_group(index, lightmap.bind_group(), &[]);
                 }
+            }
+
+            if let Some(index) = shader.bind_group_index(UniformType::ShadowMap) {
+                render_pass.set_bind_group(index, shadows.directional, &[]);
+            }
 
-                render_pass.draw_indexed(mesh.indices(), 0, 0..1);
+            if let Some(index) = shader.bind_group_index(UniformType::PointShadowMap) {
+                let point = shadows.point(*model_transform.position());
+                render_pass.set_bind_group(index, point, &[]);
+            }
+
+            if let Some(index) = shader.bind_group_index(UniformType::Joints) {
+                if let Some(skin) = skin {
+                    render_pass.set_bind_group(index, skin.bind_group(), &[]);
+                }
+            }
+
+            render_pass.draw_indexed(mesh.indices(), 0, instance_range);
+            pass.draw_calls += 1;
+
+            if debug_markers {
+                render_pass.pop_debug_group();
             }
         }
+    }
+}
 
-        self.queue.submit(std::iter::once(command_encoder.finish()));
-        surface_texture.present();
+/// The intermediate textures a frame is rendered with, matching the size of its target.
+///
+/// The window keeps its own across frames, while every offscreen render creates new ones, so that
+/// rendering offscreen at another resolution does not recreate the textures of the window.
+#[derive(Debug)]
+struct FrameTargets {
+    depth: DepthBuffer,
+    post_process: PostProcessTargets,
+    present: PresentTargets,
+}
+
+impl FrameTargets {
+    fn new(memory: &Arc<MemoryTracker>) -> Self {
+        Self {
+            depth: DepthBuffer::new(memory),
+            post_process: PostProcessTargets::default(),
+            present: PresentTargets::default(),
+        }
+    }
+}
+
+/// The texture a frame is rendered into.
+enum FrameTarget<'a> {
+    Surface(wgpu::SurfaceTexture),
+    Offscreen(MutexGuard<'a, Option<wgpu::Texture>>),
+}
+
+impl FrameTarget<'_> {
+    fn texture(&self) -> &wgpu::Texture {
+        match self {
+            Self::Surface(surface_texture) => &surface_texture.texture,
+            Self::Offscreen(texture) => texture.as_ref().expect("headless gpu has a texture"),
+        }
+    }
+
+    /// Presents the frame to the window, if rendered to a surface.
+    fn present(self) {
+        if let Self::Surface(surface_texture) = self {
+            surface_texture.present();
+        }
     }
 }
 
@@ -220,6 +1964,12 @@ pub(super) struct GpuDefaultBindGroupLayouts {
     pub camera: wgpu::BindGroupLayout,
     pub transform: wgpu::BindGroupLayout,
     pub texture_2d: wgpu::BindGroupLayout,
+    pub joints: wgpu::BindGroupLayout,
+    pub frame: wgpu::BindGroupLayout,
+    pub lights: wgpu::BindGroupLayout,
+    pub lightmap: wgpu::BindGroupLayout,
+    pub shadow_map: wgpu::BindGroupLayout,
+    pub point_shadow_map: wgpu::BindGroupLayout,
 }
 
 impl GpuDefaultBindGroupLayouts {
@@ -227,7 +1977,7 @@ impl GpuDefaultBindGroupLayouts {
     pub fn new(device: &wgpu::Device) -> Self {
         Self {
             camera: device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
-                label: None,
+                label: Some("ravia_engine::camera"),
                 entries: &[wgpu::BindGroupLayoutEntry {
                     binding: 0,
                     visibility: wgpu::ShaderStages::VERTEX,
@@ -240,7 +1990,7 @@ impl GpuDefaultBindGroupLayouts {
                 }],
             }),
             transform: device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
-                label: None,
+                label: Some("ravia_engine::transform"),
                 entries: &[wgpu::BindGroupLayoutEntry {
                     binding: 0,
                     visibility: wgpu::ShaderStages::VERTEX,
@@ -253,7 +2003,28 @@ impl GpuDefaultBindGroupLayouts {
                 }],
             }),
             texture_2d: device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
-                label: None,
+                label: Some("ravia_engine::texture_2d"),
+                entries: &[
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 0,
+                        visibility: wgpu::ShaderStages::FRAGMENT,
+                        ty: wgpu::BindingType::Texture {
+                            view_dimension: wgpu::TextureViewDimension::D2,
+                            sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                            multisampled: false,
+                        },
+                        count: None,
+                    },
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 1,
+                        visibility: wgpu::ShaderStages::FRAGMENT,
+                        ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                        count: None,
+                    },
+                ],
+            }),
+            lightmap: device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                label: Some("ravia_engine::lightmap"),
                 entries: &[
                     wgpu::BindGroupLayoutEntry {
                         binding: 0,
@@ -271,8 +2042,67 @@ impl GpuDefaultBindGroupLayouts {
                         ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
                         count: None,
                     },
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 2,
+                        visibility: wgpu::ShaderStages::VERTEX_FRAGMENT,
+                        ty: wgpu::BindingType::Buffer {
+                            ty: wgpu::BufferBindingType::Uniform,
+                            has_dynamic_offset: false,
+                            min_binding_size: None,
+                        },
+                        count: None,
+                    },
                 ],
             }),
+            shadow_map: shadow_map_layout(
+                device,
+                "ravia_engine::shadow_map",
+                wgpu::TextureViewDimension::D2,
+            ),
+            point_shadow_map: shadow_map_layout(
+                device,
+                "ravia_engine::point_shadow_map",
+                wgpu::TextureViewDimension::Cube,
+            ),
+            frame: device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                label: Some("ravia_engine::frame"),
+                entries: &[wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::VERTEX_FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                }],
+            }),
+            lights: device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                label: Some("ravia_engine::lights"),
+                entries: &[wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::VERTEX_FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                }],
+            }),
+            joints: device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                label: Some("ravia_engine::skin"),
+                entries: &[wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::VERTEX,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                }],
+            }),
         }
     }
 
@@ -282,7 +2112,133 @@ impl GpuDefaultBindGroupLayouts {
             UniformType::Camera => &self.camera,
             UniformType::CameraTransform => &self.transform,
             UniformType::ModelTransform => &self.transform,
-            UniformType::Texture2D => &self.texture_2d,
+            UniformType::Texture2D | UniformType::NormalMap => &self.texture_2d,
+            UniformType::Joints => &self.joints,
+            UniformType::Frame => &self.frame,
+            UniformType::Lights => &self.lights,
+            UniformType::Lightmap => &self.lightmap,
+            UniformType::ShadowMap => &self.shadow_map,
+            UniformType::PointShadowMap => &self.point_shadow_map,
+        }
+    }
+}
+
+/// The shadow maps bound to the meshes drawn in the scene passes.
+struct SceneShadows<'a> {
+    /// The shadow map of the directional light, or the fallback.
+    directional: &'a wgpu::BindGroup,
+    /// The position, range and cube shadow map of every point light rendering one.
+    points: Vec<(math::Vec3, f32, &'a wgpu::BindGroup)>,
+    point_fallback: &'a wgpu::BindGroup,
+}
+
+impl SceneShadows<'_> {
+    /// Returns the cube shadow map of the nearest point light within range of the position.
+    fn point(&self, position: math::Vec3) -> &wgpu::BindGroup {
+        self.points
+            .iter()
+            .map(|(light, range, bind_group)| (light.distance_squared(position), range, bind_group))
+            .filter(|(distance_squared, range, _)| *distance_squared < *range * *range)
+            .min_by(|a, b| a.0.total_cmp(&b.0))
+            .map_or(self.point_fallback, |(_, _, bind_group)| *bind_group)
+    }
+}
+
+/// Creates the layout of a shadow map: the depth texture, its comparison sampler and the values
+/// of the shadow.
+fn shadow_map_layout(
+    device: &wgpu::Device,
+    label: &str,
+    view_dimension: wgpu::TextureViewDimension,
+) -> wgpu::BindGroupLayout {
+    device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+        label: Some(label),
+        entries: &[
+            wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::FRAGMENT,
+                ty: wgpu::BindingType::Texture {
+                    view_dimension,
+                    sample_type: wgpu::TextureSampleType::Depth,
+                    multisampled: false,
+                },
+                count: None,
+            },
+            wgpu::BindGroupLayoutEntry {
+                binding: 1,
+                visibility: wgpu::ShaderStages::FRAGMENT,
+                ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Comparison),
+                count: None,
+            },
+            wgpu::BindGroupLayoutEntry {
+                binding: 2,
+                visibility: wgpu::ShaderStages::VERTEX_FRAGMENT,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            },
+        ],
+    })
+}
+
+/// Bind groups of an identity camera, whose projection and transform are identity matrices.
+#[derive(Debug)]
+struct IdentityCamera {
+    _camera_buffer: wgpu::Buffer,
+    camera: wgpu::BindGroup,
+    _transform_buffer: wgpu::Buffer,
+    transform: wgpu::BindGroup,
+    _allocation: MemoryAllocation,
+}
+
+impl IdentityCamera {
+    fn new(
+        device: &wgpu::Device,
+        layouts: &GpuDefaultBindGroupLayouts,
+        memory: &Arc<MemoryTracker>,
+    ) -> Self {
+        let camera_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("ravia_engine::identity_camera"),
+            contents: bytemuck::cast_slice(&[math::Mat4::IDENTITY]),
+            usage: wgpu::BufferUsages::UNIFORM,
+        });
+        let camera = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("ravia_engine::identity_camera"),
+            layout: &layouts.camera,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: camera_buffer.as_entire_binding(),
+            }],
+        });
+
+        let transform_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("ravia_engine::identity_camera_transform"),
+            contents: bytemuck::cast_slice(&[math::Mat4::IDENTITY, math::Mat4::IDENTITY]),
+            usage: wgpu::BufferUsages::UNIFORM,
+        });
+        let transform = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("ravia_engine::identity_camera_transform"),
+            layout: &layouts.transform,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: transform_buffer.as_entire_binding(),
+            }],
+        });
+
+        let allocation = memory.allocate(
+            MemoryCategory::Uniform,
+            camera_buffer.size() + transform_buffer.size(),
+        );
+
+        Self {
+            _camera_buffer: camera_buffer,
+            camera,
+            _transform_buffer: transform_buffer,
+            transform,
+            _allocation: allocation,
         }
     }
 }
@@ -0,0 +1,123 @@
+use crate::{ecs, engine::EngineContext, math};
+
+use super::{
+    memory::{MemoryAllocation, MemoryCategory},
+    shader::VertexBufferLayout,
+};
+
+/// An [`Instances`] component draws the [`super::mesh::Mesh`] of the same entity once per
+/// instance transform, in a single draw call.
+///
+/// The transforms are uploaded as a per-instance vertex buffer, bound to the slot after the
+/// vertex buffers of the mesh. The shader declares it with [`Instances::vertex_buffer_layout`],
+/// and reads each transform as four `vec4<f32>` columns, typically applied before the model
+/// transform of the entity.
+#[derive(Debug)]
+pub struct Instances {
+    transforms: Vec<math::Mat4>,
+
+    dirty: bool,
+    buffer: Option<(wgpu::Buffer, MemoryAllocation)>,
+    /// Number of transforms in the buffer.
+    uploaded: u32,
+}
+
+assert_impl_all!(Instances: ecs::storage::Component);
+
+impl Instances {
+    /// Creates a new [`Instances`] from the transforms of the instances.
+    pub fn new(transforms: impl IntoIterator<Item = math::Mat4>) -> Self {
+        Self {
+            transforms: transforms.into_iter().collect(),
+            dirty: true,
+            buffer: None,
+            uploaded: 0,
+        }
+    }
+
+    /// Returns the layout of the instance buffer, to declare in
+    /// [`super::shader::ShaderConfig::with_vertex_buffers`].
+    pub fn vertex_buffer_layout() -> VertexBufferLayout<'static> {
+        VertexBufferLayout {
+            attribute_formats: &[wgpu::VertexFormat::Float32x4; 4],
+            step_mode: wgpu::VertexStepMode::Instance,
+        }
+    }
+
+    /// Returns the number of instances.
+    pub fn len(&self) -> usize {
+        self.transforms.len()
+    }
+
+    /// Returns true if there is no instance, in which case nothing is drawn.
+    pub fn is_empty(&self) -> bool {
+        self.transforms.is_empty()
+    }
+
+    /// Returns the transforms of the instances.
+    pub fn transforms(&self) -> &[math::Mat4] {
+        &self.transforms
+    }
+
+    /// Returns the transforms of the instances to modify, uploaded again before the next frame.
+    pub fn transforms_mut(&mut self) -> &mut Vec<math::Mat4> {
+        self.dirty = true;
+        &mut self.transforms
+    }
+
+    /// Drops the buffer, e.g. lost along with the device, so that the transforms are uploaded
+    /// again.
+    pub(super) fn invalidate(&mut self) {
+        self.buffer = None;
+        self.dirty = true;
+    }
+
+    /// Uploads the transforms if modified, growing the buffer if necessary.
+    pub(super) fn flush(&mut self, ctx: &EngineContext) {
+        if !self.dirty {
+            return;
+        }
+        self.dirty = false;
+        self.uploaded = self.transforms.len() as u32;
+        if self.transforms.is_empty() {
+            return;
+        }
+
+        let contents: &[u8] = bytemuck::cast_slice(&self.transforms);
+        let size = contents.len() as wgpu::BufferAddress;
+        if self
+            .buffer
+            .as_ref()
+            .is_none_or(|(buffer, _)| buffer.size() < size)
+        {
+            let buffer = ctx.gpu.device.create_buffer(&wgpu::BufferDescriptor {
+                label: Some("ravia_engine::instances"),
+                size,
+                usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+                mapped_at_creation: false,
+            });
+            let allocation = ctx.gpu.track_memory(MemoryCategory::Mesh, size);
+            self.buffer = Some((buffer, allocation));
+        }
+
+        if let Some((buffer, _)) = &self.buffer {
+            ctx.gpu.queue.write_buffer(buffer, 0, contents);
+        }
+    }
+
+    /// Returns the slice of the instance buffer to bind for a render pass, or `None` if nothing
+    /// has been uploaded.
+    pub(super) fn vertex_slice(&self) -> Option<wgpu::BufferSlice<'_>> {
+        let size = self.uploaded as wgpu::BufferAddress
+            * std::mem::size_of::<math::Mat4>() as wgpu::BufferAddress;
+        self.buffer
+            .as_ref()
+            .filter(|_| size > 0)
+            .map(|(buffer, _)| buffer.slice(..size))
+    }
+
+    /// Returns the range of the uploaded instances to draw.
+    pub(super) fn instances(&self) -> std::ops::Range<u32> {
+        0..self.uploaded
+    }
+}
@@ -0,0 +1,244 @@
+use wgpu::util::DeviceExt;
+
+use crate::{ecs, engine::EngineContext, math};
+
+use super::{
+    memory::{MemoryAllocation, MemoryCategory},
+    mesh::Vertex3DStandardUv2,
+    texture::Texture,
+    uniform::Uniform,
+};
+
+/// A [`Lightmap`] component holds precomputed lighting of a static mesh, sampled with the second
+/// texture coordinate of [`Vertex3DStandardUv2`] vertices.
+///
+/// Several meshes may share one lightmap atlas, each using its own region: the uv is mapped to
+/// `uv2 * scale_offset.xy + scale_offset.zw`. Shaders access it through
+/// [`super::uniform::UniformType::Lightmap`], with the texture at binding 0, its sampler at
+/// binding 1 and the `vec4<f32>` scale and offset at binding 2.
+#[derive(Debug)]
+pub struct Lightmap {
+    texture: Texture,
+    scale_offset: math::Vec4,
+
+    _buffer: wgpu::Buffer,
+    bind_group: wgpu::BindGroup,
+    _allocation: MemoryAllocation,
+}
+
+assert_impl_all!(Lightmap: ecs::storage::Component);
+
+impl Lightmap {
+    /// Creates a new [`Lightmap`] covering the whole texture.
+    pub fn new(ctx: &EngineContext, texture: Texture) -> Self {
+        Self::with_region(ctx, texture, math::Vec2::ONE, math::Vec2::ZERO)
+    }
+
+    /// Creates a new [`Lightmap`] using the region of an atlas texture at the given uv scale and
+    /// offset.
+    pub fn with_region(
+        ctx: &EngineContext,
+        texture: Texture,
+        scale: math::Vec2,
+        offset: math::Vec2,
+    ) -> Self {
+        let scale_offset = math::Vec4::new(scale.x, scale.y, offset.x, offset.y);
+        let buffer = ctx
+            .gpu
+            .device
+            .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some("ravia_engine::lightmap"),
+                contents: bytemuck::cast_slice(&[scale_offset]),
+                usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            });
+
+        let bind_group = ctx
+            .gpu
+            .device
+            .create_bind_group(&wgpu::BindGroupDescriptor {
+                label: Some("ravia_engine::lightmap"),
+                layout: &ctx.gpu.default_bind_group_layouts.lightmap,
+                entries: &[
+                    wgpu::BindGroupEntry {
+                        binding: 0,
+                        resource: wgpu::BindingResource::TextureView(texture.view()),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 1,
+                        resource: wgpu::BindingResource::Sampler(texture.sampler()),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 2,
+                        resource: buffer.as_entire_binding(),
+                    },
+                ],
+            });
+
+        Self {
+            texture,
+            scale_offset,
+            _allocation: ctx.gpu.track_memory(MemoryCategory::Uniform, buffer.size()),
+            _buffer: buffer,
+            bind_group,
+        }
+    }
+
+    /// Returns the lightmap texture.
+    pub fn texture(&self) -> &Texture {
+        &self.texture
+    }
+
+    /// Returns the uv scale and offset of the region, packed as `(scale.x, scale.y, offset.x,
+    /// offset.y)`.
+    pub fn scale_offset(&self) -> math::Vec4 {
+        self.scale_offset
+    }
+
+    /// Sets the uv scale and offset of the region.
+    pub fn set_region(&mut self, ctx: &EngineContext, scale: math::Vec2, offset: math::Vec2) {
+        self.scale_offset = math::Vec4::new(scale.x, scale.y, offset.x, offset.y);
+        ctx.gpu
+            .queue
+            .write_buffer(&self._buffer, 0, bytemuck::cast_slice(&[self.scale_offset]));
+    }
+}
+
+impl Uniform for Lightmap {
+    fn bind_group(&self) -> &wgpu::BindGroup {
+        &self.bind_group
+    }
+}
+
+/// Bakes ambient occlusion of a static mesh into an RGBA8 lightmap, laid out by the second
+/// texture coordinate of the vertices.
+///
+/// For every texel covered by a triangle in uv2 space, `samples` rays are cast over the hemisphere
+/// around the surface normal, and the texel is darkened by the fraction of rays hitting the mesh
+/// within `max_distance`. Texels not covered by any triangle are white. The baker tests every ray
+/// against every triangle, so it is meant for simple scenes or offline use.
+pub fn bake_ambient_occlusion(
+    vertices: &[Vertex3DStandardUv2],
+    indices: &[u32],
+    size: math::UVec2,
+    samples: u32,
+    max_distance: f32,
+) -> Vec<u8> {
+    let triangles = indices
+        .chunks_exact(3)
+        .map(|t| {
+            [
+                vertices[t[0] as usize],
+                vertices[t[1] as usize],
+                vertices[t[2] as usize],
+            ]
+        })
+        .collect::<Vec<_>>();
+    let directions = hemisphere_directions(samples.max(1));
+
+    let mut data = vec![255; (size.x * size.y * 4) as usize];
+    for triangle in &triangles {
+        let uvs = triangle.map(|v| v.data.uv2 * size.as_vec2());
+        let min = uvs[0].min(uvs[1]).min(uvs[2]).floor().max(math::Vec2::ZERO);
+        let max = uvs[0].max(uvs[1]).max(uvs[2]).ceil().min(size.as_vec2());
+
+        for y in min.y as u32..max.y as u32 {
+            for x in min.x as u32..max.x as u32 {
+                let texel = math::vec2(x as f32 + 0.5, y as f32 + 0.5);
+                let Some(weights) = barycentric(texel, uvs) else {
+                    continue;
+                };
+
+                let position = triangle[0].position * weights.x
+                    + triangle[1].position * weights.y
+                    + triangle[2].position * weights.z;
+                let normal = (triangle[0].data.normal * weights.x
+                    + triangle[1].data.normal * weights.y
+                    + triangle[2].data.normal * weights.z)
+                    .normalize_or_zero();
+                if normal == math::Vec3::ZERO {
+                    continue;
+                }
+
+                // rotate the hemisphere around +z onto the normal, and offset the origin to avoid
+                // hitting the surface itself.
+                let rotation = math::Quat::from_rotation_arc(math::Vec3::Z, normal);
+                let origin = position + normal * 1e-3;
+                let occluded = directions
+                    .iter()
+                    .filter(|direction| {
+                        let direction = rotation * **direction;
+                        triangles
+                            .iter()
+                            .any(|t| intersects(origin, direction, t, max_distance))
+                    })
+                    .count();
+
+                let visibility = 1.0 - occluded as f32 / directions.len() as f32;
+                let value = (visibility * 255.0).round() as u8;
+                let index = ((y * size.x + x) * 4) as usize;
+                data[index..index + 3].fill(value);
+            }
+        }
+    }
+
+    data
+}
+
+/// Returns the barycentric weights of the point in the triangle, or `None` if it lies outside.
+fn barycentric(point: math::Vec2, triangle: [math::Vec2; 3]) -> Option<math::Vec3> {
+    let [a, b, c] = triangle;
+    let area = (b - a).perp_dot(c - a);
+    if area.abs() < f32::EPSILON {
+        return None;
+    }
+
+    let u = (c - b).perp_dot(point - b) / area;
+    let v = (a - c).perp_dot(point - c) / area;
+    let w = 1.0 - u - v;
+    (u >= 0.0 && v >= 0.0 && w >= 0.0).then_some(math::vec3(u, v, w))
+}
+
+/// Returns evenly distributed, cosine-weighted directions over the hemisphere around +z.
+fn hemisphere_directions(samples: u32) -> Vec<math::Vec3> {
+    let golden_angle = std::f32::consts::PI * (3.0 - 5.0_f32.sqrt());
+    (0..samples)
+        .map(|i| {
+            let r = ((i as f32 + 0.5) / samples as f32).sqrt();
+            let theta = i as f32 * golden_angle;
+            math::vec3(r * theta.cos(), r * theta.sin(), (1.0 - r * r).sqrt())
+        })
+        .collect()
+}
+
+/// Tests a ray against a triangle with the Möller-Trumbore algorithm.
+fn intersects(
+    origin: math::Vec3,
+    direction: math::Vec3,
+    triangle: &[Vertex3DStandardUv2; 3],
+    max_distance: f32,
+) -> bool {
+    let [a, b, c] = triangle.map(|v| v.position);
+    let edge1 = b - a;
+    let edge2 = c - a;
+    let p = direction.cross(edge2);
+    let det = edge1.dot(p);
+    if det.abs() < f32::EPSILON {
+        return false;
+    }
+
+    let inv_det = 1.0 / det;
+    let s = origin - a;
+    let u = s.dot(p) * inv_det;
+    if !(0.0..=1.0).contains(&u) {
+        return false;
+    }
+
+    let q = s.cross(edge1);
+    let v = direction.dot(q) * inv_det;
+    if v < 0.0 || u + v > 1.0 {
+        return false;
+    }
+
+    let t = edge2.dot(q) * inv_det;
+    t > 0.0 && t <= max_distance
+}
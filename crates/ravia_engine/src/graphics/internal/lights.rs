@@ -0,0 +1,237 @@
+use std::sync::Arc;
+
+use crate::{
+    ecs::{self, IntoQuery},
+    math,
+};
+
+use super::{
+    directional_light::DirectionalLight,
+    gpu::GpuDefaultBindGroupLayouts,
+    memory::{MemoryAllocation, MemoryCategory, MemoryTracker},
+    point_light::PointLight,
+    spot_light::SpotLight,
+    transform::Transform,
+};
+
+/// Maximum number of lights bound as [`super::uniform::UniformType::Lights`].
+pub const MAX_LIGHTS: usize = 32;
+
+/// WGSL declarations of the lights bound as [`super::uniform::UniformType::Lights`], with a
+/// diffuse lighting helper. Prepend it to the source of a shader consuming the lights:
+///
+/// ```ignore
+/// let source = format!("{}{}", LIGHTS_WGSL, SOURCE);
+/// ```
+///
+/// The shader declares the binding of the uniform itself, at the group of the uniform:
+///
+/// ```wgsl
+/// @group(3) @binding(0) var<uniform> lights: Lights;
+///
+/// var radiance = lights.ambient;
+/// for (var i = 0u; i < lights.count; i++) {
+///   radiance += light_radiance(lights.lights[i], world_position, normal);
+/// }
+/// ```
+pub const LIGHTS_WGSL: &str = "
+const LIGHT_DIRECTIONAL: u32 = 0u;
+const LIGHT_POINT: u32 = 1u;
+const LIGHT_SPOT: u32 = 2u;
+const MAX_LIGHTS: u32 = 32u;
+
+struct Light {
+  position: vec3<f32>,
+  kind: u32,
+  direction: vec3<f32>,
+  range: f32,
+  color: vec3<f32>,
+  intensity: f32,
+  inner_cos: f32,
+  outer_cos: f32,
+};
+
+struct Lights {
+  ambient: vec3<f32>,
+  count: u32,
+  lights: array<Light, MAX_LIGHTS>,
+};
+
+// Returns the light diffusely reflected towards every direction by a surface of the world
+// position, facing the normal.
+fn light_radiance(light: Light, world_position: vec3<f32>, normal: vec3<f32>) -> vec3<f32> {
+  if light.kind == LIGHT_DIRECTIONAL {
+    return light.color * light.intensity * max(dot(normal, -light.direction), 0.0);
+  }
+
+  let offset = light.position - world_position;
+  let distance = length(offset);
+  let to_light = offset / max(distance, 1e-6);
+
+  // inverse square falloff, windowed to reach zero at the range.
+  let window = clamp(1.0 - pow(distance / light.range, 4.0), 0.0, 1.0);
+  var attenuation = window * window / (distance * distance + 1.0);
+  if light.kind == LIGHT_SPOT {
+    attenuation *= smoothstep(light.outer_cos, light.inner_cos, dot(-to_light, light.direction));
+  }
+  return light.color * light.intensity * attenuation * max(dot(normal, to_light), 0.0);
+}
+";
+
+/// [`AmbientLight`] components light every surface evenly, approximating the light bounced
+/// around the world. The ambient lights of the world add up.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AmbientLight {
+    /// Color of the light, in linear RGB.
+    pub color: math::Vec3,
+    pub intensity: f32,
+}
+
+assert_impl_all!(AmbientLight: ecs::storage::Component);
+
+impl AmbientLight {
+    /// Creates a new [`AmbientLight`].
+    pub fn new(color: math::Vec3, intensity: f32) -> Self {
+        Self { color, intensity }
+    }
+}
+
+const DIRECTIONAL: u32 = 0;
+const POINT: u32 = 1;
+const SPOT: u32 = 2;
+
+/// A light of the `Lights` of [`LIGHTS_WGSL`].
+#[repr(C)]
+#[derive(Debug, Default, Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+struct LightUniform {
+    position: [f32; 3],
+    kind: u32,
+    direction: [f32; 3],
+    range: f32,
+    color: [f32; 3],
+    intensity: f32,
+    inner_cos: f32,
+    outer_cos: f32,
+    _padding: [f32; 2],
+}
+
+/// The `Lights` of [`LIGHTS_WGSL`].
+#[repr(C)]
+#[derive(Debug, Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+struct LightsUniform {
+    ambient: [f32; 3],
+    count: u32,
+    lights: [LightUniform; MAX_LIGHTS],
+}
+
+/// The engine-managed buffer of [`super::uniform::UniformType::Lights`], collecting the lights
+/// of the world every frame.
+#[derive(Debug)]
+pub(super) struct LightsBuffer {
+    buffer: wgpu::Buffer,
+    pub bind_group: wgpu::BindGroup,
+    _allocation: MemoryAllocation,
+}
+
+impl LightsBuffer {
+    pub fn new(
+        device: &wgpu::Device,
+        layouts: &GpuDefaultBindGroupLayouts,
+        memory: &Arc<MemoryTracker>,
+    ) -> Self {
+        let buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("ravia_engine::lights"),
+            size: std::mem::size_of::<LightsUniform>() as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("ravia_engine::lights"),
+            layout: &layouts.lights,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: buffer.as_entire_binding(),
+            }],
+        });
+
+        Self {
+            _allocation: memory.allocate(MemoryCategory::Uniform, buffer.size()),
+            buffer,
+            bind_group,
+        }
+    }
+
+    /// Uploads the lights of the world. Beyond [`MAX_LIGHTS`], the directional lights are kept
+    /// first, then the lights brightest at the camera position.
+    pub fn write(&self, queue: &wgpu::Queue, world: &ecs::World, camera_position: math::Vec3) {
+        let ambient = <&AmbientLight>::query()
+            .iter(world)
+            .map(|light| light.color * light.intensity)
+            .sum::<math::Vec3>();
+
+        let mut lights = vec![];
+        for light in <&DirectionalLight>::query().iter(world) {
+            let uniform = LightUniform {
+                kind: DIRECTIONAL,
+                direction: light.direction.normalize_or_zero().to_array(),
+                color: light.color.to_array(),
+                intensity: light.intensity,
+                ..Default::default()
+            };
+            lights.push((f32::INFINITY, uniform));
+        }
+        for (light, transform) in <(&PointLight, &Transform)>::query().iter(world) {
+            let position = *transform.position();
+            let uniform = LightUniform {
+                position: position.to_array(),
+                kind: POINT,
+                range: light.range,
+                color: light.color.to_array(),
+                intensity: light.intensity,
+                ..Default::default()
+            };
+            lights.push((
+                Self::priority(light.intensity, position, camera_position),
+                uniform,
+            ));
+        }
+        for (light, transform) in <(&SpotLight, &Transform)>::query().iter(world) {
+            let position = *transform.position();
+            let uniform = LightUniform {
+                position: position.to_array(),
+                kind: SPOT,
+                direction: light.direction.normalize_or_zero().to_array(),
+                range: light.range,
+                color: light.color.to_array(),
+                intensity: light.intensity,
+                inner_cos: light.inner_angle.min(light.outer_angle).cos(),
+                outer_cos: light.outer_angle.cos(),
+                ..Default::default()
+            };
+            lights.push((
+                Self::priority(light.intensity, position, camera_position),
+                uniform,
+            ));
+        }
+
+        if lights.len() > MAX_LIGHTS {
+            lights.sort_by(|(a, _), (b, _)| b.total_cmp(a));
+        }
+
+        let mut uniform = LightsUniform {
+            ambient: ambient.to_array(),
+            count: lights.len().min(MAX_LIGHTS) as u32,
+            lights: [LightUniform::default(); MAX_LIGHTS],
+        };
+        for (slot, (_, light)) in uniform.lights.iter_mut().zip(lights) {
+            *slot = light;
+        }
+        queue.write_buffer(&self.buffer, 0, bytemuck::cast_slice(&[uniform]));
+    }
+
+    /// Returns the brightness of a light at the camera position.
+    fn priority(intensity: f32, position: math::Vec3, camera_position: math::Vec3) -> f32 {
+        intensity / (1.0 + position.distance_squared(camera_position))
+    }
+}
@@ -1,15 +1,75 @@
-use crate::{ecs, engine::EngineContext};
+use std::collections::HashMap;
+
+use crate::{
+    ecs,
+    engine::EngineContext,
+    math,
+    resource::{Handle, Resource},
+};
 
 use super::{
     shader::{Shader, ShaderConfig},
-    texture::Texture,
+    texture::{Texture, TextureFilterMode},
+    uniform::UniformType,
+    upload::Upload,
 };
 
+/// A texture bound by a [`Material`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TextureSlot {
+    /// The base texture, bound as [`UniformType::Texture2D`].
+    Base,
+    /// The normal map, bound as [`UniformType::NormalMap`].
+    NormalMap,
+}
+
+impl TextureSlot {
+    /// Returns the uniform type the texture of the slot is bound as.
+    pub fn uniform_type(&self) -> UniformType {
+        match self {
+            Self::Base => UniformType::Texture2D,
+            Self::NormalMap => UniformType::NormalMap,
+        }
+    }
+
+    /// Creates a 1x1 texture leaving the material unchanged: white for the base texture, and
+    /// the flat normal for the normal map.
+    fn neutral_texture(&self, ctx: &EngineContext) -> Texture {
+        let pixel = match self {
+            Self::Base => vec![255, 255, 255, 255],
+            Self::NormalMap => vec![128, 128, 255, 255],
+        };
+        Texture::new_2d(ctx, math::UVec2::ONE, pixel, TextureFilterMode::Point)
+    }
+}
+
+/// A texture of a [`Material`] which is still loading.
+#[derive(Debug)]
+pub(crate) struct PendingTexture {
+    pub slot: TextureSlot,
+    pub resource: Resource,
+    pub filter_mode: TextureFilterMode,
+    /// Upload of the texture once the resource is loaded.
+    pub upload: Option<Upload<Result<Texture, anyhow::Error>>>,
+}
+
 /// A [`Material`] component describes how the shape should be rendered.
+///
+/// A material owns its compiled [`Shader`]. To render many entities the same way, store the
+/// material once in [`EngineContext::materials`] and attach a [`MaterialInstance`] to each entity.
+///
+/// Textures are bound on every draw, so they can be replaced at any time with
+/// [`Material::set_texture`] and [`Material::set_normal_map`], or loaded in the background with
+/// [`Material::load_texture`].
 #[derive(Debug)]
 pub struct Material {
     pub shader: Shader,
     pub texture: Option<Texture>,
+    pub normal_map: Option<Texture>,
+
+    pub(crate) pending: Vec<PendingTexture>,
+    /// Paths the textures of the slots have been loaded from, with their filter mode.
+    sources: HashMap<TextureSlot, (String, TextureFilterMode)>,
 }
 
 assert_impl_all!(Material: ecs::storage::Component);
@@ -17,9 +77,177 @@ assert_impl_all!(Material: ecs::storage::Component);
 impl Material {
     /// Creates a new [`Material`].
     pub fn new(ctx: &EngineContext, shader_config: &ShaderConfig) -> Self {
+        Self::with_shader(Shader::new(ctx, shader_config))
+    }
+
+    /// Creates a new [`Material`], compiling its shader in the background.
+    ///
+    /// See [`Shader::new_async`].
+    pub fn new_async(ctx: &EngineContext, shader_config: &ShaderConfig) -> Self {
+        Self::with_shader(Shader::new_async(ctx, shader_config))
+    }
+
+    fn with_shader(shader: Shader) -> Self {
         Self {
-            shader: Shader::new(ctx, shader_config),
+            shader,
             texture: None,
+            normal_map: None,
+            pending: vec![],
+            sources: HashMap::new(),
+        }
+    }
+
+    /// Returns the texture bound to the slot.
+    pub fn texture_in(&self, slot: TextureSlot) -> Option<&Texture> {
+        match slot {
+            TextureSlot::Base => self.texture.as_ref(),
+            TextureSlot::NormalMap => self.normal_map.as_ref(),
+        }
+    }
+
+    /// Sets the base texture.
+    ///
+    /// See [`Material::set_texture_in`].
+    pub fn set_texture(&mut self, ctx: &EngineContext, texture: Texture) {
+        self.set_texture_in(ctx, TextureSlot::Base, texture);
+    }
+
+    /// Sets the normal map.
+    ///
+    /// See [`Material::set_texture_in`].
+    pub fn set_normal_map(&mut self, ctx: &EngineContext, texture: Texture) {
+        self.set_texture_in(ctx, TextureSlot::NormalMap, texture);
+    }
+
+    /// Sets the texture of the slot, cancelling a pending [`Material::load_texture`] of the slot.
+    ///
+    /// A WebGPU error is reported if the shader does not declare the uniform of the slot, in which
+    /// case the texture is kept but never bound.
+    pub fn set_texture_in(&mut self, ctx: &EngineContext, slot: TextureSlot, texture: Texture) {
+        self.validate_slot(ctx, slot);
+        self.pending.retain(|pending| pending.slot != slot);
+        self.sources.remove(&slot);
+        self.bind_texture(slot, texture);
+    }
+
+    /// Sets the texture of the slot, without validating it against the shader.
+    pub(crate) fn bind_texture(&mut self, slot: TextureSlot, texture: Texture) {
+        match slot {
+            TextureSlot::Base => self.texture = Some(texture),
+            TextureSlot::NormalMap => self.normal_map = Some(texture),
         }
     }
+
+    /// Loads a binary texture from the path in the background, and binds it to the slot once
+    /// loaded.
+    ///
+    /// The current texture of the slot stays bound while loading and uploading. A failed load is
+    /// reported as [`crate::event::Event::ResourceFailed`], and a completed upload as
+    /// [`crate::event::Event::UploadCompleted`].
+    pub fn load_texture(
+        &mut self,
+        ctx: &EngineContext,
+        slot: TextureSlot,
+        path: &str,
+        filter_mode: TextureFilterMode,
+    ) {
+        self.validate_slot(ctx, slot);
+
+        let mut resource = Resource::new(path);
+        ctx.resource_manager.request(&mut resource);
+        self.pending.retain(|pending| pending.slot != slot);
+        self.sources.insert(slot, (path.to_string(), filter_mode));
+        self.pending.push(PendingTexture {
+            slot,
+            resource,
+            filter_mode,
+            upload: None,
+        });
+    }
+
+    /// Returns the path the texture of the slot has been loaded from with
+    /// [`Material::load_texture`], if any.
+    pub fn texture_source(&self, slot: TextureSlot) -> Option<&str> {
+        self.sources.get(&slot).map(|(path, _)| path.as_str())
+    }
+
+    /// Returns a copy of the material for the device recreated after a device loss.
+    ///
+    /// The shader is compiled again in the background, and the textures loaded with
+    /// [`Material::load_texture`] are loaded again. The other textures, set by the application,
+    /// are replaced by a texture leaving the material unchanged until set again.
+    pub(crate) fn restored(&self, ctx: &EngineContext) -> Self {
+        let mut material = Self::with_shader(self.shader.restored(ctx));
+        for slot in [TextureSlot::Base, TextureSlot::NormalMap] {
+            let pending = self.pending.iter().any(|pending| pending.slot == slot);
+            if self.texture_in(slot).is_none() && !pending {
+                continue;
+            }
+
+            material.bind_texture(slot, slot.neutral_texture(ctx));
+            if let Some((path, filter_mode)) = self.sources.get(&slot) {
+                material.load_texture(ctx, slot, path, *filter_mode);
+            }
+        }
+        material
+    }
+
+    /// Returns true if a texture of the material is still loading.
+    pub fn is_loading(&self) -> bool {
+        !self.pending.is_empty()
+    }
+
+    fn validate_slot(&self, ctx: &EngineContext, slot: TextureSlot) {
+        let uniform_type = slot.uniform_type();
+        if self.shader.bind_group_index(uniform_type).is_none() {
+            ctx.gpu.report_error(format!(
+                "{}: texture set to {:?}, but the shader does not declare {:?}",
+                self.shader.label(),
+                slot,
+                uniform_type,
+            ));
+        }
+    }
+}
+
+/// A [`MaterialInstance`] component renders an entity with a [`Material`] shared with other
+/// entities, overriding some of its properties.
+#[derive(Debug)]
+pub struct MaterialInstance {
+    pub material: Handle<Material>,
+    /// Texture used instead of the texture of the shared material, if any.
+    pub texture: Option<Texture>,
+}
+
+assert_impl_all!(MaterialInstance: ecs::storage::Component);
+
+impl MaterialInstance {
+    /// Creates a new [`MaterialInstance`] of the shared material, without overrides.
+    pub fn new(material: Handle<Material>) -> Self {
+        Self {
+            material,
+            texture: None,
+        }
+    }
+
+    /// Overrides the texture of the shared material.
+    pub fn with_texture(mut self, texture: Texture) -> Self {
+        self.texture = Some(texture);
+        self
+    }
+
+    /// Returns the shader of the shared material.
+    pub fn shader(&self) -> &Shader {
+        &self.material.shader
+    }
+
+    /// Returns the texture of the instance, or of the shared material if not overridden.
+    pub fn texture(&self) -> Option<&Texture> {
+        self.texture.as_ref().or(self.material.texture.as_ref())
+    }
+
+    /// Returns the normal map of the shared material.
+    pub fn normal_map(&self) -> Option<&Texture> {
+        self.material.normal_map.as_ref()
+    }
 }
@@ -0,0 +1,85 @@
+use std::sync::{
+    atomic::{AtomicU64, Ordering},
+    Arc,
+};
+
+/// Category of the GPU memory allocated by the engine.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MemoryCategory {
+    /// Vertex and index buffers of the meshes.
+    Mesh,
+    /// Uniform buffers, e.g. cameras and transforms.
+    Uniform,
+    /// Textures.
+    Texture,
+}
+
+/// [`MemoryStats`] reports the GPU memory allocated by the engine, in bytes.
+///
+/// This only accounts for the resources created by the engine, not the memory used by the driver.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct MemoryStats {
+    pub mesh_bytes: u64,
+    pub uniform_bytes: u64,
+    pub texture_bytes: u64,
+}
+
+impl MemoryStats {
+    /// Returns the total allocated bytes.
+    pub fn total_bytes(&self) -> u64 {
+        self.mesh_bytes + self.uniform_bytes + self.texture_bytes
+    }
+}
+
+/// Tracks the GPU memory allocated by the engine.
+#[derive(Debug, Default)]
+pub(crate) struct MemoryTracker {
+    mesh: AtomicU64,
+    uniform: AtomicU64,
+    texture: AtomicU64,
+}
+
+impl MemoryTracker {
+    /// Records an allocation, which is released when the returned [`MemoryAllocation`] is dropped.
+    pub fn allocate(self: &Arc<Self>, category: MemoryCategory, bytes: u64) -> MemoryAllocation {
+        self.counter(category).fetch_add(bytes, Ordering::Relaxed);
+        MemoryAllocation {
+            tracker: self.clone(),
+            category,
+            bytes,
+        }
+    }
+
+    /// Returns the snapshot of the allocated memory.
+    pub fn stats(&self) -> MemoryStats {
+        MemoryStats {
+            mesh_bytes: self.mesh.load(Ordering::Relaxed),
+            uniform_bytes: self.uniform.load(Ordering::Relaxed),
+            texture_bytes: self.texture.load(Ordering::Relaxed),
+        }
+    }
+
+    fn counter(&self, category: MemoryCategory) -> &AtomicU64 {
+        match category {
+            MemoryCategory::Mesh => &self.mesh,
+            MemoryCategory::Uniform => &self.uniform,
+            MemoryCategory::Texture => &self.texture,
+        }
+    }
+}
+
+/// A tracked GPU memory allocation, owned by the resource holding the memory.
+#[derive(Debug)]
+pub(crate) struct MemoryAllocation {
+    tracker: Arc<MemoryTracker>,
+    category: MemoryCategory,
+    bytes: u64,
+}
+
+impl Drop for MemoryAllocation {
+    fn drop(&mut self) {
+        self.tracker
+            .counter(self.category)
+            .fetch_sub(self.bytes, Ordering::Relaxed);
+    }
+}
@@ -4,6 +4,11 @@ use wgpu::util::DeviceExt;
 
 use crate::{ecs, engine::EngineContext, math};
 
+use super::{
+    coordinates::MeshImportOptions,
+    memory::{MemoryAllocation, MemoryCategory},
+};
+
 /// A trait for vertex data.
 ///
 /// The data type implementing this trait contains data for a single vertex, which should describe
@@ -107,13 +112,76 @@ impl Vertex for Vertex3DStandardColored {
     ];
 }
 
+/// A standard vertex with a normal and two texture coordinates, e.g. for lightmaps or detail
+/// textures.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, bytemuck::Zeroable)]
+pub struct VertexStandardUv2Data {
+    pub uv: math::Vec2,
+    pub normal: math::Vec3,
+    pub uv2: math::Vec2,
+}
+
+unsafe impl bytemuck::Pod for VertexStandardUv2Data {}
+
+/// A 3D vertex with a normal and two texture coordinates.
+pub type Vertex3DStandardUv2 = Vertex3D<VertexStandardUv2Data>;
+
+impl Vertex for Vertex3DStandardUv2 {
+    const ATTRIBUTE_FORMATS: &[wgpu::VertexFormat] = &[
+        wgpu::VertexFormat::Float32x3,
+        wgpu::VertexFormat::Float32x2,
+        wgpu::VertexFormat::Float32x3,
+        wgpu::VertexFormat::Float32x2,
+    ];
+}
+
+/// A skinned vertex with a normal, a texture coordinate, and the joints influencing it.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, bytemuck::Zeroable)]
+pub struct VertexSkinnedData {
+    pub uv: math::Vec2,
+    pub normal: math::Vec3,
+    /// Indices of up to 4 joints in the [`super::skin::Skin`] of the mesh.
+    pub joints: [u32; 4],
+    /// Weights of the joints, which should sum up to 1.
+    pub weights: [f32; 4],
+}
+
+unsafe impl bytemuck::Pod for VertexSkinnedData {}
+
+/// A 3D vertex with a normal, a texture coordinate, and joint influences for skinning.
+pub type Vertex3DSkinned = Vertex3D<VertexSkinnedData>;
+
+impl Vertex for Vertex3DSkinned {
+    const ATTRIBUTE_FORMATS: &[wgpu::VertexFormat] = &[
+        wgpu::VertexFormat::Float32x3,
+        wgpu::VertexFormat::Float32x2,
+        wgpu::VertexFormat::Float32x3,
+        wgpu::VertexFormat::Uint32x4,
+        wgpu::VertexFormat::Float32x4,
+    ];
+}
+
 /// A [`Mesh`] component describes a shape that can be rendered with a GPU.
+///
+/// The vertex and index data are discarded once uploaded. Add a
+/// [`super::mesh_data::MeshCpuData`] component to retain the geometry on the CPU.
 #[derive(Debug)]
 pub struct Mesh {
     vertex_buffer: wgpu::Buffer,
     index_buffer: wgpu::Buffer,
     num_vertices: u32,
     num_indices: u32,
+    num_instances: Option<u32>,
+    vertex_colors: bool,
+    import_scale: f32,
+    error: bool,
+    topology: wgpu::PrimitiveTopology,
+    _allocation: MemoryAllocation,
+
+    /// Additional vertex buffers, bound to the slots after the main vertex buffer.
+    extra_vertex_buffers: Vec<(wgpu::Buffer, MemoryAllocation)>,
 }
 
 assert_impl_all!(Mesh: ecs::storage::Component);
@@ -132,31 +200,195 @@ impl Mesh {
     /// For now, we are allocating a new buffer for each mesh. This can be later optimized by allocating
     /// a large buffer for multiple meshes and tracking their offset.
     pub fn new_indexed<V: Vertex>(ctx: &EngineContext, vertices: &[V], indices: &[u32]) -> Self {
-        let vertex_buffer = ctx
-            .gpu
-            .device
-            .create_buffer_init(&wgpu::util::BufferInitDescriptor {
-                label: None,
-                contents: bytemuck::cast_slice(vertices),
+        Self::new_indexed_with_label(ctx, vertices, indices, "ravia_engine::mesh")
+    }
+
+    /// Creates a new [`Mesh`] from vertex and index data, with a debug label for its buffers.
+    pub fn new_indexed_with_label<V: Vertex>(
+        ctx: &EngineContext,
+        vertices: &[V],
+        indices: &[u32],
+        label: &str,
+    ) -> Self {
+        Self::from_bytes(
+            ctx,
+            bytemuck::cast_slice(vertices),
+            vertices.len() as u32,
+            bytemuck::cast_slice(indices),
+            indices.len() as u32,
+            label,
+        )
+    }
+
+    /// Creates a new [`Mesh`] from raw vertex and index buffer contents.
+    ///
+    /// The caller is responsible for the vertex bytes matching the layout of the shader the mesh
+    /// is rendered with, and the index bytes being little-endian `u32`s.
+    pub(super) fn from_bytes(
+        ctx: &EngineContext,
+        vertices: &[u8],
+        num_vertices: u32,
+        indices: &[u8],
+        num_indices: u32,
+        label: &str,
+    ) -> Self {
+        let (vertex_buffer, index_buffer) = ctx.gpu.error_scope(label, |device| {
+            let vertex_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some(label),
+                contents: vertices,
                 usage: wgpu::BufferUsages::VERTEX,
             });
 
-        let index_buffer = ctx
-            .gpu
-            .device
-            .create_buffer_init(&wgpu::util::BufferInitDescriptor {
-                label: None,
-                contents: bytemuck::cast_slice(indices),
+            let index_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some(label),
+                contents: indices,
                 usage: wgpu::BufferUsages::INDEX,
             });
 
+            (vertex_buffer, index_buffer)
+        });
+
+        let allocation = ctx.gpu.track_memory(
+            MemoryCategory::Mesh,
+            vertex_buffer.size() + index_buffer.size(),
+        );
+
         Self {
             vertex_buffer,
             index_buffer,
+            _allocation: allocation,
+
+            num_vertices,
+            num_indices,
+            num_instances: None,
+            vertex_colors: false,
+            import_scale: 1.0,
+            error: false,
+            topology: wgpu::PrimitiveTopology::TriangleList,
+            extra_vertex_buffers: vec![],
+        }
+    }
+
+    /// Creates the error [`Mesh`] substituted for meshes which fail to load: a cube of a meter
+    /// of [`Vertex3DStandard`] vertices, drawn with the error shader whatever its material.
+    pub fn error_cube(ctx: &EngineContext) -> Self {
+        let size = ctx.gpu.units_per_meter();
+        let mut vertices = vec![];
+        let mut indices = vec![];
+        for normal in [
+            math::Vec3::X,
+            math::Vec3::NEG_X,
+            math::Vec3::Y,
+            math::Vec3::NEG_Y,
+            math::Vec3::Z,
+            math::Vec3::NEG_Z,
+        ] {
+            let tangent = normal.any_orthonormal_vector();
+            let bitangent = normal.cross(tangent);
+            let base = vertices.len() as u32;
+            for (u, v) in [(0.0, 0.0), (1.0, 0.0), (1.0, 1.0), (0.0, 1.0)] {
+                let position = normal + tangent * (u * 2.0 - 1.0) + bitangent * (v * 2.0 - 1.0);
+                vertices.push(Vertex3DStandard {
+                    position: position * 0.5 * size,
+                    data: VertexStandardData {
+                        uv: math::vec2(u, 1.0 - v),
+                        normal,
+                    },
+                });
+            }
+            indices.extend([base, base + 1, base + 2, base, base + 2, base + 3]);
+        }
+
+        let mut mesh =
+            Self::new_indexed_with_label(ctx, &vertices, &indices, "ravia_engine::error");
+        mesh.error = true;
+        mesh
+    }
 
-            num_vertices: vertices.len() as u32,
-            num_indices: indices.len() as u32,
+    /// Returns true if the mesh is the error mesh, see [`Mesh::error_cube`].
+    pub fn is_error(&self) -> bool {
+        self.error
+    }
+
+    /// Sets how the indices of the mesh are assembled into primitives, e.g. lines or points.
+    /// Meshes are triangle lists by default.
+    ///
+    /// Materials draw the mesh with the topology of their shader, see
+    /// [`super::shader::ShaderConfig::with_topology`], so the shader should match. The
+    /// placeholder, error and debug view pipelines follow the topology of the mesh.
+    pub fn with_topology(mut self, topology: wgpu::PrimitiveTopology) -> Self {
+        self.topology = topology;
+        self
+    }
+
+    /// Returns how the indices of the mesh are assembled into primitives.
+    pub fn topology(&self) -> wgpu::PrimitiveTopology {
+        self.topology
+    }
+
+    /// Adds a vertex buffer, bound to the next vertex buffer slot of the shader.
+    ///
+    /// The shader should declare the buffer with [`super::shader::ShaderConfig::with_vertex_buffers`].
+    /// With [`wgpu::VertexStepMode::Vertex`], the buffer holds a separate attribute stream with one
+    /// element per vertex. With [`wgpu::VertexStepMode::Instance`], it holds one element per
+    /// instance, and the mesh is drawn once per element.
+    pub fn add_vertex_buffer<V: Vertex>(
+        &mut self,
+        ctx: &EngineContext,
+        data: &[V],
+        step_mode: wgpu::VertexStepMode,
+    ) {
+        const LABEL: &str = "ravia_engine::mesh_vertex_stream";
+
+        let buffer = ctx.gpu.error_scope(LABEL, |device| {
+            device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some(LABEL),
+                contents: bytemuck::cast_slice(data),
+                usage: wgpu::BufferUsages::VERTEX,
+            })
+        });
+        let allocation = ctx.gpu.track_memory(MemoryCategory::Mesh, buffer.size());
+
+        // with several instance buffers, only the instances present in all of them are drawn.
+        if step_mode == wgpu::VertexStepMode::Instance {
+            let len = data.len() as u32;
+            self.num_instances = Some(self.num_instances.map_or(len, |n| n.min(len)));
         }
+        self.extra_vertex_buffers.push((buffer, allocation));
+    }
+
+    /// Returns the number of instances the mesh is drawn with.
+    pub fn num_instances(&self) -> u32 {
+        self.num_instances.unwrap_or(1)
+    }
+
+    /// Returns the instance range of the mesh.
+    pub fn instances(&self) -> std::ops::Range<u32> {
+        0..self.num_instances()
+    }
+
+    /// Returns true if the vertices of the mesh are [`Vertex3DStandardColored`], rendered with
+    /// [`super::standard::StandardShader::StandardColored`] by default.
+    pub fn has_vertex_colors(&self) -> bool {
+        self.vertex_colors
+    }
+
+    /// Marks the vertices of the mesh as [`Vertex3DStandardColored`].
+    pub(super) fn with_vertex_colors(mut self) -> Self {
+        self.vertex_colors = true;
+        self
+    }
+
+    /// Returns the factor the positions have been scaled by when the mesh was loaded, converting
+    /// its authored unit. See [`super::coordinates::MeshImportOptions::meters_per_unit`].
+    pub fn import_scale(&self) -> f32 {
+        self.import_scale
+    }
+
+    /// Records the factor the positions have been scaled by when loaded.
+    pub(super) fn with_import_scale(mut self, import_scale: f32) -> Self {
+        self.import_scale = import_scale;
+        self
     }
 
     /// Returns the number of vertices in the mesh.
@@ -175,12 +407,19 @@ impl Mesh {
     }
 
     /// Returns a slice of the vertex buffer to bind for a render pass.
-    pub(super) fn vertex_slice(&self) -> wgpu::BufferSlice {
+    pub(super) fn vertex_slice(&self) -> wgpu::BufferSlice<'_> {
         self.vertex_buffer.slice(..)
     }
 
+    /// Returns slices of the additional vertex buffers to bind for a render pass, in slot order.
+    pub(super) fn extra_vertex_slices(&self) -> impl Iterator<Item = wgpu::BufferSlice<'_>> {
+        self.extra_vertex_buffers
+            .iter()
+            .map(|(buffer, _)| buffer.slice(..))
+    }
+
     /// Returns a slice of the index buffer to bind for a render pass.
-    pub(super) fn index_slice(&self) -> wgpu::BufferSlice {
+    pub(super) fn index_slice(&self) -> wgpu::BufferSlice<'_> {
         self.index_buffer.slice(..)
     }
 }
@@ -188,8 +427,30 @@ impl Mesh {
 /// Loads a mesh from a buffer containing an OBJ-formatted buffer.
 ///
 /// This function expects an .obj buffer with vertex data, together with optional vertex colors,
-/// normals, or texture coordinates. The mesh will be composed with appropriate data type.
+/// normals, or texture coordinates. The mesh will be composed with appropriate data type, and
+/// meshes with vertex colors are marked with [`Mesh::has_vertex_colors`].
 pub fn load_mesh_from_obj(ctx: &EngineContext, data: &[u8]) -> Result<Mesh, anyhow::Error> {
+    load_mesh_from_obj_with_label(ctx, data, "ravia_engine::mesh")
+}
+
+/// Loads a mesh from a buffer containing an OBJ-formatted buffer, with a debug label for its
+/// buffers.
+pub fn load_mesh_from_obj_with_label(
+    ctx: &EngineContext,
+    data: &[u8],
+    label: &str,
+) -> Result<Mesh, anyhow::Error> {
+    load_mesh_from_obj_with_options(ctx, data, label, MeshImportOptions::default())
+}
+
+/// Loads a mesh from a buffer containing an OBJ-formatted buffer, converting it with the import
+/// options, e.g. when authored in another coordinate system or unit.
+pub fn load_mesh_from_obj_with_options(
+    ctx: &EngineContext,
+    data: &[u8],
+    label: &str,
+    options: MeshImportOptions,
+) -> Result<Mesh, anyhow::Error> {
     let mut buf = BufReader::new(data);
     let (models, _) = tobj::load_obj_buf(
         &mut buf,
@@ -208,6 +469,8 @@ pub fn load_mesh_from_obj(ctx: &EngineContext, data: &[u8]) -> Result<Mesh, anyh
 
     let model = models.first().unwrap();
     let num_vertices = model.mesh.positions.len() / 3;
+    let mut indices = model.mesh.indices.clone();
+    let units_per_meter = ctx.gpu.units_per_meter();
 
     let mesh = if model.mesh.vertex_color.is_empty() {
         let mut vertices = vec![];
@@ -220,7 +483,13 @@ pub fn load_mesh_from_obj(ctx: &EngineContext, data: &[u8]) -> Result<Mesh, anyh
                 },
             });
         }
-        Mesh::new_indexed(ctx, &vertices, &model.mesh.indices)
+        options.apply(
+            units_per_meter,
+            bytemuck::cast_slice_mut(&mut vertices),
+            Vertex3DStandard::SIZE as usize,
+            &mut indices,
+        );
+        Mesh::new_indexed_with_label(ctx, &vertices, &indices, label)
     } else {
         let mut vertices = vec![];
         for i in 0..num_vertices {
@@ -233,8 +502,14 @@ pub fn load_mesh_from_obj(ctx: &EngineContext, data: &[u8]) -> Result<Mesh, anyh
                 },
             });
         }
-        Mesh::new_indexed(ctx, &vertices, &model.mesh.indices)
+        options.apply(
+            units_per_meter,
+            bytemuck::cast_slice_mut(&mut vertices),
+            Vertex3DStandardColored::SIZE as usize,
+            &mut indices,
+        );
+        Mesh::new_indexed_with_label(ctx, &vertices, &indices, label).with_vertex_colors()
     };
 
-    Ok(mesh)
+    Ok(mesh.with_import_scale(options.scale(units_per_meter)))
 }
@@ -0,0 +1,120 @@
+use crate::{ecs, math};
+
+use super::{mesh::Vertex, transform::Transform};
+
+/// A [`MeshCpuData`] component retains the geometry of a [`super::mesh::Mesh`] on the CPU.
+///
+/// A [`super::mesh::Mesh`] only keeps its GPU buffers once uploaded. Entities whose geometry is
+/// needed by other engine subsystems, e.g. picking, collision or navigation, opt in by adding
+/// this component next to the mesh, created from the same vertices and indices.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MeshCpuData {
+    positions: Vec<math::Vec3>,
+    indices: Vec<u32>,
+    bounds: (math::Vec3, math::Vec3),
+}
+
+assert_impl_all!(MeshCpuData: ecs::storage::Component);
+
+impl MeshCpuData {
+    /// Creates a new [`MeshCpuData`] from vertex positions and triangle list indices.
+    ///
+    /// Indices out of range of the positions are dropped along with their triangle.
+    pub fn new(positions: Vec<math::Vec3>, indices: Vec<u32>) -> Self {
+        let num_positions = positions.len() as u32;
+        let indices = indices
+            .chunks_exact(3)
+            .filter(|triangle| triangle.iter().all(|&i| i < num_positions))
+            .flatten()
+            .copied()
+            .collect();
+
+        let bounds = positions.iter().fold(
+            (
+                math::Vec3::splat(f32::INFINITY),
+                math::Vec3::splat(f32::NEG_INFINITY),
+            ),
+            |(min, max), &p| (min.min(p), max.max(p)),
+        );
+        let bounds = if positions.is_empty() {
+            (math::Vec3::ZERO, math::Vec3::ZERO)
+        } else {
+            bounds
+        };
+
+        Self {
+            positions,
+            indices,
+            bounds,
+        }
+    }
+
+    /// Creates a new [`MeshCpuData`] from the vertex and index data a mesh is created with.
+    ///
+    /// The position of each vertex is read from its first attribute, which is expected to be a
+    /// [`wgpu::VertexFormat::Float32x3`], or a [`wgpu::VertexFormat::Float32x2`] on the xy plane
+    /// as in all vertex types of the engine.
+    pub fn from_vertices<V: Vertex>(vertices: &[V], indices: &[u32]) -> Self {
+        let components = match V::ATTRIBUTE_FORMATS.first() {
+            Some(wgpu::VertexFormat::Float32x3) => 3,
+            Some(wgpu::VertexFormat::Float32x2) => 2,
+            _ => 0,
+        };
+
+        let positions = vertices
+            .iter()
+            .map(|vertex| {
+                let bytes = bytemuck::bytes_of(vertex);
+                let mut position = [0.0f32; 3];
+                for (i, component) in position.iter_mut().take(components).enumerate() {
+                    *component = bytemuck::pod_read_unaligned(&bytes[4 * i..4 * i + 4]);
+                }
+                math::Vec3::from_array(position)
+            })
+            .collect();
+
+        Self::new(positions, indices.to_vec())
+    }
+
+    /// Returns the positions of the vertices, in the local space of the mesh.
+    pub fn positions(&self) -> &[math::Vec3] {
+        &self.positions
+    }
+
+    /// Returns the triangle list indices into [`MeshCpuData::positions`].
+    pub fn indices(&self) -> &[u32] {
+        &self.indices
+    }
+
+    /// Returns the number of triangles.
+    pub fn num_triangles(&self) -> usize {
+        self.indices.len() / 3
+    }
+
+    /// Returns the triangles, in the local space of the mesh.
+    pub fn triangles(&self) -> impl Iterator<Item = [math::Vec3; 3]> + '_ {
+        self.indices.chunks_exact(3).map(|triangle| {
+            [
+                self.positions[triangle[0] as usize],
+                self.positions[triangle[1] as usize],
+                self.positions[triangle[2] as usize],
+            ]
+        })
+    }
+
+    /// Returns the triangles, transformed into world space by the [`Transform`] of the entity.
+    pub fn world_triangles<'a>(
+        &'a self,
+        transform: &'a Transform,
+    ) -> impl Iterator<Item = [math::Vec3; 3]> + 'a {
+        let model = transform.matrix();
+        self.triangles()
+            .map(move |triangle| triangle.map(|p| model.transform_point3(p)))
+    }
+
+    /// Returns the minimum and maximum corners of the axis-aligned bounding box of the positions,
+    /// in the local space of the mesh.
+    pub fn bounds(&self) -> (math::Vec3, math::Vec3) {
+        self.bounds
+    }
+}
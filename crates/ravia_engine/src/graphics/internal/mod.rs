@@ -1,9 +1,54 @@
+pub mod binary_mesh;
+pub mod binary_texture;
+pub mod budget;
 pub mod camera;
+pub mod capabilities;
+pub mod coordinates;
+pub mod cubemap;
+pub mod custom_pass;
+pub mod debug_view;
+pub mod demo_camera;
+mod depth;
+pub mod directional_light;
+mod downsample;
+pub mod frame;
+pub mod frame_graph;
+pub mod frame_stats_overlay;
+pub mod gizmo;
 pub mod gpu;
+pub mod instances;
+pub mod lightmap;
+pub mod lights;
 pub mod material;
+pub mod memory;
 pub mod mesh;
+pub mod mesh_data;
+pub mod overlay;
+pub mod photo_mode;
+mod pipeline_cache;
+mod pipeline_compiler;
+pub mod point_light;
+pub mod post_process;
+mod preprocessor;
+pub mod present;
+pub mod procedural_texture;
+pub mod readback;
+mod reflection;
+pub mod render_order;
+pub mod render_texture;
+pub mod restore;
+pub mod sampler;
+pub mod screen_space;
 pub mod shader;
+pub mod skin;
+pub mod skybox;
+pub mod spot_light;
+pub mod standard;
 pub mod system;
 pub mod texture;
+pub mod texture_streaming;
 pub mod transform;
 pub mod uniform;
+pub mod upload;
+pub mod viewport;
+pub mod visibility;
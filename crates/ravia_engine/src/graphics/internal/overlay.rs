@@ -0,0 +1,13 @@
+use crate::ecs;
+
+/// An [`Overlay`] component moves an entity to the overlay pass, drawn after the scene of every
+/// camera, so that UI such as sprites and text is not affected by the effects applied to the
+/// scene.
+///
+/// A [`super::camera::Camera`] with an [`Overlay`] component draws only the overlay entities, over
+/// the frame without clearing it regardless of its [`super::camera::CameraClear`]. Cameras without
+/// it draw only the other entities.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Overlay;
+
+assert_impl_all!(Overlay: ecs::storage::Component);
@@ -0,0 +1,134 @@
+use crate::{ecs, event, math};
+
+use super::transform::Transform;
+
+/// [`PhotoMode`] is a resource to take screenshots of the world from a free camera.
+///
+/// While active, the time is paused, the [`super::overlay::Overlay`] cameras are hidden, and a
+/// camera with a [`PhotoCamera`] component is spawned at the first scene camera, drawn over the
+/// other cameras. The free camera rotates with the mouse dragged with the left button, and pans
+/// with the right button. Captures requested with [`PhotoMode::capture`] are rendered from the
+/// free camera, and delivered as [`crate::event::Event::PhotoCaptured`].
+#[derive(Debug, Default)]
+pub struct PhotoMode {
+    active: bool,
+    pub(super) camera: Option<ecs::Entity>,
+    capture: Option<u32>,
+}
+
+assert_impl_all!(PhotoMode: ecs::systems::Resource);
+
+impl PhotoMode {
+    /// Creates a new inactive [`PhotoMode`].
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns true if the photo mode is active.
+    pub fn is_active(&self) -> bool {
+        self.active
+    }
+
+    /// Enters or exits the photo mode.
+    pub fn set_active(&mut self, active: bool) {
+        self.active = active;
+        if !active {
+            self.capture = None;
+        }
+    }
+
+    /// Enters the photo mode if inactive, or exits it otherwise.
+    pub fn toggle(&mut self) {
+        self.set_active(!self.active);
+    }
+
+    /// Requests a screenshot at the window resolution, supersampled from a frame rendered at
+    /// `scale` times the resolution, captured after the next frame. Ignored outside of the photo
+    /// mode.
+    ///
+    /// The supersampling is limited by the maximum texture size of the device.
+    pub fn capture(&mut self, scale: u32) {
+        if self.active {
+            self.capture = Some(scale.max(1));
+        }
+    }
+
+    /// Takes the scale of the requested capture.
+    pub(crate) fn take_capture(&mut self) -> Option<u32> {
+        self.capture.take()
+    }
+}
+
+/// A [`PhotoCamera`] component makes the [`Transform`] of the same entity a free camera, spawned
+/// by the [`PhotoMode`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PhotoCamera {
+    /// Rotation around the y axis, in radians.
+    pub yaw: f32,
+    /// Rotation around the x axis, in radians.
+    pub pitch: f32,
+    /// Rotation per unit of mouse motion, in radians.
+    pub look_sensitivity: f32,
+    /// Translation per unit of mouse motion, in world units.
+    pub pan_sensitivity: f32,
+
+    looking: bool,
+    panning: bool,
+}
+
+assert_impl_all!(PhotoCamera: ecs::storage::Component);
+
+impl PhotoCamera {
+    /// Limit of the pitch, keeping the camera from flipping over the poles.
+    const MAX_PITCH: f32 = std::f32::consts::FRAC_PI_2 - 0.01;
+
+    /// Creates a new [`PhotoCamera`] facing the direction of the rotation.
+    pub fn new(rotation: math::Quat) -> Self {
+        let (yaw, pitch, _) = rotation.to_euler(math::EulerRot::YXZ);
+        Self {
+            yaw,
+            pitch,
+            look_sensitivity: 0.003,
+            pan_sensitivity: 0.01,
+            looking: false,
+            panning: false,
+        }
+    }
+
+    /// Moves the camera by a frame of mouse events.
+    pub(super) fn update(&mut self, transform: &mut Transform, events: &event::Events) {
+        for event in events.iter() {
+            match event {
+                event::Event::MouseButton {
+                    button: event::MouseButton::Left,
+                    pressed,
+                } => self.looking = *pressed,
+                event::Event::MouseButton {
+                    button: event::MouseButton::Right,
+                    pressed,
+                } => self.panning = *pressed,
+                event::Event::WindowUnfocused => {
+                    self.looking = false;
+                    self.panning = false;
+                }
+                event::Event::MouseMotion(delta) if self.looking => {
+                    self.yaw -= delta.x * self.look_sensitivity;
+                    self.pitch -= delta.y * self.look_sensitivity;
+                }
+                event::Event::MouseMotion(delta) if self.panning => {
+                    let pan = *transform.rotation()
+                        * math::vec3(-delta.x, delta.y, 0.0)
+                        * self.pan_sensitivity;
+                    transform.translate(pan);
+                }
+                _ => (),
+            }
+        }
+
+        self.pitch = self.pitch.clamp(-Self::MAX_PITCH, Self::MAX_PITCH);
+        let rotation = math::Quat::from_euler(math::EulerRot::YXZ, self.yaw, self.pitch, 0.0);
+        if *transform.rotation() != rotation {
+            transform.set_rotation(rotation);
+        }
+    }
+}
@@ -0,0 +1,88 @@
+use std::path::PathBuf;
+
+use log::{info, warn};
+
+/// A [`wgpu::PipelineCache`] persisted on disk, so that pipelines compiled in a previous run are
+/// reused.
+///
+/// Pipeline caches are only supported by some native backends (currently Vulkan), so this is never
+/// enabled on the web. The cache file is keyed by the adapter and driver, since the blobs are only
+/// valid for them.
+#[derive(Debug)]
+pub(super) struct PipelineCache {
+    cache: wgpu::PipelineCache,
+    path: PathBuf,
+}
+
+impl PipelineCache {
+    /// Returns the device features needed to create a pipeline cache in the directory, if it is set
+    /// and the adapter supports them.
+    pub fn required_features(adapter: &wgpu::Adapter, directory: Option<&str>) -> wgpu::Features {
+        if directory.is_some() && adapter.features().contains(wgpu::Features::PIPELINE_CACHE) {
+            wgpu::Features::PIPELINE_CACHE
+        } else {
+            wgpu::Features::empty()
+        }
+    }
+
+    /// Creates the pipeline cache, loading the blob of a previous run from the directory if it
+    /// exists.
+    ///
+    /// Returns `None` if the directory is not set, or the device does not support pipeline caches.
+    pub fn load(
+        device: &wgpu::Device,
+        adapter_info: &wgpu::AdapterInfo,
+        directory: Option<&str>,
+    ) -> Option<Self> {
+        let directory = directory?;
+        if !device.features().contains(wgpu::Features::PIPELINE_CACHE) {
+            info!(target: "ravia_engine::graphics::gpu", "Pipeline caches are not supported by {}", adapter_info.name);
+            return None;
+        }
+
+        let key = wgpu::util::pipeline_cache_key(adapter_info)?;
+        let path = std::path::Path::new(directory).join(key);
+        let data = std::fs::read(&path).ok();
+
+        // SAFETY: the data was written by `save` for the same adapter and driver, as the file
+        // name is keyed by them. With `fallback`, invalid data yields an empty cache.
+        let cache = unsafe {
+            device.create_pipeline_cache(&wgpu::PipelineCacheDescriptor {
+                label: Some("ravia_engine::pipeline_cache"),
+                data: data.as_deref(),
+                fallback: true,
+            })
+        };
+
+        info!(target: "ravia_engine::graphics::gpu", "Using pipeline cache {}", path.display());
+        Some(Self { cache, path })
+    }
+
+    /// Returns the underlying [`wgpu::PipelineCache`].
+    pub fn cache(&self) -> &wgpu::PipelineCache {
+        &self.cache
+    }
+
+    /// Writes the pipelines compiled so far to the cache file.
+    pub fn save(&self) {
+        let Some(data) = self.cache.get_data() else {
+            return;
+        };
+
+        if let Some(directory) = self.path.parent() {
+            if let Err(e) = std::fs::create_dir_all(directory) {
+                warn!(target: "ravia_engine::graphics::gpu", "Failed to create pipeline cache directory: {}", e);
+                return;
+            }
+        }
+
+        // write to a temporary file first, so that a crash while writing does not leave a
+        // truncated cache behind.
+        let temp_path = self.path.with_extension("tmp");
+        let result =
+            std::fs::write(&temp_path, data).and_then(|_| std::fs::rename(&temp_path, &self.path));
+        if let Err(e) = result {
+            warn!(target: "ravia_engine::graphics::gpu", "Failed to save pipeline cache: {}", e);
+        }
+    }
+}
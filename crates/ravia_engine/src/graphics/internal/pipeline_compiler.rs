@@ -0,0 +1,78 @@
+#[cfg(target_arch = "wasm32")]
+use std::collections::VecDeque;
+#[cfg(not(target_arch = "wasm32"))]
+use std::sync::mpsc;
+use std::sync::{Arc, Mutex};
+
+use super::{gpu::Gpu, shader::DeferredPipeline};
+
+/// A pipeline of the compiler queue, with the GPU to compile it with.
+#[cfg(not(target_arch = "wasm32"))]
+type Job = (Arc<Gpu>, DeferredPipeline);
+
+/// Compiles the pipelines of [`Shader::new_async`](super::shader::Shader::new_async) away from
+/// the frame.
+///
+/// On native platforms the pipelines are compiled one at a time on a single background thread,
+/// started with the first pipeline, and handed to their shaders at the start of the next frame.
+/// On the web, where the device cannot be shared between threads, the pipelines are compiled one
+/// per frame to spread the cost over the startup.
+#[derive(Debug, Default)]
+pub(super) struct PipelineCompiler {
+    #[cfg(not(target_arch = "wasm32"))]
+    sender: Mutex<Option<mpsc::Sender<Job>>>,
+    /// Pipelines compiled by the background thread, waiting for the next frame.
+    #[cfg(not(target_arch = "wasm32"))]
+    compiled: Arc<Mutex<Vec<(DeferredPipeline, wgpu::RenderPipeline)>>>,
+    #[cfg(target_arch = "wasm32")]
+    queue: Mutex<VecDeque<DeferredPipeline>>,
+}
+
+impl PipelineCompiler {
+    /// Queues the pipeline to be compiled.
+    pub fn push(&self, gpu: &Arc<Gpu>, pipeline: DeferredPipeline) {
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            let mut sender = self.sender.lock().unwrap();
+            let sender = sender.get_or_insert_with(|| self.spawn());
+            // the thread runs until the sender is dropped along with the compiler.
+            sender
+                .send((gpu.clone(), pipeline))
+                .expect("The pipeline compiler thread has stopped");
+        }
+        #[cfg(target_arch = "wasm32")]
+        {
+            let _ = gpu;
+            self.queue.lock().unwrap().push_back(pipeline);
+        }
+    }
+
+    /// Hands the compiled pipelines to their shaders, or compiles the next pipeline on the web.
+    /// Called by the frame thread at the start of every frame.
+    pub fn poll(&self, gpu: &Gpu) {
+        #[cfg(not(target_arch = "wasm32"))]
+        for (pipeline, compiled) in std::mem::take(&mut *self.compiled.lock().unwrap()) {
+            pipeline.finish(gpu, compiled);
+        }
+        #[cfg(target_arch = "wasm32")]
+        if let Some(pipeline) = self.queue.lock().unwrap().pop_front() {
+            pipeline.compile(gpu);
+        }
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn spawn(&self) -> mpsc::Sender<Job> {
+        let (sender, receiver) = mpsc::channel::<Job>();
+        let compiled = self.compiled.clone();
+        std::thread::Builder::new()
+            .name("ravia_engine::pipeline_compiler".to_string())
+            .spawn(move || {
+                for (gpu, pipeline) in receiver {
+                    let created = pipeline.create(&gpu);
+                    compiled.lock().unwrap().push((pipeline, created));
+                }
+            })
+            .expect("Failed to spawn the pipeline compiler thread");
+        sender
+    }
+}
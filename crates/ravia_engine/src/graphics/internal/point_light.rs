@@ -0,0 +1,407 @@
+use wgpu::util::DeviceExt;
+
+use crate::{ecs, engine::EngineContext, math};
+
+use super::{
+    depth::DEPTH_FORMAT,
+    directional_light::{shadow_bind_group, uniform_bind_group},
+    gpu::{Gpu, GpuDefaultBindGroupLayouts},
+    memory::{MemoryAllocation, MemoryCategory, MemoryTracker},
+};
+
+/// WGSL declarations of the cube shadow map bound as
+/// [`super::uniform::UniformType::PointShadowMap`], with percentage-closer filtering (PCF)
+/// helpers. Prepend it to the source of a shader sampling the shadow map:
+///
+/// ```ignore
+/// let source = format!("{}{}", POINT_SHADOW_WGSL, SOURCE);
+/// ```
+///
+/// The shader declares the bindings of the uniform itself, at the group of the uniform:
+///
+/// ```wgsl
+/// @group(4) @binding(0) var point_shadow_map: texture_depth_cube;
+/// @group(4) @binding(1) var point_shadow_sampler: sampler_comparison;
+/// @group(4) @binding(2) var<uniform> point_shadow: PointShadow;
+///
+/// let lit = point_shadow_pcf(point_shadow_map, point_shadow_sampler, point_shadow, world_position);
+/// ```
+pub const POINT_SHADOW_WGSL: &str = "
+struct PointShadow {
+  position: vec3<f32>,
+  near: f32,
+  far: f32,
+  bias: f32,
+  texel_size: f32,
+  enabled: u32,
+};
+
+// Returns the depth the cube shadow map stores at the distance along the major axis of a face.
+fn point_shadow_depth(shadow: PointShadow, distance: f32) -> f32 {
+  return shadow.far * (distance - shadow.near) / ((shadow.far - shadow.near) * distance);
+}
+
+// Returns the fraction of the point light reaching the world position, from 0 in full shadow to
+// 1 when lit, filtered over 8 samples around the position. Positions beyond the range of the
+// light are lit.
+fn point_shadow_pcf(
+  map: texture_depth_cube,
+  compare: sampler_comparison,
+  shadow: PointShadow,
+  world_position: vec3<f32>,
+) -> f32 {
+  let offset = world_position - shadow.position;
+  let distance = max(max(abs(offset.x), abs(offset.y)), abs(offset.z));
+  if shadow.enabled == 0u || distance >= shadow.far {
+    return 1.0;
+  }
+
+  let depth = point_shadow_depth(shadow, max(distance - shadow.bias, shadow.near));
+  let radius = 2.0 * distance * shadow.texel_size;
+  var lit = 0.0;
+  for (var i = 0u; i < 8u; i++) {
+    let corner = vec3<f32>(
+      select(-1.0, 1.0, (i & 1u) != 0u),
+      select(-1.0, 1.0, (i & 2u) != 0u),
+      select(-1.0, 1.0, (i & 4u) != 0u),
+    );
+    lit += textureSampleCompareLevel(map, compare, offset + corner * radius, depth);
+  }
+  return lit / 8.0;
+}
+";
+
+/// [`PointShadowConfig`] configures the cube shadow map of a [`PointLight`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PointShadowConfig {
+    /// Width and height of every face of the shadow map, in texels.
+    pub resolution: u32,
+    /// Distance from the light below which meshes cast no shadow, in world units.
+    pub near: f32,
+    /// Distance subtracted when comparing against the shadow map, against shadow acne, in world
+    /// units.
+    pub bias: f32,
+}
+
+impl Default for PointShadowConfig {
+    fn default() -> Self {
+        Self {
+            resolution: 512,
+            near: 0.05,
+            bias: 0.02,
+        }
+    }
+}
+
+/// Values of the cube shadow map, bound with its texture.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+struct PointShadowUniform {
+    position: [f32; 3],
+    near: f32,
+    far: f32,
+    bias: f32,
+    texel_size: f32,
+    enabled: u32,
+}
+
+/// A [`PointLight`] component lights the world in every direction from the position of its
+/// [`super::transform::Transform`], e.g. a lamp. It is bound to the shaders with the other
+/// lights as [`super::uniform::UniformType::Lights`].
+///
+/// A light with a shadow map renders the meshes around it into the six faces of a cube before
+/// the frame. Every mesh within the range of a light rendering its shadow map is bound the cube
+/// of the nearest one as [`super::uniform::UniformType::PointShadowMap`], which the shaders
+/// sample with the helpers of [`POINT_SHADOW_WGSL`]. The lights rendering their shadow maps are
+/// selected within the [`super::budget::RenderBudget`], by brightness at the primary camera.
+#[derive(Debug)]
+pub struct PointLight {
+    /// Color of the light, in linear RGB.
+    pub color: math::Vec3,
+    pub intensity: f32,
+    /// Distance beyond which the light has no effect, in world units. The shadow map covers the
+    /// same distance.
+    pub range: f32,
+
+    shadow: Option<PointShadowMap>,
+    /// Whether the shadow map is rendered this frame, selected within the render budget.
+    pub(super) casts_shadow: bool,
+}
+
+assert_impl_all!(PointLight: ecs::storage::Component);
+
+impl PointLight {
+    /// Number of passes rendering the shadow map, one per face of the cube.
+    pub(super) const SHADOW_PASSES: usize = PointShadowMap::FACES.len();
+
+    /// Creates a new [`PointLight`] without shadows.
+    pub fn new(color: math::Vec3, intensity: f32, range: f32) -> Self {
+        Self {
+            color,
+            intensity,
+            range,
+            shadow: None,
+            casts_shadow: false,
+        }
+    }
+
+    /// Specifies the shadow map of the light.
+    pub fn with_shadow(mut self, ctx: &EngineContext, config: PointShadowConfig) -> Self {
+        self.set_shadow(ctx, Some(config));
+        self
+    }
+
+    /// Sets or removes the shadow map of the light.
+    pub fn set_shadow(&mut self, ctx: &EngineContext, config: Option<PointShadowConfig>) {
+        self.shadow = config.map(|config| PointShadowMap::new(&ctx.gpu, config));
+        if self.shadow.is_none() {
+            self.casts_shadow = false;
+        }
+    }
+
+    /// Returns the configuration of the shadow map, if any.
+    pub fn shadow_config(&self) -> Option<&PointShadowConfig> {
+        self.shadow.as_ref().map(|shadow| &shadow.config)
+    }
+
+    /// Returns true if the shadow map of the light is rendered this frame.
+    pub fn casts_shadow(&self) -> bool {
+        self.casts_shadow
+    }
+
+    /// Returns the shadow map, if rendered this frame.
+    pub(super) fn shadow_map(&self) -> Option<&PointShadowMap> {
+        self.shadow.as_ref().filter(|_| self.casts_shadow)
+    }
+}
+
+/// A face of a [`PointShadowMap`], with the camera looking through it.
+#[derive(Debug)]
+pub(super) struct PointShadowFace {
+    pub view: wgpu::TextureView,
+    camera_buffer: wgpu::Buffer,
+    /// The projection of the face, bound as a camera in the shadow pass.
+    pub camera: wgpu::BindGroup,
+    transform_buffer: wgpu::Buffer,
+    /// The view of the face, bound as a camera transform in the shadow pass.
+    pub transform: wgpu::BindGroup,
+}
+
+/// The cube depth texture of a [`PointLight`] with its faces, and the bind group sampling it.
+#[derive(Debug)]
+pub(super) struct PointShadowMap {
+    config: PointShadowConfig,
+
+    _texture: wgpu::Texture,
+    faces: Vec<PointShadowFace>,
+    uniform_buffer: wgpu::Buffer,
+    /// The bind group of [`super::uniform::UniformType::PointShadowMap`].
+    pub bind_group: wgpu::BindGroup,
+    _allocation: MemoryAllocation,
+}
+
+impl PointShadowMap {
+    const LABEL: &str = "ravia_engine::point_shadow_map";
+
+    /// Directions of the faces of the cube in the order of its layers, with the directions of
+    /// the texture coordinates across the face, as sampled by WebGPU.
+    const FACES: [(math::Vec3, math::Vec3, math::Vec3); 6] = [
+        (math::Vec3::X, math::Vec3::NEG_Z, math::Vec3::NEG_Y),
+        (math::Vec3::NEG_X, math::Vec3::Z, math::Vec3::NEG_Y),
+        (math::Vec3::Y, math::Vec3::X, math::Vec3::Z),
+        (math::Vec3::NEG_Y, math::Vec3::X, math::Vec3::NEG_Z),
+        (math::Vec3::Z, math::Vec3::X, math::Vec3::NEG_Y),
+        (math::Vec3::NEG_Z, math::Vec3::NEG_X, math::Vec3::NEG_Y),
+    ];
+
+    fn new(gpu: &Gpu, config: PointShadowConfig) -> Self {
+        let max_size = gpu.capabilities().max_texture_size();
+        let resolution = config.resolution.clamp(1, max_size);
+        let layouts = &gpu.default_bind_group_layouts;
+
+        let texture = gpu.device.create_texture(&wgpu::TextureDescriptor {
+            label: Some(Self::LABEL),
+            size: wgpu::Extent3d {
+                width: resolution,
+                height: resolution,
+                depth_or_array_layers: 6,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: DEPTH_FORMAT,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING,
+            view_formats: &[],
+        });
+        let cube_view = texture.create_view(&wgpu::TextureViewDescriptor {
+            dimension: Some(wgpu::TextureViewDimension::Cube),
+            ..Default::default()
+        });
+
+        let faces = (0..6)
+            .map(|layer| {
+                let view = texture.create_view(&wgpu::TextureViewDescriptor {
+                    dimension: Some(wgpu::TextureViewDimension::D2),
+                    base_array_layer: layer,
+                    array_layer_count: Some(1),
+                    ..Default::default()
+                });
+                let camera_buffer = gpu.device.create_buffer(&wgpu::BufferDescriptor {
+                    label: Some(Self::LABEL),
+                    size: std::mem::size_of::<math::Mat4>() as wgpu::BufferAddress,
+                    usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+                    mapped_at_creation: false,
+                });
+                let transform_buffer = gpu.device.create_buffer(&wgpu::BufferDescriptor {
+                    label: Some(Self::LABEL),
+                    size: 2 * std::mem::size_of::<math::Mat4>() as wgpu::BufferAddress,
+                    usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+                    mapped_at_creation: false,
+                });
+                PointShadowFace {
+                    view,
+                    camera: uniform_bind_group(&gpu.device, &layouts.camera, &camera_buffer),
+                    camera_buffer,
+                    transform: uniform_bind_group(
+                        &gpu.device,
+                        &layouts.transform,
+                        &transform_buffer,
+                    ),
+                    transform_buffer,
+                }
+            })
+            .collect();
+
+        let uniform_buffer = gpu.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some(Self::LABEL),
+            size: std::mem::size_of::<PointShadowUniform>() as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let bind_group = shadow_bind_group(
+            &gpu.device,
+            &layouts.point_shadow_map,
+            &cube_view,
+            &uniform_buffer,
+        );
+
+        let bytes = 6
+            * resolution as u64
+            * resolution as u64
+            * DEPTH_FORMAT.block_copy_size(None).unwrap_or(4) as u64;
+        let allocation = gpu.track_memory(MemoryCategory::Texture, bytes);
+
+        Self {
+            config: PointShadowConfig {
+                resolution,
+                ..config
+            },
+            _texture: texture,
+            faces,
+            uniform_buffer,
+            bind_group,
+            _allocation: allocation,
+        }
+    }
+
+    /// Returns the faces of the cube, in the order of its layers.
+    pub fn faces(&self) -> &[PointShadowFace] {
+        &self.faces
+    }
+
+    /// Returns the size of every face.
+    pub fn size(&self) -> math::UVec2 {
+        math::UVec2::splat(self.config.resolution)
+    }
+
+    /// Places the cameras of the faces at the position of the light, and uploads them.
+    pub fn update(&self, queue: &wgpu::Queue, position: math::Vec3, range: f32) {
+        let near = self.config.near;
+        let far = range.max(near * 2.0);
+
+        // the faces are rendered with right-handed cameras whatever the handedness of the world,
+        // looking along the axis of the face, so that the texels land where WebGPU samples them.
+        let projection = math::Mat4::perspective_rh(std::f32::consts::FRAC_PI_2, 1.0, near, far);
+        for (face, (axis, u, v)) in self.faces.iter().zip(Self::FACES) {
+            let rotation = math::Mat3::from_cols(u, -v, -axis).transpose();
+            let view = math::Mat4::from_mat3(rotation) * math::Mat4::from_translation(-position);
+
+            queue.write_buffer(&face.camera_buffer, 0, bytemuck::cast_slice(&[projection]));
+            queue.write_buffer(
+                &face.transform_buffer,
+                0,
+                bytemuck::cast_slice(&[view.inverse(), view]),
+            );
+        }
+
+        let uniform = PointShadowUniform {
+            position: position.to_array(),
+            near,
+            far,
+            bias: self.config.bias,
+            texel_size: 1.0 / self.config.resolution as f32,
+            enabled: 1,
+        };
+        queue.write_buffer(&self.uniform_buffer, 0, bytemuck::cast_slice(&[uniform]));
+    }
+}
+
+/// The bind group of [`super::uniform::UniformType::PointShadowMap`] for meshes out of the range
+/// of every point light rendering a shadow map, where everything is lit.
+#[derive(Debug)]
+pub(super) struct PointShadowFallback {
+    _texture: wgpu::Texture,
+    _buffer: wgpu::Buffer,
+    pub bind_group: wgpu::BindGroup,
+    _allocation: MemoryAllocation,
+}
+
+impl PointShadowFallback {
+    pub fn new(
+        device: &wgpu::Device,
+        layouts: &GpuDefaultBindGroupLayouts,
+        memory: &std::sync::Arc<MemoryTracker>,
+    ) -> Self {
+        const LABEL: &str = "ravia_engine::point_shadow_fallback";
+
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some(LABEL),
+            size: wgpu::Extent3d {
+                width: 1,
+                height: 1,
+                depth_or_array_layers: 6,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: DEPTH_FORMAT,
+            usage: wgpu::TextureUsages::TEXTURE_BINDING,
+            view_formats: &[],
+        });
+        let view = texture.create_view(&wgpu::TextureViewDescriptor {
+            dimension: Some(wgpu::TextureViewDimension::Cube),
+            ..Default::default()
+        });
+
+        let buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some(LABEL),
+            contents: bytemuck::cast_slice(&[PointShadowUniform {
+                position: [0.0; 3],
+                near: 0.0,
+                far: 0.0,
+                bias: 0.0,
+                texel_size: 1.0,
+                enabled: 0,
+            }]),
+            usage: wgpu::BufferUsages::UNIFORM,
+        });
+        let bind_group = shadow_bind_group(device, &layouts.point_shadow_map, &view, &buffer);
+
+        Self {
+            _allocation: memory.allocate(MemoryCategory::Uniform, buffer.size()),
+            _texture: texture,
+            _buffer: buffer,
+            bind_group,
+        }
+    }
+}
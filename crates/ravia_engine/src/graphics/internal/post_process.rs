@@ -0,0 +1,533 @@
+use std::sync::{Arc, Mutex};
+
+use crate::math;
+
+use super::{
+    frame_graph::{FrameAttachment, FrameGraph, FramePass, FramePassKind},
+    gpu::Gpu,
+    memory::{MemoryAllocation, MemoryCategory, MemoryTracker},
+    sampler::{SamplerKey, TextureAddressMode},
+    texture::TextureFilterMode,
+};
+
+/// Declarations prepended to the source of every [`PostProcessEffect`].
+const PRELUDE_SOURCE: &str = "
+struct PostProcessInput {
+  @builtin(position) position: vec4<f32>,
+  @location(0) uv: vec2<f32>,
+};
+
+@group(0) @binding(0) var source: texture_2d<f32>;
+@group(0) @binding(1) var source_sampler: sampler;
+
+@vertex
+fn vs_main(@builtin(vertex_index) index: u32) -> PostProcessInput {
+  // a single triangle covering the whole screen.
+  let uv = vec2<f32>(f32(index / 2u) * 2.0, f32(index % 2u) * 2.0);
+  var out: PostProcessInput;
+  out.position = vec4<f32>(uv.x * 2.0 - 1.0, 1.0 - uv.y * 2.0, 0.0, 1.0);
+  out.uv = uv;
+  return out;
+}
+";
+
+/// A [`PostProcessEffect`] is a full-screen pass of the [`PostProcessStack`], defined by a WGSL
+/// fragment shader.
+///
+/// The shader declares `fn fs_main(in: PostProcessInput) -> @location(0) vec4<f32>`, and reads
+/// the output of the previous pass from `source` with `source_sampler`, which are declared by the
+/// engine along with `PostProcessInput { position, uv }`:
+///
+/// ```wgsl
+/// @fragment
+/// fn fs_main(in: PostProcessInput) -> @location(0) vec4<f32> {
+///   let color = textureSample(source, source_sampler, in.uv);
+///   return vec4<f32>(vec3<f32>(dot(color.rgb, vec3<f32>(0.299, 0.587, 0.114))), color.a);
+/// }
+/// ```
+///
+/// An effect with [`PostProcessEffect::with_uniforms`] declares them at `@group(1) @binding(0)`.
+#[derive(Debug, Clone)]
+pub struct PostProcessEffect {
+    label: String,
+    source: String,
+    uniforms: Vec<u8>,
+}
+
+impl PostProcessEffect {
+    /// Creates a new [`PostProcessEffect`] from the WGSL source of its fragment shader.
+    pub fn new(label: &str, source: &str) -> Self {
+        Self {
+            label: label.to_string(),
+            source: source.to_string(),
+            uniforms: vec![],
+        }
+    }
+
+    /// Specifies the uniforms of the effect, bound at `@group(1) @binding(0)`.
+    pub fn with_uniforms<T: bytemuck::Pod>(mut self, uniforms: T) -> Self {
+        self.uniforms = uniform_bytes(uniforms);
+        self
+    }
+
+    /// Returns the debug label of the effect.
+    pub fn label(&self) -> &str {
+        &self.label
+    }
+}
+
+/// Returns the bytes of the uniforms, padded to the alignment of uniform buffers.
+fn uniform_bytes<T: bytemuck::Pod>(uniforms: T) -> Vec<u8> {
+    let mut bytes = bytemuck::bytes_of(&uniforms).to_vec();
+    bytes.resize(bytes.len().next_multiple_of(16), 0);
+    bytes
+}
+
+/// Identifies an effect pushed into the [`PostProcessStack`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct PostProcessId(u64);
+
+#[derive(Debug)]
+struct EffectUniforms {
+    buffer: wgpu::Buffer,
+    bind_group: wgpu::BindGroup,
+    _allocation: MemoryAllocation,
+}
+
+#[derive(Debug)]
+struct EffectState {
+    id: PostProcessId,
+    effect: PostProcessEffect,
+    enabled: bool,
+    /// Whether the uniforms have been modified since written into the buffer.
+    dirty: bool,
+    pipeline: Option<(wgpu::TextureFormat, wgpu::RenderPipeline)>,
+    uniforms: Option<EffectUniforms>,
+}
+
+/// A texture the passes of the stack render into and sample from, in turns.
+#[derive(Debug)]
+struct ChainTexture {
+    texture: wgpu::Texture,
+    view: wgpu::TextureView,
+    bind_group: wgpu::BindGroup,
+    _allocation: MemoryAllocation,
+}
+
+/// The textures a [`PostProcessStack`] renders a frame with, matching the size of its target.
+#[derive(Debug, Default)]
+pub(super) struct PostProcessTargets {
+    textures: Mutex<Option<[ChainTexture; 2]>>,
+}
+
+/// [`PostProcessStack`] is a chain of full-screen [`PostProcessEffect`]s applied to the scene,
+/// in the order they are pushed, before the frame is presented. See
+/// [`super::gpu::Gpu::post_process`].
+///
+/// While an effect is enabled, the scene cameras render into an intermediate texture, which every
+/// effect reads and writes into the next one. The [`super::overlay::Overlay`] cameras draw over
+/// the output of the stack, unaffected by the effects. The display adjustment is applied after
+/// the stack.
+#[derive(Debug)]
+pub struct PostProcessStack {
+    effects: Mutex<Vec<EffectState>>,
+    next_id: Mutex<u64>,
+
+    texture_layout: wgpu::BindGroupLayout,
+    uniform_layout: wgpu::BindGroupLayout,
+    memory: Arc<MemoryTracker>,
+}
+
+impl PostProcessStack {
+    const LABEL: &str = "ravia_engine::post_process";
+
+    pub(super) fn new(device: &wgpu::Device, memory: &Arc<MemoryTracker>) -> Self {
+        let texture_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some(Self::LABEL),
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        multisampled: false,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                    count: None,
+                },
+            ],
+        });
+        let uniform_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some(Self::LABEL),
+            entries: &[wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::FRAGMENT,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            }],
+        });
+
+        Self {
+            effects: Mutex::new(vec![]),
+            next_id: Mutex::new(0),
+            texture_layout,
+            uniform_layout,
+            memory: memory.clone(),
+        }
+    }
+
+    /// Appends an effect to the end of the stack, enabled.
+    pub fn push(&self, effect: PostProcessEffect) -> PostProcessId {
+        let mut next_id = self.next_id.lock().unwrap();
+        let id = PostProcessId(*next_id);
+        *next_id += 1;
+
+        self.effects.lock().unwrap().push(EffectState {
+            id,
+            effect,
+            enabled: true,
+            dirty: true,
+            pipeline: None,
+            uniforms: None,
+        });
+        id
+    }
+
+    /// Removes the effect from the stack, and returns it.
+    pub fn remove(&self, id: PostProcessId) -> Option<PostProcessEffect> {
+        let mut effects = self.effects.lock().unwrap();
+        let index = effects.iter().position(|state| state.id == id)?;
+        Some(effects.remove(index).effect)
+    }
+
+    /// Removes every effect from the stack.
+    pub fn clear(&self) {
+        self.effects.lock().unwrap().clear();
+    }
+
+    /// Moves the effects of the stack of a lost device into this stack, keeping their ids and
+    /// whether they are enabled. Their pipelines and uniforms are created again before the next
+    /// frame.
+    pub(super) fn restore_from(&self, lost: &PostProcessStack) {
+        let effects = std::mem::take(&mut *lost.effects.lock().unwrap());
+        *self.effects.lock().unwrap() = effects
+            .into_iter()
+            .map(|state| EffectState {
+                id: state.id,
+                effect: state.effect,
+                enabled: state.enabled,
+                dirty: true,
+                pipeline: None,
+                uniforms: None,
+            })
+            .collect();
+        *self.next_id.lock().unwrap() = *lost.next_id.lock().unwrap();
+    }
+
+    /// Returns the number of effects in the stack, including the disabled ones.
+    pub fn len(&self) -> usize {
+        self.effects.lock().unwrap().len()
+    }
+
+    /// Returns true if the stack has no effect.
+    pub fn is_empty(&self) -> bool {
+        self.effects.lock().unwrap().is_empty()
+    }
+
+    /// Returns whether the effect is applied, or `None` if it is not in the stack.
+    pub fn is_enabled(&self, id: PostProcessId) -> Option<bool> {
+        self.with_effect(id, |state| state.enabled)
+    }
+
+    /// Sets whether the effect is applied, keeping its place in the stack.
+    pub fn set_enabled(&self, id: PostProcessId, enabled: bool) {
+        self.with_effect(id, |state| state.enabled = enabled);
+    }
+
+    /// Sets the uniforms of the effect, written before the next frame.
+    ///
+    /// A WebGPU error is reported if the size differs from the uniforms the effect has been
+    /// created with.
+    pub fn set_uniforms<T: bytemuck::Pod>(&self, id: PostProcessId, uniforms: T) {
+        self.with_effect(id, |state| {
+            state.effect.uniforms = uniform_bytes(uniforms);
+            state.dirty = true;
+        });
+    }
+
+    fn with_effect<T>(
+        &self,
+        id: PostProcessId,
+        f: impl FnOnce(&mut EffectState) -> T,
+    ) -> Option<T> {
+        let mut effects = self.effects.lock().unwrap();
+        effects.iter_mut().find(|state| state.id == id).map(f)
+    }
+
+    /// Returns the view to render the scene into, or `None` if no effect is enabled.
+    pub(super) fn input_view(
+        &self,
+        gpu: &Gpu,
+        targets: &PostProcessTargets,
+        target: &wgpu::Texture,
+    ) -> Option<wgpu::TextureView> {
+        let mut textures = targets.textures.lock().unwrap();
+        if !self
+            .effects
+            .lock()
+            .unwrap()
+            .iter()
+            .any(|state| state.enabled)
+        {
+            // the textures are not used until an effect is enabled again.
+            textures.take();
+            return None;
+        }
+
+        let outdated = textures.as_ref().is_none_or(|[texture, _]| {
+            texture.texture.size() != target.size()
+                || texture.texture.format() != gpu.scene_format()
+        });
+        if outdated {
+            *textures = Some([
+                self.create_texture(gpu, target),
+                self.create_texture(gpu, target),
+            ]);
+        }
+        textures.as_ref().map(|[texture, _]| {
+            texture
+                .texture
+                .create_view(&wgpu::TextureViewDescriptor::default())
+        })
+    }
+
+    fn create_texture(&self, gpu: &Gpu, target: &wgpu::Texture) -> ChainTexture {
+        let texture = gpu.device.create_texture(&wgpu::TextureDescriptor {
+            label: Some(Self::LABEL),
+            size: target.size(),
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: gpu.scene_format(),
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING,
+            view_formats: &[],
+        });
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+
+        let sampler = gpu.sampler(SamplerKey {
+            filter_mode: TextureFilterMode::Bilinear,
+            address_mode: TextureAddressMode::ClampToEdge,
+            anisotropy: 1,
+        });
+        let bind_group = gpu.device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some(Self::LABEL),
+            layout: &self.texture_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(&view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::Sampler(&sampler),
+                },
+            ],
+        });
+
+        let bytes = target.size().width as u64
+            * target.size().height as u64
+            * texture.format().block_copy_size(None).unwrap_or(4) as u64;
+        ChainTexture {
+            _allocation: self.memory.allocate(MemoryCategory::Texture, bytes),
+            texture,
+            view,
+            bind_group,
+        }
+    }
+
+    /// Runs the enabled effects over the scene rendered into [`PostProcessStack::input_view`], the
+    /// last one writing into the output.
+    pub(super) fn draw(
+        &self,
+        gpu: &Gpu,
+        targets: &PostProcessTargets,
+        encoder: &mut wgpu::CommandEncoder,
+        output: &wgpu::TextureView,
+        output_attachment: FrameAttachment,
+        frame_graph: &mut FrameGraph,
+    ) {
+        let textures = targets.textures.lock().unwrap();
+        let Some(textures) = textures.as_ref() else {
+            return;
+        };
+
+        let format = textures[0].texture.format();
+        let size = textures[0].texture.size();
+        let mut effects = self.effects.lock().unwrap();
+        let mut enabled = effects
+            .iter_mut()
+            .filter(|state| state.enabled)
+            .collect::<Vec<_>>();
+        let num_passes = enabled.len();
+
+        for (i, state) in enabled.iter_mut().enumerate() {
+            let last = i + 1 == num_passes;
+            let (view, attachment) = match last {
+                true => (output, output_attachment),
+                false => (&textures[(i + 1) % 2].view, FrameAttachment::PostProcess),
+            };
+
+            self.prepare(gpu, state, format);
+            let (_, pipeline) = state.pipeline.as_ref().expect("pipeline is prepared");
+
+            let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some(&state.effect.label),
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(wgpu::Color::BLACK),
+                        store: wgpu::StoreOp::Store,
+                    },
+                })],
+                depth_stencil_attachment: None,
+                occlusion_query_set: None,
+                timestamp_writes: None,
+            });
+            render_pass.set_pipeline(pipeline);
+            render_pass.set_bind_group(0, &textures[i % 2].bind_group, &[]);
+            if let Some(uniforms) = &state.uniforms {
+                render_pass.set_bind_group(1, &uniforms.bind_group, &[]);
+            }
+            render_pass.draw(0..3, 0..1);
+            drop(render_pass);
+
+            frame_graph.push(FramePass {
+                kind: FramePassKind::PostProcess,
+                camera_order: None,
+                attachment,
+                size: math::uvec2(size.width, size.height),
+                format,
+                clear: true,
+                depth: false,
+                draw_calls: 1,
+                placeholder_draws: 0,
+                gizmos: false,
+            });
+        }
+    }
+
+    /// Creates the pipeline of the effect for the format, and writes its modified uniforms.
+    fn prepare(&self, gpu: &Gpu, state: &mut EffectState, format: wgpu::TextureFormat) {
+        let has_uniforms = !state.effect.uniforms.is_empty();
+        if state
+            .pipeline
+            .as_ref()
+            .is_none_or(|(pipeline_format, _)| *pipeline_format != format)
+        {
+            let pipeline = gpu.error_scope(&state.effect.label, |device| {
+                self.create_pipeline(gpu, device, &state.effect, format)
+            });
+            state.pipeline = Some((format, pipeline));
+        }
+
+        if has_uniforms && state.uniforms.is_none() {
+            let buffer = gpu.device.create_buffer(&wgpu::BufferDescriptor {
+                label: Some(&state.effect.label),
+                size: state.effect.uniforms.len() as wgpu::BufferAddress,
+                usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+                mapped_at_creation: false,
+            });
+            let bind_group = gpu.device.create_bind_group(&wgpu::BindGroupDescriptor {
+                label: Some(&state.effect.label),
+                layout: &self.uniform_layout,
+                entries: &[wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: buffer.as_entire_binding(),
+                }],
+            });
+            state.uniforms = Some(EffectUniforms {
+                _allocation: self.memory.allocate(MemoryCategory::Uniform, buffer.size()),
+                buffer,
+                bind_group,
+            });
+        }
+
+        if state.dirty {
+            state.dirty = false;
+            if let Some(uniforms) = &state.uniforms {
+                if uniforms.buffer.size() != state.effect.uniforms.len() as wgpu::BufferAddress {
+                    gpu.report_error(format!(
+                        "{}: uniforms of {} bytes set, but the effect has been created with {} bytes",
+                        state.effect.label,
+                        state.effect.uniforms.len(),
+                        uniforms.buffer.size(),
+                    ));
+                    return;
+                }
+                gpu.queue
+                    .write_buffer(&uniforms.buffer, 0, &state.effect.uniforms);
+            }
+        }
+    }
+
+    fn create_pipeline(
+        &self,
+        gpu: &Gpu,
+        device: &wgpu::Device,
+        effect: &PostProcessEffect,
+        format: wgpu::TextureFormat,
+    ) -> wgpu::RenderPipeline {
+        let source = format!("{}\n{}", PRELUDE_SOURCE, effect.source);
+        let shader_module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some(&effect.label),
+            source: wgpu::ShaderSource::Wgsl(source.into()),
+        });
+
+        let mut bind_group_layouts = vec![&self.texture_layout];
+        if !effect.uniforms.is_empty() {
+            bind_group_layouts.push(&self.uniform_layout);
+        }
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some(&effect.label),
+            bind_group_layouts: &bind_group_layouts,
+            push_constant_ranges: &[],
+        });
+
+        device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some(&effect.label),
+            layout: Some(&pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &shader_module,
+                entry_point: Some("vs_main"),
+                buffers: &[],
+                compilation_options: wgpu::PipelineCompilationOptions::default(),
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader_module,
+                entry_point: Some("fs_main"),
+                targets: &[Some(wgpu::ColorTargetState {
+                    format,
+                    blend: None,
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+                compilation_options: wgpu::PipelineCompilationOptions::default(),
+            }),
+            primitive: wgpu::PrimitiveState::default(),
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState::default(),
+            multiview: None,
+            cache: gpu.pipeline_cache(),
+        })
+    }
+}
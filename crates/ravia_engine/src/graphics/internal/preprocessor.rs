@@ -0,0 +1,134 @@
+/// Expands the conditional directives of a WGSL source with the given defines:
+///
+/// ```wgsl
+/// #ifdef HAS_NORMAL_MAP
+/// let normal = sample_normal(in.uv);
+/// #else
+/// let normal = in.normal;
+/// #endif
+/// ```
+///
+/// `#ifdef NAME` and `#ifndef NAME` keep the lines up to the matching `#else` or `#endif` if the
+/// name is defined, respectively not defined, and may be nested. Directives must be alone on
+/// their line, which may be indented.
+///
+/// Removed lines are left empty, so that the line numbers of compilation errors match the
+/// source. Returns the expanded source with a description of every malformed directive, which is
+/// ignored.
+pub fn preprocess(source: &str, defines: &[&str]) -> (String, Vec<String>) {
+    // for every open conditional: whether its current branch is kept, and whether the enclosing
+    // branch is.
+    let mut conditionals: Vec<(bool, bool)> = vec![];
+    let mut errors = vec![];
+    let mut output = String::with_capacity(source.len());
+
+    for (i, line) in source.lines().enumerate() {
+        let line_number = i + 1;
+        let active = conditionals.last().is_none_or(|(kept, _)| *kept);
+
+        let mut tokens = line.split_whitespace();
+        match (tokens.next(), tokens.next(), tokens.next()) {
+            (Some(directive @ ("#ifdef" | "#ifndef")), Some(name), None) => {
+                let defined = defines.contains(&name);
+                conditionals.push((active && defined == (directive == "#ifdef"), active));
+            }
+            (Some("#else"), None, None) => match conditionals.last_mut() {
+                Some((kept, parent)) => *kept = *parent && !*kept,
+                None => errors.push(format!("line {}: `#else` without `#ifdef`", line_number)),
+            },
+            (Some("#endif"), None, None) => {
+                if conditionals.pop().is_none() {
+                    errors.push(format!("line {}: `#endif` without `#ifdef`", line_number));
+                }
+            }
+            (Some(directive), _, _)
+                if matches!(directive, "#ifdef" | "#ifndef" | "#else" | "#endif") =>
+            {
+                errors.push(format!(
+                    "line {}: malformed `{}` directive",
+                    line_number, directive
+                ));
+            }
+            _ => {
+                if active {
+                    output.push_str(line);
+                }
+            }
+        }
+        output.push('\n');
+    }
+
+    if !conditionals.is_empty() {
+        errors.push(format!("{} `#ifdef` without `#endif`", conditionals.len()));
+    }
+
+    (output, errors)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SOURCE: &str = "a
+#ifdef FOO
+  foo
+  #ifndef BAR
+  foo_not_bar
+  #else
+  foo_bar
+  #endif
+#else
+not_foo
+#endif
+b";
+
+    fn kept_lines(output: &str) -> Vec<&str> {
+        output
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .collect()
+    }
+
+    #[test]
+    fn keeps_branches_of_defines() {
+        for (defines, kept) in [
+            (&[][..], &["a", "not_foo", "b"][..]),
+            (&["FOO"], &["a", "foo", "foo_not_bar", "b"]),
+            (&["FOO", "BAR"], &["a", "foo", "foo_bar", "b"]),
+            // a nested branch is not kept within a removed branch.
+            (&["BAR"], &["a", "not_foo", "b"]),
+        ] {
+            let (output, errors) = preprocess(SOURCE, defines);
+            assert_eq!(kept_lines(&output), kept, "defines {:?}", defines);
+            assert!(errors.is_empty(), "{:?}", errors);
+        }
+    }
+
+    #[test]
+    fn keeps_line_numbers() {
+        let (output, _) = preprocess(SOURCE, &["FOO"]);
+        let lines = output.lines().collect::<Vec<_>>();
+        assert_eq!(lines.len(), SOURCE.lines().count());
+        assert_eq!(lines[2], "  foo");
+        assert_eq!(lines[4], "  foo_not_bar");
+        assert_eq!(lines[1], "");
+    }
+
+    #[test]
+    fn reports_malformed_directives() {
+        let source = "#else\n#endif\n#ifdef\n#ifdef A B\n#ifdef A\nkept\n";
+        let (output, errors) = preprocess(source, &["A"]);
+        assert_eq!(
+            errors,
+            [
+                "line 1: `#else` without `#ifdef`",
+                "line 2: `#endif` without `#ifdef`",
+                "line 3: malformed `#ifdef` directive",
+                "line 4: malformed `#ifdef` directive",
+                "1 `#ifdef` without `#endif`",
+            ]
+        );
+        assert_eq!(kept_lines(&output), ["kept"]);
+    }
+}
@@ -0,0 +1,415 @@
+use std::sync::{Arc, Mutex, OnceLock};
+
+use wgpu::util::DeviceExt;
+
+use super::{
+    binary_texture::parse_binary_texture,
+    gpu::Gpu,
+    memory::{MemoryAllocation, MemoryCategory, MemoryTracker},
+};
+
+/// A tileable blue-noise texture, offsetting every pixel by less than a step of the target
+/// format so that gradients dither instead of banding.
+const BLUE_NOISE: &[u8] = include_bytes!("../../../../ravia_res/texture/blue_noise.rtex");
+
+/// Format the scene is rendered into while dithering, with the precision lost when the frame is
+/// quantized to the target.
+pub(super) const DITHERING_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba16Float;
+
+/// [`DisplayAdjustment`] holds the user display settings applied to the whole frame when it is
+/// presented, e.g. from an options menu, independently from the color grading of the scene.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DisplayAdjustment {
+    /// Gamma of the display. Values above 1 brighten the midtones, values below 1 darken them.
+    pub gamma: f32,
+    /// Multiplier of the frame colors.
+    pub brightness: f32,
+}
+
+impl Default for DisplayAdjustment {
+    fn default() -> Self {
+        Self {
+            gamma: 1.0,
+            brightness: 1.0,
+        }
+    }
+}
+
+impl DisplayAdjustment {
+    /// Returns true if the adjustment leaves the frame unchanged.
+    pub fn is_identity(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+const PRESENT_SOURCE: &str = "
+struct Adjustment {
+  gamma: f32,
+  brightness: f32,
+  // amplitude of the noise in steps of the target format, 0 without dithering.
+  dither: f32,
+  // whether the target encodes the colors to sRGB, where the steps are spaced.
+  srgb: u32,
+};
+
+@group(0) @binding(0) var frame: texture_2d<f32>;
+@group(0) @binding(1) var<uniform> adjustment: Adjustment;
+@group(0) @binding(2) var blue_noise: texture_2d<f32>;
+
+fn linear_to_srgb(rgb: vec3<f32>) -> vec3<f32> {
+  return select(1.055 * pow(rgb, vec3<f32>(1.0 / 2.4)) - 0.055, rgb * 12.92, rgb <= vec3<f32>(0.0031308));
+}
+
+fn srgb_to_linear(rgb: vec3<f32>) -> vec3<f32> {
+  return select(pow((rgb + 0.055) / 1.055, vec3<f32>(2.4)), rgb / 12.92, rgb <= vec3<f32>(0.04045));
+}
+
+fn dither(rgb: vec3<f32>, position: vec2<u32>) -> vec3<f32> {
+  let size = textureDimensions(blue_noise);
+  let noise = textureLoad(blue_noise, position % size, 0).rgb - 0.5;
+  let offset = noise * adjustment.dither / 255.0;
+  if adjustment.srgb == 0u {
+    return rgb + offset;
+  }
+  return srgb_to_linear(max(linear_to_srgb(clamp(rgb, vec3<f32>(0.0), vec3<f32>(1.0))) + offset, vec3<f32>(0.0)));
+}
+
+@vertex
+fn vs_main(@builtin(vertex_index) index: u32) -> @builtin(position) vec4<f32> {
+  // a single triangle covering the whole screen.
+  let ndc = vec2<f32>(f32(index / 2u) * 4.0 - 1.0, f32(index % 2u) * 4.0 - 1.0);
+  return vec4<f32>(ndc, 0.0, 1.0);
+}
+
+@fragment
+fn fs_main(@builtin(position) position: vec4<f32>) -> @location(0) vec4<f32> {
+  let color = textureLoad(frame, vec2<i32>(position.xy), 0);
+  var rgb = pow(max(color.rgb * adjustment.brightness, vec3<f32>(0.0)), vec3<f32>(1.0 / adjustment.gamma));
+  if adjustment.dither > 0.0 {
+    rgb = dither(rgb, vec2<u32>(position.xy));
+  }
+  return vec4<f32>(rgb, color.a);
+}
+";
+
+/// The `Adjustment` of the present shader.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+struct AdjustmentUniform {
+    gamma: f32,
+    brightness: f32,
+    dither: f32,
+    srgb: u32,
+}
+
+/// The blue-noise texture sampled when dithering.
+#[derive(Debug)]
+struct BlueNoise {
+    view: wgpu::TextureView,
+    _allocation: MemoryAllocation,
+}
+
+/// The texture a frame is rendered into before being adjusted into the frame target.
+#[derive(Debug)]
+struct FrameTexture {
+    texture: wgpu::Texture,
+    bind_group: wgpu::BindGroup,
+    _allocation: MemoryAllocation,
+}
+
+/// The texture a [`PresentPass`] renders a frame into, matching the size of its target.
+#[derive(Debug, Default)]
+pub(super) struct PresentTargets {
+    frame: Mutex<Option<FrameTexture>>,
+}
+
+/// Applies the [`DisplayAdjustment`] and dithering while copying the rendered frame into the
+/// frame target.
+///
+/// Frames are rendered straight into the frame target while the adjustment is the identity and
+/// dithering is disabled.
+#[derive(Debug)]
+pub(super) struct PresentPass {
+    adjustment: Mutex<DisplayAdjustment>,
+    dithering: bool,
+
+    buffer: wgpu::Buffer,
+    bind_group_layout: wgpu::BindGroupLayout,
+    _allocation: MemoryAllocation,
+    memory: Arc<MemoryTracker>,
+
+    blue_noise: OnceLock<BlueNoise>,
+    pipeline: OnceLock<wgpu::RenderPipeline>,
+}
+
+impl PresentPass {
+    const LABEL: &str = "ravia_engine::present";
+
+    pub fn new(
+        device: &wgpu::Device,
+        adjustment: DisplayAdjustment,
+        dithering: bool,
+        memory: &Arc<MemoryTracker>,
+    ) -> Self {
+        let buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some(Self::LABEL),
+            size: std::mem::size_of::<AdjustmentUniform>() as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some(Self::LABEL),
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        sample_type: wgpu::TextureSampleType::Float { filterable: false },
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        multisampled: false,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 2,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        sample_type: wgpu::TextureSampleType::Float { filterable: false },
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        multisampled: false,
+                    },
+                    count: None,
+                },
+            ],
+        });
+
+        Self {
+            adjustment: Mutex::new(adjustment),
+            dithering,
+            _allocation: memory.allocate(MemoryCategory::Uniform, buffer.size()),
+            memory: memory.clone(),
+            buffer,
+            bind_group_layout,
+            blue_noise: OnceLock::new(),
+            pipeline: OnceLock::new(),
+        }
+    }
+
+    pub fn adjustment(&self) -> DisplayAdjustment {
+        *self.adjustment.lock().unwrap()
+    }
+
+    pub fn set_adjustment(&self, adjustment: DisplayAdjustment) {
+        *self.adjustment.lock().unwrap() = adjustment;
+    }
+
+    /// Returns the view to render the frame into, or `None` to render into the frame target.
+    ///
+    /// While dithering, the frame is always rendered into the view, whose format has more
+    /// precision than the target. Otherwise, only the frames presented with an adjustment are.
+    pub fn frame_view(
+        &self,
+        gpu: &Gpu,
+        targets: &PresentTargets,
+        target: &wgpu::Texture,
+        presenting: bool,
+    ) -> Option<wgpu::TextureView> {
+        let mut frame = targets.frame.lock().unwrap();
+        if !self.dithering && (!presenting || self.adjustment().is_identity()) {
+            // the frame texture is not used until the adjustment changes again.
+            frame.take();
+            return None;
+        }
+
+        let outdated = frame.as_ref().is_none_or(|frame| {
+            frame.texture.size() != target.size() || frame.texture.format() != gpu.scene_format()
+        });
+        if outdated {
+            *frame = Some(self.create_frame_texture(gpu, target));
+        }
+        frame.as_ref().map(|frame| {
+            frame
+                .texture
+                .create_view(&wgpu::TextureViewDescriptor::default())
+        })
+    }
+
+    fn create_frame_texture(&self, gpu: &Gpu, target: &wgpu::Texture) -> FrameTexture {
+        let texture = gpu.device.create_texture(&wgpu::TextureDescriptor {
+            label: Some(Self::LABEL),
+            size: target.size(),
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: gpu.scene_format(),
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING,
+            view_formats: &[],
+        });
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+
+        let bind_group = gpu.device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some(Self::LABEL),
+            layout: &self.bind_group_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(&view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: self.buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: wgpu::BindingResource::TextureView(&self.blue_noise(gpu).view),
+                },
+            ],
+        });
+
+        let bytes = target.size().width as u64
+            * target.size().height as u64
+            * gpu.scene_format().block_copy_size(None).unwrap_or(4) as u64;
+        FrameTexture {
+            _allocation: self.memory.allocate(MemoryCategory::Texture, bytes),
+            texture,
+            bind_group,
+        }
+    }
+
+    /// Copies the frame rendered into [`PresentPass::frame_view`] into the frame target, applying
+    /// the adjustment if presenting, and dithering.
+    pub fn draw(
+        &self,
+        gpu: &Gpu,
+        targets: &PresentTargets,
+        encoder: &mut wgpu::CommandEncoder,
+        target: &wgpu::TextureView,
+        target_format: wgpu::TextureFormat,
+        presenting: bool,
+    ) {
+        let frame = targets.frame.lock().unwrap();
+        let Some(frame) = frame.as_ref() else {
+            return;
+        };
+
+        let adjustment = match presenting {
+            true => self.adjustment(),
+            false => DisplayAdjustment::default(),
+        };
+        let uniform = AdjustmentUniform {
+            gamma: adjustment.gamma.max(0.01),
+            brightness: adjustment.brightness,
+            dither: if self.dithering { 1.0 } else { 0.0 },
+            srgb: target_format.is_srgb() as u32,
+        };
+        gpu.queue
+            .write_buffer(&self.buffer, 0, bytemuck::cast_slice(&[uniform]));
+
+        let pipeline = self.pipeline(gpu, target_format);
+        let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some(Self::LABEL),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view: target,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(wgpu::Color::BLACK),
+                    store: wgpu::StoreOp::Store,
+                },
+            })],
+            depth_stencil_attachment: None,
+            occlusion_query_set: None,
+            timestamp_writes: None,
+        });
+        render_pass.set_pipeline(pipeline);
+        render_pass.set_bind_group(0, &frame.bind_group, &[]);
+        render_pass.draw(0..3, 0..1);
+    }
+
+    fn blue_noise(&self, gpu: &Gpu) -> &BlueNoise {
+        self.blue_noise.get_or_init(|| {
+            let (size, _, data) =
+                parse_binary_texture(BLUE_NOISE).expect("the blue noise texture is valid");
+            let texture = gpu.device.create_texture_with_data(
+                &gpu.queue,
+                &wgpu::TextureDescriptor {
+                    label: Some(Self::LABEL),
+                    size: wgpu::Extent3d {
+                        width: size.x,
+                        height: size.y,
+                        depth_or_array_layers: 1,
+                    },
+                    mip_level_count: 1,
+                    sample_count: 1,
+                    dimension: wgpu::TextureDimension::D2,
+                    format: wgpu::TextureFormat::Rgba8Unorm,
+                    usage: wgpu::TextureUsages::TEXTURE_BINDING,
+                    view_formats: &[],
+                },
+                wgpu::util::TextureDataOrder::LayerMajor,
+                &data[..(size.x * size.y * 4) as usize],
+            );
+            BlueNoise {
+                view: texture.create_view(&wgpu::TextureViewDescriptor::default()),
+                _allocation: self
+                    .memory
+                    .allocate(MemoryCategory::Texture, data.len() as u64),
+            }
+        })
+    }
+
+    fn pipeline(&self, gpu: &Gpu, format: wgpu::TextureFormat) -> &wgpu::RenderPipeline {
+        self.pipeline.get_or_init(|| {
+            let shader_module = gpu
+                .device
+                .create_shader_module(wgpu::ShaderModuleDescriptor {
+                    label: Some(Self::LABEL),
+                    source: wgpu::ShaderSource::Wgsl(PRESENT_SOURCE.into()),
+                });
+            let pipeline_layout =
+                gpu.device
+                    .create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                        label: Some(Self::LABEL),
+                        bind_group_layouts: &[&self.bind_group_layout],
+                        push_constant_ranges: &[],
+                    });
+
+            gpu.device
+                .create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+                    label: Some(Self::LABEL),
+                    layout: Some(&pipeline_layout),
+                    vertex: wgpu::VertexState {
+                        module: &shader_module,
+                        entry_point: Some("vs_main"),
+                        buffers: &[],
+                        compilation_options: wgpu::PipelineCompilationOptions::default(),
+                    },
+                    fragment: Some(wgpu::FragmentState {
+                        module: &shader_module,
+                        entry_point: Some("fs_main"),
+                        targets: &[Some(wgpu::ColorTargetState {
+                            format,
+                            blend: None,
+                            write_mask: wgpu::ColorWrites::ALL,
+                        })],
+                        compilation_options: wgpu::PipelineCompilationOptions::default(),
+                    }),
+                    primitive: wgpu::PrimitiveState::default(),
+                    depth_stencil: None,
+                    multisample: wgpu::MultisampleState::default(),
+                    multiview: None,
+                    cache: gpu.pipeline_cache(),
+                })
+        })
+    }
+}
@@ -0,0 +1,371 @@
+use std::fmt::Write;
+
+use crate::{engine::EngineContext, math};
+
+use super::texture::{Texture, TextureFilterMode};
+
+/// How [`ProceduralTexture::Blend`] combines its layer with its base.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum ProceduralBlendMode {
+    /// Replaces the base with the layer.
+    #[default]
+    Normal,
+    Multiply,
+    Add,
+    Screen,
+}
+
+/// A [`ProceduralTexture`] describes the texels of a texture as a tree of nodes, e.g. noise
+/// blended over a gradient, to prototype materials without image assets.
+///
+/// The tree is evaluated into a [`Texture`] by a compute shader, see
+/// [`ProceduralTexture::generate`]. The colors are RGBA in `[0, 1]`, stored as sRGB like the
+/// texels of an image.
+///
+/// ```ignore
+/// let marble = ProceduralTexture::Blend {
+///     base: Box::new(ProceduralTexture::Color(math::vec4(0.9, 0.9, 0.85, 1.0))),
+///     layer: Box::new(ProceduralTexture::Noise {
+///         low: math::vec4(0.2, 0.2, 0.25, 1.0),
+///         high: math::vec4(0.9, 0.9, 0.85, 1.0),
+///         frequency: 8,
+///         octaves: 5,
+///         seed: 7,
+///     }),
+///     mode: ProceduralBlendMode::Multiply,
+///     opacity: 0.6,
+/// };
+/// let texture = marble.generate(ctx, math::uvec2(512, 512), TextureFilterMode::Bilinear);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub enum ProceduralTexture {
+    /// A single color.
+    Color(math::Vec4),
+    /// A gradient from `from` to `to` across the texture, along the direction in uv space.
+    LinearGradient {
+        from: math::Vec4,
+        to: math::Vec4,
+        direction: math::Vec2,
+    },
+    /// A gradient from `inner` at the center to `outer` at the radius, in uv space.
+    RadialGradient {
+        inner: math::Vec4,
+        outer: math::Vec4,
+        center: math::Vec2,
+        radius: f32,
+    },
+    /// A checkerboard of the given number of cells across the texture.
+    Checker {
+        a: math::Vec4,
+        b: math::Vec4,
+        cells: math::UVec2,
+    },
+    /// Fractal value noise between `low` and `high`, with `frequency` cells across the texture
+    /// for the first octave, doubling every octave. The noise tiles with the texture.
+    Noise {
+        low: math::Vec4,
+        high: math::Vec4,
+        frequency: u32,
+        octaves: u32,
+        seed: u32,
+    },
+    /// The layer over the base, combined with the blend mode and mixed by the opacity.
+    Blend {
+        base: Box<ProceduralTexture>,
+        layer: Box<ProceduralTexture>,
+        mode: ProceduralBlendMode,
+        opacity: f32,
+    },
+    /// `a` where the luminance of the mask is 0, `b` where it is 1, and mixed in between.
+    Mask {
+        a: Box<ProceduralTexture>,
+        b: Box<ProceduralTexture>,
+        mask: Box<ProceduralTexture>,
+    },
+}
+
+/// Helpers of the generated shader.
+const PRELUDE_SOURCE: &str = "
+@group(0) @binding(0) var output: texture_storage_2d<rgba8unorm, write>;
+
+fn hash(cell: vec2<u32>, seed: u32) -> f32 {
+  var h = cell.x * 0x8da6b343u ^ cell.y * 0xd8163841u ^ seed * 0xcb1ab31fu;
+  h = (h ^ (h >> 16u)) * 0x7feb352du;
+  h = (h ^ (h >> 15u)) * 0x846ca68bu;
+  h = h ^ (h >> 16u);
+  return f32(h) / 4294967295.0;
+}
+
+// Value noise of the cells of the lattice, wrapping around every `period` cells.
+fn value_noise(p: vec2<f32>, period: u32, seed: u32) -> f32 {
+  let cell = vec2<u32>(floor(p));
+  let f = fract(p);
+  let t = f * f * (3.0 - 2.0 * f);
+  let c0 = cell % period;
+  let c1 = (cell + 1u) % period;
+  let a = hash(c0, seed);
+  let b = hash(vec2<u32>(c1.x, c0.y), seed);
+  let c = hash(vec2<u32>(c0.x, c1.y), seed);
+  let d = hash(c1, seed);
+  return mix(mix(a, b, t.x), mix(c, d, t.x), t.y);
+}
+
+fn fractal_noise(uv: vec2<f32>, frequency: u32, octaves: u32, seed: u32) -> f32 {
+  var value = 0.0;
+  var amplitude = 0.5;
+  var total = 0.0;
+  var period = frequency;
+  for (var i = 0u; i < octaves; i++) {
+    value += amplitude * value_noise(uv * f32(period), period, seed + i);
+    total += amplitude;
+    amplitude *= 0.5;
+    period *= 2u;
+  }
+  return value / max(total, 1e-6);
+}
+
+fn luminance(color: vec4<f32>) -> f32 {
+  return dot(color.rgb, vec3<f32>(0.2126, 0.7152, 0.0722));
+}
+";
+
+const MAIN_SOURCE: &str = "
+@compute @workgroup_size(8, 8)
+fn cs_main(@builtin(global_invocation_id) id: vec3<u32>) {
+  let size = textureDimensions(output);
+  if id.x >= size.x || id.y >= size.y {
+    return;
+  }
+  let uv = (vec2<f32>(id.xy) + 0.5) / vec2<f32>(size);
+  textureStore(output, id.xy, clamp(node_0(uv), vec4<f32>(0.0), vec4<f32>(1.0)));
+}
+";
+
+impl ProceduralTexture {
+    /// Evaluates the texture into a new [`Texture`] of the given size.
+    ///
+    /// Adapters without compute shaders report an error, and return the default checkerboard
+    /// texture instead.
+    pub fn generate(
+        &self,
+        ctx: &EngineContext,
+        size: math::UVec2,
+        filter_mode: TextureFilterMode,
+    ) -> Texture {
+        const LABEL: &str = "ravia_engine::procedural_texture";
+
+        if !ctx.gpu.capabilities().compute() {
+            ctx.gpu.report_error(format!(
+                "{}: compute shaders are not supported by the adapter",
+                LABEL
+            ));
+            return Texture::default_2d(ctx);
+        }
+
+        let max_size = ctx.gpu.capabilities().max_texture_size();
+        let size = size.clamp(math::UVec2::ONE, math::UVec2::splat(max_size));
+        let extent = wgpu::Extent3d {
+            width: size.x,
+            height: size.y,
+            depth_or_array_layers: 1,
+        };
+
+        // storage textures cannot be sRGB, so the texels are written into a linear texture, and
+        // copied into the sRGB one as they are.
+        let (storage, texture) = ctx.gpu.error_scope(LABEL, |device| {
+            let storage = device.create_texture(&wgpu::TextureDescriptor {
+                label: Some(LABEL),
+                size: extent,
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: wgpu::TextureDimension::D2,
+                format: wgpu::TextureFormat::Rgba8Unorm,
+                usage: wgpu::TextureUsages::STORAGE_BINDING | wgpu::TextureUsages::COPY_SRC,
+                view_formats: &[],
+            });
+            let texture = device.create_texture(&wgpu::TextureDescriptor {
+                label: Some(LABEL),
+                size: extent,
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: wgpu::TextureDimension::D2,
+                format: wgpu::TextureFormat::Rgba8UnormSrgb,
+                usage: wgpu::TextureUsages::TEXTURE_BINDING
+                    | wgpu::TextureUsages::COPY_DST
+                    | wgpu::TextureUsages::COPY_SRC,
+                view_formats: &[],
+            });
+            (storage, texture)
+        });
+
+        ctx.gpu.error_scope(LABEL, |device| {
+            let module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+                label: Some(LABEL),
+                source: wgpu::ShaderSource::Wgsl(self.source().into()),
+            });
+            let pipeline = device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+                label: Some(LABEL),
+                layout: None,
+                module: &module,
+                entry_point: Some("cs_main"),
+                compilation_options: wgpu::PipelineCompilationOptions::default(),
+                cache: ctx.gpu.pipeline_cache(),
+            });
+            let view = storage.create_view(&wgpu::TextureViewDescriptor::default());
+            let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+                label: Some(LABEL),
+                layout: &pipeline.get_bind_group_layout(0),
+                entries: &[wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(&view),
+                }],
+            });
+
+            let mut encoder = device
+                .create_command_encoder(&wgpu::CommandEncoderDescriptor { label: Some(LABEL) });
+            {
+                let mut pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+                    label: Some(LABEL),
+                    timestamp_writes: None,
+                });
+                pass.set_pipeline(&pipeline);
+                pass.set_bind_group(0, &bind_group, &[]);
+                pass.dispatch_workgroups(size.x.div_ceil(8), size.y.div_ceil(8), 1);
+            }
+            encoder.copy_texture_to_texture(
+                storage.as_image_copy(),
+                texture.as_image_copy(),
+                extent,
+            );
+            ctx.gpu.queue.submit(std::iter::once(encoder.finish()));
+        });
+
+        Texture::from_owned(ctx, texture, filter_mode)
+    }
+
+    /// Returns the WGSL source of the compute shader evaluating the texture, with a function
+    /// `node_<i>(uv) -> vec4<f32>` per node, the root being `node_0`.
+    fn source(&self) -> String {
+        let mut nodes = vec![];
+        self.push_node(&mut nodes);
+
+        let mut source = PRELUDE_SOURCE.to_string();
+        for (i, body) in nodes.iter().enumerate() {
+            let _ = write!(
+                source,
+                "\nfn node_{}(uv: vec2<f32>) -> vec4<f32> {{\n{}}}\n",
+                i, body
+            );
+        }
+        source.push_str(MAIN_SOURCE);
+        source
+    }
+
+    /// Pushes the body of the function of the node and of its children, returning the index of
+    /// the node.
+    fn push_node(&self, nodes: &mut Vec<String>) -> usize {
+        let index = nodes.len();
+        nodes.push(String::new());
+
+        let body = match self {
+            Self::Color(color) => format!("  return {};\n", vec4(*color)),
+            Self::LinearGradient {
+                from,
+                to,
+                direction,
+            } => {
+                let direction = direction.try_normalize().unwrap_or(math::Vec2::X);
+                format!(
+                    "  let t = clamp(dot(uv - 0.5, {}) + 0.5, 0.0, 1.0);\n  return mix({}, {}, t);\n",
+                    vec2(direction),
+                    vec4(*from),
+                    vec4(*to)
+                )
+            }
+            Self::RadialGradient {
+                inner,
+                outer,
+                center,
+                radius,
+            } => format!(
+                "  let t = clamp(distance(uv, {}) / {}, 0.0, 1.0);\n  return mix({}, {}, t);\n",
+                vec2(*center),
+                float(radius.max(f32::EPSILON)),
+                vec4(*inner),
+                vec4(*outer)
+            ),
+            Self::Checker { a, b, cells } => format!(
+                "  let cell = vec2<u32>(uv * {});\n  return select({}, {}, (cell.x + cell.y) % 2u == 1u);\n",
+                vec2(cells.max(math::UVec2::ONE).as_vec2()),
+                vec4(*a),
+                vec4(*b)
+            ),
+            Self::Noise {
+                low,
+                high,
+                frequency,
+                octaves,
+                seed,
+            } => format!(
+                "  return mix({}, {}, fractal_noise(uv, {}u, {}u, {}u));\n",
+                vec4(*low),
+                vec4(*high),
+                frequency.max(&1),
+                octaves.max(&1),
+                seed
+            ),
+            Self::Blend {
+                base,
+                layer,
+                mode,
+                opacity,
+            } => {
+                let base = base.push_node(nodes);
+                let layer = layer.push_node(nodes);
+                let blended = match mode {
+                    ProceduralBlendMode::Normal => "l",
+                    ProceduralBlendMode::Multiply => "b * l",
+                    ProceduralBlendMode::Add => "b + l",
+                    ProceduralBlendMode::Screen => "1.0 - (1.0 - b) * (1.0 - l)",
+                };
+                format!(
+                    "  let b = node_{}(uv);\n  let l = node_{}(uv);\n  return mix(b, {}, {});\n",
+                    base,
+                    layer,
+                    blended,
+                    float(opacity.clamp(0.0, 1.0))
+                )
+            }
+            Self::Mask { a, b, mask } => {
+                let a = a.push_node(nodes);
+                let b = b.push_node(nodes);
+                let mask = mask.push_node(nodes);
+                format!(
+                    "  return mix(node_{}(uv), node_{}(uv), clamp(luminance(node_{}(uv)), 0.0, 1.0));\n",
+                    a, b, mask
+                )
+            }
+        };
+
+        nodes[index] = body;
+        index
+    }
+}
+
+fn float(value: f32) -> String {
+    format!("{:.6}", value)
+}
+
+fn vec2(value: math::Vec2) -> String {
+    format!("vec2<f32>({}, {})", float(value.x), float(value.y))
+}
+
+fn vec4(value: math::Vec4) -> String {
+    format!(
+        "vec4<f32>({}, {}, {}, {})",
+        float(value.x),
+        float(value.y),
+        float(value.z),
+        float(value.w)
+    )
+}
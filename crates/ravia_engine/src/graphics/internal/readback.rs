@@ -0,0 +1,201 @@
+use std::{
+    fmt,
+    future::Future,
+    pin::Pin,
+    sync::{Arc, Mutex},
+    task::{Context, Poll, Waker},
+};
+
+use super::gpu::Gpu;
+
+/// Possible errors while reading GPU data back to the CPU.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ReadbackError {
+    /// The texture format has no defined size per texel, e.g. a depth-stencil format.
+    UnsupportedFormat(wgpu::TextureFormat),
+    /// Failed to map the staging buffer, e.g. because the device has been lost.
+    Map(wgpu::BufferAsyncError),
+}
+
+impl fmt::Display for ReadbackError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReadbackError::UnsupportedFormat(format) => {
+                write!(f, "texture format {:?} cannot be read back", format)
+            }
+            ReadbackError::Map(e) => write!(f, "failed to map staging buffer: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for ReadbackError {}
+
+impl Gpu {
+    /// Reads the contents of a buffer back to the CPU.
+    ///
+    /// The buffer must have been created with [`wgpu::BufferUsages::COPY_SRC`]. The contents are
+    /// copied into a staging buffer, which is mapped once the GPU has finished the copy, see
+    /// [`Readback`].
+    pub fn read_buffer(&self, buffer: &wgpu::Buffer) -> Readback {
+        let size = buffer.size();
+        let staging = self.staging_buffer(size);
+
+        let mut encoder = self
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("ravia_engine::readback"),
+            });
+        encoder.copy_buffer_to_buffer(buffer, 0, &staging, 0, size);
+        self.queue.submit(std::iter::once(encoder.finish()));
+
+        Readback::new(staging, None)
+    }
+
+    /// Reads the contents of the first mip level of a 2D texture back to the CPU, as tightly packed
+    /// rows of texels, see [`Readback`].
+    ///
+    /// The texture must have been created with [`wgpu::TextureUsages::COPY_SRC`]. Formats without
+    /// a defined size per texel, e.g. depth-stencil formats, cannot be read back.
+    pub fn read_texture(&self, texture: &wgpu::Texture) -> Result<Readback, ReadbackError> {
+        let format = texture.format();
+        let texel_size = format
+            .block_copy_size(None)
+            .ok_or(ReadbackError::UnsupportedFormat(format))?;
+        let (width, height) = (texture.width(), texture.height());
+
+        // rows of a texture copy must be aligned, so the padding is stripped after mapping.
+        let unpadded_bytes_per_row = width * texel_size;
+        let bytes_per_row = unpadded_bytes_per_row.div_ceil(wgpu::COPY_BYTES_PER_ROW_ALIGNMENT)
+            * wgpu::COPY_BYTES_PER_ROW_ALIGNMENT;
+        let staging = self.staging_buffer(bytes_per_row as u64 * height as u64);
+
+        let mut encoder = self
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("ravia_engine::readback"),
+            });
+        encoder.copy_texture_to_buffer(
+            texture.as_image_copy(),
+            wgpu::ImageCopyBuffer {
+                buffer: &staging,
+                layout: wgpu::ImageDataLayout {
+                    offset: 0,
+                    bytes_per_row: Some(bytes_per_row),
+                    rows_per_image: Some(height),
+                },
+            },
+            wgpu::Extent3d {
+                width,
+                height,
+                depth_or_array_layers: 1,
+            },
+        );
+        self.queue.submit(std::iter::once(encoder.finish()));
+
+        Ok(Readback::new(
+            staging,
+            Some((bytes_per_row as usize, unpadded_bytes_per_row as usize)),
+        ))
+    }
+
+    fn staging_buffer(&self, size: u64) -> wgpu::Buffer {
+        self.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("ravia_engine::readback"),
+            size: size.next_multiple_of(wgpu::COPY_BUFFER_ALIGNMENT),
+            usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        })
+    }
+}
+
+#[derive(Debug, Default)]
+struct MapState {
+    result: Option<Result<(), wgpu::BufferAsyncError>>,
+    waker: Option<Waker>,
+}
+
+/// Data being read back from the GPU with [`Gpu::read_buffer`] or [`Gpu::read_texture`].
+///
+/// The data is available once the GPU has finished the copy and the staging buffer is mapped,
+/// without blocking: on native platforms, when the device is next polled, which every frame does;
+/// on the web, when the browser has mapped the buffer. Await the readback, or check it every
+/// frame with [`Readback::try_take`]. Outside of the frames, e.g. on a headless [`Gpu`], poll the
+/// device with [`wgpu::Maintain::Wait`] to wait for the copy.
+#[derive(Debug)]
+pub struct Readback {
+    staging: wgpu::Buffer,
+    /// The padded and unpadded sizes of the rows of a texture.
+    rows: Option<(usize, usize)>,
+    state: Arc<Mutex<MapState>>,
+}
+
+impl Readback {
+    fn new(staging: wgpu::Buffer, rows: Option<(usize, usize)>) -> Self {
+        let state = Arc::new(Mutex::new(MapState::default()));
+        {
+            let state = state.clone();
+            staging
+                .slice(..)
+                .map_async(wgpu::MapMode::Read, move |result| {
+                    let mut state = state.lock().unwrap();
+                    state.result = Some(result);
+                    if let Some(waker) = state.waker.take() {
+                        waker.wake();
+                    }
+                });
+        }
+
+        Self {
+            staging,
+            rows,
+            state,
+        }
+    }
+
+    /// Returns the data once mapped, or `None` while the copy is in progress or once the data
+    /// has been taken.
+    pub fn try_take(&mut self) -> Option<Result<Vec<u8>, ReadbackError>> {
+        let result = self.state.lock().unwrap().result.take()?;
+        Some(self.finish(result))
+    }
+
+    fn finish(&self, result: Result<(), wgpu::BufferAsyncError>) -> Result<Vec<u8>, ReadbackError> {
+        result.map_err(ReadbackError::Map)?;
+        Ok(self.read())
+    }
+
+    fn read(&self) -> Vec<u8> {
+        let data = {
+            let mapped = self.staging.slice(..).get_mapped_range();
+            match self.rows {
+                Some((bytes_per_row, unpadded_bytes_per_row)) => mapped
+                    .chunks(bytes_per_row)
+                    .flat_map(|row| &row[..unpadded_bytes_per_row])
+                    .copied()
+                    .collect(),
+                None => mapped.to_vec(),
+            }
+        };
+        self.staging.unmap();
+        data
+    }
+}
+
+impl Future for Readback {
+    type Output = Result<Vec<u8>, ReadbackError>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        // the waker is registered under the same lock, so that the callback cannot run between.
+        let mut state = self.state.lock().unwrap();
+        match state.result.take() {
+            Some(result) => {
+                drop(state);
+                Poll::Ready(self.finish(result))
+            }
+            None => {
+                state.waker = Some(cx.waker().clone());
+                Poll::Pending
+            }
+        }
+    }
+}
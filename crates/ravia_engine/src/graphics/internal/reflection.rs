@@ -0,0 +1,113 @@
+use super::uniform::UniformType;
+
+/// Kind of a resource bound to a shader.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum BindingKind {
+    UniformBuffer,
+    Texture,
+    Sampler,
+}
+
+impl std::fmt::Display for BindingKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            BindingKind::UniformBuffer => "a uniform buffer",
+            BindingKind::Texture => "a texture",
+            BindingKind::Sampler => "a sampler",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// Returns the bindings of the bind group the engine provides for the uniform type.
+fn expected_bindings(uniform_type: UniformType) -> &'static [BindingKind] {
+    match uniform_type {
+        UniformType::Texture2D | UniformType::NormalMap => {
+            &[BindingKind::Texture, BindingKind::Sampler]
+        }
+        UniformType::Lightmap | UniformType::ShadowMap | UniformType::PointShadowMap => &[
+            BindingKind::Texture,
+            BindingKind::Sampler,
+            BindingKind::UniformBuffer,
+        ],
+        UniformType::Camera
+        | UniformType::CameraTransform
+        | UniformType::ModelTransform
+        | UniformType::Joints
+        | UniformType::Frame
+        | UniformType::Lights => &[BindingKind::UniformBuffer],
+    }
+}
+
+/// Reflects the WGSL source, and validates the resource bindings it declares against the
+/// uniforms of the shader, where bind group `i` is `uniforms[i]`.
+///
+/// Returns a description of every mismatch. Sources which fail to parse are not validated, as
+/// shader compilation reports them anyway.
+pub fn validate_bindings(source: &str, uniforms: &[UniformType]) -> Vec<String> {
+    let Ok(module) = naga::front::wgsl::parse_str(source) else {
+        return vec![];
+    };
+
+    let mut mismatches = vec![];
+    for (_, global) in module.global_variables.iter() {
+        let Some(binding) = &global.binding else {
+            continue;
+        };
+
+        let name = global.name.as_deref().unwrap_or("<unnamed>");
+        let Some(uniform_type) = uniforms.get(binding.group as usize) else {
+            mismatches.push(format!(
+                "`{}` is bound to group {}, but only {} uniforms are declared",
+                name,
+                binding.group,
+                uniforms.len()
+            ));
+            continue;
+        };
+
+        let kind = match (&module.types[global.ty].inner, global.space) {
+            (naga::TypeInner::Image { .. }, _) => BindingKind::Texture,
+            (naga::TypeInner::Sampler { .. }, _) => BindingKind::Sampler,
+            (_, naga::AddressSpace::Uniform) => BindingKind::UniformBuffer,
+            _ => {
+                mismatches.push(format!(
+                    "`{}` at group {} binding {} is not a uniform, texture or sampler",
+                    name, binding.group, binding.binding
+                ));
+                continue;
+            }
+        };
+
+        match expected_bindings(*uniform_type).get(binding.binding as usize) {
+            Some(expected) if *expected == kind => {}
+            Some(expected) => mismatches.push(format!(
+                "`{}` at group {} binding {} is {}, but {:?} provides {}",
+                name, binding.group, binding.binding, kind, uniform_type, expected
+            )),
+            None => mismatches.push(format!(
+                "`{}` at group {} binding {} does not exist in {:?}",
+                name, binding.group, binding.binding, uniform_type
+            )),
+        }
+    }
+
+    mismatches
+}
+
+/// Parses and validates the WGSL source, returning the error which would fail the compilation
+/// of the shader, if any.
+pub fn compile_error(source: &str) -> Option<String> {
+    let module = match naga::front::wgsl::parse_str(source) {
+        Ok(module) => module,
+        Err(e) => return Some(e.emit_to_string(source)),
+    };
+
+    naga::valid::Validator::new(
+        naga::valid::ValidationFlags::all(),
+        naga::valid::Capabilities::all(),
+    )
+    .validate(&module)
+    .err()
+    .map(|e| e.emit_to_string(source))
+}
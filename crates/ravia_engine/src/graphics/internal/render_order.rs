@@ -0,0 +1,32 @@
+use crate::ecs;
+
+/// A [`RenderLayer`] component groups entities into a layer, drawn after the layers below it by
+/// every camera, e.g. to draw UI quads after the world.
+///
+/// Entities without the component are in [`RenderLayer::WORLD`]. Within a layer, the entities are
+/// drawn by their [`RenderOrder`].
+///
+/// Drawing later does not draw over the earlier entities if the depth test fails, so entities
+/// meant to be on top use shaders without a depth test, see
+/// [`super::shader::ShaderConfig::with_depth_test`].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct RenderLayer(pub i32);
+
+assert_impl_all!(RenderLayer: ecs::storage::Component);
+
+impl RenderLayer {
+    /// The layer of the entities without a [`RenderLayer`].
+    pub const WORLD: Self = Self(0);
+    /// A layer drawn after the world, for UI.
+    pub const UI: Self = Self(100);
+}
+
+/// A [`RenderOrder`] component orders the drawing of an entity within its [`RenderLayer`], lower
+/// orders first.
+///
+/// Entities without the component have the order 0, and entities of the same order are drawn in
+/// an unspecified order.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct RenderOrder(pub i32);
+
+assert_impl_all!(RenderOrder: ecs::storage::Component);
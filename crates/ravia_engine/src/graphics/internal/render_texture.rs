@@ -0,0 +1,122 @@
+use std::sync::Arc;
+
+use crate::{engine::EngineContext, math};
+
+use super::{
+    depth::DEPTH_FORMAT,
+    memory::{MemoryAllocation, MemoryCategory},
+    texture::{Texture, TextureFilterMode},
+};
+
+/// A [`RenderTexture`] is a texture a [`super::camera::Camera`] renders into instead of the
+/// window, e.g. for minimaps, portals or security monitors.
+///
+/// Point a camera at it with [`super::camera::Camera::with_target`], and sample it in a material
+/// with [`RenderTexture::to_texture`], bound as [`super::uniform::UniformType::Texture2D`]. The
+/// cameras rendering into textures are drawn before the cameras rendering into the window, so the
+/// window shows the texture drawn in the same frame.
+///
+/// Cloning a [`RenderTexture`] is cheap, and refers to the same texture.
+#[derive(Debug, Clone)]
+pub struct RenderTexture {
+    inner: Arc<RenderTextureInner>,
+}
+
+#[derive(Debug)]
+struct RenderTextureInner {
+    texture: Arc<wgpu::Texture>,
+    depth: wgpu::Texture,
+    _allocation: MemoryAllocation,
+}
+
+impl RenderTexture {
+    const LABEL: &str = "ravia_engine::render_texture";
+
+    /// Creates a new [`RenderTexture`] of the given size, with the format the scene is rendered
+    /// in, see [`Gpu::scene_format`](super::gpu::Gpu::scene_format).
+    pub fn new(ctx: &EngineContext, size: math::UVec2) -> Self {
+        let max_size = ctx.gpu.capabilities().max_texture_size();
+        let size = size.clamp(math::UVec2::ONE, math::UVec2::splat(max_size));
+        let format = ctx.gpu.scene_format();
+
+        let (texture, depth) = ctx.gpu.error_scope(Self::LABEL, |device| {
+            let extent = wgpu::Extent3d {
+                width: size.x,
+                height: size.y,
+                depth_or_array_layers: 1,
+            };
+            let texture = device.create_texture(&wgpu::TextureDescriptor {
+                label: Some(Self::LABEL),
+                size: extent,
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: wgpu::TextureDimension::D2,
+                format,
+                usage: wgpu::TextureUsages::RENDER_ATTACHMENT
+                    | wgpu::TextureUsages::TEXTURE_BINDING
+                    | wgpu::TextureUsages::COPY_SRC,
+                view_formats: &[],
+            });
+            let depth = device.create_texture(&wgpu::TextureDescriptor {
+                label: Some(Self::LABEL),
+                size: extent,
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: wgpu::TextureDimension::D2,
+                format: DEPTH_FORMAT,
+                usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+                view_formats: &[],
+            });
+            (texture, depth)
+        });
+
+        let pixels = size.x as u64 * size.y as u64;
+        let bytes = pixels
+            * (format.block_copy_size(None).unwrap_or(4)
+                + DEPTH_FORMAT.block_copy_size(None).unwrap_or(4)) as u64;
+        Self {
+            inner: Arc::new(RenderTextureInner {
+                texture: Arc::new(texture),
+                depth,
+                _allocation: ctx.gpu.track_memory(MemoryCategory::Texture, bytes),
+            }),
+        }
+    }
+
+    /// Returns the size of the texture.
+    pub fn size(&self) -> math::UVec2 {
+        math::uvec2(self.inner.texture.width(), self.inner.texture.height())
+    }
+
+    /// Returns the aspect ratio of the texture, to create the projection of its camera with.
+    pub fn aspect_ratio(&self) -> f32 {
+        let size = self.size().as_vec2();
+        size.x / size.y
+    }
+
+    /// Returns the underlying [`wgpu::Texture`], e.g. to read it back with
+    /// [`super::gpu::Gpu::read_texture`].
+    pub fn texture(&self) -> &wgpu::Texture {
+        &self.inner.texture
+    }
+
+    /// Creates a [`Texture`] sampling the render texture, e.g. to set as the texture of a
+    /// [`super::material::Material`].
+    pub fn to_texture(&self, ctx: &EngineContext, filter_mode: TextureFilterMode) -> Texture {
+        Texture::from_shared(ctx, self.inner.texture.clone(), filter_mode)
+    }
+
+    /// Returns true if both refer to the same texture.
+    pub fn ptr_eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.inner, &other.inner)
+    }
+
+    /// Returns the views of the color and depth textures to render into.
+    pub(super) fn views(&self) -> (wgpu::TextureView, wgpu::TextureView) {
+        let descriptor = wgpu::TextureViewDescriptor::default();
+        (
+            self.inner.texture.create_view(&descriptor),
+            self.inner.depth.create_view(&descriptor),
+        )
+    }
+}
@@ -0,0 +1,150 @@
+use crate::{
+    ecs::{self, Entity, IntoQuery},
+    engine::EngineContext,
+    resource::{Handle, Resource},
+};
+
+use super::{
+    camera::Camera,
+    directional_light::DirectionalLight,
+    instances::Instances,
+    lightmap::Lightmap,
+    material::{Material, MaterialInstance},
+    mesh::Mesh,
+    point_light::PointLight,
+    render_texture::RenderTexture,
+    skin::Skin,
+    skybox::Skybox,
+    texture_streaming::StreamedTexture,
+    transform::Transform,
+};
+
+/// Recreates the components of the world for the device recreated after a device loss, see
+/// [`crate::event::Event::DeviceRestored`].
+///
+/// Components are recreated in place from the state they keep on the CPU, and assets loaded
+/// from resources are loaded again by the systems loading them in the first place. The
+/// components which cannot be recreated are removed: the meshes created by the application, the
+/// [`Lightmap`]s, and the [`Skybox`]es whose cubemap has not been loaded with [`Skybox::load`].
+/// The meshes loaded from a [`Resource`] are requested again by the resource engine.
+pub(crate) fn restore_world(world: &mut ecs::World, ctx: &EngineContext) {
+    for transform in <&mut Transform>::query().iter_mut(world) {
+        *transform = Transform::new(
+            ctx,
+            *transform.position(),
+            *transform.rotation(),
+            *transform.scale(),
+        );
+    }
+
+    // cameras rendering into the same texture keep sharing it.
+    let mut targets = vec![];
+    for camera in <&mut Camera>::query().iter_mut(world) {
+        let target = camera.target().map(|target| {
+            restored(&mut targets, target, RenderTexture::ptr_eq, |target| {
+                RenderTexture::new(ctx, target.size())
+            })
+        });
+        *camera = camera.restored(ctx, target);
+    }
+
+    for light in <&mut DirectionalLight>::query().iter_mut(world) {
+        let config = light.shadow_config().copied();
+        light.set_shadow(ctx, config);
+    }
+    for light in <&mut PointLight>::query().iter_mut(world) {
+        let config = light.shadow_config().copied();
+        light.set_shadow(ctx, config);
+    }
+
+    for skin in <&mut Skin>::query().iter_mut(world) {
+        let mut restored = Skin::new(ctx, skin.joint_matrices().len());
+        restored.set_joint_matrices(ctx, skin.joint_matrices());
+        *skin = restored;
+    }
+    for instances in <&mut Instances>::query().iter_mut(world) {
+        instances.invalidate();
+    }
+    for texture in <&mut StreamedTexture>::query().iter_mut(world) {
+        texture.invalidate();
+    }
+
+    for material in <&mut Material>::query().iter_mut(world) {
+        *material = material.restored(ctx);
+    }
+
+    // the shared materials are recreated once, for the instances and the context alike. The
+    // textures overriding the shared materials are dropped.
+    let mut materials = vec![];
+    for instance in <&mut MaterialInstance>::query().iter_mut(world) {
+        instance.material = restored(
+            &mut materials,
+            &instance.material,
+            Handle::ptr_eq,
+            |material| Handle::new(material.restored(ctx)),
+        );
+        instance.texture = None;
+    }
+    ctx.materials.replace_all(|material| {
+        restored(&mut materials, material, Handle::ptr_eq, |material| {
+            Handle::new(material.restored(ctx))
+        })
+    });
+
+    let mut removed_meshes = vec![];
+    for (entity, mesh) in <(Entity, &mut Mesh)>::query()
+        .filter(!ecs::component::<Resource>())
+        .iter_mut(world)
+    {
+        if mesh.is_error() {
+            *mesh = Mesh::error_cube(ctx);
+        } else {
+            removed_meshes.push(*entity);
+        }
+    }
+    for entity in removed_meshes {
+        if let Some(mut entry) = world.entry(entity) {
+            entry.remove_component::<Mesh>();
+        }
+    }
+
+    let lightmaps = <Entity>::query()
+        .filter(ecs::component::<Lightmap>())
+        .iter(world)
+        .copied()
+        .collect::<Vec<_>>();
+    for entity in lightmaps {
+        if let Some(mut entry) = world.entry(entity) {
+            entry.remove_component::<Lightmap>();
+        }
+    }
+
+    let mut removed_skyboxes = vec![];
+    for (entity, skybox) in <(Entity, &mut Skybox)>::query().iter_mut(world) {
+        match skybox.restored(ctx) {
+            Some(restored) => *skybox = restored,
+            None => removed_skyboxes.push(*entity),
+        }
+    }
+    for entity in removed_skyboxes {
+        if let Some(mut entry) = world.entry(entity) {
+            entry.remove_component::<Skybox>();
+        }
+    }
+}
+
+/// Returns the asset recreated for the given one, creating it on first use, so that assets
+/// shared before the device loss stay shared.
+fn restored<T: Clone>(
+    restored: &mut Vec<(T, T)>,
+    asset: &T,
+    eq: impl Fn(&T, &T) -> bool,
+    create: impl FnOnce(&T) -> T,
+) -> T {
+    if let Some((_, new)) = restored.iter().find(|(old, _)| eq(old, asset)) {
+        return new.clone();
+    }
+    let new = create(asset);
+    restored.push((asset.clone(), new.clone()));
+    new
+}
@@ -0,0 +1,81 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+use super::texture::TextureFilterMode;
+
+/// Address mode of a texture, applied when sampling outside of the `[0, 1]` uv range.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TextureAddressMode {
+    /// Clamps the uv to the edge of the texture.
+    #[default]
+    ClampToEdge,
+    /// Repeats the texture.
+    Repeat,
+    /// Repeats the texture, mirrored at every repetition.
+    MirrorRepeat,
+}
+
+impl TextureAddressMode {
+    fn address_mode(&self) -> wgpu::AddressMode {
+        match self {
+            Self::ClampToEdge => wgpu::AddressMode::ClampToEdge,
+            Self::Repeat => wgpu::AddressMode::Repeat,
+            Self::MirrorRepeat => wgpu::AddressMode::MirrorRepeat,
+        }
+    }
+}
+
+/// State of a sampler, identifying a shared sampler in the [`SamplerCache`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub(crate) struct SamplerKey {
+    pub filter_mode: TextureFilterMode,
+    pub address_mode: TextureAddressMode,
+    pub anisotropy: u16,
+}
+
+impl SamplerKey {
+    /// Returns the key with its anisotropy clamped to what the filter mode supports.
+    ///
+    /// Anisotropic filtering requires linear filtering, and is supported up to 16 samples.
+    fn normalized(self) -> Self {
+        let anisotropy = match self.filter_mode {
+            TextureFilterMode::Trilinear => self.anisotropy.clamp(1, 16),
+            _ => 1,
+        };
+        Self { anisotropy, ..self }
+    }
+}
+
+/// Caches samplers by their state, so that textures sampled the same way share one sampler.
+#[derive(Debug, Default)]
+pub(crate) struct SamplerCache {
+    samplers: Mutex<HashMap<SamplerKey, Arc<wgpu::Sampler>>>,
+}
+
+impl SamplerCache {
+    /// Returns the sampler with the given state, creating it if it does not exist yet.
+    pub fn get(&self, device: &wgpu::Device, key: SamplerKey) -> Arc<wgpu::Sampler> {
+        let key = key.normalized();
+        self.samplers
+            .lock()
+            .unwrap()
+            .entry(key)
+            .or_insert_with(|| {
+                let address_mode = key.address_mode.address_mode();
+                Arc::new(device.create_sampler(&wgpu::SamplerDescriptor {
+                    label: Some("ravia_engine::sampler"),
+                    address_mode_u: address_mode,
+                    address_mode_v: address_mode,
+                    address_mode_w: address_mode,
+                    mag_filter: key.filter_mode.mag_filter(),
+                    min_filter: key.filter_mode.min_filter(),
+                    mipmap_filter: key.filter_mode.mipmap_filter(),
+                    anisotropy_clamp: key.anisotropy,
+                    ..Default::default()
+                }))
+            })
+            .clone()
+    }
+}
@@ -0,0 +1,62 @@
+use crate::{ecs, math};
+
+/// Where the origin of the pixel coordinates of a [`ScreenSpace`] camera is placed.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum ScreenOrigin {
+    /// The origin is at the top-left corner, and y grows downwards.
+    #[default]
+    TopLeft,
+    /// The origin is at the bottom-left corner, and y grows upwards.
+    BottomLeft,
+}
+
+/// A [`ScreenSpace`] component makes the [`super::camera::Camera`] of the same entity map
+/// transforms to pixel coordinates, so that HUD elements can be placed without projection math.
+///
+/// One unit is one logical pixel, and the projection follows the window as it is resized. Entities
+/// are visible within the depth range of [`ScreenSpace::DEPTH`].
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct ScreenSpace {
+    pub origin: ScreenOrigin,
+
+    /// The screen size and origin the camera projection was last computed for.
+    applied: Option<(math::Vec2, ScreenOrigin)>,
+}
+
+assert_impl_all!(ScreenSpace: ecs::storage::Component);
+
+impl ScreenSpace {
+    /// Half of the depth range visible to a screen space camera, around z = 0.
+    pub const DEPTH: f32 = 1000.0;
+
+    /// Creates a new [`ScreenSpace`] with the given origin.
+    pub fn new(origin: ScreenOrigin) -> Self {
+        Self {
+            origin,
+            applied: None,
+        }
+    }
+
+    /// Returns the projection for a screen of the given logical size, or `None` if the current
+    /// projection is already up to date.
+    pub(super) fn update(&mut self, size: math::Vec2) -> Option<math::Mat4> {
+        // a minimized window has no meaningful projection.
+        if self.applied == Some((size, self.origin)) || size.x <= 0.0 || size.y <= 0.0 {
+            return None;
+        }
+        self.applied = Some((size, self.origin));
+
+        let (bottom, top) = match self.origin {
+            ScreenOrigin::TopLeft => (size.y, 0.0),
+            ScreenOrigin::BottomLeft => (0.0, size.y),
+        };
+        Some(math::Mat4::orthographic_rh(
+            0.0,
+            size.x,
+            bottom,
+            top,
+            -Self::DEPTH,
+            Self::DEPTH,
+        ))
+    }
+}
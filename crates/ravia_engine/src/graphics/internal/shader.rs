@@ -1,164 +1,767 @@
-use std::collections::HashMap;
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex, OnceLock,
+    },
+};
+
+use log::warn;
 
 use crate::engine::EngineContext;
 
-use super::{mesh::Vertex, uniform::UniformType};
+use super::{
+    debug_view::DebugLayout,
+    depth::{depth_stencil_state, shadow_depth_stencil_state},
+    gpu::Gpu,
+    mesh::Vertex,
+    preprocessor::preprocess,
+    reflection,
+    uniform::UniformType,
+};
 
 /// [`ShaderConfig`] holds the source, entry points and other configuration for a shader.
 #[derive(Clone, Copy, Debug)]
 pub struct ShaderConfig<'a> {
+    label: Option<&'a str>,
     source: &'a str,
+    defines: &'a [&'a str],
     vertex_entry_point: &'static str,
     vertex_attribute_formats: &'a [wgpu::VertexFormat],
+    vertex_buffers: &'a [VertexBufferLayout<'a>],
     fragment_entry_point: &'static str,
     uniforms: &'a [UniformType],
+    depth_test: bool,
+    polygon_mode: wgpu::PolygonMode,
+    topology: wgpu::PrimitiveTopology,
 }
 
 impl<'a> ShaderConfig<'a> {
     /// Creates a new [`ShaderConfig`] from a WGSL shader source.
     pub fn new(source: &'a str) -> Self {
         Self {
+            label: None,
             source,
+            defines: &[],
             vertex_entry_point: "vs_main",
             vertex_attribute_formats: &[],
+            vertex_buffers: &[],
             fragment_entry_point: "fs_main",
             uniforms: &[],
+            depth_test: true,
+            polygon_mode: wgpu::PolygonMode::Fill,
+            topology: wgpu::PrimitiveTopology::TriangleList,
         }
     }
 
+    /// Specifies the debug label of the shader and its pipeline.
+    pub fn with_label(mut self, label: &'a str) -> Self {
+        self.label = Some(label);
+        self
+    }
+
+    /// Specifies the names defined for the `#ifdef` directives of the source, e.g.
+    /// `HAS_NORMAL_MAP`, so that a single source covers the permutations of a shader family.
+    ///
+    /// The source is expanded before it is compiled, see [`Shader`]. The defines are appended to
+    /// the label, so that every permutation is a distinct pipeline in frame captures, the
+    /// pipeline cache and the [`EngineContext::materials`] keyed by it.
+    pub fn with_defines(mut self, defines: &'a [&'a str]) -> Self {
+        self.defines = defines;
+        self
+    }
+
     /// Specifies the vertex type.
     pub fn with_vertex_type<V: Vertex>(mut self) -> Self {
         self.vertex_attribute_formats = V::ATTRIBUTE_FORMATS;
         self
     }
 
+    /// Specifies additional vertex buffers, bound to the slots after the buffer of the vertex type.
+    ///
+    /// Shader locations continue from the attributes of the vertex type, in the order of the
+    /// buffers and their attributes.
+    pub fn with_vertex_buffers(mut self, vertex_buffers: &'a [VertexBufferLayout<'a>]) -> Self {
+        self.vertex_buffers = vertex_buffers;
+        self
+    }
+
     /// Specifies the uniforms.
     pub fn with_uniforms(mut self, uniforms: &'a [UniformType]) -> Self {
         self.uniforms = uniforms;
         self
     }
+
+    /// Specifies whether the meshes are tested against and written into the depth buffer, which
+    /// is the default. Meshes without depth test are drawn over the previous draws in the order
+    /// they are drawn, e.g. for 2D content.
+    pub fn with_depth_test(mut self, depth_test: bool) -> Self {
+        self.depth_test = depth_test;
+        self
+    }
+
+    /// Specifies how the triangles are rasterized, e.g. [`wgpu::PolygonMode::Line`] to draw the
+    /// meshes in wireframe for debugging.
+    ///
+    /// The line and point modes require [`super::gpu::GpuConfig::polygon_modes`], and fall back to
+    /// [`wgpu::PolygonMode::Fill`] if the adapter does not support them.
+    pub fn with_polygon_mode(mut self, polygon_mode: wgpu::PolygonMode) -> Self {
+        self.polygon_mode = polygon_mode;
+        self
+    }
+
+    /// Specifies how the indices of the meshes are assembled into primitives, e.g.
+    /// [`wgpu::PrimitiveTopology::LineList`] for line-based effects. Shaders draw triangle lists
+    /// by default.
+    ///
+    /// The meshes drawn with the shader should have the same topology, see
+    /// [`super::mesh::Mesh::with_topology`].
+    pub fn with_topology(mut self, topology: wgpu::PrimitiveTopology) -> Self {
+        self.topology = topology;
+        self
+    }
+
+    /// Returns the label followed by the sorted defines, if any, e.g.
+    /// `ravia_engine::standard[VERTEX_COLOR]`.
+    pub fn permutation_label(&self) -> String {
+        let label = self.label.unwrap_or("ravia_engine::shader");
+        if self.defines.is_empty() {
+            return label.to_string();
+        }
+
+        let mut defines = self.defines.to_vec();
+        defines.sort_unstable();
+        defines.dedup();
+        format!("{}[{}]", label, defines.join(","))
+    }
 }
 
 impl Default for ShaderConfig<'_> {
     fn default() -> Self {
         Self {
+            label: None,
             source: "",
+            defines: &[],
             vertex_entry_point: "vs_main",
             vertex_attribute_formats: &[],
+            vertex_buffers: &[],
             fragment_entry_point: "fs_main",
             uniforms: &[],
+            depth_test: true,
+            polygon_mode: wgpu::PolygonMode::Fill,
+            topology: wgpu::PrimitiveTopology::TriangleList,
+        }
+    }
+}
+
+/// [`VertexBufferLayout`] describes an additional vertex buffer of a shader.
+#[derive(Clone, Copy, Debug)]
+pub struct VertexBufferLayout<'a> {
+    pub attribute_formats: &'a [wgpu::VertexFormat],
+    pub step_mode: wgpu::VertexStepMode,
+}
+
+impl VertexBufferLayout<'static> {
+    /// Creates a layout holding one element of the given type per vertex.
+    pub fn per_vertex<V: Vertex>() -> Self {
+        Self {
+            attribute_formats: V::ATTRIBUTE_FORMATS,
+            step_mode: wgpu::VertexStepMode::Vertex,
+        }
+    }
+
+    /// Creates a layout holding one element of the given type per instance.
+    pub fn per_instance<V: Vertex>() -> Self {
+        Self {
+            attribute_formats: V::ATTRIBUTE_FORMATS,
+            step_mode: wgpu::VertexStepMode::Instance,
         }
     }
 }
 
 /// Holds a compiled shader and underlying rendering pipeline.
+///
+/// Shaders created with [`Shader::new_async`] compile their pipeline in the background; until it is
+/// ready, meshes using the shader are drawn with a flat placeholder pipeline. If the pipeline fails
+/// to compile, the meshes are drawn in the flat pink of the error shader instead.
 #[derive(Debug)]
 pub struct Shader {
-    pipeline: wgpu::RenderPipeline,
+    label: String,
+    pipeline: Arc<OnceLock<wgpu::RenderPipeline>>,
+    failed: Arc<AtomicBool>,
     uniforms: HashMap<UniformType, u32>,
+    placeholder_layout: Option<PlaceholderKey>,
+    debug_layout: Option<DebugLayout>,
+    /// The descriptor the pipeline is compiled from, kept to compile it again for a recreated
+    /// device. `None` if the shader has failed before compiling.
+    descriptor: Option<Arc<PipelineDescriptor>>,
 }
 
 impl Shader {
-    /// Creates a new [`Shader`].
+    /// Creates a new [`Shader`], compiling its pipeline immediately.
     pub fn new(ctx: &EngineContext, config: &ShaderConfig) -> Self {
-        let surface_config = ctx.gpu.surface_config.lock().unwrap();
+        let (shader, descriptor) = Self::prepare(ctx, config);
+        if shader.has_failed() {
+            return shader;
+        }
 
-        let shader_module = ctx
+        let failed = Some(shader.failed.clone());
+        let pipeline = ctx
             .gpu
-            .device
-            .create_shader_module(wgpu::ShaderModuleDescriptor {
-                label: None,
-                source: wgpu::ShaderSource::Wgsl(config.source.into()),
+            .flagged_error_scope(&descriptor.label, failed, |device| {
+                descriptor.create_pipeline(&ctx.gpu, device)
             });
+        let _ = shader.pipeline.set(pipeline);
+        shader
+    }
 
-        let mut uniforms = HashMap::new();
-        let mut bind_group_layouts = vec![];
-        for (i, uniform_type) in config.uniforms.iter().enumerate() {
-            uniforms.insert(*uniform_type, i as u32);
-            bind_group_layouts.push(
-                ctx.gpu
-                    .default_bind_group_layouts
-                    .uniform_layout(uniform_type),
-            );
+    /// Creates a new [`Shader`], compiling its pipeline in the background.
+    ///
+    /// On native platforms the pipelines are compiled one at a time on a background thread of the
+    /// engine, and become available from the next frame on. On the web, where the device cannot
+    /// be shared between threads, pending pipelines are compiled one per frame to spread the cost
+    /// over the startup.
+    pub fn new_async(ctx: &EngineContext, config: &ShaderConfig) -> Self {
+        let (shader, descriptor) = Self::prepare(ctx, config);
+        if shader.has_failed() {
+            return shader;
+        }
+
+        let deferred = DeferredPipeline {
+            descriptor,
+            pipeline: shader.pipeline.clone(),
+            failed: shader.failed.clone(),
+        };
+        ctx.gpu.pipeline_compiler.push(&ctx.gpu, deferred);
+
+        shader
+    }
+
+    /// Returns a copy of the shader for the device recreated after a device loss, compiling its
+    /// pipeline in the background as [`Shader::new_async`].
+    pub(crate) fn restored(&self, ctx: &EngineContext) -> Self {
+        let shader = Self {
+            label: self.label.clone(),
+            pipeline: Arc::new(OnceLock::new()),
+            failed: Arc::new(AtomicBool::new(self.descriptor.is_none())),
+            uniforms: self.uniforms.clone(),
+            placeholder_layout: self.placeholder_layout,
+            debug_layout: self.debug_layout,
+            descriptor: self.descriptor.clone(),
+        };
+
+        if let Some(descriptor) = &shader.descriptor {
+            let deferred = DeferredPipeline {
+                descriptor: descriptor.clone(),
+                pipeline: shader.pipeline.clone(),
+                failed: shader.failed.clone(),
+            };
+            ctx.gpu.pipeline_compiler.push(&ctx.gpu, deferred);
         }
+        shader
+    }
+
+    /// Validates the config, and splits it into the shader waiting for its pipeline and the owned
+    /// descriptor of the pipeline.
+    fn prepare(ctx: &EngineContext, config: &ShaderConfig) -> (Self, Arc<PipelineDescriptor>) {
+        let label = config.permutation_label();
+        let label = label.as_str();
 
-        let pipeline_layout =
+        let (source, errors) = preprocess(config.source, config.defines);
+        for error in errors {
             ctx.gpu
-                .device
-                .create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
-                    label: None,
-                    bind_group_layouts: &bind_group_layouts,
-                    push_constant_ranges: &[],
-                });
+                .report_error(format!("{}: preprocessor error: {}", label, error));
+        }
+
+        // the pipeline of a source which does not compile is not created, nor is the pipeline
+        // of mismatching bindings, which fails to compile against the layouts of the uniforms.
+        let mut failed = false;
+        if let Some(error) = reflection::compile_error(&source) {
+            ctx.gpu
+                .report_error(format!("{}: compilation error: {}", label, error));
+            failed = true;
+        }
+        for mismatch in reflection::validate_bindings(&source, config.uniforms) {
+            ctx.gpu
+                .report_error(format!("{}: binding mismatch: {}", label, mismatch));
+            failed = true;
+        }
+
+        let polygon_mode = if ctx.gpu.capabilities().polygon_mode(config.polygon_mode) {
+            config.polygon_mode
+        } else {
+            warn!(
+                target: "ravia_engine::graphics::shader",
+                "{}: polygon mode {:?} is not enabled, using Fill",
+                label,
+                config.polygon_mode
+            );
+            wgpu::PolygonMode::Fill
+        };
+
+        let uniforms = config
+            .uniforms
+            .iter()
+            .enumerate()
+            .map(|(i, uniform_type)| (*uniform_type, i as u32))
+            .collect();
+
+        let placeholder_layout = PlaceholderKey::new(config.vertex_attribute_formats);
+        let debug_layout = DebugLayout::new(config.vertex_attribute_formats);
+
+        let descriptor = Arc::new(PipelineDescriptor {
+            label: label.to_string(),
+            source,
+            vertex_entry_point: config.vertex_entry_point,
+            fragment_entry_point: config.fragment_entry_point,
+            vertex_buffers: std::iter::once((
+                config.vertex_attribute_formats.to_vec(),
+                wgpu::VertexStepMode::Vertex,
+            ))
+            .chain(
+                config
+                    .vertex_buffers
+                    .iter()
+                    .map(|layout| (layout.attribute_formats.to_vec(), layout.step_mode)),
+            )
+            .collect(),
+            uniforms: config.uniforms.to_vec(),
+            depth_test: config.depth_test,
+            polygon_mode,
+            topology: config.topology,
+        });
+
+        let shader = Self {
+            label: label.to_string(),
+            pipeline: Arc::new(OnceLock::new()),
+            failed: Arc::new(AtomicBool::new(failed)),
+            uniforms,
+            placeholder_layout,
+            debug_layout,
+            descriptor: (!failed).then(|| descriptor.clone()),
+        };
+
+        (shader, descriptor)
+    }
+
+    /// Returns the debug label of the shader.
+    pub fn label(&self) -> &str {
+        &self.label
+    }
+
+    /// Returns the underlying [`wgpu::RenderPipeline`], or `None` if it is still compiling.
+    pub fn pipeline(&self) -> Option<&wgpu::RenderPipeline> {
+        self.pipeline.get()
+    }
+
+    /// Returns whether the pipeline has been compiled.
+    pub fn is_ready(&self) -> bool {
+        self.pipeline.get().is_some()
+    }
+
+    /// Returns whether the pipeline has failed to compile, in which case the meshes using the
+    /// shader are drawn with the error shader.
+    pub fn has_failed(&self) -> bool {
+        self.failed.load(Ordering::SeqCst)
+    }
+
+    /// Returns the bind group index for the given uniform type.
+    ///
+    /// Returns `None` if the uniform type is not used in this shader.
+    pub fn bind_group_index(&self, uniform_type: UniformType) -> Option<u32> {
+        self.uniforms.get(&uniform_type).copied()
+    }
+
+    /// Returns the vertex layout of the placeholder pipeline drawn while compiling, or `None` if
+    /// the shader has no vertex type, or its position is not made of floats.
+    pub(super) fn placeholder_layout(&self) -> Option<PlaceholderKey> {
+        self.placeholder_layout
+    }
+
+    /// Returns the vertex layout of the pipelines drawn while a
+    /// [`DebugView`](super::debug_view::DebugView) is active, or `None` if the shader has no
+    /// vertex type, or its position is not made of floats.
+    pub(super) fn debug_layout(&self) -> Option<DebugLayout> {
+        self.debug_layout
+    }
+}
 
+/// Owned description of a render pipeline, which can be compiled away from its [`ShaderConfig`].
+#[derive(Debug)]
+struct PipelineDescriptor {
+    label: String,
+    source: String,
+    vertex_entry_point: &'static str,
+    fragment_entry_point: &'static str,
+    vertex_buffers: Vec<(Vec<wgpu::VertexFormat>, wgpu::VertexStepMode)>,
+    uniforms: Vec<UniformType>,
+    depth_test: bool,
+    polygon_mode: wgpu::PolygonMode,
+    topology: wgpu::PrimitiveTopology,
+}
+
+impl PipelineDescriptor {
+    fn create_pipeline(&self, gpu: &Gpu, device: &wgpu::Device) -> wgpu::RenderPipeline {
+        let label = self.label.as_str();
+        let scene_format = gpu.scene_format();
+
+        let shader_module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some(label),
+            source: wgpu::ShaderSource::Wgsl(self.source.as_str().into()),
+        });
+
+        let bind_group_layouts = self
+            .uniforms
+            .iter()
+            .map(|uniform_type| gpu.default_bind_group_layouts.uniform_layout(uniform_type))
+            .collect::<Vec<_>>();
+
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some(label),
+            bind_group_layouts: &bind_group_layouts,
+            push_constant_ranges: &[],
+        });
+
+        let mut shader_location = 0;
         let mut vertex_buffer_attributes = vec![];
-        let vertex_buffer_layout = {
+        for (formats, step_mode) in &self.vertex_buffers {
             let mut offset = 0;
-            for (i, format) in config.vertex_attribute_formats.iter().enumerate() {
-                vertex_buffer_attributes.push(wgpu::VertexAttribute {
+            let mut attributes = vec![];
+            for format in formats {
+                attributes.push(wgpu::VertexAttribute {
                     format: *format,
                     offset,
-                    shader_location: i as u32,
+                    shader_location,
                 });
                 offset += format.size();
+                shader_location += 1;
             }
+            vertex_buffer_attributes.push((offset, *step_mode, attributes));
+        }
+
+        let vertex_buffer_layout = vertex_buffer_attributes
+            .iter()
+            .map(
+                |(array_stride, step_mode, attributes)| wgpu::VertexBufferLayout {
+                    array_stride: *array_stride,
+                    step_mode: *step_mode,
+                    attributes,
+                },
+            )
+            .collect::<Vec<_>>();
 
-            vec![wgpu::VertexBufferLayout {
-                array_stride: offset,
-                step_mode: wgpu::VertexStepMode::Vertex,
-                attributes: &vertex_buffer_attributes,
-            }]
+        device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some(label),
+            layout: Some(&pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &shader_module,
+                entry_point: Some(self.vertex_entry_point),
+                buffers: &vertex_buffer_layout,
+                compilation_options: wgpu::PipelineCompilationOptions::default(),
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader_module,
+                entry_point: Some(self.fragment_entry_point),
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: scene_format,
+                    blend: Some(wgpu::BlendState::REPLACE),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+                compilation_options: wgpu::PipelineCompilationOptions::default(),
+            }),
+            primitive: wgpu::PrimitiveState {
+                topology: self.topology,
+                strip_index_format: strip_index_format(self.topology),
+                front_face: wgpu::FrontFace::Ccw,
+                cull_mode: Some(wgpu::Face::Back),
+                unclipped_depth: false,
+                polygon_mode: self.polygon_mode,
+                conservative: false,
+            },
+            depth_stencil: Some(depth_stencil_state(self.depth_test)),
+            multisample: wgpu::MultisampleState::default(),
+            multiview: None,
+            cache: gpu.pipeline_cache(),
+        })
+    }
+}
+
+/// A pipeline of a [`Shader`] waiting to be compiled by the
+/// [`PipelineCompiler`](super::pipeline_compiler::PipelineCompiler).
+#[derive(Debug)]
+pub(super) struct DeferredPipeline {
+    descriptor: Arc<PipelineDescriptor>,
+    pipeline: Arc<OnceLock<wgpu::RenderPipeline>>,
+    failed: Arc<AtomicBool>,
+}
+
+impl DeferredPipeline {
+    /// Compiles the pipeline within an error scope, making it available to the shader.
+    #[cfg(target_arch = "wasm32")]
+    pub fn compile(self, gpu: &Gpu) {
+        let failed = Some(self.failed.clone());
+        let pipeline = gpu.flagged_error_scope(&self.descriptor.label, failed, |device| {
+            self.descriptor.create_pipeline(gpu, device)
+        });
+        let _ = self.pipeline.set(pipeline);
+    }
+
+    /// Creates the pipeline on a background thread. No error scope is pushed meanwhile, as it
+    /// would capture the errors of the other threads; a validation error is reported as an
+    /// uncaptured error instead, and detected by [`DeferredPipeline::finish`].
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn create(&self, gpu: &Gpu) -> wgpu::RenderPipeline {
+        gpu.outside_error_scopes(|device| self.descriptor.create_pipeline(gpu, device))
+    }
+
+    /// Makes the pipeline created by [`DeferredPipeline::create`] available to the shader, on
+    /// the frame thread.
+    ///
+    /// A pipeline failing validation is created invalid, so the shader is flagged as failed if
+    /// the layout of its first bind group cannot be retrieved. Pipelines without uniforms have no
+    /// bind group to probe, and are assumed valid.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn finish(self, gpu: &Gpu, pipeline: wgpu::RenderPipeline) {
+        if !self.descriptor.uniforms.is_empty() {
+            let failed = Some(self.failed.clone());
+            gpu.flagged_error_scope(&self.descriptor.label, failed, |_| {
+                pipeline.get_bind_group_layout(0)
+            });
+        }
+        let _ = self.pipeline.set(pipeline);
+    }
+}
+
+/// Vertex layout of a placeholder pipeline: the stride of the vertex buffer, and the format of the
+/// position, which is expected to be the first attribute. The topology is the one of the drawn
+/// mesh.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub(super) struct PlaceholderKey {
+    array_stride: u64,
+    position_format: wgpu::VertexFormat,
+    topology: wgpu::PrimitiveTopology,
+}
+
+impl PlaceholderKey {
+    /// Returns the layout of the vertex attributes, or `None` if there is no position made of
+    /// floats.
+    pub fn new(formats: &[wgpu::VertexFormat]) -> Option<Self> {
+        formats
+            .first()
+            .filter(|format| Self::position_format_type(**format).is_some())
+            .map(|position_format| Self {
+                array_stride: formats.iter().map(|f| f.size()).sum(),
+                position_format: *position_format,
+                topology: wgpu::PrimitiveTopology::TriangleList,
+            })
+    }
+
+    /// Returns the key with the topology of the drawn mesh.
+    pub fn with_topology(mut self, topology: wgpu::PrimitiveTopology) -> Self {
+        self.topology = topology;
+        self
+    }
+
+    /// Returns the topology of the pipeline.
+    pub fn topology(&self) -> wgpu::PrimitiveTopology {
+        self.topology
+    }
+
+    /// Returns the stride of the vertex buffer.
+    pub fn array_stride(&self) -> u64 {
+        self.array_stride
+    }
+
+    /// Returns the vertex attribute of the position.
+    pub fn attribute(&self) -> wgpu::VertexAttribute {
+        wgpu::VertexAttribute {
+            format: self.position_format,
+            offset: 0,
+            shader_location: 0,
+        }
+    }
+
+    /// Returns the WGSL type of the position, and the expression extending it to homogeneous
+    /// coordinates.
+    pub fn position_type(&self) -> (&'static str, &'static str) {
+        Self::position_format_type(self.position_format)
+            .expect("placeholder keys have a float position")
+    }
+
+    fn position_format_type(format: wgpu::VertexFormat) -> Option<(&'static str, &'static str)> {
+        match format {
+            wgpu::VertexFormat::Float32x2 => Some(("vec2<f32>", "vec4<f32>(position, 0.0, 1.0)")),
+            wgpu::VertexFormat::Float32x3 => Some(("vec3<f32>", "vec4<f32>(position, 1.0)")),
+            wgpu::VertexFormat::Float32x4 => Some(("vec4<f32>", "position")),
+            _ => None,
+        }
+    }
+}
+
+/// Returns the index format of the strip topologies, which is the format of the mesh indices.
+pub(super) fn strip_index_format(topology: wgpu::PrimitiveTopology) -> Option<wgpu::IndexFormat> {
+    topology.is_strip().then_some(wgpu::IndexFormat::Uint32)
+}
+
+/// Bind group index of the camera in placeholder pipelines.
+pub(super) const PLACEHOLDER_CAMERA_INDEX: u32 = 0;
+/// Bind group index of the camera transform in placeholder pipelines.
+pub(super) const PLACEHOLDER_CAMERA_TRANSFORM_INDEX: u32 = 1;
+/// Bind group index of the model transform in placeholder pipelines.
+pub(super) const PLACEHOLDER_MODEL_TRANSFORM_INDEX: u32 = 2;
+
+const PLACEHOLDER_SOURCE: &str = "
+struct CameraUniform {
+  projection: mat4x4<f32>,
+};
+
+struct TransformUniform {
+  transform: mat4x4<f32>,
+  transform_inv: mat4x4<f32>,
+};
+
+@group(0) @binding(0) var<uniform> camera: CameraUniform;
+@group(1) @binding(0) var<uniform> camera_transform: TransformUniform;
+@group(2) @binding(0) var<uniform> model_transform: TransformUniform;
+
+@vertex
+fn vs_main(@location(0) position: POSITION_TYPE) -> @builtin(position) vec4<f32> {
+  return camera.projection * camera_transform.transform_inv * model_transform.transform * POSITION;
+}
+
+@fragment
+fn fs_main() -> @location(0) vec4<f32> {
+  return COLOR;
+}
+";
+
+/// Variant of a placeholder pipeline.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum PlaceholderKind {
+    Placeholder,
+    Error,
+    ShadowCaster,
+}
+
+/// Caches the placeholder pipelines drawn in place of shaders which are still compiling, one per
+/// vertex layout.
+///
+/// The error variants draw the meshes of shaders which have failed to compile, and error meshes,
+/// in a flat pink. The depth-only variants draw the meshes into shadow maps, from the camera of
+/// the light.
+#[derive(Debug, Default)]
+pub(super) struct PlaceholderPipelines {
+    pipelines: Mutex<HashMap<PlaceholderKey, Arc<wgpu::RenderPipeline>>>,
+    errors: Mutex<HashMap<PlaceholderKey, Arc<wgpu::RenderPipeline>>>,
+    depth_only: Mutex<HashMap<PlaceholderKey, Arc<wgpu::RenderPipeline>>>,
+}
+
+impl PlaceholderPipelines {
+    /// Returns the placeholder pipeline for the vertex layout, creating it if necessary.
+    pub fn get(&self, gpu: &Gpu, key: PlaceholderKey) -> Arc<wgpu::RenderPipeline> {
+        Self::get_or_create(&self.pipelines, gpu, key, PlaceholderKind::Placeholder)
+    }
+
+    /// Returns the error pipeline for the vertex layout, creating it if necessary.
+    pub fn get_error(&self, gpu: &Gpu, key: PlaceholderKey) -> Arc<wgpu::RenderPipeline> {
+        Self::get_or_create(&self.errors, gpu, key, PlaceholderKind::Error)
+    }
+
+    /// Returns the depth-only pipeline for the vertex layout, creating it if necessary.
+    pub fn get_depth_only(&self, gpu: &Gpu, key: PlaceholderKey) -> Arc<wgpu::RenderPipeline> {
+        Self::get_or_create(&self.depth_only, gpu, key, PlaceholderKind::ShadowCaster)
+    }
+
+    fn get_or_create(
+        pipelines: &Mutex<HashMap<PlaceholderKey, Arc<wgpu::RenderPipeline>>>,
+        gpu: &Gpu,
+        key: PlaceholderKey,
+        kind: PlaceholderKind,
+    ) -> Arc<wgpu::RenderPipeline> {
+        pipelines
+            .lock()
+            .unwrap()
+            .entry(key)
+            .or_insert_with(|| Arc::new(Self::create(gpu, key, kind)))
+            .clone()
+    }
+
+    fn create(gpu: &Gpu, key: PlaceholderKey, kind: PlaceholderKind) -> wgpu::RenderPipeline {
+        let depth_only = kind == PlaceholderKind::ShadowCaster;
+        let label = match kind {
+            PlaceholderKind::Placeholder => "ravia_engine::placeholder",
+            PlaceholderKind::Error => "ravia_engine::error",
+            PlaceholderKind::ShadowCaster => "ravia_engine::shadow_caster",
+        };
+        let color = match kind {
+            PlaceholderKind::Error => "vec4<f32>(1.0, 0.0, 1.0, 1.0)",
+            _ => "vec4<f32>(0.5, 0.5, 0.5, 1.0)",
         };
 
-        let pipeline = ctx
-            .gpu
+        let scene_format = gpu.scene_format();
+        let layouts = &gpu.default_bind_group_layouts;
+
+        let (position_type, position) = key.position_type();
+        let source = PLACEHOLDER_SOURCE
+            .replace("POSITION_TYPE", position_type)
+            .replace("POSITION;", &format!("{};", position))
+            .replace("COLOR", color);
+        let shader_module = gpu
+            .device
+            .create_shader_module(wgpu::ShaderModuleDescriptor {
+                label: Some(label),
+                source: wgpu::ShaderSource::Wgsl(source.into()),
+            });
+
+        let pipeline_layout = gpu
             .device
+            .create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                label: Some(label),
+                bind_group_layouts: &[&layouts.camera, &layouts.transform, &layouts.transform],
+                push_constant_ranges: &[],
+            });
+
+        let color_targets = [Some(wgpu::ColorTargetState {
+            format: scene_format,
+            blend: Some(wgpu::BlendState::REPLACE),
+            write_mask: wgpu::ColorWrites::ALL,
+        })];
+
+        // only the position is read from the vertex buffer.
+        gpu.device
             .create_render_pipeline(&wgpu::RenderPipelineDescriptor {
-                label: None,
+                label: Some(label),
                 layout: Some(&pipeline_layout),
                 vertex: wgpu::VertexState {
                     module: &shader_module,
-                    entry_point: Some(config.vertex_entry_point),
-                    buffers: &vertex_buffer_layout,
+                    entry_point: Some("vs_main"),
+                    buffers: &[wgpu::VertexBufferLayout {
+                        array_stride: key.array_stride,
+                        step_mode: wgpu::VertexStepMode::Vertex,
+                        attributes: &[key.attribute()],
+                    }],
                     compilation_options: wgpu::PipelineCompilationOptions::default(),
                 },
-                fragment: Some(wgpu::FragmentState {
+                fragment: (!depth_only).then(|| wgpu::FragmentState {
                     module: &shader_module,
-                    entry_point: Some(config.fragment_entry_point),
-                    targets: &[Some(wgpu::ColorTargetState {
-                        format: surface_config.format,
-                        blend: Some(wgpu::BlendState::REPLACE),
-                        write_mask: wgpu::ColorWrites::ALL,
-                    })],
+                    entry_point: Some("fs_main"),
+                    targets: &color_targets,
                     compilation_options: wgpu::PipelineCompilationOptions::default(),
                 }),
+                // shadow casters draw both faces, so that open meshes cast shadows too.
                 primitive: wgpu::PrimitiveState {
-                    topology: wgpu::PrimitiveTopology::TriangleList,
-                    strip_index_format: None,
-                    front_face: wgpu::FrontFace::Ccw,
-                    cull_mode: Some(wgpu::Face::Back),
-                    unclipped_depth: false,
-                    polygon_mode: wgpu::PolygonMode::Fill,
-                    conservative: false,
+                    topology: key.topology,
+                    strip_index_format: strip_index_format(key.topology),
+                    cull_mode: (!depth_only).then_some(wgpu::Face::Back),
+                    ..Default::default()
                 },
-                depth_stencil: None,
+                depth_stencil: Some(match depth_only {
+                    true => shadow_depth_stencil_state(),
+                    false => depth_stencil_state(true),
+                }),
                 multisample: wgpu::MultisampleState::default(),
                 multiview: None,
-                cache: None,
-            });
-
-        Self { pipeline, uniforms }
-    }
-
-    /// Returns the underlying [`wgpu::RenderPipeline`].
-    pub fn pipeline(&self) -> &wgpu::RenderPipeline {
-        &self.pipeline
-    }
-
-    /// Returns the bind group index for the given uniform type.
-    ///
-    /// Returns `None` if the uniform type is not used in this shader.
-    pub fn bind_group_index(&self, uniform_type: UniformType) -> Option<u32> {
-        self.uniforms.get(&uniform_type).copied()
+                cache: gpu.pipeline_cache(),
+            })
     }
 }
@@ -0,0 +1,85 @@
+use wgpu::util::DeviceExt;
+
+use crate::{ecs, engine::EngineContext, math};
+
+use super::{
+    memory::{MemoryAllocation, MemoryCategory},
+    uniform::Uniform,
+};
+
+/// A [`Skin`] component holds the joint matrices deforming a skinned [`super::mesh::Mesh`],
+/// whose vertices are [`super::mesh::Vertex3DSkinned`].
+///
+/// Shaders access the matrices through [`super::uniform::UniformType::Joints`], bound as a
+/// `array<mat4x4<f32>, 128>` uniform. Each vertex is transformed by the weighted sum of the
+/// matrices of its joints.
+#[derive(Debug)]
+pub struct Skin {
+    joint_matrices: Vec<math::Mat4>,
+
+    _buffer: wgpu::Buffer,
+    bind_group: wgpu::BindGroup,
+    _allocation: MemoryAllocation,
+}
+
+assert_impl_all!(Skin: ecs::storage::Component);
+
+impl Skin {
+    /// Maximum number of joints in a skin.
+    pub const MAX_JOINTS: usize = 128;
+
+    /// Creates a new [`Skin`] with the given number of joints, all set to identity.
+    pub fn new(ctx: &EngineContext, num_joints: usize) -> Self {
+        let num_joints = num_joints.min(Self::MAX_JOINTS);
+        let buffer = ctx
+            .gpu
+            .device
+            .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some("ravia_engine::skin"),
+                contents: bytemuck::cast_slice(&[math::Mat4::IDENTITY; Self::MAX_JOINTS]),
+                usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            });
+
+        let bind_group = ctx
+            .gpu
+            .device
+            .create_bind_group(&wgpu::BindGroupDescriptor {
+                label: Some("ravia_engine::skin"),
+                layout: &ctx.gpu.default_bind_group_layouts.joints,
+                entries: &[wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: buffer.as_entire_binding(),
+                }],
+            });
+
+        Self {
+            joint_matrices: vec![math::Mat4::IDENTITY; num_joints],
+            _allocation: ctx.gpu.track_memory(MemoryCategory::Uniform, buffer.size()),
+            _buffer: buffer,
+            bind_group,
+        }
+    }
+
+    /// Returns the joint matrices of the skin.
+    pub fn joint_matrices(&self) -> &[math::Mat4] {
+        &self.joint_matrices
+    }
+
+    /// Sets the joint matrices of the skin, and uploads them to the GPU.
+    ///
+    /// Matrices beyond the number of joints of the skin are ignored.
+    pub fn set_joint_matrices(&mut self, ctx: &EngineContext, joint_matrices: &[math::Mat4]) {
+        let len = joint_matrices.len().min(self.joint_matrices.len());
+        self.joint_matrices[..len].copy_from_slice(&joint_matrices[..len]);
+
+        ctx.gpu
+            .queue
+            .write_buffer(&self._buffer, 0, bytemuck::cast_slice(&self.joint_matrices));
+    }
+}
+
+impl Uniform for Skin {
+    fn bind_group(&self) -> &wgpu::BindGroup {
+        &self.bind_group
+    }
+}
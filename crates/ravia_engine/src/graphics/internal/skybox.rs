@@ -0,0 +1,301 @@
+use std::sync::{Arc, OnceLock};
+
+use crate::{ecs, engine::EngineContext, resource::Resource};
+
+use super::{
+    cubemap::Cubemap,
+    depth::DEPTH_FORMAT,
+    gpu::Gpu,
+    sampler::{SamplerKey, TextureAddressMode},
+    texture::TextureFilterMode,
+    upload::Upload,
+};
+
+const SKYBOX_SOURCE: &str = "
+struct CameraUniform {
+  projection: mat4x4<f32>,
+};
+
+struct TransformUniform {
+  transform: mat4x4<f32>,
+  transform_inv: mat4x4<f32>,
+};
+
+@group(0) @binding(0) var<uniform> camera: CameraUniform;
+
+@group(1) @binding(0) var<uniform> camera_transform: TransformUniform;
+
+@group(2) @binding(0) var cubemap: texture_cube<f32>;
+@group(2) @binding(1) var cubemap_sampler: sampler;
+
+struct VertexOutput {
+  @builtin(position) position: vec4<f32>,
+  @location(0) direction: vec3<f32>,
+};
+
+@vertex
+fn vs_main(@builtin(vertex_index) index: u32) -> VertexOutput {
+  // a unit cube around the camera, as a strip of 14 vertices.
+  let bit = 1u << index;
+  let direction = vec3<f32>(
+    f32((0x287au & bit) != 0u),
+    f32((0x02afu & bit) != 0u),
+    f32((0x31e3u & bit) != 0u),
+  ) * 2.0 - 1.0;
+
+  // only the rotation of the camera applies, so that the sky stays infinitely far.
+  let view = camera_transform.transform_inv;
+  let rotation = mat3x3<f32>(view[0].xyz, view[1].xyz, view[2].xyz);
+  let position = camera.projection * vec4<f32>(rotation * direction, 1.0);
+
+  // at the far plane, behind everything drawn in the scene.
+  return VertexOutput(position.xyww, direction);
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+  return vec4<f32>(textureSample(cubemap, cubemap_sampler, in.direction).rgb, 1.0);
+}
+";
+
+/// A [`Skybox`] component draws a [`Cubemap`] behind the scene, e.g. a sky or a distant
+/// environment.
+///
+/// The skybox is drawn by every camera but the [`super::overlay::Overlay`] cameras, following the
+/// rotation of the camera only, as if infinitely far. A single skybox is drawn, even if several
+/// entities of the world have one. Skyboxes are meant for perspective cameras.
+#[derive(Debug)]
+pub struct Skybox {
+    cubemap: Option<Arc<Cubemap>>,
+    bind_group: Option<wgpu::BindGroup>,
+    pub(crate) pending: Option<PendingSkybox>,
+    /// Paths the faces have been loaded from with [`Skybox::load`].
+    sources: Option<[String; 6]>,
+}
+
+assert_impl_all!(Skybox: ecs::storage::Component);
+
+/// The faces of a [`Skybox`] loading with [`Skybox::load`].
+#[derive(Debug)]
+pub(crate) struct PendingSkybox {
+    pub faces: [Resource; 6],
+    pub upload: Option<Upload<Result<Cubemap, anyhow::Error>>>,
+}
+
+impl Skybox {
+    /// Creates a new [`Skybox`] drawing the cubemap.
+    pub fn new(ctx: &EngineContext, cubemap: Arc<Cubemap>) -> Self {
+        let mut skybox = Self {
+            cubemap: None,
+            bind_group: None,
+            pending: None,
+            sources: None,
+        };
+        skybox.set_cubemap(ctx, cubemap);
+        skybox
+    }
+
+    /// Creates a new [`Skybox`] which loads its cubemap from six binary textures in the
+    /// background, one per face in the order +x, -x, +y, -y, +z, -z. See
+    /// [`super::cubemap::load_cubemap_from_binary`].
+    ///
+    /// Nothing is drawn until every face is loaded and uploaded. A failed load is reported as
+    /// [`crate::event::Event::ResourceFailed`], and a completed upload as
+    /// [`crate::event::Event::UploadCompleted`] for every face.
+    pub fn load(ctx: &EngineContext, faces: [&str; 6]) -> Self {
+        let sources = faces.map(str::to_string);
+        let faces = faces.map(|path| {
+            let mut resource = Resource::new(path);
+            ctx.resource_manager.request(&mut resource);
+            resource
+        });
+
+        Self {
+            cubemap: None,
+            bind_group: None,
+            pending: Some(PendingSkybox {
+                faces,
+                upload: None,
+            }),
+            sources: Some(sources),
+        }
+    }
+
+    /// Returns a copy of the skybox for the device recreated after a device loss, loading its
+    /// faces again, or `None` if its cubemap has not been loaded with [`Skybox::load`].
+    pub(super) fn restored(&self, ctx: &EngineContext) -> Option<Self> {
+        let sources = self.sources.as_ref()?;
+        Some(Self::load(
+            ctx,
+            std::array::from_fn(|i| sources[i].as_str()),
+        ))
+    }
+
+    /// Returns the drawn cubemap, or `None` while loading.
+    pub fn cubemap(&self) -> Option<&Arc<Cubemap>> {
+        self.cubemap.as_ref()
+    }
+
+    /// Sets the drawn cubemap, cancelling a pending [`Skybox::load`].
+    pub fn set_cubemap(&mut self, ctx: &EngineContext, cubemap: Arc<Cubemap>) {
+        self.bind_cubemap(ctx, cubemap);
+        self.sources = None;
+    }
+
+    /// Sets the drawn cubemap, e.g. once loaded by [`Skybox::load`].
+    pub(crate) fn bind_cubemap(&mut self, ctx: &EngineContext, cubemap: Arc<Cubemap>) {
+        let sampler = ctx.gpu.sampler(SamplerKey {
+            filter_mode: TextureFilterMode::Trilinear,
+            address_mode: TextureAddressMode::ClampToEdge,
+            anisotropy: 1,
+        });
+        let bind_group = ctx
+            .gpu
+            .device
+            .create_bind_group(&wgpu::BindGroupDescriptor {
+                label: Some(SkyboxPipeline::LABEL),
+                layout: &ctx.gpu.skybox.bind_group_layout,
+                entries: &[
+                    wgpu::BindGroupEntry {
+                        binding: 0,
+                        resource: wgpu::BindingResource::TextureView(cubemap.view()),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 1,
+                        resource: wgpu::BindingResource::Sampler(&sampler),
+                    },
+                ],
+            });
+
+        self.cubemap = Some(cubemap);
+        self.bind_group = Some(bind_group);
+        self.pending = None;
+    }
+
+    /// Returns true if the cubemap of the skybox is still loading.
+    pub fn is_loading(&self) -> bool {
+        self.pending.is_some()
+    }
+}
+
+/// The pipeline drawing the [`Skybox`] of the world.
+#[derive(Debug)]
+pub(super) struct SkyboxPipeline {
+    pub bind_group_layout: wgpu::BindGroupLayout,
+    pipeline: OnceLock<wgpu::RenderPipeline>,
+}
+
+impl SkyboxPipeline {
+    const LABEL: &str = "ravia_engine::skybox";
+
+    pub fn new(device: &wgpu::Device) -> Self {
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some(Self::LABEL),
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        view_dimension: wgpu::TextureViewDimension::Cube,
+                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                        multisampled: false,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                    count: None,
+                },
+            ],
+        });
+
+        Self {
+            bind_group_layout,
+            pipeline: OnceLock::new(),
+        }
+    }
+
+    /// Draws the skybox from the camera and its transform, returning whether it has been drawn.
+    pub fn draw(
+        &self,
+        gpu: &Gpu,
+        render_pass: &mut wgpu::RenderPass,
+        skybox: &Skybox,
+        (camera, camera_transform): (&wgpu::BindGroup, &wgpu::BindGroup),
+    ) -> bool {
+        let Some(bind_group) = &skybox.bind_group else {
+            return false;
+        };
+
+        render_pass.set_pipeline(self.pipeline(gpu));
+        render_pass.set_bind_group(0, camera, &[]);
+        render_pass.set_bind_group(1, camera_transform, &[]);
+        render_pass.set_bind_group(2, bind_group, &[]);
+        render_pass.draw(0..14, 0..1);
+        true
+    }
+
+    fn pipeline(&self, gpu: &Gpu) -> &wgpu::RenderPipeline {
+        self.pipeline.get_or_init(|| {
+            let layouts = &gpu.default_bind_group_layouts;
+            let shader_module = gpu
+                .device
+                .create_shader_module(wgpu::ShaderModuleDescriptor {
+                    label: Some(Self::LABEL),
+                    source: wgpu::ShaderSource::Wgsl(SKYBOX_SOURCE.into()),
+                });
+            let pipeline_layout =
+                gpu.device
+                    .create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                        label: Some(Self::LABEL),
+                        bind_group_layouts: &[
+                            &layouts.camera,
+                            &layouts.transform,
+                            &self.bind_group_layout,
+                        ],
+                        push_constant_ranges: &[],
+                    });
+
+            gpu.device
+                .create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+                    label: Some(Self::LABEL),
+                    layout: Some(&pipeline_layout),
+                    vertex: wgpu::VertexState {
+                        module: &shader_module,
+                        entry_point: Some("vs_main"),
+                        buffers: &[],
+                        compilation_options: wgpu::PipelineCompilationOptions::default(),
+                    },
+                    fragment: Some(wgpu::FragmentState {
+                        module: &shader_module,
+                        entry_point: Some("fs_main"),
+                        targets: &[Some(wgpu::ColorTargetState {
+                            format: gpu.scene_format(),
+                            blend: None,
+                            write_mask: wgpu::ColorWrites::ALL,
+                        })],
+                        compilation_options: wgpu::PipelineCompilationOptions::default(),
+                    }),
+                    // the cube is seen from the inside.
+                    primitive: wgpu::PrimitiveState {
+                        topology: wgpu::PrimitiveTopology::TriangleStrip,
+                        cull_mode: None,
+                        ..Default::default()
+                    },
+                    // the sky passes the depth test at the far plane, without occluding anything.
+                    depth_stencil: Some(wgpu::DepthStencilState {
+                        format: DEPTH_FORMAT,
+                        depth_write_enabled: false,
+                        depth_compare: wgpu::CompareFunction::LessEqual,
+                        stencil: wgpu::StencilState::default(),
+                        bias: wgpu::DepthBiasState::default(),
+                    }),
+                    multisample: wgpu::MultisampleState::default(),
+                    multiview: None,
+                    cache: gpu.pipeline_cache(),
+                })
+        })
+    }
+}
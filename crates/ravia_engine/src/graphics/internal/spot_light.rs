@@ -0,0 +1,51 @@
+use crate::{ecs, math};
+
+/// A [`SpotLight`] component lights the world in a cone from the position of its
+/// [`super::transform::Transform`], e.g. a flashlight.
+///
+/// The light is full within the inner angle of the cone, and fades out towards the outer angle.
+/// Spot lights are bound to the shaders with the other lights as
+/// [`super::uniform::UniformType::Lights`], and do not render shadow maps.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SpotLight {
+    /// Direction the cone points towards, in world space.
+    pub direction: math::Vec3,
+    /// Color of the light, in linear RGB.
+    pub color: math::Vec3,
+    pub intensity: f32,
+    /// Distance beyond which the light has no effect, in world units.
+    pub range: f32,
+    /// Angle between the direction and the edge of the full light, in radians.
+    pub inner_angle: f32,
+    /// Angle between the direction and the edge of the cone, in radians.
+    pub outer_angle: f32,
+}
+
+assert_impl_all!(SpotLight: ecs::storage::Component);
+
+impl SpotLight {
+    /// Creates a new [`SpotLight`] with a cone of the given angle, fading out over its outer
+    /// fifth.
+    pub fn new(
+        direction: math::Vec3,
+        color: math::Vec3,
+        intensity: f32,
+        range: f32,
+        angle: f32,
+    ) -> Self {
+        Self {
+            direction,
+            color,
+            intensity,
+            range,
+            inner_angle: angle * 0.8,
+            outer_angle: angle,
+        }
+    }
+
+    /// Specifies the angle of the full light within the cone.
+    pub fn with_inner_angle(mut self, inner_angle: f32) -> Self {
+        self.inner_angle = inner_angle;
+        self
+    }
+}
@@ -0,0 +1,216 @@
+use std::sync::LazyLock;
+
+use crate::{engine::EngineContext, math, resource::Handle};
+
+use super::{
+    lights::LIGHTS_WGSL,
+    material::Material,
+    mesh::{Mesh, Vertex3DStandard, Vertex3DStandardColored},
+    shader::ShaderConfig,
+    texture::{Texture, TextureFilterMode},
+    uniform::UniformType,
+};
+
+const STANDARD_SOURCE: &str = r#"
+struct VertexInput {
+  @location(0) position: vec3<f32>,
+  @location(1) uv: vec2<f32>,
+  @location(2) normal: vec3<f32>,
+#ifdef VERTEX_COLOR
+  @location(3) color: vec3<f32>,
+#endif
+};
+
+struct VertexOutput {
+  @builtin(position) position: vec4<f32>,
+  @location(0) uv: vec2<f32>,
+#ifdef VERTEX_COLOR
+  @location(1) color: vec3<f32>,
+#endif
+};
+
+struct CameraUniform {
+  projection: mat4x4<f32>,
+};
+
+struct TransformUniform {
+  transform: mat4x4<f32>,
+  transform_inv: mat4x4<f32>,
+};
+
+@group(0) @binding(0) var tex: texture_2d<f32>;
+@group(0) @binding(1) var tex_sampler: sampler;
+
+@group(1) @binding(0) var<uniform> camera: CameraUniform;
+
+@group(2) @binding(0) var<uniform> camera_transform: TransformUniform;
+
+@group(3) @binding(0) var<uniform> model_transform: TransformUniform;
+
+@vertex
+fn vs_main(in: VertexInput) -> VertexOutput {
+  var out: VertexOutput;
+  out.position = camera.projection * camera_transform.transform_inv * model_transform.transform * vec4<f32>(in.position, 1.0);
+  out.uv = in.uv;
+#ifdef VERTEX_COLOR
+  out.color = in.color;
+#endif
+  return out;
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+  let base = textureSample(tex, tex_sampler, in.uv);
+#ifdef VERTEX_COLOR
+  return vec4<f32>(base.rgb * in.color, base.a);
+#else
+  return base;
+#endif
+}
+"#;
+
+const LIT_SOURCE: &str = r#"
+struct VertexInput {
+  @location(0) position: vec3<f32>,
+  @location(1) uv: vec2<f32>,
+  @location(2) normal: vec3<f32>,
+  @location(3) color: vec3<f32>,
+};
+
+struct VertexOutput {
+  @builtin(position) position: vec4<f32>,
+  @location(0) color: vec3<f32>,
+  @location(1) world_position: vec3<f32>,
+  @location(2) normal: vec3<f32>,
+};
+
+struct CameraUniform {
+  projection: mat4x4<f32>,
+};
+
+struct TransformUniform {
+  transform: mat4x4<f32>,
+  transform_inv: mat4x4<f32>,
+};
+
+@group(0) @binding(0) var<uniform> camera: CameraUniform;
+
+@group(1) @binding(0) var<uniform> camera_transform: TransformUniform;
+
+@group(2) @binding(0) var<uniform> model_transform: TransformUniform;
+
+@group(3) @binding(0) var<uniform> lights: Lights;
+
+@vertex
+fn vs_main(in: VertexInput) -> VertexOutput {
+  var out: VertexOutput;
+  let world_position = model_transform.transform * vec4<f32>(in.position, 1.0);
+  out.position = camera.projection * camera_transform.transform_inv * world_position;
+  out.color = in.color;
+  out.world_position = world_position.xyz;
+  out.normal = (transpose(model_transform.transform_inv) * vec4<f32>(in.normal, 0.0)).xyz;
+  return out;
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+  let normal = normalize(in.normal);
+  var radiance = lights.ambient;
+  for (var i = 0u; i < lights.count; i++) {
+    radiance += light_radiance(lights.lights[i], in.world_position, normal);
+  }
+  return vec4<f32>(in.color * radiance, 1.0);
+}
+"#;
+
+/// The lit shader with the declarations of the lights it consumes.
+static LIT_SHADER_SOURCE: LazyLock<String> =
+    LazyLock::new(|| format!("{}{}", LIGHTS_WGSL, LIT_SOURCE));
+
+const UNIFORMS: &[UniformType] = &[
+    UniformType::Texture2D,
+    UniformType::Camera,
+    UniformType::CameraTransform,
+    UniformType::ModelTransform,
+];
+
+// the lit variant has no base texture, to bind the lights within the four bind groups WebGPU
+// guarantees.
+const LIT_UNIFORMS: &[UniformType] = &[
+    UniformType::Camera,
+    UniformType::CameraTransform,
+    UniformType::ModelTransform,
+    UniformType::Lights,
+];
+
+/// Built-in shaders for meshes with the standard vertex types, e.g. loaded from OBJ files.
+///
+/// The unlit variants sample the base texture of the material. Meshes loaded without a material
+/// are rendered with the unlit variant matching their vertices, chosen by
+/// [`StandardShader::for_mesh`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum StandardShader {
+    /// Renders [`Vertex3DStandard`] vertices with the base texture.
+    Standard,
+    /// Renders [`Vertex3DStandardColored`] vertices with the base texture multiplied by the
+    /// vertex color.
+    StandardColored,
+    /// Renders [`Vertex3DStandardColored`] vertices with the vertex color lit by the
+    /// [`super::uniform::UniformType::Lights`] of the world, with diffuse lighting.
+    Lit,
+}
+
+impl StandardShader {
+    /// Returns the variant rendering the vertices of the mesh.
+    pub fn for_mesh(mesh: &Mesh) -> Self {
+        if mesh.has_vertex_colors() {
+            Self::StandardColored
+        } else {
+            Self::Standard
+        }
+    }
+
+    /// Returns the debug label of the variant, also naming its shared material.
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Standard => "ravia_engine::standard",
+            Self::StandardColored => "ravia_engine::standard_colored",
+            Self::Lit => "ravia_engine::standard_lit",
+        }
+    }
+
+    /// Returns the shader config of the variant.
+    pub fn config(self) -> ShaderConfig<'static> {
+        let config = match self {
+            Self::Standard => ShaderConfig::new(STANDARD_SOURCE)
+                .with_vertex_type::<Vertex3DStandard>()
+                .with_uniforms(UNIFORMS),
+            Self::StandardColored => ShaderConfig::new(STANDARD_SOURCE)
+                .with_defines(&["VERTEX_COLOR"])
+                .with_vertex_type::<Vertex3DStandardColored>()
+                .with_uniforms(UNIFORMS),
+            Self::Lit => ShaderConfig::new(LIT_SHADER_SOURCE.as_str())
+                .with_vertex_type::<Vertex3DStandardColored>()
+                .with_uniforms(LIT_UNIFORMS),
+        };
+        config.with_label(self.label())
+    }
+
+    /// Returns the material of the variant shared in [`EngineContext::materials`], creating it
+    /// with a white base texture on first use, for the variants sampling one.
+    pub fn material(self, ctx: &EngineContext) -> Handle<Material> {
+        ctx.materials.get_or_insert_with(self.label(), || {
+            let mut material = Material::new(ctx, &self.config());
+            if self != Self::Lit {
+                let white = Texture::new_2d(
+                    ctx,
+                    math::UVec2::ONE,
+                    vec![255; 4],
+                    TextureFilterMode::Point,
+                );
+                material.set_texture(ctx, white);
+            }
+            material
+        })
+    }
+}
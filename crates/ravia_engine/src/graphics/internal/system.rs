@@ -1,10 +1,124 @@
-use crate::{ecs, engine::EngineContext};
+use std::collections::HashMap;
 
-use super::transform::Transform;
+use crate::{
+    ecs::{self, systems::CommandBuffer, world::SubWorld, Entity, EntityStore, IntoQuery},
+    engine::EngineContext,
+    event::Events,
+    math,
+    time::Time,
+    ui::UiNode,
+    window::ScreenSize,
+};
+
+use super::{
+    budget::RenderBudget,
+    camera::{primary_camera_position, Camera},
+    demo_camera::DemoCamera,
+    directional_light::DirectionalLight,
+    instances::Instances,
+    material::Material,
+    overlay::Overlay,
+    photo_mode::{PhotoCamera, PhotoMode},
+    point_light::PointLight,
+    screen_space::ScreenSpace,
+    texture_streaming::StreamedTexture,
+    transform::Transform,
+    visibility::{ComputedVisibility, Visibility},
+};
 
 /// Attaches a system of the graphics engine.
 pub fn system(builder: &mut ecs::systems::Builder) {
+    builder.add_system(update_screen_space_system());
+    builder.add_system(update_demo_camera_system());
+    builder.add_system(update_photo_mode_system());
+    builder.add_system(update_photo_camera_system());
     builder.add_system(flush_transform_system());
+    builder.add_system(flush_instances_system());
+    builder.add_system(stream_textures_system());
+    builder.add_system(select_shadow_maps_system());
+    builder.add_system(compute_visibility_system());
+}
+
+#[ecs::system(for_each)]
+fn update_screen_space(
+    camera: &mut Camera,
+    screen_space: &mut ScreenSpace,
+    #[resource] ctx: &EngineContext,
+    #[resource] screen_size: &ScreenSize,
+) {
+    if let Some(projection) = screen_space.update(screen_size.logical_size()) {
+        camera.set_projection(ctx, projection);
+    }
+}
+
+#[ecs::system(for_each)]
+fn update_demo_camera(
+    demo_camera: &mut DemoCamera,
+    transform: &mut Transform,
+    #[resource] ctx: &EngineContext,
+    #[resource] time: &Time,
+    #[resource] events: &Events,
+) {
+    demo_camera.update(time.delta_seconds(), events);
+    demo_camera.apply(ctx.gpu.handedness(), transform);
+}
+
+#[ecs::system]
+#[read_component(Camera)]
+#[read_component(Transform)]
+#[read_component(Overlay)]
+#[read_component(PhotoCamera)]
+fn update_photo_mode(
+    world: &mut SubWorld,
+    cmd: &mut CommandBuffer,
+    #[resource] photo_mode: &mut PhotoMode,
+    #[resource] ctx: &EngineContext,
+) {
+    match (photo_mode.is_active(), photo_mode.camera) {
+        (true, None) => {
+            // the free camera starts from the first scene camera.
+            let primary = <(&Camera, &Transform)>::query()
+                .filter(!ecs::component::<Overlay>() & !ecs::component::<PhotoCamera>())
+                .iter(world)
+                .filter(|(camera, _)| camera.target().is_none())
+                .min_by_key(|(camera, _)| camera.order())
+                .map(|(camera, transform)| {
+                    (
+                        *camera.projection(),
+                        *transform.position(),
+                        *transform.rotation(),
+                    )
+                });
+
+            let mut camera = Camera::perspective_with_defaults(ctx).with_order(i32::MAX);
+            let (position, rotation) = match primary {
+                Some((projection, position, rotation)) => {
+                    camera.set_projection(ctx, projection);
+                    (position, rotation)
+                }
+                None => (math::Vec3::ZERO, math::Quat::IDENTITY),
+            };
+            let transform = Transform::new(ctx, position, rotation, math::Vec3::ONE);
+
+            photo_mode.camera = Some(cmd.push((camera, transform, PhotoCamera::new(rotation))));
+            ctx.gpu.set_overlays_visible(false);
+        }
+        (false, Some(entity)) => {
+            cmd.remove(entity);
+            photo_mode.camera = None;
+            ctx.gpu.set_overlays_visible(true);
+        }
+        _ => (),
+    }
+}
+
+#[ecs::system(for_each)]
+fn update_photo_camera(
+    photo_camera: &mut PhotoCamera,
+    transform: &mut Transform,
+    #[resource] events: &Events,
+) {
+    photo_camera.update(transform, events);
 }
 
 #[ecs::system(for_each)]
@@ -12,3 +126,209 @@ pub fn system(builder: &mut ecs::systems::Builder) {
 fn flush_transform(transform: &mut Transform, #[resource] ctx: &EngineContext) {
     transform.flush(ctx);
 }
+
+/// Selects the lights rendering their shadow maps this frame within the render budget: the
+/// brightest directional light first, then the point lights brightest at the primary camera.
+#[ecs::system]
+#[read_component(Camera)]
+#[read_component(Overlay)]
+#[read_component(Transform)]
+#[write_component(DirectionalLight)]
+#[write_component(PointLight)]
+fn select_shadow_maps(world: &mut SubWorld, #[resource] budget: &RenderBudget) {
+    // only a single directional light renders its shadow map, see `Gpu::render`.
+    let directional = <(Entity, &DirectionalLight)>::query()
+        .iter(world)
+        .filter(|(_, light)| light.shadow_config().is_some())
+        .max_by(|(_, a), (_, b)| a.intensity.total_cmp(&b.intensity))
+        .map(|(entity, _)| (*entity, f32::INFINITY, 1));
+
+    let camera_position = primary_camera_position(world).unwrap_or(math::Vec3::ZERO);
+    let points = <(Entity, &PointLight, &Transform)>::query()
+        .iter(world)
+        .filter(|(_, light, _)| light.shadow_config().is_some())
+        .map(|(entity, light, transform)| {
+            let distance_squared = transform.position().distance_squared(camera_position);
+            let priority = light.intensity / (1.0 + distance_squared);
+            (*entity, priority, PointLight::SHADOW_PASSES)
+        })
+        .collect::<Vec<_>>();
+
+    let selected = budget.select_shadow_maps(directional.into_iter().chain(points));
+
+    for (entity, light) in <(Entity, &mut DirectionalLight)>::query().iter_mut(world) {
+        light.casts_shadow = selected.contains(entity) && light.shadow_config().is_some();
+    }
+    for (entity, light) in <(Entity, &mut PointLight)>::query().iter_mut(world) {
+        light.casts_shadow = selected.contains(entity) && light.shadow_config().is_some();
+    }
+}
+
+#[ecs::system(for_each)]
+fn flush_instances(instances: &mut Instances, #[resource] ctx: &EngineContext) {
+    instances.flush(ctx);
+}
+
+#[ecs::system]
+#[read_component(Camera)]
+#[read_component(Overlay)]
+#[read_component(Transform)]
+#[write_component(StreamedTexture)]
+#[write_component(Material)]
+fn stream_textures(
+    world: &mut SubWorld,
+    #[resource] ctx: &EngineContext,
+    #[resource] budget: &RenderBudget,
+) {
+    let config = ctx.gpu.texture_streaming_config();
+
+    let Some(camera_position) = primary_camera_position(world) else {
+        return;
+    };
+
+    // the mip level each texture wants at its distance, limited by its base mip.
+    let mut candidates = <(Entity, &StreamedTexture, &Transform)>::query()
+        .iter(world)
+        .map(|(entity, texture, transform)| {
+            let distance = transform.position().distance(camera_position);
+            let base_mip = texture.base_mip(config.base_size);
+            let mip = if distance <= config.full_resolution_distance {
+                0
+            } else {
+                (distance / config.full_resolution_distance).log2().ceil() as u32
+            };
+            (*entity, distance, mip.min(base_mip), base_mip, texture)
+        })
+        .collect::<Vec<_>>();
+
+    // streamed textures share the texture memory of the render budget with the other textures.
+    let resident_bytes = candidates
+        .iter()
+        .map(|(_, _, _, _, texture)| texture.resident_bytes())
+        .sum::<u64>();
+    let budget_bytes = budget.streaming_bytes(
+        config.budget_bytes,
+        ctx.gpu
+            .memory_stats()
+            .texture_bytes
+            .saturating_sub(resident_bytes),
+    );
+
+    // over the budget, drop one level at a time from the farthest textures.
+    candidates.sort_by(|a, b| b.1.total_cmp(&a.1));
+    let mut total_bytes = candidates
+        .iter()
+        .map(|(_, _, mip, _, texture)| texture.bytes_from(*mip))
+        .sum::<u64>();
+    while total_bytes > budget_bytes {
+        let mut dropped = false;
+        for (_, _, mip, base_mip, texture) in candidates.iter_mut() {
+            if total_bytes <= budget_bytes {
+                break;
+            }
+            if *mip < *base_mip {
+                total_bytes -= texture.bytes_from(*mip) - texture.bytes_from(*mip + 1);
+                *mip += 1;
+                dropped = true;
+            }
+        }
+        if !dropped {
+            break;
+        }
+    }
+
+    // upload the nearest textures first.
+    let uploads = candidates
+        .iter()
+        .rev()
+        .filter(|(_, _, mip, _, texture)| texture.resident_mip() != Some(*mip))
+        .take(config.max_uploads_per_frame)
+        .map(|(entity, _, mip, _, _)| (*entity, *mip))
+        .collect::<HashMap<_, _>>();
+    if uploads.is_empty() {
+        return;
+    }
+
+    for (entity, texture, material) in
+        <(Entity, &mut StreamedTexture, &mut Material)>::query().iter_mut(world)
+    {
+        if let Some(mip) = uploads.get(entity) {
+            material.texture = Some(texture.upload(ctx, *mip));
+        }
+    }
+}
+
+#[ecs::system]
+#[read_component(Visibility)]
+#[read_component(UiNode)]
+#[write_component(ComputedVisibility)]
+fn compute_visibility(world: &mut SubWorld, cmd: &mut CommandBuffer) {
+    let ordered_nodes = <(Entity, Option<&Visibility>, Option<&UiNode>)>::query()
+        .filter(ecs::component::<Visibility>() | ecs::component::<UiNode>())
+        .iter(world)
+        .map(|(entity, visibility, node)| {
+            (
+                *entity,
+                (
+                    visibility.copied().unwrap_or_default(),
+                    node.and_then(|node| node.parent),
+                ),
+            )
+        })
+        .collect::<Vec<_>>();
+    let nodes = ordered_nodes.iter().copied().collect::<HashMap<_, _>>();
+
+    // the entities are resolved in the order of the query rather than of the map, so that the
+    // commands are pushed in the same order every run.
+    let mut resolved = HashMap::new();
+    for (entity, _) in ordered_nodes {
+        let visible = resolve_visibility(entity, &nodes, &mut resolved, 0);
+        if let Ok(mut entry) = world.entry_mut(entity) {
+            if let Ok(current) = entry.get_component_mut::<ComputedVisibility>() {
+                *current = ComputedVisibility::new(visible);
+                continue;
+            }
+        }
+        cmd.add_component(entity, ComputedVisibility::new(visible));
+    }
+
+    // entities which no longer take part in the hierarchy are visible again.
+    for entity in <Entity>::query()
+        .filter(
+            ecs::component::<ComputedVisibility>()
+                & !ecs::component::<Visibility>()
+                & !ecs::component::<UiNode>(),
+        )
+        .iter(world)
+    {
+        cmd.remove_component::<ComputedVisibility>(*entity);
+    }
+}
+
+/// Resolves the visibility of an entity, resolving its ancestors first.
+///
+/// Entities nested too deep (e.g. in a cycle) are resolved as if they had no parent.
+fn resolve_visibility(
+    entity: Entity,
+    nodes: &HashMap<Entity, (Visibility, Option<Entity>)>,
+    resolved: &mut HashMap<Entity, bool>,
+    depth: usize,
+) -> bool {
+    const MAX_DEPTH: usize = 64;
+
+    if let Some(visible) = resolved.get(&entity) {
+        return *visible;
+    }
+
+    let (visibility, parent) = nodes[&entity];
+    let parent_visible = match parent {
+        Some(parent) if nodes.contains_key(&parent) && depth < MAX_DEPTH => {
+            resolve_visibility(parent, nodes, resolved, depth + 1)
+        }
+        _ => true,
+    };
+
+    let visible = parent_visible && visibility != Visibility::Hidden;
+    resolved.insert(entity, visible);
+    visible
+}
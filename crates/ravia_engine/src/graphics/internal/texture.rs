@@ -1,30 +1,30 @@
-use std::ops::Deref;
+use std::{ops::Deref, sync::Arc};
 
 use wgpu::util::DeviceExt;
 
 use crate::{engine::EngineContext, math};
 
-use super::uniform::Uniform;
+use super::{
+    binary_texture::mip_level_bytes,
+    memory::{MemoryAllocation, MemoryCategory},
+    sampler::{SamplerKey, TextureAddressMode},
+    uniform::Uniform,
+};
 
 /// Filter mode for the texture.
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum TextureFilterMode {
     /// Nearest neighbor sampling.
     Point,
     /// Bilinear interpolation in uv space.
+    #[default]
     Bilinear,
     /// Trilinear interpolation in uv space and mipmap levels.
     Trilinear,
 }
 
-impl Default for TextureFilterMode {
-    fn default() -> Self {
-        Self::Bilinear
-    }
-}
-
 impl TextureFilterMode {
-    fn mag_filter(&self) -> wgpu::FilterMode {
+    pub(super) fn mag_filter(&self) -> wgpu::FilterMode {
         match self {
             Self::Point => wgpu::FilterMode::Nearest,
             Self::Bilinear => wgpu::FilterMode::Linear,
@@ -32,7 +32,7 @@ impl TextureFilterMode {
         }
     }
 
-    fn min_filter(&self) -> wgpu::FilterMode {
+    pub(super) fn min_filter(&self) -> wgpu::FilterMode {
         match self {
             Self::Point => wgpu::FilterMode::Nearest,
             Self::Bilinear => wgpu::FilterMode::Linear,
@@ -40,7 +40,7 @@ impl TextureFilterMode {
         }
     }
 
-    fn mipmap_filter(&self) -> wgpu::FilterMode {
+    pub(super) fn mipmap_filter(&self) -> wgpu::FilterMode {
         match self {
             Self::Point => wgpu::FilterMode::Nearest,
             Self::Bilinear => wgpu::FilterMode::Nearest,
@@ -50,13 +50,16 @@ impl TextureFilterMode {
 }
 
 /// [`Texture`] contains the WebGPU texture and its underlying resources, and abind group.
+///
+/// Samplers are shared between textures sampled the same way.
 #[derive(Debug)]
 pub struct Texture {
-    _texture: wgpu::Texture,
+    _texture: Arc<wgpu::Texture>,
     _texture_view: wgpu::TextureView,
-    _sampler: wgpu::Sampler,
+    _sampler: Arc<wgpu::Sampler>,
     bind_group: wgpu::BindGroup,
-    filter_mode: TextureFilterMode,
+    sampler_key: SamplerKey,
+    _allocation: MemoryAllocation,
 }
 
 impl Texture {
@@ -67,61 +70,126 @@ impl Texture {
         data: D,
         filter_mode: TextureFilterMode,
     ) -> Self {
-        let texture = ctx.gpu.device.create_texture_with_data(
-            &ctx.gpu.queue,
-            &wgpu::TextureDescriptor {
-                size: wgpu::Extent3d {
-                    width: size.x,
-                    height: size.y,
-                    depth_or_array_layers: 1,
+        Self::new_2d_with_mips(ctx, size, 1, data, filter_mode)
+    }
+
+    /// Creates a new 2D [`Texture`] with a precomputed mip chain.
+    ///
+    /// `data` contains every mip level in RGBA8, from the largest to the smallest, each level
+    /// half the size of the previous one (rounded down, at least 1).
+    pub fn new_2d_with_mips<D: Deref<Target = [u8]>>(
+        ctx: &EngineContext,
+        size: math::UVec2,
+        mip_level_count: u32,
+        data: D,
+        filter_mode: TextureFilterMode,
+    ) -> Self {
+        const LABEL: &str = "ravia_engine::texture_2d";
+
+        // textures larger than the adapter supports are created without their largest levels.
+        let max_size = ctx.gpu.capabilities().max_texture_size();
+        let (mut size, mut mip_level_count, mut data) = (size, mip_level_count, &data[..]);
+        while size.max_element() > max_size && mip_level_count > 1 {
+            let bytes = mip_level_bytes(size, 0) as usize;
+            if bytes >= data.len() {
+                break;
+            }
+            data = &data[bytes..];
+            size = (size / 2).max(math::UVec2::ONE);
+            mip_level_count -= 1;
+        }
+        if size.max_element() > max_size {
+            ctx.gpu.report_error(format!(
+                "{}: size {}x{} exceeds the maximum texture size {}",
+                LABEL, size.x, size.y, max_size
+            ));
+        }
+
+        let texture = ctx.gpu.error_scope(LABEL, |device| {
+            device.create_texture_with_data(
+                &ctx.gpu.queue,
+                &wgpu::TextureDescriptor {
+                    size: wgpu::Extent3d {
+                        width: size.x,
+                        height: size.y,
+                        depth_or_array_layers: 1,
+                    },
+                    mip_level_count,
+                    sample_count: 1,
+                    dimension: wgpu::TextureDimension::D2,
+                    format: wgpu::TextureFormat::Rgba8UnormSrgb,
+                    usage: wgpu::TextureUsages::TEXTURE_BINDING
+                        | wgpu::TextureUsages::COPY_DST
+                        | wgpu::TextureUsages::COPY_SRC,
+                    label: Some(LABEL),
+                    view_formats: &[],
                 },
-                mip_level_count: 1,
-                sample_count: 1,
-                dimension: wgpu::TextureDimension::D2,
-                format: wgpu::TextureFormat::Rgba8UnormSrgb,
-                usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
-                label: None,
-                view_formats: &[],
-            },
-            Default::default(),
-            &data,
-        );
+                Default::default(),
+                data,
+            )
+        });
 
         let texture_view = texture.create_view(&wgpu::TextureViewDescriptor::default());
-        let sampler = ctx.gpu.device.create_sampler(&wgpu::SamplerDescriptor {
-            address_mode_u: wgpu::AddressMode::ClampToEdge,
-            address_mode_v: wgpu::AddressMode::ClampToEdge,
-            address_mode_w: wgpu::AddressMode::ClampToEdge,
-            mag_filter: filter_mode.mag_filter(),
-            min_filter: filter_mode.min_filter(),
-            mipmap_filter: filter_mode.mipmap_filter(),
-            ..Default::default()
-        });
+        let sampler_key = SamplerKey {
+            filter_mode,
+            address_mode: TextureAddressMode::default(),
+            anisotropy: 1,
+        };
+        let sampler = ctx.gpu.sampler(sampler_key);
+        let bind_group = Self::create_bind_group(ctx, &texture_view, &sampler);
 
-        let bind_group = ctx
+        // the textures are created with a 4 bytes per pixel format.
+        let allocation = ctx
             .gpu
-            .device
-            .create_bind_group(&wgpu::BindGroupDescriptor {
-                layout: &ctx.gpu.default_bind_group_layouts.texture_2d,
-                entries: &[
-                    wgpu::BindGroupEntry {
-                        binding: 0,
-                        resource: wgpu::BindingResource::TextureView(&texture_view),
-                    },
-                    wgpu::BindGroupEntry {
-                        binding: 1,
-                        resource: wgpu::BindingResource::Sampler(&sampler),
-                    },
-                ],
-                label: None,
-            });
+            .track_memory(MemoryCategory::Texture, data.len() as u64);
 
         Self {
-            _texture: texture,
+            _texture: Arc::new(texture),
             _texture_view: texture_view,
             _sampler: sampler,
             bind_group,
+            sampler_key,
+            _allocation: allocation,
+        }
+    }
+
+    /// Creates a 2D [`Texture`] sampling a texture owned elsewhere, e.g. by a
+    /// [`super::render_texture::RenderTexture`] which tracks its memory.
+    pub(super) fn from_shared(
+        ctx: &EngineContext,
+        texture: Arc<wgpu::Texture>,
+        filter_mode: TextureFilterMode,
+    ) -> Self {
+        let texture_view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+        let sampler_key = SamplerKey {
             filter_mode,
+            address_mode: TextureAddressMode::default(),
+            anisotropy: 1,
+        };
+        let sampler = ctx.gpu.sampler(sampler_key);
+        let bind_group = Self::create_bind_group(ctx, &texture_view, &sampler);
+
+        Self {
+            _texture: texture,
+            _texture_view: texture_view,
+            _sampler: sampler,
+            bind_group,
+            sampler_key,
+            _allocation: ctx.gpu.track_memory(MemoryCategory::Texture, 0),
+        }
+    }
+
+    /// Creates a 2D [`Texture`] sampling a 4 bytes per pixel texture created by the engine, e.g.
+    /// by a [`super::procedural_texture::ProceduralTexture`].
+    pub(super) fn from_owned(
+        ctx: &EngineContext,
+        texture: wgpu::Texture,
+        filter_mode: TextureFilterMode,
+    ) -> Self {
+        let size = texture.width() as u64 * texture.height() as u64 * 4;
+        Self {
+            _allocation: ctx.gpu.track_memory(MemoryCategory::Texture, size),
+            ..Self::from_shared(ctx, Arc::new(texture), filter_mode)
         }
     }
 
@@ -151,43 +219,133 @@ impl Texture {
         )
     }
 
+    /// Creates the error 2D [`Texture`] substituted for textures which fail to load: a magenta
+    /// and black checkerboard pattern.
+    pub fn error_2d(ctx: &EngineContext) -> Self {
+        const MAGENTA: [u8; 4] = [255, 0, 255, 255];
+        const BLACK: [u8; 4] = [0, 0, 0, 255];
+
+        let (width, height) = (8, 8);
+        let data = (0..width * height)
+            .flat_map(|i| match (i / width + i % width) % 2 {
+                0 => MAGENTA,
+                _ => BLACK,
+            })
+            .collect::<Vec<_>>();
+
+        Self::new_2d(
+            ctx,
+            math::uvec2(width as u32, height as u32),
+            data,
+            TextureFilterMode::Point,
+        )
+    }
+
+    /// Returns the underlying [`wgpu::Texture`], e.g. to read it back with
+    /// [`super::gpu::Gpu::read_texture`].
+    pub fn texture(&self) -> &wgpu::Texture {
+        &self._texture
+    }
+
+    /// Returns the view of the texture.
+    pub(super) fn view(&self) -> &wgpu::TextureView {
+        &self._texture_view
+    }
+
+    /// Returns the sampler of the texture.
+    pub(super) fn sampler(&self) -> &wgpu::Sampler {
+        &self._sampler
+    }
+
     /// Returns the filter mode for the texture.
     pub fn filter_mode(&self) -> TextureFilterMode {
-        self.filter_mode
+        self.sampler_key.filter_mode
     }
 
     /// Sets the filter mode for the texture.
     pub fn set_filter_mode(&mut self, ctx: &EngineContext, filter_mode: TextureFilterMode) {
-        if self.filter_mode == filter_mode {
+        self.set_sampler(
+            ctx,
+            SamplerKey {
+                filter_mode,
+                ..self.sampler_key
+            },
+        );
+    }
+
+    /// Returns the address mode for the texture.
+    pub fn address_mode(&self) -> TextureAddressMode {
+        self.sampler_key.address_mode
+    }
+
+    /// Sets the address mode for the texture.
+    pub fn set_address_mode(&mut self, ctx: &EngineContext, address_mode: TextureAddressMode) {
+        self.set_sampler(
+            ctx,
+            SamplerKey {
+                address_mode,
+                ..self.sampler_key
+            },
+        );
+    }
+
+    /// Returns the maximum anisotropy for the texture.
+    pub fn anisotropy(&self) -> u16 {
+        self.sampler_key.anisotropy
+    }
+
+    /// Sets the maximum anisotropy for the texture, from 1 (disabled) to 16.
+    ///
+    /// Anisotropic filtering only applies to [`TextureFilterMode::Trilinear`] textures.
+    pub fn set_anisotropy(&mut self, ctx: &EngineContext, anisotropy: u16) {
+        self.set_sampler(
+            ctx,
+            SamplerKey {
+                anisotropy,
+                ..self.sampler_key
+            },
+        );
+    }
+
+    /// Switches to the shared sampler for the state, rebuilding the bind group only if the sampler
+    /// changed.
+    fn set_sampler(&mut self, ctx: &EngineContext, sampler_key: SamplerKey) {
+        if self.sampler_key == sampler_key {
             return;
         }
 
-        self.filter_mode = filter_mode;
+        self.sampler_key = sampler_key;
 
-        self._sampler = ctx.gpu.device.create_sampler(&wgpu::SamplerDescriptor {
-            mag_filter: filter_mode.mag_filter(),
-            min_filter: filter_mode.min_filter(),
-            mipmap_filter: filter_mode.mipmap_filter(),
-            ..Default::default()
-        });
+        let sampler = ctx.gpu.sampler(sampler_key);
+        if Arc::ptr_eq(&sampler, &self._sampler) {
+            return;
+        }
 
-        self.bind_group = ctx
-            .gpu
+        self.bind_group = Self::create_bind_group(ctx, &self._texture_view, &sampler);
+        self._sampler = sampler;
+    }
+
+    fn create_bind_group(
+        ctx: &EngineContext,
+        texture_view: &wgpu::TextureView,
+        sampler: &wgpu::Sampler,
+    ) -> wgpu::BindGroup {
+        ctx.gpu
             .device
             .create_bind_group(&wgpu::BindGroupDescriptor {
                 layout: &ctx.gpu.default_bind_group_layouts.texture_2d,
                 entries: &[
                     wgpu::BindGroupEntry {
                         binding: 0,
-                        resource: wgpu::BindingResource::TextureView(&self._texture_view),
+                        resource: wgpu::BindingResource::TextureView(texture_view),
                     },
                     wgpu::BindGroupEntry {
                         binding: 1,
-                        resource: wgpu::BindingResource::Sampler(&self._sampler),
+                        resource: wgpu::BindingResource::Sampler(sampler),
                     },
                 ],
-                label: None,
-            });
+                label: Some("ravia_engine::texture_2d"),
+            })
     }
 }
 
@@ -0,0 +1,124 @@
+use crate::{ecs, engine::EngineContext, math};
+
+use super::{
+    binary_texture::{mip_level_bytes, parse_binary_texture},
+    texture::{Texture, TextureFilterMode},
+};
+
+/// [`TextureStreamingConfig`] holds the configuration of texture streaming.
+#[derive(Debug, Clone, Copy)]
+pub struct TextureStreamingConfig {
+    /// GPU memory available to the resident mip levels of streamed textures, in bytes.
+    ///
+    /// When the textures near the camera exceed the budget, the high-resolution mip levels of the
+    /// farthest textures are streamed out first.
+    pub budget_bytes: u64,
+    /// Largest width or height of the low-resolution base mip level, which is always resident
+    /// regardless of the distance and the budget.
+    pub base_size: u32,
+    /// Distance from the camera up to which textures are streamed in at full resolution. Every
+    /// doubling of the distance drops one mip level.
+    pub full_resolution_distance: f32,
+    /// Maximum number of textures uploaded per frame, to spread the uploads over frames.
+    pub max_uploads_per_frame: usize,
+}
+
+impl Default for TextureStreamingConfig {
+    fn default() -> Self {
+        Self {
+            budget_bytes: 256 * 1024 * 1024,
+            base_size: 64,
+            full_resolution_distance: 10.0,
+            max_uploads_per_frame: 4,
+        }
+    }
+}
+
+/// A [`StreamedTexture`] component keeps the full mip chain of a texture on the CPU, and uploads
+/// only the mip levels needed at the distance of the entity from the camera.
+///
+/// The resident levels are uploaded as the texture of the [`super::material::Material`] of the
+/// entity, whenever they change.
+#[derive(Debug)]
+pub struct StreamedTexture {
+    size: math::UVec2,
+    mip_level_count: u32,
+    mips: Vec<u8>,
+    filter_mode: TextureFilterMode,
+    resident_mip: Option<u32>,
+}
+
+assert_impl_all!(StreamedTexture: ecs::storage::Component);
+
+impl StreamedTexture {
+    /// Creates a new [`StreamedTexture`] from a buffer containing a binary texture.
+    ///
+    /// See [`super::binary_texture::load_texture_from_binary`] for the format.
+    pub fn from_binary(data: &[u8], filter_mode: TextureFilterMode) -> Result<Self, anyhow::Error> {
+        let (size, mip_level_count, mips) = parse_binary_texture(data)?;
+        Ok(Self {
+            size,
+            mip_level_count,
+            mips: mips.to_vec(),
+            filter_mode,
+            resident_mip: None,
+        })
+    }
+
+    /// Returns the size of the full resolution mip level.
+    pub fn size(&self) -> math::UVec2 {
+        self.size
+    }
+
+    /// Returns the number of mip levels.
+    pub fn mip_level_count(&self) -> u32 {
+        self.mip_level_count
+    }
+
+    /// Returns the highest resolution mip level uploaded to the GPU, or `None` if the texture has
+    /// not been uploaded yet.
+    pub fn resident_mip(&self) -> Option<u32> {
+        self.resident_mip
+    }
+
+    /// Returns the GPU memory used by the resident mip levels, in bytes.
+    pub fn resident_bytes(&self) -> u64 {
+        self.resident_mip.map_or(0, |mip| self.bytes_from(mip))
+    }
+
+    /// Returns the size in bytes of the mip chain starting at the given level.
+    pub(super) fn bytes_from(&self, mip: u32) -> u64 {
+        (mip..self.mip_level_count)
+            .map(|level| mip_level_bytes(self.size, level))
+            .sum()
+    }
+
+    /// Returns the level of the base mip, the first level no larger than `base_size`.
+    pub(super) fn base_mip(&self, base_size: u32) -> u32 {
+        let last = self.mip_level_count.saturating_sub(1);
+        (0..last)
+            .find(|level| (self.size.x >> level).max(self.size.y >> level) <= base_size)
+            .unwrap_or(last)
+    }
+
+    /// Forgets the resident levels, e.g. lost along with the device, so that they are uploaded
+    /// again.
+    pub(super) fn invalidate(&mut self) {
+        self.resident_mip = None;
+    }
+
+    /// Uploads the mip chain starting at the given level as a new texture.
+    pub(super) fn upload(&mut self, ctx: &EngineContext, mip: u32) -> Texture {
+        let offset = (self.bytes_from(0) - self.bytes_from(mip)) as usize;
+        let size = math::uvec2((self.size.x >> mip).max(1), (self.size.y >> mip).max(1));
+
+        self.resident_mip = Some(mip);
+        Texture::new_2d_with_mips(
+            ctx,
+            size,
+            self.mip_level_count - mip,
+            &self.mips[offset..],
+            self.filter_mode,
+        )
+    }
+}
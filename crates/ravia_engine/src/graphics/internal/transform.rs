@@ -1,6 +1,9 @@
 use crate::{ecs, engine::EngineContext, math};
 
-use super::uniform::Uniform;
+use super::{
+    memory::{MemoryAllocation, MemoryCategory},
+    uniform::Uniform,
+};
 
 /// A [`Transform`] component describes the position, rotation, and scale of an entity.
 #[derive(Debug)]
@@ -15,6 +18,7 @@ pub struct Transform {
 
     _buffer: wgpu::Buffer,
     bind_group: wgpu::BindGroup,
+    _allocation: MemoryAllocation,
 }
 
 assert_impl_all!(Transform: ecs::storage::Component);
@@ -28,7 +32,7 @@ impl Transform {
         scale: math::Vec3,
     ) -> Self {
         let buffer = ctx.gpu.device.create_buffer(&wgpu::BufferDescriptor {
-            label: None,
+            label: Some("ravia_engine::transform"),
             size: 2 * std::mem::size_of::<math::Mat4>() as wgpu::BufferAddress,
             usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
             mapped_at_creation: false,
@@ -38,7 +42,7 @@ impl Transform {
             .gpu
             .device
             .create_bind_group(&wgpu::BindGroupDescriptor {
-                label: None,
+                label: Some("ravia_engine::transform"),
                 layout: &ctx.gpu.default_bind_group_layouts.transform,
                 entries: &[wgpu::BindGroupEntry {
                     binding: 0,
@@ -53,6 +57,7 @@ impl Transform {
             dirty: true,
             transform: math::Mat4::IDENTITY,
             transform_inv: math::Mat4::IDENTITY,
+            _allocation: ctx.gpu.track_memory(MemoryCategory::Uniform, buffer.size()),
             _buffer: buffer,
             bind_group,
         }
@@ -108,7 +113,7 @@ impl Transform {
         self.dirty = true;
     }
 
-    /// Returns the transformation matrix of the transform.
+    /// Returns the transformation matrix of the transform, as of the last flush.
     pub fn transform(&self) -> &math::Mat4 {
         &self.transform
     }
@@ -118,14 +123,19 @@ impl Transform {
         &self.transform_inv
     }
 
+    /// Returns the transformation matrix of the current position, rotation and scale, including
+    /// the changes not flushed yet.
+    pub fn matrix(&self) -> math::Mat4 {
+        math::Mat4::from_scale_rotation_translation(self.scale, self.rotation, self.position)
+    }
+
     /// Flushes the changes to the transformation matrix to the GPU.
     pub fn flush(&mut self, ctx: &EngineContext) {
         if !self.dirty {
             return;
         }
 
-        self.transform =
-            math::Mat4::from_scale_rotation_translation(self.scale, self.rotation, self.position);
+        self.transform = self.matrix();
         self.transform_inv = self.transform.inverse();
         self.dirty = false;
 
@@ -16,4 +16,24 @@ pub enum UniformType {
     CameraTransform,
     /// Binds a model (mesh) [`super::transform::Transform`] type as a uniform.
     ModelTransform,
+    /// Binds the joint matrices of a [`super::skin::Skin`] type as a uniform.
+    Joints,
+    /// Binds the engine-managed [`super::frame::FrameUniform`] as a uniform.
+    Frame,
+    /// Binds a [`super::lightmap::Lightmap`] type as a uniform.
+    Lightmap,
+    /// Binds the normal map of a [`super::material::Material`] as a uniform, with the same layout
+    /// as [`UniformType::Texture2D`].
+    NormalMap,
+    /// Binds the shadow map of a [`super::directional_light::DirectionalLight`] as a uniform, with
+    /// the depth texture at binding 0, its comparison sampler at binding 1 and the `Shadow` of
+    /// [`super::directional_light::SHADOW_WGSL`] at binding 2.
+    ShadowMap,
+    /// Binds the cube shadow map of the [`super::point_light::PointLight`] nearest to the mesh as
+    /// a uniform, with the cube depth texture at binding 0, its comparison sampler at binding 1
+    /// and the `PointShadow` of [`super::point_light::POINT_SHADOW_WGSL`] at binding 2.
+    PointShadowMap,
+    /// Binds the engine-managed lights of the world as a uniform, with the `Lights` of
+    /// [`super::lights::LIGHTS_WGSL`] at binding 0.
+    Lights,
 }
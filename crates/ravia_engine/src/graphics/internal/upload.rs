@@ -0,0 +1,48 @@
+use std::sync::{Arc, Mutex};
+
+use crate::engine::EngineContext;
+
+/// An [`Upload`] creates a GPU asset, e.g. a [`super::mesh::Mesh`] or a
+/// [`super::texture::Texture`] from a loaded resource, without blocking the frame.
+///
+/// On native platforms the asset is parsed, created and written on a separate thread, and
+/// [`Upload::poll`] returns it once the queue has submitted the writes. On the web, where the
+/// device cannot be shared between threads, the asset is created immediately.
+#[derive(Debug)]
+pub(crate) struct Upload<T> {
+    result: Arc<Mutex<Option<T>>>,
+}
+
+impl<T: Send + 'static> Upload<T> {
+    /// Spawns the creation of the asset.
+    pub fn spawn(
+        ctx: &EngineContext,
+        create: impl FnOnce(&EngineContext) -> T + Send + 'static,
+    ) -> Self {
+        let result = Arc::new(Mutex::new(None));
+
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            let ctx = ctx.clone();
+            let result = result.clone();
+            std::thread::spawn(move || {
+                let asset = create(&ctx);
+                // submit the writes staged by the creation, so that the asset is resident by the
+                // time it is drawn, rather than flushed along with the frame.
+                ctx.gpu.queue.submit([]);
+                *result.lock().unwrap() = Some(asset);
+            });
+        }
+        #[cfg(target_arch = "wasm32")]
+        {
+            *result.lock().unwrap() = Some(create(ctx));
+        }
+
+        Self { result }
+    }
+
+    /// Takes the created asset, if the upload has completed.
+    pub fn poll(&self) -> Option<T> {
+        self.result.lock().unwrap().take()
+    }
+}
@@ -0,0 +1,188 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+use crate::math;
+
+use super::{depth::DEPTH_FORMAT, gpu::Gpu};
+
+/// The rectangle of the frame a [`super::camera::Camera`] draws into, in fractions of the frame
+/// from its top-left corner, e.g. the left half of the frame for split-screen.
+///
+/// The projection of the camera is not adjusted to the viewport, so perspective cameras are
+/// created with the aspect ratio of the viewport.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Viewport {
+    pub origin: math::Vec2,
+    pub size: math::Vec2,
+}
+
+impl Default for Viewport {
+    fn default() -> Self {
+        Self::FULL
+    }
+}
+
+impl Viewport {
+    /// The whole frame.
+    pub const FULL: Self = Self {
+        origin: math::Vec2::ZERO,
+        size: math::Vec2::ONE,
+    };
+
+    /// Creates a new [`Viewport`].
+    pub fn new(origin: math::Vec2, size: math::Vec2) -> Self {
+        Self { origin, size }
+    }
+
+    /// Returns the position relative to the viewport of a position in pixels of a frame of the
+    /// given size, normalized from `(0, 0)` at the top-left corner of the viewport to `(1, 1)` at
+    /// its bottom-right corner. Positions outside the viewport are outside of this range.
+    pub fn normalize(&self, position: math::Vec2, frame_size: math::Vec2) -> math::Vec2 {
+        (position / frame_size - self.origin) / self.size
+    }
+
+    /// Returns true if the position, in pixels of a frame of the given size, is in the viewport.
+    pub fn contains(&self, position: math::Vec2, frame_size: math::Vec2) -> bool {
+        let position = self.normalize(position, frame_size);
+        position.cmpge(math::Vec2::ZERO).all() && position.cmplt(math::Vec2::ONE).all()
+    }
+
+    /// Returns the load operation of a pass drawing into the viewport, and the color the viewport
+    /// is cleared to after the pass begins, if the clear of the pass would exceed the viewport.
+    pub(super) fn load_op(
+        &self,
+        load: wgpu::LoadOp<wgpu::Color>,
+    ) -> (wgpu::LoadOp<wgpu::Color>, Option<wgpu::Color>) {
+        match load {
+            wgpu::LoadOp::Clear(color) if *self != Self::FULL => (wgpu::LoadOp::Load, Some(color)),
+            load => (load, None),
+        }
+    }
+
+    /// Returns the origin and the size of the viewport in pixels of a frame of the given size,
+    /// clamped into the frame.
+    pub(super) fn pixels(&self, frame_size: math::UVec2) -> (math::UVec2, math::UVec2) {
+        let frame_size = frame_size.as_vec2();
+        let min = (self.origin * frame_size)
+            .round()
+            .clamp(math::Vec2::ZERO, frame_size);
+        let max = ((self.origin + self.size) * frame_size)
+            .round()
+            .clamp(min, frame_size);
+        (min.as_uvec2(), (max - min).as_uvec2())
+    }
+}
+
+const CLEAR_SOURCE: &str = "
+@vertex
+fn vs_main(@builtin(vertex_index) index: u32) -> @builtin(position) vec4<f32> {
+  // a triangle covering the frame, cut to the viewport by the scissor rectangle.
+  let uv = vec2<f32>(f32((index << 1u) & 2u), f32(index & 2u));
+  return vec4<f32>(uv * 2.0 - 1.0, 0.0, 1.0);
+}
+
+@fragment
+fn fs_main() -> @location(0) vec4<f32> {
+  return vec4<f32>(1.0);
+}
+";
+
+/// Clears the viewports of the cameras which do not cover the frame, as the clear of a render
+/// pass covers the whole attachment. The color is the blend constant of the pass, so that a
+/// pipeline per target format covers every color.
+#[derive(Debug, Default)]
+pub(super) struct ViewportClearPipelines {
+    pipelines: Mutex<HashMap<wgpu::TextureFormat, Arc<wgpu::RenderPipeline>>>,
+}
+
+impl ViewportClearPipelines {
+    const LABEL: &str = "ravia_engine::viewport_clear";
+
+    /// Restricts the render pass to the viewport, in a target of the given size and format, and
+    /// clears the viewport to the color if any.
+    pub fn apply(
+        &self,
+        gpu: &Gpu,
+        render_pass: &mut wgpu::RenderPass,
+        viewport: Viewport,
+        (size, format): (math::UVec2, wgpu::TextureFormat),
+        clear: Option<wgpu::Color>,
+    ) {
+        let (origin, size) = viewport.pixels(size);
+        render_pass.set_viewport(
+            origin.x as f32,
+            origin.y as f32,
+            size.x as f32,
+            size.y as f32,
+            0.0,
+            1.0,
+        );
+        render_pass.set_scissor_rect(origin.x, origin.y, size.x, size.y);
+
+        if let Some(color) = clear {
+            let pipeline = self
+                .pipelines
+                .lock()
+                .unwrap()
+                .entry(format)
+                .or_insert_with(|| Arc::new(Self::create(gpu, format)))
+                .clone();
+            render_pass.set_pipeline(&pipeline);
+            render_pass.set_blend_constant(color);
+            render_pass.draw(0..3, 0..1);
+        }
+    }
+
+    fn create(gpu: &Gpu, format: wgpu::TextureFormat) -> wgpu::RenderPipeline {
+        let shader_module = gpu
+            .device
+            .create_shader_module(wgpu::ShaderModuleDescriptor {
+                label: Some(Self::LABEL),
+                source: wgpu::ShaderSource::Wgsl(CLEAR_SOURCE.into()),
+            });
+        let constant = wgpu::BlendComponent {
+            src_factor: wgpu::BlendFactor::Constant,
+            dst_factor: wgpu::BlendFactor::Zero,
+            operation: wgpu::BlendOperation::Add,
+        };
+
+        gpu.device
+            .create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+                label: Some(Self::LABEL),
+                layout: None,
+                vertex: wgpu::VertexState {
+                    module: &shader_module,
+                    entry_point: Some("vs_main"),
+                    buffers: &[],
+                    compilation_options: wgpu::PipelineCompilationOptions::default(),
+                },
+                fragment: Some(wgpu::FragmentState {
+                    module: &shader_module,
+                    entry_point: Some("fs_main"),
+                    targets: &[Some(wgpu::ColorTargetState {
+                        format,
+                        blend: Some(wgpu::BlendState {
+                            color: constant,
+                            alpha: constant,
+                        }),
+                        write_mask: wgpu::ColorWrites::ALL,
+                    })],
+                    compilation_options: wgpu::PipelineCompilationOptions::default(),
+                }),
+                primitive: wgpu::PrimitiveState::default(),
+                // the depth buffer is cleared by the pass already.
+                depth_stencil: Some(wgpu::DepthStencilState {
+                    format: DEPTH_FORMAT,
+                    depth_write_enabled: false,
+                    depth_compare: wgpu::CompareFunction::Always,
+                    stencil: wgpu::StencilState::default(),
+                    bias: wgpu::DepthBiasState::default(),
+                }),
+                multisample: wgpu::MultisampleState::default(),
+                multiview: None,
+                cache: gpu.pipeline_cache(),
+            })
+    }
+}
@@ -0,0 +1,54 @@
+use crate::ecs;
+
+/// A [`Visibility`] component shows or hides an entity in every render queue, without removing
+/// its [`super::mesh::Mesh`] or [`super::material::Material`].
+///
+/// Entities inherit the visibility of their parent [`crate::ui::UiNode`] by default. Entities
+/// without a [`Visibility`] component are visible unless an ancestor is hidden. The resolved
+/// visibility is written to the [`ComputedVisibility`] component of the entity every frame.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Visibility {
+    /// Follows the visibility of the parent, or is visible without a parent.
+    #[default]
+    Inherit,
+    /// Visible, unless an ancestor is hidden.
+    Visible,
+    /// Hidden, along with the descendants which inherit their visibility.
+    Hidden,
+}
+
+assert_impl_all!(Visibility: ecs::storage::Component);
+
+/// The visibility of an entity, resolved from the [`Visibility`] of the entity and its ancestors.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ComputedVisibility {
+    visible: bool,
+}
+
+assert_impl_all!(ComputedVisibility: ecs::storage::Component);
+
+impl ComputedVisibility {
+    pub(super) fn new(visible: bool) -> Self {
+        Self { visible }
+    }
+
+    /// Returns true if the entity is rendered.
+    pub fn is_visible(&self) -> bool {
+        self.visible
+    }
+}
+
+/// Returns true if an entity with the given components is rendered.
+///
+/// The [`ComputedVisibility`] is resolved one frame after a [`Visibility`] is added, so a newly
+/// hidden entity falls back to its own [`Visibility`] until then.
+pub(super) fn is_rendered(
+    visibility: Option<&Visibility>,
+    computed: Option<&ComputedVisibility>,
+) -> bool {
+    match (computed, visibility) {
+        (Some(computed), _) => computed.is_visible(),
+        (None, Some(Visibility::Hidden)) => false,
+        _ => true,
+    }
+}
@@ -2,17 +2,62 @@
 mod internal;
 
 pub use internal::{
-    camera::Camera,
-    gpu::Gpu,
-    material::Material,
+    binary_mesh::{
+        load_mesh_from_binary, load_mesh_from_binary_with_label,
+        load_mesh_from_binary_with_options, BINARY_MESH_EXTENSION,
+    },
+    binary_texture::{load_texture_from_binary, BINARY_TEXTURE_EXTENSION},
+    budget::RenderBudget,
+    camera::{Camera, CameraClear},
+    capabilities::GpuCapabilities,
+    coordinates::{Handedness, MeshImportOptions},
+    cubemap::{load_cubemap_from_binary, load_cubemap_from_hdr, Cubemap, HDR_EXTENSION},
+    custom_pass::{CustomPass, CustomPassContext, CustomPassId, CustomPassStage, CustomPasses},
+    debug_view::DebugView,
+    demo_camera::DemoCamera,
+    directional_light::{DirectionalLight, ShadowConfig, SHADOW_WGSL},
+    frame::FrameUniform,
+    frame_graph::{FrameAttachment, FrameGraph, FramePass, FramePassKind},
+    frame_stats_overlay::FrameStatsOverlayConfig,
+    gizmo::GizmoConfig,
+    gpu::{CameraFallback, Gpu, GpuConfig},
+    instances::Instances,
+    lightmap::{bake_ambient_occlusion, Lightmap},
+    lights::{AmbientLight, LIGHTS_WGSL, MAX_LIGHTS},
+    material::{Material, MaterialInstance, TextureSlot},
+    memory::{MemoryCategory, MemoryStats},
     mesh::{
-        load_mesh_from_obj, Mesh, Vertex, Vertex2D, Vertex2DColor, Vertex2DTexture, Vertex3D,
-        Vertex3DStandard, Vertex3DStandardColored, Vertex3DTexture, VertexStandardColoredData,
-        VertexStandardData,
+        load_mesh_from_obj, load_mesh_from_obj_with_label, load_mesh_from_obj_with_options, Mesh,
+        Vertex, Vertex2D, Vertex2DColor, Vertex2DTexture, Vertex3D, Vertex3DSkinned,
+        Vertex3DStandard, Vertex3DStandardColored, Vertex3DStandardUv2, Vertex3DTexture,
+        VertexSkinnedData, VertexStandardColoredData, VertexStandardData, VertexStandardUv2Data,
     },
-    shader::{Shader, ShaderConfig},
+    mesh_data::MeshCpuData,
+    overlay::Overlay,
+    photo_mode::{PhotoCamera, PhotoMode},
+    point_light::{PointLight, PointShadowConfig, POINT_SHADOW_WGSL},
+    post_process::{PostProcessEffect, PostProcessId, PostProcessStack},
+    present::DisplayAdjustment,
+    procedural_texture::{ProceduralBlendMode, ProceduralTexture},
+    readback::{Readback, ReadbackError},
+    render_order::{RenderLayer, RenderOrder},
+    render_texture::RenderTexture,
+    sampler::TextureAddressMode,
+    screen_space::{ScreenOrigin, ScreenSpace},
+    shader::{Shader, ShaderConfig, VertexBufferLayout},
+    skin::Skin,
+    skybox::Skybox,
+    spot_light::SpotLight,
+    standard::StandardShader,
     system::system,
     texture::{Texture, TextureFilterMode},
+    texture_streaming::{StreamedTexture, TextureStreamingConfig},
     transform::Transform,
     uniform::{Uniform, UniformType},
+    viewport::Viewport,
+    visibility::{ComputedVisibility, Visibility},
+};
+
+pub(crate) use internal::{
+    camera::primary_camera_position, restore::restore_world, upload::Upload,
 };
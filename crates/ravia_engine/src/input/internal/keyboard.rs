@@ -0,0 +1,138 @@
+use std::collections::{HashMap, HashSet};
+
+use winit::keyboard::{Key, KeyCode};
+
+/// [`Keyboard`] holds the state of the keyboard, read through
+/// [`crate::engine::EngineContext::keyboard`].
+///
+/// Keys are identified either physically, by the [`KeyCode`] of their position on a US QWERTY
+/// layout whatever the layout of the user, or logically, by the [`Key`] they produce in the
+/// layout of the user. E.g. the key left of `E` is [`KeyCode::KeyW`] on every layout, and produces
+/// `"z"` on AZERTY layouts. Physical keys suit movement bindings, and logical keys suit shortcuts
+/// named after a letter.
+///
+/// Like [`crate::event::Events`], presses and releases are visible to the systems from the next
+/// frame on, for a single frame.
+#[derive(Debug, Default)]
+pub struct Keyboard {
+    /// Held physical keys, with the logical key they have produced.
+    pressed: HashMap<KeyCode, Key>,
+    just_pressed: HashSet<KeyCode>,
+    just_released: HashSet<KeyCode>,
+    pending_pressed: HashSet<KeyCode>,
+    pending_released: HashSet<KeyCode>,
+    /// Logical keys last produced by the physical keys, learned from the presses.
+    layout: HashMap<KeyCode, Key>,
+}
+
+assert_impl_all!(Keyboard: Send, Sync);
+
+impl Keyboard {
+    /// Creates a new [`Keyboard`] without any held key.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns true if the physical key is held.
+    pub fn pressed(&self, code: KeyCode) -> bool {
+        self.pressed.contains_key(&code)
+    }
+
+    /// Returns true if the physical key has been pressed since the previous frame.
+    pub fn just_pressed(&self, code: KeyCode) -> bool {
+        self.just_pressed.contains(&code)
+    }
+
+    /// Returns true if the physical key has been released since the previous frame.
+    pub fn just_released(&self, code: KeyCode) -> bool {
+        self.just_released.contains(&code)
+    }
+
+    /// Returns an iterator over the held physical keys.
+    pub fn pressed_keys(&self) -> impl Iterator<Item = KeyCode> + '_ {
+        self.pressed.keys().copied()
+    }
+
+    /// Returns true if a held key produces the logical key. Characters are compared ignoring
+    /// their case, e.g. `Key::Character("z".into())` matches while shift is held.
+    pub fn logical_pressed(&self, key: &Key) -> bool {
+        let key = normalize(key.clone());
+        self.pressed.values().any(|pressed| *pressed == key)
+    }
+
+    /// Returns true if a key producing the logical key has been pressed since the previous frame.
+    /// See [`Keyboard::logical_pressed`].
+    pub fn logical_just_pressed(&self, key: &Key) -> bool {
+        let key = normalize(key.clone());
+        self.just_pressed
+            .iter()
+            .any(|code| self.layout.get(code) == Some(&key))
+    }
+
+    /// Returns the logical key the physical key produces in the layout of the user, or `None` if
+    /// the key has not been pressed yet.
+    ///
+    /// The layout is not exposed by every platform, so it is learned from the presses of the
+    /// keys, without modifiers where the platform tells them apart.
+    pub fn logical_key(&self, code: KeyCode) -> Option<&Key> {
+        self.layout.get(&code)
+    }
+
+    /// Returns the name of the physical key to display to the user, e.g. in a key binding menu.
+    ///
+    /// Keys which have been pressed are named after the logical key they produce in the layout of
+    /// the user, e.g. `"Z"` for [`KeyCode::KeyW`] on AZERTY layouts. Other keys are named after
+    /// their US QWERTY position, e.g. `"W"`.
+    pub fn display_name(&self, code: KeyCode) -> String {
+        match self.layout.get(&code) {
+            Some(Key::Character(text)) => text.to_uppercase(),
+            Some(Key::Named(named)) => format!("{:?}", named),
+            _ => {
+                let name = format!("{:?}", code);
+                match name.strip_prefix("Key").or(name.strip_prefix("Digit")) {
+                    Some(short) if !short.is_empty() => short.to_string(),
+                    _ => name,
+                }
+            }
+        }
+    }
+
+    /// Records the press of a physical key, producing the logical key.
+    pub(crate) fn press(&mut self, code: KeyCode, key: Key) {
+        let key = normalize(key);
+        if !matches!(key, Key::Unidentified(_) | Key::Dead(_)) {
+            self.layout.insert(code, key.clone());
+        }
+        if self.pressed.insert(code, key).is_none() {
+            self.pending_pressed.insert(code);
+        }
+    }
+
+    /// Records the release of a physical key.
+    pub(crate) fn release(&mut self, code: KeyCode) {
+        if self.pressed.remove(&code).is_some() {
+            self.pending_released.insert(code);
+        }
+    }
+
+    /// Releases every held key, e.g. when the window loses focus and stops receiving releases.
+    pub(crate) fn release_all(&mut self) {
+        for (code, _) in self.pressed.drain() {
+            self.pending_released.insert(code);
+        }
+    }
+
+    /// Promotes the pending presses and releases to the current frame.
+    pub(crate) fn update(&mut self) {
+        self.just_pressed = std::mem::take(&mut self.pending_pressed);
+        self.just_released = std::mem::take(&mut self.pending_released);
+    }
+}
+
+/// Lowercases the characters, which are uppercase while shift is held.
+fn normalize(key: Key) -> Key {
+    match key {
+        Key::Character(text) => Key::Character(text.to_lowercase().into()),
+        key => key,
+    }
+}
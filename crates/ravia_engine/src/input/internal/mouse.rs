@@ -0,0 +1,83 @@
+use crate::{
+    ecs::{self, EntityStore, IntoQuery},
+    graphics::{Camera, Overlay},
+    math,
+    window::ScreenSize,
+};
+
+/// [`Mouse`] holds the position of the cursor, read through
+/// [`crate::engine::EngineContext::mouse`].
+///
+/// The position relates to the viewports of the cameras drawing into the window, see
+/// [`Mouse::viewport_position`] and [`Mouse::camera_under_cursor`], e.g. to pick the entities
+/// under the cursor or to drag a camera of a split-screen.
+#[derive(Debug, Default)]
+pub struct Mouse {
+    position: Option<math::Vec2>,
+}
+
+assert_impl_all!(Mouse: Send, Sync);
+
+impl Mouse {
+    /// Creates a new [`Mouse`] with the cursor outside of the window.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the position of the cursor in physical pixels from the top-left corner of the
+    /// window, or `None` if the cursor is outside of the window.
+    pub fn position(&self) -> Option<math::Vec2> {
+        self.position
+    }
+
+    /// Returns the position of the cursor in the [`crate::graphics::Viewport`] of the camera,
+    /// from `(0, 0)` at the top-left corner of the viewport to `(1, 1)` at its bottom-right
+    /// corner. The position is outside of this range if the cursor is outside of the viewport,
+    /// e.g. while dragging from the viewport.
+    ///
+    /// Returns `None` if the cursor is outside of the window, or if the camera draws into a
+    /// texture rather than the window.
+    pub fn viewport_position(
+        &self,
+        camera: &Camera,
+        screen_size: &ScreenSize,
+    ) -> Option<math::Vec2> {
+        if camera.target().is_some() {
+            return None;
+        }
+
+        let position = self.position?;
+        Some(
+            camera
+                .viewport()
+                .normalize(position, screen_size.size.as_vec2()),
+        )
+    }
+
+    /// Returns the camera whose viewport is under the cursor, with the position of the cursor in
+    /// the viewport as of [`Mouse::viewport_position`]. Where viewports overlap, the camera drawn
+    /// last is returned, i.e. the overlay cameras first, then the cameras of higher order.
+    ///
+    /// Cameras drawing into textures are ignored. Systems calling it with their subworld read
+    /// the [`Camera`] and [`Overlay`] components.
+    pub fn camera_under_cursor<W: EntityStore>(
+        &self,
+        world: &W,
+        screen_size: &ScreenSize,
+    ) -> Option<(ecs::Entity, math::Vec2)> {
+        let position = self.position?;
+        let frame_size = screen_size.size.as_vec2();
+
+        <(ecs::Entity, &Camera, Option<&Overlay>)>::query()
+            .iter(world)
+            .filter(|(_, camera, _)| {
+                camera.target().is_none() && camera.viewport().contains(position, frame_size)
+            })
+            .max_by_key(|(_, camera, overlay)| (overlay.is_some(), camera.order()))
+            .map(|(entity, camera, _)| (*entity, camera.viewport().normalize(position, frame_size)))
+    }
+
+    pub(crate) fn set_position(&mut self, position: Option<math::Vec2>) {
+        self.position = position;
+    }
+}
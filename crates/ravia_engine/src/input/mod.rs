@@ -0,0 +1,4 @@
+// implementation module
+mod internal;
+
+pub use internal::{keyboard::Keyboard, mouse::Mouse};
@@ -1,12 +1,21 @@
 #[macro_use]
 extern crate static_assertions;
 
+pub mod clipboard;
 pub mod ecs;
 pub mod engine;
+pub mod event;
 pub mod graphics;
+pub mod input;
 pub mod math;
+pub mod nav;
+pub mod net;
 pub mod resource;
+pub mod spatial;
+pub mod streaming;
 pub mod time;
+pub mod ui;
+pub mod window;
 
 /// Engine name.
 pub const ENGINE_NAME: &str = "ravia_engine";
@@ -15,20 +24,38 @@ pub const ENGINE_NAME: &str = "ravia_engine";
 pub const ENGINE_VERSION: &str = "0.1.0";
 
 /// Starts the engine.
+///
+/// Errors are reported through [`engine::EngineConfig::on_error`].
 pub fn boot(config: engine::EngineConfig) {
+    let _ = boot_with_result(config);
+}
+
+/// Starts the engine, and returns the error which stopped the engine if any.
+pub fn boot_with_result(config: engine::EngineConfig) -> Result<(), engine::EngineError> {
     log::info!(target: "ravia_engine", "Booting {} {}", ENGINE_NAME, ENGINE_VERSION);
 
-    engine::Engine::run(config);
+    engine::Engine::run(config)
 }
 
+// the module systems are all named `system`, and are attached through their module path.
+#[allow(ambiguous_glob_reexports)]
 pub mod prelude {
     pub use crate::boot;
+    pub use crate::boot_with_result;
+    pub use crate::clipboard::*;
     pub use crate::ecs::*;
     pub use crate::engine::*;
+    pub use crate::event::*;
     pub use crate::graphics::*;
+    pub use crate::input::*;
     pub use crate::math::*;
+    pub use crate::nav::*;
     pub use crate::resource::*;
+    pub use crate::spatial::*;
+    pub use crate::streaming::*;
     pub use crate::time::*;
+    pub use crate::ui::*;
+    pub use crate::window::*;
     pub use crate::ENGINE_NAME;
     pub use crate::ENGINE_VERSION;
 
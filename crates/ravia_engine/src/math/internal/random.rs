@@ -0,0 +1,101 @@
+use std::ops::Range;
+
+/// [`Random`] is a small, seedable pseudo-random number generator.
+///
+/// The engine inserts a [`Random`] resource, seeded from the clock, or from
+/// [`crate::engine::EngineConfig::random_seed`] in deterministic mode so that the same seed
+/// produces the same sequence on every run and platform. It uses xoshiro256**, which is fast but
+/// not cryptographically secure.
+#[derive(Debug, Clone)]
+pub struct Random {
+    seed: u64,
+    state: [u64; 4],
+}
+
+impl Random {
+    /// Creates a new [`Random`] from a seed.
+    pub fn new(seed: u64) -> Self {
+        // expand the seed with splitmix64, as xoshiro must not start from an all-zero state.
+        let mut x = seed;
+        let mut next = || {
+            x = x.wrapping_add(0x9e37_79b9_7f4a_7c15);
+            let mut z = x;
+            z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+            z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+            z ^ (z >> 31)
+        };
+
+        Self {
+            seed,
+            state: [next(), next(), next(), next()],
+        }
+    }
+
+    /// Creates a new [`Random`] seeded from the clock.
+    pub fn from_clock() -> Self {
+        #[cfg(not(target_arch = "wasm32"))]
+        use std::time::{SystemTime, UNIX_EPOCH};
+        #[cfg(target_arch = "wasm32")]
+        use web_time::{SystemTime, UNIX_EPOCH};
+
+        let seed = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |duration| duration.as_nanos() as u64);
+        Self::new(seed)
+    }
+
+    /// Returns the seed of the generator.
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// Returns a random `u64`.
+    pub fn next_u64(&mut self) -> u64 {
+        let result = self.state[1].wrapping_mul(5).rotate_left(7).wrapping_mul(9);
+        let t = self.state[1] << 17;
+
+        self.state[2] ^= self.state[0];
+        self.state[3] ^= self.state[1];
+        self.state[1] ^= self.state[2];
+        self.state[0] ^= self.state[3];
+        self.state[2] ^= t;
+        self.state[3] = self.state[3].rotate_left(45);
+
+        result
+    }
+
+    /// Returns a random `u32`.
+    pub fn next_u32(&mut self) -> u32 {
+        (self.next_u64() >> 32) as u32
+    }
+
+    /// Returns a random `f32` in `[0, 1)`.
+    pub fn next_f32(&mut self) -> f32 {
+        (self.next_u64() >> 40) as f32 / (1u64 << 24) as f32
+    }
+
+    /// Returns a random `bool`.
+    pub fn next_bool(&mut self) -> bool {
+        self.next_u64() >> 63 == 1
+    }
+
+    /// Returns a random `f32` in the range.
+    pub fn range_f32(&mut self, range: Range<f32>) -> f32 {
+        range.start + (range.end - range.start) * self.next_f32()
+    }
+
+    /// Returns a random `u32` in the range, or `range.start` if it is empty.
+    pub fn range_u32(&mut self, range: Range<u32>) -> u32 {
+        let span = range.end.saturating_sub(range.start);
+        if span == 0 {
+            return range.start;
+        }
+        range.start + ((self.next_u32() as u64 * span as u64) >> 32) as u32
+    }
+}
+
+impl Default for Random {
+    fn default() -> Self {
+        Self::from_clock()
+    }
+}
@@ -1 +1,6 @@
+// implementation module
+mod internal;
+
 pub use glam::*;
+
+pub use internal::random::Random;
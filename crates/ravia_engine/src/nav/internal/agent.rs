@@ -0,0 +1,129 @@
+use std::collections::VecDeque;
+
+use crate::{ecs, graphics::Transform, math};
+
+use super::navmesh::NavMesh;
+
+/// A [`NavAgent`] component moves the [`Transform`] of the same entity to a destination along a
+/// path over the [`NavMesh`].
+///
+/// The path is found when a destination is set, and again whenever the [`NavMesh`] is baked. The
+/// agent walks along the path at a constant speed, facing the direction it moves to.
+#[derive(Debug, Clone)]
+pub struct NavAgent {
+    /// Speed of the agent, in world units per second.
+    pub speed: f32,
+    /// The destination is reached once the agent is within this distance from it.
+    pub arrival_distance: f32,
+    /// Whether the agent turns around the y axis to face the direction it moves to.
+    pub face_movement: bool,
+
+    destination: Option<math::Vec3>,
+    path: VecDeque<math::Vec3>,
+    /// Version of the [`NavMesh`] the path is found on.
+    path_version: Option<u64>,
+}
+
+assert_impl_all!(NavAgent: ecs::storage::Component);
+
+impl NavAgent {
+    /// Creates a new [`NavAgent`] moving at the given speed, without a destination.
+    pub fn new(speed: f32) -> Self {
+        Self {
+            speed,
+            arrival_distance: 0.05,
+            face_movement: true,
+            destination: None,
+            path: VecDeque::new(),
+            path_version: None,
+        }
+    }
+
+    /// Returns the destination of the agent, if any.
+    pub fn destination(&self) -> Option<math::Vec3> {
+        self.destination
+    }
+
+    /// Sets the destination of the agent, finding a path to it on the next frame.
+    pub fn set_destination(&mut self, destination: math::Vec3) {
+        self.destination = Some(destination);
+        self.path.clear();
+        self.path_version = None;
+    }
+
+    /// Stops the agent where it is.
+    pub fn stop(&mut self) {
+        self.destination = None;
+        self.path.clear();
+        self.path_version = None;
+    }
+
+    /// Returns the remaining waypoints of the path, ending at the destination.
+    pub fn path(&self) -> impl Iterator<Item = &math::Vec3> {
+        self.path.iter()
+    }
+
+    /// Returns true if the agent is walking along a path.
+    pub fn is_moving(&self) -> bool {
+        !self.path.is_empty()
+    }
+
+    /// Returns true if the agent has a destination which is not reachable over the [`NavMesh`].
+    pub fn is_unreachable(&self) -> bool {
+        self.destination.is_some() && self.path_version.is_some() && self.path.is_empty()
+    }
+
+    /// Finds the path if outdated, and moves the transform along it.
+    pub(super) fn update(&mut self, transform: &mut Transform, nav_mesh: &NavMesh, delta: f32) {
+        let Some(destination) = self.destination else {
+            return;
+        };
+
+        let mut position = *transform.position();
+        if self.path_version != Some(nav_mesh.version()) {
+            self.path = nav_mesh
+                .find_path(position, destination)
+                .unwrap_or_default()
+                .into();
+            self.path_version = Some(nav_mesh.version());
+        }
+
+        let had_path = !self.path.is_empty();
+        let mut distance = self.speed * delta;
+        let mut direction = None;
+        while let Some(&waypoint) = self.path.front() {
+            let offset = waypoint - position;
+            let length = offset.length();
+            if length > f32::EPSILON {
+                direction = Some(offset / length);
+            }
+            if length > distance {
+                position += offset / length * distance;
+                break;
+            }
+            position = waypoint;
+            distance -= length;
+            self.path.pop_front();
+        }
+
+        if position != *transform.position() {
+            transform.set_position(position);
+        }
+        if let Some(direction) = direction.filter(|_| self.face_movement) {
+            if direction.x != 0.0 || direction.z != 0.0 {
+                // entities face towards -z, as cameras do.
+                let yaw = f32::atan2(-direction.x, -direction.z);
+                transform.set_rotation(math::Quat::from_rotation_y(yaw));
+            }
+        }
+
+        let arrived = match self.path.len() {
+            0 => had_path,
+            1 => position.distance(self.path[0]) <= self.arrival_distance,
+            _ => false,
+        };
+        if arrived {
+            self.stop();
+        }
+    }
+}
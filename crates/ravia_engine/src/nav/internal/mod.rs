@@ -0,0 +1,5 @@
+pub mod agent;
+pub mod navmesh;
+mod spatial_hash;
+pub mod steering;
+pub mod system;
@@ -0,0 +1,520 @@
+use std::{
+    cmp::Ordering,
+    collections::{BinaryHeap, HashMap},
+};
+
+use crate::{
+    ecs,
+    graphics::{MeshCpuData, Transform},
+    math,
+};
+
+/// [`NavMeshConfig`] describes the agents walking on the [`NavMesh`].
+#[derive(Debug, Clone, Copy)]
+pub struct NavMeshConfig {
+    /// Radius of the agents, kept as clearance when passing between triangles.
+    pub agent_radius: f32,
+    /// Maximum slope of a walkable triangle, in radians.
+    pub max_slope: f32,
+    /// Vertices closer than this distance are merged, connecting the triangles of separate
+    /// meshes sharing an edge.
+    pub weld_distance: f32,
+}
+
+impl Default for NavMeshConfig {
+    fn default() -> Self {
+        Self {
+            agent_radius: 0.5,
+            max_slope: 45f32.to_radians(),
+            weld_distance: 0.01,
+        }
+    }
+}
+
+/// A [`NavWalkable`] component marks an entity whose [`MeshCpuData`] is walkable by the agents,
+/// baked into the [`NavMesh`] with its [`Transform`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct NavWalkable;
+
+assert_impl_all!(NavWalkable: ecs::storage::Component);
+
+/// A triangle of the navigation mesh.
+#[derive(Debug, Clone)]
+struct NavTriangle {
+    vertices: [usize; 3],
+    /// Neighbor across the edge from `vertices[i]` to `vertices[(i + 1) % 3]`.
+    neighbors: [Option<usize>; 3],
+    center: math::Vec3,
+}
+
+/// [`NavMesh`] is a resource holding the walkable surface of the world, on which paths of the
+/// [`super::agent::NavAgent`]s are found.
+///
+/// The navigation mesh is baked from the triangles of every entity with [`NavWalkable`],
+/// [`MeshCpuData`] and [`Transform`] components, whose slope is within
+/// [`NavMeshConfig::max_slope`]. Walkable triangles face upwards, i.e. their vertices are in
+/// counter-clockwise order when seen from above, as the front faces of the renderer. The mesh is
+/// baked on the first frame, and again whenever the number of walkable entities changes or
+/// [`NavMesh::rebake`] is called.
+#[derive(Debug, Default)]
+pub struct NavMesh {
+    config: NavMeshConfig,
+    vertices: Vec<math::Vec3>,
+    triangles: Vec<NavTriangle>,
+    version: u64,
+
+    rebake: bool,
+    pub(super) num_sources: usize,
+}
+
+assert_impl_all!(NavMesh: ecs::systems::Resource);
+
+impl NavMesh {
+    /// Creates a new empty [`NavMesh`], baked on the first frame.
+    pub fn new(config: NavMeshConfig) -> Self {
+        Self {
+            config,
+            rebake: true,
+            ..Default::default()
+        }
+    }
+
+    /// Returns the configuration of the navigation mesh.
+    pub fn config(&self) -> &NavMeshConfig {
+        &self.config
+    }
+
+    /// Replaces the configuration, and bakes the navigation mesh again on the next frame.
+    pub fn set_config(&mut self, config: NavMeshConfig) {
+        self.config = config;
+        self.rebake = true;
+    }
+
+    /// Bakes the navigation mesh again on the next frame, e.g. after moving walkable entities.
+    pub fn rebake(&mut self) {
+        self.rebake = true;
+    }
+
+    /// Returns true if the navigation mesh is baked again on the next frame.
+    pub(super) fn needs_rebake(&self) -> bool {
+        self.rebake
+    }
+
+    /// Returns the number of times the navigation mesh has been baked. Paths found on a previous
+    /// version should be found again.
+    pub fn version(&self) -> u64 {
+        self.version
+    }
+
+    /// Returns the number of walkable triangles.
+    pub fn num_triangles(&self) -> usize {
+        self.triangles.len()
+    }
+
+    /// Returns true if there is no walkable triangle.
+    pub fn is_empty(&self) -> bool {
+        self.triangles.is_empty()
+    }
+
+    /// Bakes the navigation mesh from the walkable geometry, in world space.
+    pub fn bake<'a>(
+        &mut self,
+        sources: impl IntoIterator<Item = (&'a MeshCpuData, &'a Transform)>,
+    ) {
+        self.bake_triangles(
+            sources
+                .into_iter()
+                .flat_map(|(data, transform)| data.world_triangles(transform)),
+        );
+    }
+
+    /// Bakes the navigation mesh from the walkable triangles, in world space.
+    fn bake_triangles(&mut self, sources: impl IntoIterator<Item = [math::Vec3; 3]>) {
+        let max_slope_cos = self.config.max_slope.cos();
+        let weld_distance = self.config.weld_distance.max(f32::EPSILON);
+
+        let mut vertices = vec![];
+        let mut welded = HashMap::new();
+        let mut weld = |p: math::Vec3| {
+            let key = (p / weld_distance).round().as_ivec3();
+            *welded.entry(key).or_insert_with(|| {
+                vertices.push(p);
+                vertices.len() - 1
+            })
+        };
+
+        let mut triangles = vec![];
+        for [a, b, c] in sources {
+            let normal = (b - a).cross(c - a);
+            if normal.length_squared() <= f32::EPSILON || normal.normalize().y < max_slope_cos {
+                continue;
+            }
+
+            let indices = [weld(a), weld(b), weld(c)];
+            if indices[0] == indices[1] || indices[1] == indices[2] || indices[2] == indices[0] {
+                continue;
+            }
+            triangles.push(NavTriangle {
+                vertices: indices,
+                neighbors: [None; 3],
+                center: (a + b + c) / 3.0,
+            });
+        }
+
+        // connect the triangles sharing an edge, unless more than two triangles share it.
+        let mut edges = HashMap::<(usize, usize), Vec<(usize, usize)>>::new();
+        for (t, triangle) in triangles.iter().enumerate() {
+            for e in 0..3 {
+                let (a, b) = (triangle.vertices[e], triangle.vertices[(e + 1) % 3]);
+                edges.entry((a.min(b), a.max(b))).or_default().push((t, e));
+            }
+        }
+        for shared in edges.values() {
+            if let [(t0, e0), (t1, e1)] = shared[..] {
+                triangles[t0].neighbors[e0] = Some(t1);
+                triangles[t1].neighbors[e1] = Some(t0);
+            }
+        }
+
+        self.vertices = vertices;
+        self.triangles = triangles;
+        self.version += 1;
+        self.rebake = false;
+    }
+
+    /// Returns the point on the navigation mesh nearest to the given point, if any.
+    pub fn nearest_point(&self, point: math::Vec3) -> Option<math::Vec3> {
+        self.locate(point).map(|(_, nearest)| nearest)
+    }
+
+    /// Returns the triangle nearest to the point, and the nearest point on it.
+    fn locate(&self, point: math::Vec3) -> Option<(usize, math::Vec3)> {
+        self.triangles
+            .iter()
+            .enumerate()
+            .map(|(t, triangle)| {
+                let [a, b, c] = triangle.vertices.map(|v| self.vertices[v]);
+                (t, closest_point_on_triangle(point, a, b, c))
+            })
+            .min_by(|(_, p), (_, q)| {
+                point
+                    .distance_squared(*p)
+                    .total_cmp(&point.distance_squared(*q))
+            })
+    }
+
+    /// Finds a path from `start` to `end` over the navigation mesh.
+    ///
+    /// Both points are first moved to the nearest point on the navigation mesh. Returns the
+    /// waypoints of the path, starting at the first corner to walk to and ending at `end`, or
+    /// `None` if `end` is not reachable from `start`.
+    pub fn find_path(&self, start: math::Vec3, end: math::Vec3) -> Option<Vec<math::Vec3>> {
+        let (start_triangle, start) = self.locate(start)?;
+        let (end_triangle, end) = self.locate(end)?;
+
+        let corridor = self.find_corridor(start_triangle, end_triangle, end)?;
+        let portals = self.portals(&corridor, start, end);
+        Some(string_pull(&portals))
+    }
+
+    /// Finds the triangles to walk through with A*, between the centers of the triangles.
+    fn find_corridor(&self, start: usize, end: usize, goal: math::Vec3) -> Option<Vec<usize>> {
+        let mut costs = vec![f32::INFINITY; self.triangles.len()];
+        let mut previous = vec![None; self.triangles.len()];
+        let mut open = BinaryHeap::new();
+
+        costs[start] = 0.0;
+        open.push(OpenTriangle {
+            estimate: self.triangles[start].center.distance(goal),
+            triangle: start,
+        });
+
+        while let Some(OpenTriangle { triangle, .. }) = open.pop() {
+            if triangle == end {
+                let mut corridor = vec![end];
+                while let Some(p) = previous[*corridor.last().unwrap()] {
+                    corridor.push(p);
+                }
+                corridor.reverse();
+                return Some(corridor);
+            }
+
+            let current = &self.triangles[triangle];
+            for neighbor in current.neighbors.iter().flatten().copied() {
+                let next = &self.triangles[neighbor];
+                let cost = costs[triangle] + current.center.distance(next.center);
+                if cost < costs[neighbor] {
+                    costs[neighbor] = cost;
+                    previous[neighbor] = Some(triangle);
+                    open.push(OpenTriangle {
+                        estimate: cost + next.center.distance(goal),
+                        triangle: neighbor,
+                    });
+                }
+            }
+        }
+
+        None
+    }
+
+    /// Returns the (left, right) portals crossed by the corridor, narrowed by the agent radius,
+    /// enclosed by the start and end points.
+    fn portals(
+        &self,
+        corridor: &[usize],
+        start: math::Vec3,
+        end: math::Vec3,
+    ) -> Vec<(math::Vec3, math::Vec3)> {
+        let radius = self.config.agent_radius.max(0.0);
+
+        let mut portals = vec![(start, start)];
+        for pair in corridor.windows(2) {
+            let triangle = &self.triangles[pair[0]];
+            let Some(e) = triangle.neighbors.iter().position(|n| *n == Some(pair[1])) else {
+                continue;
+            };
+            let left = self.vertices[triangle.vertices[e]];
+            let right = self.vertices[triangle.vertices[(e + 1) % 3]];
+
+            let width = left.distance(right);
+            let portal = if width > 2.0 * radius {
+                let inset = (left - right) / width * radius;
+                (left - inset, right + inset)
+            } else {
+                let middle = (left + right) / 2.0;
+                (middle, middle)
+            };
+            portals.push(portal);
+        }
+        portals.push((end, end));
+        portals
+    }
+}
+
+/// A triangle to visit in A*, ordered by the lowest estimated cost first.
+#[derive(Debug)]
+struct OpenTriangle {
+    estimate: f32,
+    triangle: usize,
+}
+
+impl PartialEq for OpenTriangle {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for OpenTriangle {}
+
+impl PartialOrd for OpenTriangle {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for OpenTriangle {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.estimate.total_cmp(&self.estimate)
+    }
+}
+
+/// Returns twice the signed area of the triangle projected onto the ground (xz) plane, positive
+/// if `c` is to the left of the line from `a` to `b` when seen from above.
+fn area2(a: math::Vec3, b: math::Vec3, c: math::Vec3) -> f32 {
+    (c.x - a.x) * (b.z - a.z) - (b.x - a.x) * (c.z - a.z)
+}
+
+/// Finds the shortest path through the portals with the funnel algorithm, returning the corners
+/// of the path after the start point.
+fn string_pull(portals: &[(math::Vec3, math::Vec3)]) -> Vec<math::Vec3> {
+    let mut path = vec![];
+    let (mut apex, mut left, mut right) = (portals[0].0, portals[0].0, portals[0].1);
+    let (mut left_index, mut right_index) = (0, 0);
+
+    let mut i = 1;
+    while i < portals.len() {
+        let (portal_left, portal_right) = portals[i];
+
+        // tighten the right side of the funnel.
+        if area2(apex, right, portal_right) <= 0.0 {
+            if apex == right || area2(apex, left, portal_right) > 0.0 {
+                right = portal_right;
+                right_index = i;
+            } else {
+                // the right side crosses the left side, so the left side becomes a corner.
+                path.push(left);
+                apex = left;
+                let apex_index = left_index;
+                (left, right) = (apex, apex);
+                (left_index, right_index) = (apex_index, apex_index);
+                i = apex_index + 1;
+                continue;
+            }
+        }
+
+        // tighten the left side of the funnel.
+        if area2(apex, left, portal_left) >= 0.0 {
+            if apex == left || area2(apex, right, portal_left) < 0.0 {
+                left = portal_left;
+                left_index = i;
+            } else {
+                // the left side crosses the right side, so the right side becomes a corner.
+                path.push(right);
+                apex = right;
+                let apex_index = right_index;
+                (left, right) = (apex, apex);
+                (left_index, right_index) = (apex_index, apex_index);
+                i = apex_index + 1;
+                continue;
+            }
+        }
+
+        i += 1;
+    }
+
+    let end = portals[portals.len() - 1].0;
+    if path.last() != Some(&end) {
+        path.push(end);
+    }
+    path
+}
+
+/// Returns the point on the triangle nearest to the given point.
+fn closest_point_on_triangle(
+    p: math::Vec3,
+    a: math::Vec3,
+    b: math::Vec3,
+    c: math::Vec3,
+) -> math::Vec3 {
+    let (ab, ac, ap) = (b - a, c - a, p - a);
+    let (d1, d2) = (ab.dot(ap), ac.dot(ap));
+    if d1 <= 0.0 && d2 <= 0.0 {
+        return a;
+    }
+
+    let bp = p - b;
+    let (d3, d4) = (ab.dot(bp), ac.dot(bp));
+    if d3 >= 0.0 && d4 <= d3 {
+        return b;
+    }
+
+    let vc = d1 * d4 - d3 * d2;
+    if vc <= 0.0 && d1 >= 0.0 && d3 <= 0.0 {
+        return a + ab * (d1 / (d1 - d3));
+    }
+
+    let cp = p - c;
+    let (d5, d6) = (ab.dot(cp), ac.dot(cp));
+    if d6 >= 0.0 && d5 <= d6 {
+        return c;
+    }
+
+    let vb = d5 * d2 - d1 * d6;
+    if vb <= 0.0 && d2 >= 0.0 && d6 <= 0.0 {
+        return a + ac * (d2 / (d2 - d6));
+    }
+
+    let va = d3 * d6 - d5 * d4;
+    if va <= 0.0 && d4 - d3 >= 0.0 && d5 - d6 >= 0.0 {
+        return b + (c - b) * ((d4 - d3) / ((d4 - d3) + (d5 - d6)));
+    }
+
+    let denominator = 1.0 / (va + vb + vc);
+    a + ab * (vb * denominator) + ac * (vc * denominator)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Returns the two upward-facing triangles of the square from `min` to `max` on the ground.
+    fn square(min: math::Vec2, max: math::Vec2) -> [[math::Vec3; 3]; 2] {
+        let corner = |x: f32, z: f32| math::vec3(x, 0.0, z);
+        let (p00, p01) = (corner(min.x, min.y), corner(min.x, max.y));
+        let (p10, p11) = (corner(max.x, min.y), corner(max.x, max.y));
+        [[p00, p01, p11], [p00, p11, p10]]
+    }
+
+    fn navmesh(config: NavMeshConfig, squares: &[(math::Vec2, math::Vec2)]) -> NavMesh {
+        let mut navmesh = NavMesh::new(config);
+        navmesh.bake_triangles(squares.iter().flat_map(|&(min, max)| square(min, max)));
+        navmesh
+    }
+
+    fn point_config() -> NavMeshConfig {
+        NavMeshConfig {
+            agent_radius: 0.0,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn straight_path_across_two_triangles() {
+        let navmesh = navmesh(point_config(), &[(math::Vec2::ZERO, math::vec2(2.0, 2.0))]);
+        assert_eq!(navmesh.num_triangles(), 2);
+        assert!(!navmesh.needs_rebake());
+
+        let (start, end) = (math::vec3(0.2, 0.0, 1.0), math::vec3(1.8, 0.0, 1.0));
+        assert_ne!(
+            navmesh.locate(start).map(|(t, _)| t),
+            navmesh.locate(end).map(|(t, _)| t)
+        );
+        assert_eq!(navmesh.find_path(start, end), Some(vec![end]));
+    }
+
+    #[test]
+    fn path_around_l_shaped_corner() {
+        let navmesh = navmesh(
+            point_config(),
+            &[
+                (math::vec2(0.0, 0.0), math::vec2(1.0, 1.0)),
+                (math::vec2(1.0, 0.0), math::vec2(2.0, 1.0)),
+                (math::vec2(0.0, 1.0), math::vec2(1.0, 2.0)),
+            ],
+        );
+
+        let (start, end) = (math::vec3(1.8, 0.0, 0.5), math::vec3(0.5, 0.0, 1.8));
+        let path = navmesh.find_path(start, end).unwrap();
+        assert_eq!(path.len(), 2);
+        assert!(path[0].distance(math::vec3(1.0, 0.0, 1.0)) < 1e-5);
+        assert_eq!(path[1], end);
+    }
+
+    #[test]
+    fn unreachable_goal() {
+        let navmesh = navmesh(
+            point_config(),
+            &[
+                (math::vec2(0.0, 0.0), math::vec2(1.0, 1.0)),
+                (math::vec2(2.0, 0.0), math::vec2(3.0, 1.0)),
+            ],
+        );
+
+        let (start, end) = (math::vec3(0.5, 0.0, 0.5), math::vec3(2.5, 0.0, 0.5));
+        assert_eq!(navmesh.find_path(start, end), None);
+    }
+
+    #[test]
+    fn welds_meshes_sharing_an_edge() {
+        // the second square starts slightly past the edge of the first, within the weld distance.
+        let squares = [
+            (math::vec2(0.0, 0.0), math::vec2(1.0, 1.0)),
+            (math::vec2(1.004, 0.0), math::vec2(2.0, 1.0)),
+        ];
+        let (start, end) = (math::vec3(0.5, 0.0, 0.5), math::vec3(1.5, 0.0, 0.5));
+
+        let welded = navmesh(point_config(), &squares);
+        assert_eq!(welded.vertices.len(), 6);
+        assert_eq!(welded.find_path(start, end), Some(vec![end]));
+
+        let apart = navmesh(
+            NavMeshConfig {
+                weld_distance: 0.001,
+                ..point_config()
+            },
+            &squares,
+        );
+        assert_eq!(apart.vertices.len(), 8);
+        assert_eq!(apart.find_path(start, end), None);
+    }
+}
@@ -0,0 +1,43 @@
+use std::collections::HashMap;
+
+use crate::math;
+
+/// A uniform grid of points hashed by their cell, to find the points near a position without
+/// visiting all of them.
+#[derive(Debug)]
+pub(super) struct SpatialHash {
+    cell_size: f32,
+    cells: HashMap<math::IVec3, Vec<usize>>,
+}
+
+impl SpatialHash {
+    /// Creates a new [`SpatialHash`] of the points, identified by their index.
+    pub fn new(cell_size: f32, points: impl IntoIterator<Item = math::Vec3>) -> Self {
+        let mut hash = Self {
+            cell_size: cell_size.max(f32::EPSILON),
+            cells: HashMap::new(),
+        };
+        for (index, point) in points.into_iter().enumerate() {
+            let cell = hash.cell(point);
+            hash.cells.entry(cell).or_default().push(index);
+        }
+        hash
+    }
+
+    fn cell(&self, point: math::Vec3) -> math::IVec3 {
+        (point / self.cell_size).floor().as_ivec3()
+    }
+
+    /// Returns the indices of the points in the cells overlapping the sphere. Some of them may be
+    /// outside of the sphere.
+    pub fn candidates(&self, center: math::Vec3, radius: f32) -> impl Iterator<Item = usize> + '_ {
+        let min = self.cell(center - radius);
+        let max = self.cell(center + radius);
+        (min.x..=max.x)
+            .flat_map(move |x| (min.y..=max.y).map(move |y| (x, y)))
+            .flat_map(move |(x, y)| (min.z..=max.z).map(move |z| math::ivec3(x, y, z)))
+            .filter_map(|cell| self.cells.get(&cell))
+            .flatten()
+            .copied()
+    }
+}
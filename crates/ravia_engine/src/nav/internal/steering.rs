@@ -0,0 +1,202 @@
+use crate::{ecs, graphics::Transform, math};
+
+/// A behavior of a [`Steering`] agent, producing a steering force every frame.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SteeringBehavior {
+    /// Moves towards the target at full speed.
+    Seek(math::Vec3),
+    /// Moves away from a position at full speed, while within the radius of it.
+    Flee { from: math::Vec3, radius: f32 },
+    /// Moves towards the target, slowing down within the radius of it to stop at the target.
+    Arrive {
+        target: math::Vec3,
+        slowing_radius: f32,
+    },
+    /// Moves away from the neighbors within the radius.
+    Separation { radius: f32 },
+    /// Moves towards the center of the neighbors within the radius.
+    Cohesion { radius: f32 },
+    /// Matches the average velocity of the neighbors within the radius.
+    Alignment { radius: f32 },
+}
+
+impl SteeringBehavior {
+    /// Radius of the neighbors the behavior reacts to.
+    pub(super) fn neighbor_radius(&self) -> f32 {
+        match *self {
+            Self::Separation { radius }
+            | Self::Cohesion { radius }
+            | Self::Alignment { radius } => radius,
+            _ => 0.0,
+        }
+    }
+}
+
+/// A neighbor of a [`Steering`] agent.
+#[derive(Debug, Clone, Copy)]
+pub(super) struct Neighbor {
+    pub position: math::Vec3,
+    pub velocity: math::Vec3,
+}
+
+/// A [`Steering`] component moves the [`Transform`] of the same entity by the weighted sum of its
+/// [`SteeringBehavior`]s, e.g. to flock with other agents as boids.
+///
+/// Neighbors of an agent are the other entities with a [`Steering`] component, found through a
+/// spatial hash every frame.
+#[derive(Debug, Clone)]
+pub struct Steering {
+    /// Maximum speed of the agent, in world units per second.
+    pub max_speed: f32,
+    /// Maximum change of the velocity, in world units per second squared.
+    pub max_force: f32,
+    /// Whether the agent turns to face the direction it moves to.
+    pub face_movement: bool,
+
+    behaviors: Vec<(SteeringBehavior, f32)>,
+    velocity: math::Vec3,
+}
+
+assert_impl_all!(Steering: ecs::storage::Component);
+
+impl Steering {
+    /// Creates a new [`Steering`] without behaviors, at rest.
+    pub fn new(max_speed: f32, max_force: f32) -> Self {
+        Self {
+            max_speed,
+            max_force,
+            face_movement: true,
+            behaviors: vec![],
+            velocity: math::Vec3::ZERO,
+        }
+    }
+
+    /// Adds a behavior, whose force is scaled by the weight.
+    pub fn with_behavior(mut self, behavior: SteeringBehavior, weight: f32) -> Self {
+        self.behaviors.push((behavior, weight));
+        self
+    }
+
+    /// Specifies the initial velocity.
+    pub fn with_velocity(mut self, velocity: math::Vec3) -> Self {
+        self.velocity = velocity;
+        self
+    }
+
+    /// Returns the behaviors with their weights.
+    pub fn behaviors(&self) -> &[(SteeringBehavior, f32)] {
+        &self.behaviors
+    }
+
+    /// Returns the behaviors with their weights, e.g. to move the target of a behavior.
+    pub fn behaviors_mut(&mut self) -> &mut Vec<(SteeringBehavior, f32)> {
+        &mut self.behaviors
+    }
+
+    /// Returns the current velocity.
+    pub fn velocity(&self) -> math::Vec3 {
+        self.velocity
+    }
+
+    /// Returns the largest radius of the neighbors the behaviors react to.
+    pub(super) fn neighbor_radius(&self) -> f32 {
+        self.behaviors
+            .iter()
+            .map(|(behavior, _)| behavior.neighbor_radius())
+            .fold(0.0, f32::max)
+    }
+
+    /// Returns the velocity moving towards the direction at the given speed.
+    fn desired(&self, direction: math::Vec3, speed: f32) -> math::Vec3 {
+        direction.normalize_or_zero() * speed - self.velocity
+    }
+
+    /// Returns the force of a behavior.
+    fn force(
+        &self,
+        behavior: &SteeringBehavior,
+        position: math::Vec3,
+        neighbors: &[Neighbor],
+    ) -> math::Vec3 {
+        let within = |radius: f32| {
+            neighbors
+                .iter()
+                .filter(move |n| n.position.distance_squared(position) < radius * radius)
+        };
+
+        match *behavior {
+            SteeringBehavior::Seek(target) => self.desired(target - position, self.max_speed),
+            SteeringBehavior::Flee { from, radius } => {
+                if position.distance(from) < radius {
+                    self.desired(position - from, self.max_speed)
+                } else {
+                    math::Vec3::ZERO
+                }
+            }
+            SteeringBehavior::Arrive {
+                target,
+                slowing_radius,
+            } => {
+                let distance = position.distance(target);
+                let speed = self.max_speed * (distance / slowing_radius.max(f32::EPSILON)).min(1.0);
+                self.desired(target - position, speed)
+            }
+            SteeringBehavior::Separation { radius } => {
+                let away = within(radius)
+                    .map(|n| {
+                        let offset = position - n.position;
+                        offset / offset.length_squared().max(f32::EPSILON)
+                    })
+                    .sum::<math::Vec3>();
+                if away == math::Vec3::ZERO {
+                    math::Vec3::ZERO
+                } else {
+                    self.desired(away, self.max_speed)
+                }
+            }
+            SteeringBehavior::Cohesion { radius } => {
+                let (sum, count) = within(radius).fold((math::Vec3::ZERO, 0), |(sum, count), n| {
+                    (sum + n.position, count + 1)
+                });
+                if count == 0 {
+                    math::Vec3::ZERO
+                } else {
+                    self.desired(sum / count as f32 - position, self.max_speed)
+                }
+            }
+            SteeringBehavior::Alignment { radius } => {
+                let (sum, count) = within(radius).fold((math::Vec3::ZERO, 0), |(sum, count), n| {
+                    (sum + n.velocity, count + 1)
+                });
+                if count == 0 {
+                    math::Vec3::ZERO
+                } else {
+                    sum / count as f32 - self.velocity
+                }
+            }
+        }
+    }
+
+    /// Accumulates the forces of the behaviors, and moves the transform by the velocity.
+    pub(super) fn update(&mut self, transform: &mut Transform, neighbors: &[Neighbor], delta: f32) {
+        let position = *transform.position();
+        let force = self
+            .behaviors
+            .iter()
+            .map(|(behavior, weight)| self.force(behavior, position, neighbors) * *weight)
+            .sum::<math::Vec3>()
+            .clamp_length_max(self.max_force);
+
+        self.velocity = (self.velocity + force * delta).clamp_length_max(self.max_speed);
+        if self.velocity == math::Vec3::ZERO {
+            return;
+        }
+
+        transform.translate(self.velocity * delta);
+        if self.face_movement {
+            // entities face towards -z, as cameras do.
+            let direction = self.velocity.normalize();
+            transform.set_rotation(math::Quat::from_rotation_arc(math::Vec3::NEG_Z, direction));
+        }
+    }
+}
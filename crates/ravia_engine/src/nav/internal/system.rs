@@ -0,0 +1,84 @@
+use crate::{
+    ecs::{self, world::SubWorld, IntoQuery},
+    graphics::{MeshCpuData, Transform},
+    time::Time,
+};
+
+use super::{
+    agent::NavAgent,
+    navmesh::{NavMesh, NavWalkable},
+    spatial_hash::SpatialHash,
+    steering::{Neighbor, Steering},
+};
+
+/// Attaches a system of the navigation engine.
+pub fn system(builder: &mut ecs::systems::Builder) {
+    builder
+        .add_system(bake_nav_mesh_system())
+        .add_system(update_nav_agents_system())
+        .add_system(update_steering_system());
+}
+
+#[ecs::system]
+#[read_component(NavWalkable)]
+#[read_component(MeshCpuData)]
+#[read_component(Transform)]
+fn bake_nav_mesh(world: &mut SubWorld, #[resource] nav_mesh: &mut NavMesh) {
+    let mut query = <(&MeshCpuData, &Transform)>::query().filter(ecs::component::<NavWalkable>());
+
+    let num_sources = query.iter(world).count();
+    if !nav_mesh.needs_rebake() && num_sources == nav_mesh.num_sources {
+        return;
+    }
+
+    nav_mesh.bake(query.iter(world));
+    nav_mesh.num_sources = num_sources;
+}
+
+#[ecs::system(for_each)]
+fn update_nav_agents(
+    agent: &mut NavAgent,
+    transform: &mut Transform,
+    #[resource] nav_mesh: &NavMesh,
+    #[resource] time: &Time,
+) {
+    agent.update(transform, nav_mesh, time.delta_seconds());
+}
+
+#[ecs::system]
+#[write_component(Steering)]
+#[write_component(Transform)]
+fn update_steering(world: &mut SubWorld, #[resource] time: &Time) {
+    let mut query = <(&mut Steering, &mut Transform)>::query();
+
+    // every agent steers from the positions and velocities at the start of the frame.
+    let mut cell_size: f32 = 0.0;
+    let agents = query
+        .iter_mut(world)
+        .map(|(steering, transform)| {
+            cell_size = cell_size.max(steering.neighbor_radius());
+            Neighbor {
+                position: *transform.position(),
+                velocity: steering.velocity(),
+            }
+        })
+        .collect::<Vec<_>>();
+    if agents.is_empty() {
+        return;
+    }
+
+    let hash = SpatialHash::new(cell_size, agents.iter().map(|agent| agent.position));
+    let mut neighbors = vec![];
+    for (i, (steering, transform)) in query.iter_mut(world).enumerate() {
+        let radius = steering.neighbor_radius();
+        neighbors.clear();
+        if radius > 0.0 {
+            neighbors.extend(
+                hash.candidates(agents[i].position, radius)
+                    .filter(|&j| j != i)
+                    .map(|j| agents[j]),
+            );
+        }
+        steering.update(transform, &neighbors, time.delta_seconds());
+    }
+}
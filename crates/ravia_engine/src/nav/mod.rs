@@ -0,0 +1,9 @@
+// implementation module
+mod internal;
+
+pub use internal::{
+    agent::NavAgent,
+    navmesh::{NavMesh, NavMeshConfig, NavWalkable},
+    steering::{Steering, SteeringBehavior},
+    system::system,
+};
@@ -0,0 +1,318 @@
+use std::{
+    collections::VecDeque,
+    sync::{Arc, Mutex},
+};
+
+use crate::{ecs, event::Event};
+
+/// A message exchanged with the server.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NetworkMessage {
+    Text(String),
+    Binary(Vec<u8>),
+}
+
+/// The state of the connection of a [`NetworkClient`].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum NetworkState {
+    #[default]
+    Disconnected,
+    Connecting,
+    Connected,
+}
+
+/// What a connection reports to the client, tagged with the generation of the connection.
+#[derive(Debug)]
+enum Incoming {
+    Connected,
+    Message(NetworkMessage),
+    Disconnected(Option<String>),
+}
+
+/// Reports of the connections, shared with the tasks driving them.
+type IncomingQueue = Arc<Mutex<VecDeque<(u64, Incoming)>>>;
+
+/// [`NetworkClient`] is a resource holding a WebSocket connection to a server, with the same
+/// behavior in native and web builds.
+///
+/// Messages are sent without blocking, and the connection is driven in the background: on a
+/// separate thread in native mode, and by the browser in web mode. Received messages and changes
+/// of the connection are sent to the systems as [`Event::NetworkConnected`],
+/// [`Event::NetworkMessage`] and [`Event::NetworkDisconnected`].
+#[derive(Default)]
+pub struct NetworkClient {
+    state: NetworkState,
+    generation: u64,
+    incoming: IncomingQueue,
+    connection: Option<Connection>,
+
+    /// Runtime driving the connections, created on the first connection.
+    #[cfg(not(target_arch = "wasm32"))]
+    runtime: Option<tokio::runtime::Runtime>,
+}
+
+assert_impl_all!(NetworkClient: ecs::systems::Resource);
+
+impl std::fmt::Debug for NetworkClient {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("NetworkClient")
+            .field("state", &self.state)
+            .finish_non_exhaustive()
+    }
+}
+
+impl NetworkClient {
+    /// Creates a new disconnected [`NetworkClient`].
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the state of the connection.
+    pub fn state(&self) -> NetworkState {
+        self.state
+    }
+
+    /// Returns true if the client is connected to a server.
+    pub fn is_connected(&self) -> bool {
+        self.state == NetworkState::Connected
+    }
+
+    /// Connects to the WebSocket server at the url, e.g. `wss://example.com/game`, closing the
+    /// previous connection if any.
+    pub fn connect(&mut self, url: &str) {
+        self.close();
+        self.generation += 1;
+        self.state = NetworkState::Connecting;
+        self.connection = Some(self.open(url));
+    }
+
+    /// Closes the connection. No [`Event::NetworkDisconnected`] is sent for a connection closed
+    /// by the client.
+    pub fn close(&mut self) {
+        if let Some(connection) = self.connection.take() {
+            connection.close();
+        }
+        self.state = NetworkState::Disconnected;
+    }
+
+    /// Sends a message to the server. Messages sent while not connected are dropped.
+    pub fn send(&self, message: NetworkMessage) {
+        if let (NetworkState::Connected, Some(connection)) = (self.state, &self.connection) {
+            connection.send(message);
+        }
+    }
+
+    /// Sends a binary message to the server, e.g. a [`super::snapshot::Snapshot`] delta.
+    pub fn send_binary(&self, data: Vec<u8>) {
+        self.send(NetworkMessage::Binary(data));
+    }
+
+    /// Sends a text message to the server.
+    pub fn send_text(&self, text: impl Into<String>) {
+        self.send(NetworkMessage::Text(text.into()));
+    }
+
+    /// Sends the reports of the current connection to the systems.
+    pub(super) fn poll(&mut self, events: &mut crate::event::Events) {
+        let reports = std::mem::take(&mut *self.incoming.lock().unwrap());
+        for (generation, report) in reports {
+            // reports of the previous connections arrive late after reconnecting.
+            if generation != self.generation || self.connection.is_none() {
+                continue;
+            }
+
+            match report {
+                Incoming::Connected => {
+                    self.state = NetworkState::Connected;
+                    events.send(Event::NetworkConnected);
+                }
+                Incoming::Message(message) => events.send(Event::NetworkMessage(message)),
+                Incoming::Disconnected(error) => {
+                    self.connection = None;
+                    self.state = NetworkState::Disconnected;
+                    events.send(Event::NetworkDisconnected { error });
+                }
+            }
+        }
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn open(&mut self, url: &str) -> Connection {
+        use futures_util::{SinkExt, StreamExt};
+        use tokio_tungstenite::tungstenite::Message;
+
+        let runtime = self.runtime.get_or_insert_with(|| {
+            tokio::runtime::Builder::new_multi_thread()
+                .worker_threads(1)
+                .thread_name("ravia_engine::net")
+                .enable_all()
+                .build()
+                .expect("failed to build network runtime")
+        });
+
+        let (outgoing, mut outgoing_rx) = tokio::sync::mpsc::unbounded_channel::<Message>();
+        let incoming = self.incoming.clone();
+        let generation = self.generation;
+        let url = url.to_string();
+
+        runtime.spawn(async move {
+            let report = |report| incoming.lock().unwrap().push_back((generation, report));
+
+            let socket = match tokio_tungstenite::connect_async(url.as_str()).await {
+                Ok((socket, _)) => socket,
+                Err(e) => {
+                    report(Incoming::Disconnected(Some(e.to_string())));
+                    return;
+                }
+            };
+            report(Incoming::Connected);
+
+            // the writer closes the socket once the client drops the connection.
+            let (mut sink, mut stream) = socket.split();
+            tokio::spawn(async move {
+                while let Some(message) = outgoing_rx.recv().await {
+                    if sink.send(message).await.is_err() {
+                        return;
+                    }
+                }
+                let _ = sink.close().await;
+            });
+
+            let mut error = None;
+            while let Some(message) = stream.next().await {
+                match message {
+                    Ok(Message::Text(text)) => {
+                        report(Incoming::Message(NetworkMessage::Text(text)));
+                    }
+                    Ok(Message::Binary(data)) => {
+                        report(Incoming::Message(NetworkMessage::Binary(data)));
+                    }
+                    Ok(Message::Close(_)) => break,
+                    Ok(_) => (),
+                    Err(e) => {
+                        error = Some(e.to_string());
+                        break;
+                    }
+                }
+            }
+            report(Incoming::Disconnected(error));
+        });
+
+        Connection { outgoing }
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    fn open(&mut self, url: &str) -> Connection {
+        use wasm_bindgen::{closure::Closure, JsCast};
+
+        let incoming = self.incoming.clone();
+        let generation = self.generation;
+        let report =
+            move |report: Incoming| incoming.lock().unwrap().push_back((generation, report));
+
+        let socket = match web_sys::WebSocket::new(url) {
+            Ok(socket) => socket,
+            Err(e) => {
+                report(Incoming::Disconnected(Some(format!("{:?}", e))));
+                return Connection {
+                    socket: None,
+                    _callbacks: vec![],
+                };
+            }
+        };
+        socket.set_binary_type(web_sys::BinaryType::Arraybuffer);
+
+        let on_open = Closure::<dyn FnMut()>::new({
+            let report = report.clone();
+            move || report(Incoming::Connected)
+        });
+        let on_message = Closure::<dyn FnMut(web_sys::MessageEvent)>::new({
+            let report = report.clone();
+            move |event: web_sys::MessageEvent| {
+                let data = event.data();
+                if let Some(text) = data.as_string() {
+                    report(Incoming::Message(NetworkMessage::Text(text)));
+                } else if let Ok(buffer) = data.dyn_into::<js_sys::ArrayBuffer>() {
+                    let data = js_sys::Uint8Array::new(&buffer).to_vec();
+                    report(Incoming::Message(NetworkMessage::Binary(data)));
+                }
+            }
+        });
+        let on_close =
+            Closure::<dyn FnMut(web_sys::CloseEvent)>::new(move |event: web_sys::CloseEvent| {
+                let error = (!event.was_clean())
+                    .then(|| format!("connection closed with code {}", event.code()));
+                report(Incoming::Disconnected(error));
+            });
+
+        socket.set_onopen(Some(on_open.as_ref().unchecked_ref()));
+        socket.set_onmessage(Some(on_message.as_ref().unchecked_ref()));
+        socket.set_onclose(Some(on_close.as_ref().unchecked_ref()));
+
+        Connection {
+            socket: Some(socket),
+            _callbacks: vec![
+                on_open.into_js_value(),
+                on_message.into_js_value(),
+                on_close.into_js_value(),
+            ],
+        }
+    }
+}
+
+/// The sending half of a connection.
+#[cfg(not(target_arch = "wasm32"))]
+struct Connection {
+    outgoing: tokio::sync::mpsc::UnboundedSender<tokio_tungstenite::tungstenite::Message>,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl Connection {
+    fn send(&self, message: NetworkMessage) {
+        use tokio_tungstenite::tungstenite::Message;
+
+        let _ = self.outgoing.send(match message {
+            NetworkMessage::Text(text) => Message::Text(text),
+            NetworkMessage::Binary(data) => Message::Binary(data),
+        });
+    }
+
+    fn close(self) {
+        // dropping the sender stops the writer, which closes the socket.
+    }
+}
+
+/// The browser WebSocket of a connection, with the callbacks it reports through.
+#[cfg(target_arch = "wasm32")]
+struct Connection {
+    socket: Option<web_sys::WebSocket>,
+    _callbacks: Vec<wasm_bindgen::JsValue>,
+}
+
+// the web has a single thread, so the socket is never accessed from another thread.
+#[cfg(target_arch = "wasm32")]
+unsafe impl Send for Connection {}
+#[cfg(target_arch = "wasm32")]
+unsafe impl Sync for Connection {}
+
+#[cfg(target_arch = "wasm32")]
+impl Connection {
+    fn send(&self, message: NetworkMessage) {
+        let Some(socket) = &self.socket else {
+            return;
+        };
+        let _ = match message {
+            NetworkMessage::Text(text) => socket.send_with_str(&text),
+            NetworkMessage::Binary(data) => socket.send_with_u8_array(&data),
+        };
+    }
+
+    fn close(self) {
+        if let Some(socket) = self.socket {
+            socket.set_onopen(None);
+            socket.set_onmessage(None);
+            socket.set_onclose(None);
+            let _ = socket.close();
+        }
+    }
+}
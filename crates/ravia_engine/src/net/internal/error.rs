@@ -0,0 +1,25 @@
+/// Possible errors for decoding network data.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Error {
+    /// The data ended before the value being read.
+    UnexpectedEnd,
+    /// A delta snapshot refers to a baseline tick which was not given.
+    MissingBaseline(u32),
+    /// The data contains an invalid value.
+    Invalid(&'static str),
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::UnexpectedEnd => write!(f, "unexpected end of network data"),
+            Error::MissingBaseline(tick) => write!(f, "missing baseline snapshot of tick {}", tick),
+            Error::Invalid(what) => write!(f, "invalid network data: {}", what),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+/// Result type for network data.
+pub type Result<T> = std::result::Result<T, Error>;
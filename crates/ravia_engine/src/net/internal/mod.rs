@@ -0,0 +1,5 @@
+pub mod client;
+pub mod error;
+pub mod replicate;
+pub mod snapshot;
+pub mod system;
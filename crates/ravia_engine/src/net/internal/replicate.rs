@@ -0,0 +1,116 @@
+use crate::{ecs, graphics::Transform, math};
+
+use super::error::{Error, Result};
+
+/// A [`NetId`] component identifies a replicated entity with the same id on every peer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct NetId(pub u64);
+
+assert_impl_all!(NetId: ecs::storage::Component);
+
+/// A trait for components replicated in [`super::snapshot::Snapshot`]s.
+///
+/// The state of a component is encoded into bytes by the peer owning the entity, and applied to
+/// the existing component of the entity on the other peers. Register the component type with
+/// [`super::snapshot::SnapshotRegistry::register`] to include it in snapshots.
+pub trait Replicate: ecs::storage::Component {
+    /// Appends the replicated state to `out`.
+    fn encode(&self, out: &mut Vec<u8>);
+
+    /// Applies the state encoded by [`Replicate::encode`].
+    fn decode(&mut self, data: &[u8]) -> Result<()>;
+
+    /// Applies the state between two encoded states, at `t` from 0 (`from`) to 1 (`to`).
+    ///
+    /// By default, the state snaps to `to` once `t` reaches 1.
+    fn interpolate(&mut self, from: &[u8], to: &[u8], t: f32) -> Result<()> {
+        self.decode(if t < 1.0 { from } else { to })
+    }
+}
+
+/// Appends a little-endian `f32` to `out`.
+pub fn write_f32(out: &mut Vec<u8>, value: f32) {
+    out.extend_from_slice(&value.to_le_bytes());
+}
+
+/// Reads a little-endian `f32`, advancing `data` past it.
+pub fn read_f32(data: &mut &[u8]) -> Result<f32> {
+    let (bytes, rest) = data.split_first_chunk::<4>().ok_or(Error::UnexpectedEnd)?;
+    *data = rest;
+    Ok(f32::from_le_bytes(*bytes))
+}
+
+/// Appends an unsigned integer to `out` as a LEB128 variable-length integer, taking a single
+/// byte for values below 128.
+pub fn write_varint(out: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        out.push(value as u8 | 0x80);
+        value >>= 7;
+    }
+    out.push(value as u8);
+}
+
+/// Reads an unsigned integer written by [`write_varint`], advancing `data` past it.
+pub fn read_varint(data: &mut &[u8]) -> Result<u64> {
+    let mut value = 0u64;
+    for shift in (0..64).step_by(7) {
+        let (&byte, rest) = data.split_first().ok_or(Error::UnexpectedEnd)?;
+        *data = rest;
+        value |= ((byte & 0x7f) as u64) << shift;
+        if byte & 0x80 == 0 {
+            return Ok(value);
+        }
+    }
+    Err(Error::Invalid("varint overflow"))
+}
+
+fn read_vec3(data: &mut &[u8]) -> Result<math::Vec3> {
+    Ok(math::vec3(
+        read_f32(data)?,
+        read_f32(data)?,
+        read_f32(data)?,
+    ))
+}
+
+fn read_quat(data: &mut &[u8]) -> Result<math::Quat> {
+    Ok(math::Quat::from_xyzw(
+        read_f32(data)?,
+        read_f32(data)?,
+        read_f32(data)?,
+        read_f32(data)?,
+    ))
+}
+
+/// Reads the position, rotation and scale encoded by [`Transform`].
+fn read_transform(mut data: &[u8]) -> Result<(math::Vec3, math::Quat, math::Vec3)> {
+    let data = &mut data;
+    Ok((read_vec3(data)?, read_quat(data)?, read_vec3(data)?))
+}
+
+impl Replicate for Transform {
+    fn encode(&self, out: &mut Vec<u8>) {
+        let position = self.position().to_array();
+        let rotation = self.rotation().to_array();
+        let scale = self.scale().to_array();
+        for value in position.into_iter().chain(rotation).chain(scale) {
+            write_f32(out, value);
+        }
+    }
+
+    fn decode(&mut self, data: &[u8]) -> Result<()> {
+        let (position, rotation, scale) = read_transform(data)?;
+        self.set_position(position);
+        self.set_rotation(rotation);
+        self.set_scale(scale);
+        Ok(())
+    }
+
+    fn interpolate(&mut self, from: &[u8], to: &[u8], t: f32) -> Result<()> {
+        let (from_position, from_rotation, from_scale) = read_transform(from)?;
+        let (to_position, to_rotation, to_scale) = read_transform(to)?;
+        self.set_position(from_position.lerp(to_position, t));
+        self.set_rotation(from_rotation.slerp(to_rotation, t));
+        self.set_scale(from_scale.lerp(to_scale, t));
+        Ok(())
+    }
+}
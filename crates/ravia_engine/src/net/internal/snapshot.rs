@@ -0,0 +1,551 @@
+use std::collections::{BTreeMap, HashMap, VecDeque};
+
+use crate::ecs::{
+    self,
+    world::{EntryMut, EntryRef},
+    EntityStore, IntoQuery,
+};
+
+use super::{
+    error::{Error, Result},
+    replicate::{read_varint, write_varint, NetId, Replicate},
+};
+
+/// Encoded states of the replicated components of an entity, by component kind.
+type EntityState = BTreeMap<u16, Vec<u8>>;
+
+/// A [`Snapshot`] holds the states of the replicated components of every entity with a
+/// [`NetId`] at a tick of the simulation.
+///
+/// Snapshots are captured with [`SnapshotRegistry::capture`], and sent as deltas against a
+/// snapshot the receiver already has with [`Snapshot::encode_delta`], so that only the changed
+/// components are sent. The transport of the encoded bytes is left to the application.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Snapshot {
+    tick: u32,
+    entities: BTreeMap<u64, EntityState>,
+}
+
+impl Snapshot {
+    /// Returns the tick the snapshot is captured at.
+    pub fn tick(&self) -> u32 {
+        self.tick
+    }
+
+    /// Returns the number of entities in the snapshot.
+    pub fn len(&self) -> usize {
+        self.entities.len()
+    }
+
+    /// Returns true if the snapshot has no entity.
+    pub fn is_empty(&self) -> bool {
+        self.entities.is_empty()
+    }
+
+    /// Returns the ids of the entities in the snapshot, in ascending order.
+    pub fn net_ids(&self) -> impl Iterator<Item = NetId> + '_ {
+        self.entities.keys().map(|id| NetId(*id))
+    }
+
+    /// Returns true if the snapshot has the entity.
+    pub fn contains(&self, id: NetId) -> bool {
+        self.entities.contains_key(&id.0)
+    }
+
+    /// Encodes the snapshot as a delta against `baseline`, or in full without a baseline.
+    ///
+    /// The receiver decodes the delta with [`Snapshot::decode_delta`] against the same baseline,
+    /// typically the latest snapshot it acknowledged.
+    pub fn encode_delta(&self, baseline: Option<&Snapshot>) -> Vec<u8> {
+        let empty = BTreeMap::new();
+        let base = baseline.map_or(&empty, |baseline| &baseline.entities);
+
+        let mut out = vec![];
+        write_varint(&mut out, self.tick as u64);
+        write_varint(
+            &mut out,
+            baseline.map_or(0, |baseline| baseline.tick as u64 + 1),
+        );
+
+        // ids are sorted, so they are written as differences from the previous id.
+        let removed = base
+            .keys()
+            .filter(|id| !self.entities.contains_key(id))
+            .collect::<Vec<_>>();
+        write_varint(&mut out, removed.len() as u64);
+        let mut previous = 0;
+        for &id in removed {
+            write_varint(&mut out, id - previous);
+            previous = id;
+        }
+
+        let changed = self
+            .entities
+            .iter()
+            .filter(|(id, state)| base.get(id) != Some(state))
+            .collect::<Vec<_>>();
+        write_varint(&mut out, changed.len() as u64);
+        let mut previous = 0;
+        for (&id, state) in changed {
+            write_varint(&mut out, id - previous);
+            previous = id;
+
+            let base_state = base.get(&id);
+            let removed = base_state
+                .into_iter()
+                .flat_map(|base_state| base_state.keys())
+                .filter(|kind| !state.contains_key(kind))
+                .collect::<Vec<_>>();
+            write_varint(&mut out, removed.len() as u64);
+            for &kind in removed {
+                write_varint(&mut out, kind as u64);
+            }
+
+            let components = state
+                .iter()
+                .filter(|(kind, data)| base_state.and_then(|base| base.get(kind)) != Some(data))
+                .collect::<Vec<_>>();
+            write_varint(&mut out, components.len() as u64);
+            for (&kind, data) in components {
+                write_varint(&mut out, kind as u64);
+                write_varint(&mut out, data.len() as u64);
+                out.extend_from_slice(data);
+            }
+        }
+
+        out
+    }
+
+    /// Decodes a snapshot encoded by [`Snapshot::encode_delta`].
+    ///
+    /// `baseline` should be the snapshot the delta was encoded against, if any. Returns
+    /// [`Error::MissingBaseline`] with the tick of the expected baseline otherwise.
+    pub fn decode_delta(mut data: &[u8], baseline: Option<&Snapshot>) -> Result<Snapshot> {
+        let data = &mut data;
+        let read_u32 = |data: &mut &[u8]| {
+            u32::try_from(read_varint(data)?).map_err(|_| Error::Invalid("tick out of range"))
+        };
+        let read_kind = |data: &mut &[u8]| {
+            u16::try_from(read_varint(data)?).map_err(|_| Error::Invalid("component kind"))
+        };
+
+        let tick = read_u32(data)?;
+        let mut entities = match read_u32(data)?.checked_sub(1) {
+            None => BTreeMap::new(),
+            Some(base_tick) => match baseline {
+                Some(baseline) if baseline.tick == base_tick => baseline.entities.clone(),
+                _ => return Err(Error::MissingBaseline(base_tick)),
+            },
+        };
+
+        let mut id = 0u64;
+        for _ in 0..read_varint(data)? {
+            id = id.wrapping_add(read_varint(data)?);
+            entities.remove(&id);
+        }
+
+        let mut id = 0u64;
+        for _ in 0..read_varint(data)? {
+            id = id.wrapping_add(read_varint(data)?);
+            let state = entities.entry(id).or_default();
+
+            for _ in 0..read_varint(data)? {
+                state.remove(&read_kind(data)?);
+            }
+            for _ in 0..read_varint(data)? {
+                let kind = read_kind(data)?;
+                let len = read_varint(data)? as usize;
+                if data.len() < len {
+                    return Err(Error::UnexpectedEnd);
+                }
+                let (component, rest) = data.split_at(len);
+                state.insert(kind, component.to_vec());
+                *data = rest;
+            }
+        }
+
+        if !data.is_empty() {
+            return Err(Error::Invalid("trailing bytes"));
+        }
+        Ok(Snapshot { tick, entities })
+    }
+}
+
+/// A component type registered to the [`SnapshotRegistry`].
+#[derive(Debug)]
+struct Registration {
+    name: &'static str,
+    encode: fn(&EntryRef) -> Option<Vec<u8>>,
+    apply: fn(&mut EntryMut, &[u8], &[u8], f32) -> Result<()>,
+}
+
+fn encode<T: Replicate>(entry: &EntryRef) -> Option<Vec<u8>> {
+    let component = entry.get_component::<T>().ok()?;
+    let mut data = vec![];
+    component.encode(&mut data);
+    Some(data)
+}
+
+fn apply<T: Replicate>(entry: &mut EntryMut, from: &[u8], to: &[u8], t: f32) -> Result<()> {
+    match entry.get_component_mut::<T>() {
+        Ok(component) => component.interpolate(from, to, t),
+        // the entity does not have the component on this peer.
+        Err(_) => Ok(()),
+    }
+}
+
+/// [`SnapshotRegistry`] holds the component types replicated in [`Snapshot`]s.
+///
+/// Every peer should register the same component types in the same order, as a component is
+/// identified by its registration order in the encoded snapshots.
+#[derive(Debug, Default)]
+pub struct SnapshotRegistry {
+    components: Vec<Registration>,
+}
+
+assert_impl_all!(SnapshotRegistry: ecs::systems::Resource);
+
+impl SnapshotRegistry {
+    /// Creates a new empty [`SnapshotRegistry`].
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers a component type to replicate. Registering the same type again has no effect.
+    pub fn register<T: Replicate>(&mut self) -> &mut Self {
+        let name = std::any::type_name::<T>();
+        if self.components.iter().all(|c| c.name != name) {
+            self.components.push(Registration {
+                name,
+                encode: encode::<T>,
+                apply: apply::<T>,
+            });
+        }
+        self
+    }
+
+    /// Returns the number of registered component types.
+    pub fn len(&self) -> usize {
+        self.components.len()
+    }
+
+    /// Returns true if no component type is registered.
+    pub fn is_empty(&self) -> bool {
+        self.components.is_empty()
+    }
+
+    /// Captures the states of the registered components of every entity with a [`NetId`].
+    ///
+    /// Within a system, the system should read [`NetId`] and the registered components.
+    pub fn capture<W: EntityStore>(&self, world: &W, tick: u32) -> Snapshot {
+        let entities = <(ecs::Entity, &NetId)>::query()
+            .iter(world)
+            .map(|(entity, id)| (*entity, id.0))
+            .collect::<Vec<_>>();
+
+        let entities = entities
+            .into_iter()
+            .filter_map(|(entity, id)| {
+                let entry = world.entry_ref(entity).ok()?;
+                let state = self
+                    .components
+                    .iter()
+                    .enumerate()
+                    .filter_map(|(kind, c)| Some((kind as u16, (c.encode)(&entry)?)))
+                    .collect();
+                Some((id, state))
+            })
+            .collect();
+
+        Snapshot { tick, entities }
+    }
+
+    /// Applies the states of a snapshot to the entities with the same [`NetId`]s.
+    ///
+    /// Returns the ids in the snapshot without an entity in the world, which the application
+    /// should spawn with the replicated components to apply the next snapshots to them.
+    pub fn apply<W: EntityStore>(&self, world: &mut W, snapshot: &Snapshot) -> Result<Vec<NetId>> {
+        self.apply_interpolated(world, snapshot, snapshot, 1.0)
+    }
+
+    /// Applies the states between two snapshots, at `t` from 0 (`from`) to 1 (`to`), to the
+    /// entities with the same [`NetId`]s.
+    ///
+    /// Entities missing from `from` take their state from `to`. Returns the ids in `to` without
+    /// an entity in the world.
+    ///
+    /// Within a system, the system should read [`NetId`] and write the registered components.
+    pub fn apply_interpolated<W: EntityStore>(
+        &self,
+        world: &mut W,
+        from: &Snapshot,
+        to: &Snapshot,
+        t: f32,
+    ) -> Result<Vec<NetId>> {
+        let entities = <(ecs::Entity, &NetId)>::query()
+            .iter(world)
+            .map(|(entity, id)| (id.0, *entity))
+            .collect::<HashMap<_, _>>();
+
+        let mut missing = vec![];
+        for (id, to_state) in &to.entities {
+            let Some(entity) = entities.get(id) else {
+                missing.push(NetId(*id));
+                continue;
+            };
+            let Ok(mut entry) = world.entry_mut(*entity) else {
+                continue;
+            };
+
+            let from_state = from.entities.get(id);
+            for (&kind, to_data) in to_state {
+                let Some(registration) = self.components.get(kind as usize) else {
+                    return Err(Error::Invalid("unregistered component kind"));
+                };
+                let from_data = from_state
+                    .and_then(|state| state.get(&kind))
+                    .unwrap_or(to_data);
+                (registration.apply)(&mut entry, from_data, to_data, t)?;
+            }
+        }
+
+        Ok(missing)
+    }
+}
+
+/// [`SnapshotBuffer`] keeps the latest received [`Snapshot`]s, to decode deltas against them and
+/// to interpolate between them.
+///
+/// Snapshots arrive at the rate of the sender and with jitter, so the receiver renders the world
+/// slightly in the past, interpolating between the two snapshots around the rendered tick.
+#[derive(Debug, Clone)]
+pub struct SnapshotBuffer {
+    snapshots: VecDeque<Snapshot>,
+    capacity: usize,
+}
+
+impl Default for SnapshotBuffer {
+    fn default() -> Self {
+        Self::new(32)
+    }
+}
+
+impl SnapshotBuffer {
+    /// Creates a new empty [`SnapshotBuffer`] keeping up to `capacity` snapshots.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            snapshots: VecDeque::new(),
+            capacity: capacity.max(2),
+        }
+    }
+
+    /// Adds a snapshot, dropping the oldest snapshots beyond the capacity. Snapshots arriving out
+    /// of order are kept in tick order, and a snapshot of a tick already buffered is ignored.
+    pub fn push(&mut self, snapshot: Snapshot) {
+        let index = self
+            .snapshots
+            .partition_point(|buffered| buffered.tick < snapshot.tick);
+        if self.snapshots.get(index).map(|s| s.tick) == Some(snapshot.tick) {
+            return;
+        }
+        self.snapshots.insert(index, snapshot);
+        while self.snapshots.len() > self.capacity {
+            self.snapshots.pop_front();
+        }
+    }
+
+    /// Decodes a delta against the buffered baseline it refers to, and adds the snapshot.
+    pub fn push_delta(&mut self, data: &[u8]) -> Result<()> {
+        let snapshot = match Snapshot::decode_delta(data, None) {
+            Err(Error::MissingBaseline(tick)) => Snapshot::decode_delta(data, self.get(tick))?,
+            result => result?,
+        };
+        self.push(snapshot);
+        Ok(())
+    }
+
+    /// Returns the buffered snapshot of the tick.
+    pub fn get(&self, tick: u32) -> Option<&Snapshot> {
+        self.snapshots.iter().find(|snapshot| snapshot.tick == tick)
+    }
+
+    /// Returns the latest snapshot, e.g. to acknowledge it as the next baseline.
+    pub fn latest(&self) -> Option<&Snapshot> {
+        self.snapshots.back()
+    }
+
+    /// Applies the world state at the fractional tick, interpolated between the buffered
+    /// snapshots around it. Ticks outside of the buffered range take the nearest snapshot.
+    ///
+    /// Returns the ids without an entity in the world, as [`SnapshotRegistry::apply`].
+    pub fn interpolate<W: EntityStore>(
+        &self,
+        registry: &SnapshotRegistry,
+        world: &mut W,
+        tick: f32,
+    ) -> Result<Vec<NetId>> {
+        let next = self
+            .snapshots
+            .partition_point(|snapshot| (snapshot.tick as f32) <= tick);
+        let (from, to) = match (
+            next.checked_sub(1).and_then(|i| self.snapshots.get(i)),
+            self.snapshots.get(next),
+        ) {
+            (Some(from), Some(to)) => (from, to),
+            (Some(nearest), None) | (None, Some(nearest)) => (nearest, nearest),
+            (None, None) => return Ok(vec![]),
+        };
+
+        let t = if from.tick == to.tick {
+            1.0
+        } else {
+            (tick - from.tick as f32) / (to.tick - from.tick) as f32
+        };
+        registry.apply_interpolated(world, from, to, t.clamp(0.0, 1.0))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// An entity id with its components, by kind.
+    type TestEntity<'a> = (u64, &'a [(u16, &'a [u8])]);
+
+    fn snapshot(tick: u32, entities: &[TestEntity]) -> Snapshot {
+        let entities = entities
+            .iter()
+            .map(|(id, components)| {
+                let state = components
+                    .iter()
+                    .map(|(kind, data)| (*kind, data.to_vec()))
+                    .collect();
+                (*id, state)
+            })
+            .collect();
+        Snapshot { tick, entities }
+    }
+
+    #[test]
+    fn full_round_trip() {
+        let full = snapshot(
+            7,
+            &[
+                (1, &[(0, &[1, 2, 3]), (2, &[])]),
+                (300, &[(1, &[4; 200])]),
+                (301, &[]),
+            ],
+        );
+
+        let data = full.encode_delta(None);
+        assert_eq!(Snapshot::decode_delta(&data, None), Ok(full.clone()));
+        // a full snapshot ignores the baseline.
+        let other = snapshot(6, &[(2, &[(0, &[9])])]);
+        assert_eq!(Snapshot::decode_delta(&data, Some(&other)), Ok(full));
+    }
+
+    #[test]
+    fn delta_round_trip() {
+        let baseline = snapshot(
+            10,
+            &[
+                (1, &[(0, &[1]), (1, &[2])]),
+                (2, &[(0, &[3])]),
+                (3, &[(0, &[4])]),
+            ],
+        );
+        let next = snapshot(
+            12,
+            &[
+                // a changed component, and a removed one.
+                (1, &[(0, &[5])]),
+                // an unchanged entity, and an added component.
+                (2, &[(0, &[3]), (1, &[6])]),
+                // an added entity, while the entity 3 is removed.
+                (4, &[(0, &[7])]),
+            ],
+        );
+
+        let delta = next.encode_delta(Some(&baseline));
+        assert!(delta.len() < next.encode_delta(None).len());
+        assert_eq!(Snapshot::decode_delta(&delta, Some(&baseline)), Ok(next));
+
+        // an unchanged snapshot only encodes its ticks and empty lists.
+        let unchanged = Snapshot {
+            tick: 11,
+            ..baseline.clone()
+        };
+        let delta = unchanged.encode_delta(Some(&baseline));
+        assert_eq!(delta.len(), 4);
+        assert_eq!(
+            Snapshot::decode_delta(&delta, Some(&baseline)),
+            Ok(unchanged)
+        );
+    }
+
+    #[test]
+    fn missing_baseline() {
+        let baseline = snapshot(10, &[(1, &[(0, &[1])])]);
+        let delta = snapshot(11, &[(1, &[(0, &[2])])]).encode_delta(Some(&baseline));
+
+        assert_eq!(
+            Snapshot::decode_delta(&delta, None),
+            Err(Error::MissingBaseline(10))
+        );
+        let other = snapshot(9, &[(1, &[(0, &[1])])]);
+        assert_eq!(
+            Snapshot::decode_delta(&delta, Some(&other)),
+            Err(Error::MissingBaseline(10))
+        );
+
+        let mut buffer = SnapshotBuffer::default();
+        assert_eq!(buffer.push_delta(&delta), Err(Error::MissingBaseline(10)));
+        buffer.push(baseline);
+        assert_eq!(buffer.push_delta(&delta), Ok(()));
+        assert_eq!(buffer.latest().map(Snapshot::tick), Some(11));
+    }
+
+    #[test]
+    fn truncated_input() {
+        let data = snapshot(3, &[(1, &[(0, &[1, 2, 3, 4])]), (2, &[(1, &[5])])]).encode_delta(None);
+
+        for len in 0..data.len() {
+            assert_eq!(
+                Snapshot::decode_delta(&data[..len], None),
+                Err(Error::UnexpectedEnd),
+                "truncated to {} bytes",
+                len
+            );
+        }
+
+        let mut trailing = data.clone();
+        trailing.push(0);
+        assert_eq!(
+            Snapshot::decode_delta(&trailing, None),
+            Err(Error::Invalid("trailing bytes"))
+        );
+    }
+
+    #[test]
+    fn out_of_order_push() {
+        let mut buffer = SnapshotBuffer::new(3);
+        for tick in [5, 2, 8, 5, 3] {
+            buffer.push(snapshot(tick, &[(1, &[(0, &[tick as u8])])]));
+        }
+
+        // the duplicate tick is ignored, and the oldest tick is dropped beyond the capacity.
+        let ticks = buffer
+            .snapshots
+            .iter()
+            .map(Snapshot::tick)
+            .collect::<Vec<_>>();
+        assert_eq!(ticks, [3, 5, 8]);
+        assert_eq!(buffer.latest().map(Snapshot::tick), Some(8));
+        assert!(buffer.get(2).is_none());
+
+        // a late snapshot older than the buffered ones is dropped right away.
+        buffer.push(snapshot(1, &[]));
+        assert!(buffer.get(1).is_none());
+        assert_eq!(buffer.snapshots.len(), 3);
+    }
+}
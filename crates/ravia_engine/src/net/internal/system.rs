@@ -0,0 +1,13 @@
+use crate::{ecs, event::Events};
+
+use super::client::NetworkClient;
+
+/// Attaches a system of the network client.
+pub fn system(builder: &mut ecs::systems::Builder) {
+    builder.add_system(poll_network_system());
+}
+
+#[ecs::system]
+fn poll_network(#[resource] client: &mut NetworkClient, #[resource] events: &mut Events) {
+    client.poll(events);
+}
@@ -0,0 +1,10 @@
+// implementation module
+mod internal;
+
+pub use internal::{
+    client::{NetworkClient, NetworkMessage, NetworkState},
+    error::{Error, Result},
+    replicate::{read_f32, read_varint, write_f32, write_varint, NetId, Replicate},
+    snapshot::{Snapshot, SnapshotBuffer, SnapshotRegistry},
+    system::system,
+};
@@ -0,0 +1,131 @@
+use std::{
+    collections::HashMap,
+    ops::Deref,
+    sync::{Arc, Mutex},
+};
+
+/// A shared reference to an asset stored in [`Assets`].
+///
+/// Cloning a handle is cheap, and every clone refers to the same asset, so that entities using
+/// the same data (e.g. a [`crate::graphics::Material`]) hold it once.
+#[derive(Debug)]
+pub struct Handle<T> {
+    asset: Arc<T>,
+}
+
+impl<T> Handle<T> {
+    /// Creates a new [`Handle`] owning the asset, without registering it in [`Assets`].
+    pub fn new(asset: T) -> Self {
+        Self {
+            asset: Arc::new(asset),
+        }
+    }
+
+    /// Returns true if both handles refer to the same asset.
+    pub fn ptr_eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.asset, &other.asset)
+    }
+
+    /// Returns the number of handles referring to the asset, including this one.
+    pub fn strong_count(&self) -> usize {
+        Arc::strong_count(&self.asset)
+    }
+}
+
+impl<T> Clone for Handle<T> {
+    fn clone(&self) -> Self {
+        Self {
+            asset: self.asset.clone(),
+        }
+    }
+}
+
+impl<T> Deref for Handle<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.asset
+    }
+}
+
+/// [`Assets`] stores assets by name, so that entities asking for the same asset share a single
+/// [`Handle`] instead of creating their own copy.
+#[derive(Debug)]
+pub struct Assets<T> {
+    assets: Mutex<HashMap<String, Handle<T>>>,
+}
+
+impl<T> Default for Assets<T> {
+    fn default() -> Self {
+        Self {
+            assets: Mutex::default(),
+        }
+    }
+}
+
+impl<T> Assets<T> {
+    /// Creates a new empty [`Assets`].
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Stores the asset under the name, replacing the previous asset of the name if any.
+    ///
+    /// Handles to the replaced asset remain valid, but are no longer returned by [`Assets::get`].
+    pub fn insert(&self, name: &str, asset: T) -> Handle<T> {
+        let handle = Handle::new(asset);
+        self.assets
+            .lock()
+            .unwrap()
+            .insert(name.to_string(), handle.clone());
+        handle
+    }
+
+    /// Returns the asset stored under the name.
+    pub fn get(&self, name: &str) -> Option<Handle<T>> {
+        self.assets.lock().unwrap().get(name).cloned()
+    }
+
+    /// Returns the asset stored under the name, creating it if it does not exist yet.
+    pub fn get_or_insert_with(&self, name: &str, f: impl FnOnce() -> T) -> Handle<T> {
+        self.assets
+            .lock()
+            .unwrap()
+            .entry(name.to_string())
+            .or_insert_with(|| Handle::new(f()))
+            .clone()
+    }
+
+    /// Removes the asset stored under the name, and returns it.
+    ///
+    /// The asset is dropped once every handle referring to it is dropped.
+    pub fn remove(&self, name: &str) -> Option<Handle<T>> {
+        self.assets.lock().unwrap().remove(name)
+    }
+
+    /// Removes the assets which are not referred to by any handle outside of [`Assets`].
+    pub fn remove_unused(&self) {
+        self.assets
+            .lock()
+            .unwrap()
+            .retain(|_, handle| handle.strong_count() > 1);
+    }
+
+    /// Replaces every stored handle by the handle returned for it, under the same name, e.g.
+    /// to store the assets recreated for a new device.
+    pub(crate) fn replace_all(&self, mut f: impl FnMut(&Handle<T>) -> Handle<T>) {
+        for handle in self.assets.lock().unwrap().values_mut() {
+            *handle = f(handle);
+        }
+    }
+
+    /// Returns the number of stored assets.
+    pub fn len(&self) -> usize {
+        self.assets.lock().unwrap().len()
+    }
+
+    /// Returns true if no asset is stored.
+    pub fn is_empty(&self) -> bool {
+        self.assets.lock().unwrap().is_empty()
+    }
+}
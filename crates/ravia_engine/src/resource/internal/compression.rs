@@ -0,0 +1,45 @@
+use std::io::Read;
+
+/// Compression formats the resource manager decompresses transparently, detected by file suffix.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Compression {
+    Gzip,
+    Zstd,
+}
+
+impl Compression {
+    /// All supported formats, in the order compressed variants of a resource are looked up.
+    pub const ALL: [Compression; 2] = [Compression::Zstd, Compression::Gzip];
+
+    /// Returns the file suffix of the format, without the leading dot.
+    pub fn extension(self) -> &'static str {
+        match self {
+            Compression::Gzip => "gz",
+            Compression::Zstd => "zst",
+        }
+    }
+
+    /// Detects the format from the suffix of a resource path.
+    pub fn from_path(path: &str) -> Option<Self> {
+        let (_, extension) = path.rsplit_once('.')?;
+        Self::ALL
+            .into_iter()
+            .find(|compression| compression.extension() == extension)
+    }
+
+    /// Decompresses the data.
+    pub fn decompress(self, data: &[u8]) -> std::io::Result<Vec<u8>> {
+        let mut buffer = Vec::new();
+        match self {
+            Compression::Gzip => {
+                flate2::read::GzDecoder::new(data).read_to_end(&mut buffer)?;
+            }
+            Compression::Zstd => {
+                ruzstd::decoding::StreamingDecoder::new(data)
+                    .map_err(std::io::Error::other)?
+                    .read_to_end(&mut buffer)?;
+            }
+        }
+        Ok(buffer)
+    }
+}
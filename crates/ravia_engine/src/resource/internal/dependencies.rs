@@ -0,0 +1,104 @@
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Mutex,
+};
+
+use crate::{
+    ecs::Entity,
+    graphics::{TextureFilterMode, TextureSlot},
+};
+
+/// A GPU asset derived from a loaded resource.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Dependent {
+    /// The [`crate::graphics::Mesh`] of an entity, loaded from its [`super::resource::Resource`].
+    Mesh(Entity),
+    /// A texture of the [`crate::graphics::Material`] of an entity, loaded with
+    /// [`crate::graphics::Material::load_texture`].
+    Texture {
+        entity: Entity,
+        slot: TextureSlot,
+        filter_mode: TextureFilterMode,
+    },
+}
+
+impl Dependent {
+    fn entity(&self) -> Entity {
+        match self {
+            Self::Mesh(entity) => *entity,
+            Self::Texture { entity, .. } => *entity,
+        }
+    }
+}
+
+/// [`DependencyGraph`] tracks which GPU assets derive from which resource paths, so that
+/// reloading a resource with [`super::resource_manager::ResourceManager::reload`] rebuilds exactly
+/// the assets depending on it, rather than the whole world.
+///
+/// The edges are recorded when the loaded resources are bound to their entities, and edges of
+/// despawned entities are dropped when their resource is reloaded.
+#[derive(Debug, Default)]
+pub struct DependencyGraph {
+    dependents: Mutex<HashMap<String, HashSet<Dependent>>>,
+}
+
+impl DependencyGraph {
+    /// Returns the assets derived from the resource at the path.
+    pub fn dependents(&self, path: &str) -> Vec<Dependent> {
+        self.dependents
+            .lock()
+            .unwrap()
+            .get(path)
+            .map(|dependents| dependents.iter().copied().collect())
+            .unwrap_or_default()
+    }
+
+    /// Returns the paths of the resources the assets of the entity derive from.
+    pub fn dependencies(&self, entity: Entity) -> Vec<String> {
+        self.dependents
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|(_, dependents)| dependents.iter().any(|d| d.entity() == entity))
+            .map(|(path, _)| path.clone())
+            .collect()
+    }
+
+    /// Records that the asset derives from the resource at the path, replacing the resource the
+    /// same asset derived from before.
+    pub(crate) fn insert(&self, path: &str, dependent: Dependent) {
+        let mut dependents = self.dependents.lock().unwrap();
+        let replaces = |other: &Dependent| match (other, &dependent) {
+            (Dependent::Mesh(a), Dependent::Mesh(b)) => a == b,
+            (
+                Dependent::Texture { entity, slot, .. },
+                Dependent::Texture {
+                    entity: other_entity,
+                    slot: other_slot,
+                    ..
+                },
+            ) => entity == other_entity && slot == other_slot,
+            _ => false,
+        };
+        for edges in dependents.values_mut() {
+            edges.retain(|other| !replaces(other));
+        }
+        dependents.retain(|_, edges| !edges.is_empty());
+
+        dependents
+            .entry(path.to_string())
+            .or_default()
+            .insert(dependent);
+    }
+
+    /// Removes the edge, e.g. when the asset no longer derives from the resource.
+    pub(crate) fn remove(&self, path: &str, dependent: &Dependent) {
+        let mut dependents = self.dependents.lock().unwrap();
+        if let Some(edges) = dependents.get_mut(path) {
+            edges.remove(dependent);
+            if edges.is_empty() {
+                dependents.remove(path);
+            }
+        }
+    }
+}
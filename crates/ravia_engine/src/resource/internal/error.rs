@@ -4,6 +4,7 @@ use super::resource::Resource;
 #[derive(Debug, Clone, PartialEq)]
 pub enum Error {
     LoadFailed(Resource),
+    DecodeFailed(Resource, String),
     NotFound(Resource),
     Unknown,
 }
@@ -12,6 +13,7 @@ impl std::fmt::Display for Error {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let msg = match self {
             Error::LoadFailed(res) => format!("failed to load resource: {}", res.path),
+            Error::DecodeFailed(res, e) => format!("failed to decode resource {}: {}", res.path, e),
             Error::NotFound(res) => format!("resource not found: {}", res.path),
             Error::Unknown => "unknown resource requested".to_string(),
         };
@@ -1,4 +1,11 @@
+pub mod assets;
+mod compression;
+pub mod dependencies;
 pub mod error;
+pub mod preload;
 pub mod resource;
 pub mod resource_manager;
+pub mod retry;
 pub mod system;
+#[cfg(target_arch = "wasm32")]
+mod web;
@@ -0,0 +1,26 @@
+/// [`PreloadProgress`] reports the progress of the resources preloaded before the first frame.
+///
+/// See [`crate::engine::EngineConfig::preload`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PreloadProgress {
+    /// Number of the preloaded resources which are either loaded or failed to load.
+    pub settled: usize,
+    /// Number of the resources to preload.
+    pub total: usize,
+}
+
+impl PreloadProgress {
+    /// Returns the progress as a fraction between 0 and 1.
+    pub fn fraction(&self) -> f32 {
+        if self.total == 0 {
+            1.0
+        } else {
+            self.settled as f32 / self.total as f32
+        }
+    }
+
+    /// Returns true if all the resources are settled.
+    pub fn is_done(&self) -> bool {
+        self.settled >= self.total
+    }
+}
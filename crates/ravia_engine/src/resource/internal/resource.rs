@@ -10,6 +10,7 @@ pub struct Resource {
     pub path: String,
 
     pub(crate) key: Option<ResourceKey>,
+    pub(crate) settled: bool,
 }
 
 impl Resource {
@@ -18,6 +19,7 @@ impl Resource {
         Self {
             path: path.to_string(),
             key: None,
+            settled: false,
         }
     }
 
@@ -25,6 +27,11 @@ impl Resource {
     pub(crate) fn should_request(&self) -> bool {
         self.key.is_none()
     }
+
+    /// Returns true if the loaded resource has been consumed, or its failure has been reported.
+    pub(crate) fn is_settled(&self) -> bool {
+        self.settled
+    }
 }
 
 assert_impl_all!(Resource: ecs::storage::Component);
@@ -1,19 +1,16 @@
 use std::{
     collections::HashMap,
-    io::Read,
-    sync::{mpsc, Arc, Mutex},
+    sync::{Arc, Mutex},
 };
 
 use super::{
+    compression::Compression,
+    dependencies::DependencyGraph,
     error::{Error, Result},
     resource::Resource,
+    retry::RetryPolicy,
 };
 
-#[derive(Debug)]
-enum ResourceRequest {
-    Load(Resource),
-}
-
 /// The state of a resource.
 #[derive(Debug, Clone)]
 pub enum ResourceState {
@@ -27,11 +24,15 @@ pub enum ResourceState {
 pub struct ResourceKey(u64);
 
 /// Resource manager handles loading external resources from filesystem or the web
-/// and caching them for reuse.=
+/// and caching them for reuse.
 pub struct ResourceManager {
-    request_tx: mpsc::Sender<ResourceRequest>,
     resource_key_counter: Mutex<u64>,
+    keys: Mutex<HashMap<String, ResourceKey>>,
     store: Arc<Mutex<HashMap<ResourceKey, ResourceState>>>,
+    dependencies: DependencyGraph,
+    /// Paths reloaded since the dependents were last rebuilt.
+    reloads: Mutex<Vec<String>>,
+    retry: RetryPolicy,
 
     #[cfg(not(target_arch = "wasm32"))]
     runtime: tokio::runtime::Runtime,
@@ -43,75 +44,53 @@ impl std::fmt::Debug for ResourceManager {
     }
 }
 
+impl Default for ResourceManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl ResourceManager {
     /// Creates a new [`ResourceManager`].
     pub fn new() -> Self {
-        let (request_tx, request_rx) = mpsc::channel::<ResourceRequest>();
-        let store = Arc::new(Mutex::new(HashMap::new()));
-
-        // spawn a thread to handle resource requests.
-        #[cfg(target_arch = "wasm32")]
-        {
-            wasm_bindgen_futures::spawn_local(async move {
-                while let Ok(request) = request_rx.recv() {
-                    match request {
-                        ResourceRequest::Load(res) => {
-                            let _result = Self::load(&res).await;
-                        }
-                    }
-                }
-            });
+        Self::with_retry_policy(RetryPolicy::default())
+    }
 
-            Self {
-                request_tx,
-                resource_key_counter: Mutex::new(0),
-                store,
-            }
-        }
-        #[cfg(not(target_arch = "wasm32"))]
-        {
-            let runtime = tokio::runtime::Builder::new_multi_thread()
+    /// Creates a new [`ResourceManager`] which retries failed loads with the given policy.
+    pub fn with_retry_policy(retry: RetryPolicy) -> Self {
+        Self {
+            resource_key_counter: Mutex::new(0),
+            keys: Mutex::new(HashMap::new()),
+            store: Arc::new(Mutex::new(HashMap::new())),
+            dependencies: DependencyGraph::default(),
+            reloads: Mutex::new(vec![]),
+            retry,
+            #[cfg(not(target_arch = "wasm32"))]
+            runtime: tokio::runtime::Builder::new_multi_thread()
                 .enable_all()
                 .build()
-                .expect("failed to build async runtime");
-
-            {
-                let store = store.clone();
-                runtime.spawn(async move {
-                    while let Ok(request) = request_rx.recv() {
-                        match request {
-                            ResourceRequest::Load(res) => {
-                                let result = Self::load(&res).await;
-                                let key = res.key.unwrap();
-                                let mut store = store.lock().unwrap();
-                                match result {
-                                    Ok(data) => {
-                                        store.insert(key, ResourceState::Loaded(data));
-                                    }
-                                    Err(e) => {
-                                        store.insert(key, ResourceState::Error(e));
-                                    }
-                                }
-                            }
-                        }
-                    }
-                });
-            }
-
-            Self {
-                request_tx,
-                resource_key_counter: Mutex::new(0),
-                store,
-                runtime,
-            }
+                .expect("failed to build async runtime"),
         }
     }
 
     /// Requests a resource to be loaded.
+    ///
+    /// If the resource at the same path has already been requested, the resource shares the
+    /// previous request instead of loading it again.
     pub fn request(&self, res: &mut Resource) {
-        log::info!("requesting resource: {:?}", res);
+        let key = {
+            let mut keys = self.keys.lock().unwrap();
+            if let Some(key) = keys.get(&res.path) {
+                res.key = Some(*key);
+                return;
+            }
+
+            let key = self.issue_key();
+            keys.insert(res.path.clone(), key);
+            key
+        };
 
-        let key = self.issue_key();
+        log::info!("requesting resource: {:?}", res);
         res.key = Some(key);
 
         self.store
@@ -119,11 +98,36 @@ impl ResourceManager {
             .unwrap()
             .insert(key, ResourceState::Loading);
 
-        self.request_tx
-            .send(ResourceRequest::Load(res.clone()))
-            .expect("failed to send resource request");
+        // each request is spawned as its own task, so that a load waiting to be retried does not
+        // hold back the others.
+        let store = self.store.clone();
+        let retry = self.retry;
+        let res = res.clone();
+        let load = async move {
+            let result = Self::load_with_retry(&res, retry).await;
+            Self::complete(&store, &res, result);
+        };
+        #[cfg(target_arch = "wasm32")]
+        wasm_bindgen_futures::spawn_local(load);
+        #[cfg(not(target_arch = "wasm32"))]
+        self.runtime.spawn(load);
+    }
+
+    /// Stores the result of a finished load.
+    fn complete(
+        store: &Mutex<HashMap<ResourceKey, ResourceState>>,
+        res: &Resource,
+        result: Result<Vec<u8>>,
+    ) {
+        let key = res.key.unwrap();
+        let state = match result {
+            Ok(data) => ResourceState::Loaded(data),
+            Err(e) => ResourceState::Error(e),
+        };
+        store.lock().unwrap().insert(key, state);
     }
 
+    /// Returns the state of the resource with the given key.
     pub fn get(&self, key: ResourceKey) -> ResourceState {
         let store = self.store.lock().unwrap();
         if let Some(state) = store.get(&key) {
@@ -133,6 +137,64 @@ impl ResourceManager {
         }
     }
 
+    /// Returns true if the resource has been requested and is still loading.
+    ///
+    /// Unlike [`ResourceManager::get`], the loaded data is not cloned, so it is cheap to call
+    /// every frame.
+    pub fn is_loading(&self, res: &Resource) -> bool {
+        res.key.is_some_and(|key| {
+            matches!(
+                self.store.lock().unwrap().get(&key),
+                Some(ResourceState::Loading)
+            )
+        })
+    }
+
+    /// Releases the loaded data of a resource, so that a later request loads it again.
+    ///
+    /// Resources still loading are kept, as their loads cannot be cancelled.
+    pub fn evict(&self, res: &Resource) {
+        if let Some(key) = res.key {
+            self.evict_key(key, &res.path);
+        }
+    }
+
+    /// Releases the loaded data of the key, and returns false if it is still loading.
+    fn evict_key(&self, key: ResourceKey, path: &str) -> bool {
+        let mut store = self.store.lock().unwrap();
+        if matches!(store.get(&key), Some(ResourceState::Loading)) {
+            return false;
+        }
+
+        store.remove(&key);
+        self.keys.lock().unwrap().remove(path);
+        true
+    }
+
+    /// Loads the resource at the path again, e.g. after it has been modified on disk, and
+    /// rebuilds the assets depending on it once loaded. See [`ResourceManager::dependencies`].
+    ///
+    /// Resources still loading are not reloaded, as their loads cannot be cancelled.
+    pub fn reload(&self, path: &str) {
+        let key = self.keys.lock().unwrap().get(path).copied();
+        if key.is_some_and(|key| !self.evict_key(key, path)) {
+            return;
+        }
+
+        log::info!("reloading resource: {}", path);
+        self.reloads.lock().unwrap().push(path.to_string());
+    }
+
+    /// Returns the paths reloaded since the last call.
+    pub(crate) fn take_reloads(&self) -> Vec<String> {
+        std::mem::take(&mut *self.reloads.lock().unwrap())
+    }
+
+    /// Returns the graph of the assets derived from the loaded resources.
+    pub fn dependencies(&self) -> &DependencyGraph {
+        &self.dependencies
+    }
+
     fn issue_key(&self) -> ResourceKey {
         let mut counter = self.resource_key_counter.lock().unwrap();
         let key = ResourceKey(*counter);
@@ -140,11 +202,88 @@ impl ResourceManager {
         key
     }
 
-    /// Loads resource and provide it as an [`std::io::Read`] stream.
+    /// Loads resource, retrying on failure according to the retry policy.
+    async fn load_with_retry(res: &Resource, retry: RetryPolicy) -> Result<Vec<u8>> {
+        let mut attempts = 0;
+        loop {
+            match Self::load(res).await {
+                Ok(data) => return Ok(data),
+                Err(e) if attempts < retry.max_retries => {
+                    attempts += 1;
+                    log::warn!(
+                        "failed to load resource, retrying ({}/{}): {}",
+                        attempts,
+                        retry.max_retries,
+                        e
+                    );
+                    Self::sleep(retry.delay).await;
+                }
+                Err(e) => {
+                    log::error!("{}", e);
+                    return Err(e);
+                }
+            }
+        }
+    }
+
+    async fn sleep(duration: std::time::Duration) {
+        #[cfg(target_arch = "wasm32")]
+        {
+            let promise = js_sys::Promise::new(&mut |resolve, _| {
+                if let Some(window) = web_sys::window() {
+                    let _ = window.set_timeout_with_callback_and_timeout_and_arguments_0(
+                        &resolve,
+                        duration.as_millis() as i32,
+                    );
+                }
+            });
+            let _ = wasm_bindgen_futures::JsFuture::from(promise).await;
+        }
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            tokio::time::sleep(duration).await;
+        }
+    }
+
+    /// Loads resource, decompressing it if needed.
+    ///
+    /// If the resource is not found, compressed variants of it (e.g. `model.obj.gz`) are looked up,
+    /// since the build step may have compressed it.
     async fn load(res: &Resource) -> Result<Vec<u8>> {
+        if let Some(compression) = Compression::from_path(&res.path) {
+            let data = Self::load_raw(res).await?;
+            return Self::decompress(res, compression, &data);
+        }
+
+        match Self::load_raw(res).await {
+            Err(Error::NotFound(_)) => {}
+            result => return result,
+        }
+
+        for compression in Compression::ALL {
+            let compressed = Resource::new(&format!("{}.{}", res.path, compression.extension()));
+            match Self::load_raw(&compressed).await {
+                Ok(data) => return Self::decompress(res, compression, &data),
+                Err(Error::NotFound(_)) => continue,
+                Err(_) => return Err(Error::LoadFailed(res.clone())),
+            }
+        }
+
+        Err(Error::NotFound(res.clone()))
+    }
+
+    fn decompress(res: &Resource, compression: Compression, data: &[u8]) -> Result<Vec<u8>> {
+        compression.decompress(data).map_err(|e| {
+            log::error!("failed to decompress resource {}: {}", res.path, e);
+            Error::LoadFailed(res.clone())
+        })
+    }
+
+    /// Loads the raw bytes of a resource.
+    async fn load_raw(res: &Resource) -> Result<Vec<u8>> {
         #[cfg(target_arch = "wasm32")]
         {
-            todo!()
+            super::web::load(res).await
         }
         #[cfg(not(target_arch = "wasm32"))]
         {
@@ -154,16 +293,22 @@ impl ResourceManager {
 
     #[cfg(not(target_arch = "wasm32"))]
     async fn load_from_filesystem(res: &Resource) -> Result<Vec<u8>> {
+        use std::io::Read;
+
         log::info!("loading resource from filesystem: {:?}", res);
 
-        let resource_root = std::env::var("RAVIA_RES").expect("RAVIA_RES is not set");
+        let Ok(resource_root) = std::env::var("RAVIA_RES") else {
+            log::error!("RAVIA_RES is not set, cannot locate resources");
+            return Err(Error::LoadFailed(res.clone()));
+        };
         let resource_root = std::path::PathBuf::from(&resource_root);
 
         let path = resource_root.join(&res.path);
         match std::fs::File::open(path) {
             Ok(mut file) => {
                 let mut buffer = Vec::new();
-                file.read_to_end(&mut buffer).unwrap();
+                file.read_to_end(&mut buffer)
+                    .map_err(|_| Error::LoadFailed(res.clone()))?;
                 Ok(buffer)
             }
             Err(_) => Err(Error::NotFound(res.clone())),
@@ -0,0 +1,24 @@
+use std::time::Duration;
+
+/// [`RetryPolicy`] describes how failed resource loads are retried.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// Maximum number of retries after the first failed attempt.
+    pub max_retries: u32,
+    /// Delay between the attempts.
+    pub delay: Duration,
+}
+
+impl RetryPolicy {
+    /// A policy which never retries.
+    pub const NEVER: Self = Self {
+        max_retries: 0,
+        delay: Duration::ZERO,
+    };
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self::NEVER
+    }
+}
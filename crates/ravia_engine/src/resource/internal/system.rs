@@ -1,15 +1,85 @@
 use crate::{
-    ecs::{self, systems::CommandBuffer, Entity},
+    ecs::{self, systems::CommandBuffer, world::SubWorld, Entity, EntityStore, IntoQuery},
     engine::EngineContext,
-    graphics::load_mesh_from_obj,
+    event::{Event, Events},
+    graphics::{
+        load_cubemap_from_binary, load_mesh_from_binary_with_options,
+        load_mesh_from_obj_with_options, load_texture_from_binary, Material, MaterialInstance,
+        Mesh, Skybox, StandardShader, Texture, Upload, BINARY_MESH_EXTENSION,
+    },
 };
 
-use super::{resource::Resource, resource_manager::ResourceState};
+use super::{
+    dependencies::Dependent, error::Error, resource::Resource, resource_manager::ResourceState,
+};
 
 /// Attaches a system of the resource engine.
 pub fn system(builder: &mut ecs::systems::Builder) {
+    builder.add_system(cascade_reloads_system());
     builder.add_system(request_resource_system());
     builder.add_system(bind_mesh_system());
+    builder.add_system(finish_mesh_upload_system());
+    builder.add_system(bind_material_textures_system());
+    builder.add_system(bind_skybox_system());
+}
+
+/// Rebuilds the assets depending on the reloaded resources, by requesting them again.
+#[ecs::system]
+#[write_component(Resource)]
+#[write_component(Material)]
+fn cascade_reloads(world: &mut SubWorld, #[resource] ctx: &EngineContext) {
+    let dependencies = ctx.resource_manager.dependencies();
+    for path in ctx.resource_manager.take_reloads() {
+        for dependent in dependencies.dependents(&path) {
+            let rebuilt = match dependent {
+                Dependent::Mesh(entity) => world.entry_mut(entity).is_ok_and(|mut entry| {
+                    entry
+                        .get_component_mut::<Resource>()
+                        .ok()
+                        .filter(|resource| resource.path == path)
+                        .map(|resource| *resource = Resource::new(&path))
+                        .is_some()
+                }),
+                Dependent::Texture {
+                    entity,
+                    slot,
+                    filter_mode,
+                } => world.entry_mut(entity).is_ok_and(|mut entry| {
+                    entry
+                        .get_component_mut::<Material>()
+                        .ok()
+                        .filter(|material| material.texture_source(slot) == Some(&path))
+                        .map(|material| material.load_texture(ctx, slot, &path, filter_mode))
+                        .is_some()
+                }),
+            };
+
+            // the asset has been removed, or derives from another resource since.
+            if !rebuilt {
+                dependencies.remove(&path, &dependent);
+            }
+        }
+    }
+}
+
+/// Requests the meshes loaded from resources again for the device recreated after a device loss,
+/// see [`Event::DeviceRestored`], cancelling the uploads in progress.
+pub(crate) fn restore_world(world: &mut ecs::World) {
+    let entities = <Entity>::query()
+        .filter(ecs::component::<Resource>())
+        .iter(world)
+        .copied()
+        .collect::<Vec<_>>();
+    for entity in entities {
+        let Some(mut entry) = world.entry(entity) else {
+            continue;
+        };
+        entry.remove_component::<Mesh>();
+        entry.remove_component::<MeshUpload>();
+        if let Ok(resource) = entry.get_component_mut::<Resource>() {
+            *resource = Resource::new(&resource.path.clone());
+        }
+    }
 }
 
 #[ecs::system(for_each)]
@@ -21,20 +91,266 @@ fn request_resource(resource: &mut Resource, #[resource] ctx: &EngineContext) {
     ctx.resource_manager.request(resource);
 }
 
+/// A [`Mesh`] being uploaded from its loaded [`Resource`].
+#[derive(Debug)]
+struct MeshUpload {
+    resource: Resource,
+    upload: Upload<Result<Mesh, anyhow::Error>>,
+}
+
+/// Binds the [`Mesh::error_cube`] to an entity whose mesh has failed to load, with a standard
+/// material if it has none, so that the failure shows up in the scene.
+fn bind_error_mesh(cmd: &mut CommandBuffer, ctx: &EngineContext, entity: Entity, material: bool) {
+    if !material {
+        let material = StandardShader::Standard.material(ctx);
+        cmd.add_component(entity, MaterialInstance::new(material));
+    }
+    cmd.add_component(entity, Mesh::error_cube(ctx));
+}
+
+/// Uploads the loaded mesh of the entity in the background, see [`Upload`].
 #[ecs::system(for_each)]
 fn bind_mesh(
     cmd: &mut CommandBuffer,
     #[resource] ctx: &EngineContext,
+    #[resource] events: &mut Events,
+    entity: &Entity,
+    resource: &mut Resource,
+    material: Option<&Material>,
+    material_instance: Option<&MaterialInstance>,
+) {
+    if resource.should_request() || resource.is_settled() {
+        return;
+    }
+
+    match ctx.resource_manager.get(resource.key.unwrap()) {
+        ResourceState::Loading => return,
+        ResourceState::Loaded(data) => {
+            let path = resource.path.clone();
+            let upload = Upload::spawn(ctx, move |ctx| {
+                let extension = format!(".{}", BINARY_MESH_EXTENSION);
+                let options = ctx.gpu.mesh_import_options();
+                if path.ends_with(&extension) {
+                    load_mesh_from_binary_with_options(ctx, &data, &path, options)
+                } else {
+                    load_mesh_from_obj_with_options(ctx, &data, &path, options)
+                }
+            });
+            cmd.add_component(
+                *entity,
+                MeshUpload {
+                    resource: resource.clone(),
+                    upload,
+                },
+            );
+        }
+        ResourceState::Error(error) => {
+            bind_error_mesh(
+                cmd,
+                ctx,
+                *entity,
+                material.is_some() || material_instance.is_some(),
+            );
+            events.send(Event::ResourceFailed {
+                path: resource.path.clone(),
+                error,
+            });
+        }
+    }
+
+    resource.settled = true;
+}
+
+/// Binds the uploaded mesh to the entity. Entities without a material are rendered with the
+/// [`StandardShader`] matching the vertices of the mesh.
+#[ecs::system(for_each)]
+fn finish_mesh_upload(
+    cmd: &mut CommandBuffer,
+    #[resource] ctx: &EngineContext,
+    #[resource] events: &mut Events,
+    entity: &Entity,
+    mesh_upload: &MeshUpload,
+    material: Option<&Material>,
+    material_instance: Option<&MaterialInstance>,
+) {
+    let Some(mesh) = mesh_upload.upload.poll() else {
+        return;
+    };
+
+    let resource = &mesh_upload.resource;
+    match mesh {
+        Ok(mesh) => {
+            ctx.resource_manager
+                .dependencies()
+                .insert(&resource.path, Dependent::Mesh(*entity));
+            if material.is_none() && material_instance.is_none() {
+                let material = StandardShader::for_mesh(&mesh).material(ctx);
+                cmd.add_component(*entity, MaterialInstance::new(material));
+            }
+            cmd.add_component(*entity, mesh);
+            events.send(Event::UploadCompleted {
+                path: resource.path.clone(),
+            });
+        }
+        Err(e) => {
+            log::error!("failed to load mesh from {}: {}", resource.path, e);
+            bind_error_mesh(
+                cmd,
+                ctx,
+                *entity,
+                material.is_some() || material_instance.is_some(),
+            );
+            events.send(Event::ResourceFailed {
+                path: resource.path.clone(),
+                error: Error::DecodeFailed(resource.clone(), e.to_string()),
+            });
+        }
+    }
+    cmd.remove_component::<MeshUpload>(*entity);
+}
+
+/// Uploads the loaded textures of the material in the background, and binds them once uploaded.
+/// Textures which fail to load are replaced by the [`Texture::error_2d`].
+#[ecs::system(for_each)]
+fn bind_material_textures(
+    #[resource] ctx: &EngineContext,
+    #[resource] events: &mut Events,
     entity: &Entity,
-    resource: &Resource,
+    material: &mut Material,
 ) {
-    if resource.should_request() {
+    if material.pending.is_empty() {
         return;
     }
 
-    if let ResourceState::Loaded(data) = ctx.resource_manager.get(resource.key.unwrap()) {
-        if let Ok(mesh) = load_mesh_from_obj(ctx, &data) {
-            cmd.add_component(entity.clone(), mesh);
+    let mut loaded = vec![];
+    material.pending.retain_mut(|pending| {
+        let Some(upload) = &pending.upload else {
+            match ctx.resource_manager.get(
+                pending
+                    .resource
+                    .key
+                    .expect("pending textures are requested"),
+            ) {
+                ResourceState::Loading => {}
+                ResourceState::Loaded(data) => {
+                    let filter_mode = pending.filter_mode;
+                    pending.upload = Some(Upload::spawn(ctx, move |ctx| {
+                        load_texture_from_binary(ctx, &data, filter_mode)
+                    }));
+                }
+                ResourceState::Error(error) => {
+                    loaded.push((pending.slot, Texture::error_2d(ctx)));
+                    events.send(Event::ResourceFailed {
+                        path: pending.resource.path.clone(),
+                        error,
+                    });
+                    return false;
+                }
+            }
+            return true;
+        };
+
+        match upload.poll() {
+            None => return true,
+            Some(Ok(texture)) => {
+                ctx.resource_manager.dependencies().insert(
+                    &pending.resource.path,
+                    Dependent::Texture {
+                        entity: *entity,
+                        slot: pending.slot,
+                        filter_mode: pending.filter_mode,
+                    },
+                );
+                loaded.push((pending.slot, texture));
+                events.send(Event::UploadCompleted {
+                    path: pending.resource.path.clone(),
+                });
+            }
+            Some(Err(e)) => {
+                log::error!(
+                    "failed to load texture from {}: {}",
+                    pending.resource.path,
+                    e
+                );
+                loaded.push((pending.slot, Texture::error_2d(ctx)));
+                events.send(Event::ResourceFailed {
+                    path: pending.resource.path.clone(),
+                    error: Error::DecodeFailed(pending.resource.clone(), e.to_string()),
+                });
+            }
+        }
+        false
+    });
+
+    // the slots have been validated when the loads were requested.
+    for (slot, texture) in loaded {
+        material.bind_texture(slot, texture);
+    }
+}
+
+/// Uploads the cubemap of the skybox in the background once its faces are loaded, and binds it
+/// once uploaded.
+#[ecs::system(for_each)]
+fn bind_skybox(
+    #[resource] ctx: &EngineContext,
+    #[resource] events: &mut Events,
+    skybox: &mut Skybox,
+) {
+    let Some(pending) = &mut skybox.pending else {
+        return;
+    };
+
+    let Some(upload) = &pending.upload else {
+        let mut faces = vec![];
+        for face in &pending.faces {
+            match ctx
+                .resource_manager
+                .get(face.key.expect("skybox faces are requested"))
+            {
+                ResourceState::Loading => return,
+                ResourceState::Loaded(data) => faces.push(data),
+                ResourceState::Error(error) => {
+                    events.send(Event::ResourceFailed {
+                        path: face.path.clone(),
+                        error,
+                    });
+                    skybox.pending = None;
+                    return;
+                }
+            }
+        }
+
+        pending.upload = Some(Upload::spawn(ctx, move |ctx| {
+            let faces: [&[u8]; 6] = std::array::from_fn(|i| &faces[i][..]);
+            load_cubemap_from_binary(ctx, faces)
+        }));
+        return;
+    };
+
+    match upload.poll() {
+        None => {}
+        Some(Ok(cubemap)) => {
+            for face in &pending.faces {
+                events.send(Event::UploadCompleted {
+                    path: face.path.clone(),
+                });
+            }
+            skybox.bind_cubemap(ctx, cubemap.into());
+        }
+        Some(Err(e)) => {
+            let paths = pending.faces.iter().map(|face| face.path.as_str());
+            log::error!(
+                "failed to load skybox from {}: {}",
+                paths.collect::<Vec<_>>().join(", "),
+                e
+            );
+            for face in &pending.faces {
+                events.send(Event::ResourceFailed {
+                    path: face.path.clone(),
+                    error: Error::DecodeFailed(face.clone(), e.to_string()),
+                });
+            }
+            skybox.pending = None;
         }
     }
 }
@@ -0,0 +1,210 @@
+use std::cell::OnceCell;
+
+use js_sys::{Promise, Uint8Array};
+use wasm_bindgen::{prelude::*, JsCast};
+use wasm_bindgen_futures::JsFuture;
+use web_sys::{IdbDatabase, IdbRequest, IdbTransactionMode, Response};
+
+use super::{
+    error::{Error, Result},
+    resource::Resource,
+};
+
+/// Root url of the resources, relative to the page. Matches the output directory of `ravia_build`.
+const RESOURCE_ROOT: &str = "pkg/static/res";
+
+/// Path of the build manifest, relative to [`RESOURCE_ROOT`].
+const MANIFEST_PATH: &str = "manifest";
+
+/// Name of the object store holding cached resources.
+const STORE_NAME: &str = "resources";
+
+/// Key of the local storage item holding the name of the database of the last opened build.
+const DATABASE_KEY: &str = "ravia_engine::resources";
+
+thread_local! {
+    /// The cache opened by the first load of the page, resolving to its database, or to
+    /// `undefined` if caching is unavailable. Kept as a promise, so that the loads started while
+    /// it is opening wait for the same database.
+    static CACHE: OnceCell<Promise> = const { OnceCell::new() };
+}
+
+/// Loads a resource from the web, serving it from the IndexedDB cache when possible.
+pub async fn load(res: &Resource) -> Result<Vec<u8>> {
+    let cache = Cache::shared().await;
+
+    if let Some(data) = match &cache {
+        Some(cache) => cache.get(&res.path).await,
+        None => None,
+    } {
+        log::info!("loading resource from cache: {:?}", res);
+        return Ok(data);
+    }
+
+    log::info!("loading resource from web: {:?}", res);
+    let data = fetch(&format!("{}/{}", RESOURCE_ROOT, res.path))
+        .await
+        .map_err(|status| match status {
+            Some(404) => Error::NotFound(res.clone()),
+            _ => Error::LoadFailed(res.clone()),
+        })?;
+
+    if let Some(cache) = &cache {
+        cache.put(&res.path, &data).await;
+    }
+
+    Ok(data)
+}
+
+/// Fetches the given url. On failure, returns the http status if the server responded.
+async fn fetch(url: &str) -> std::result::Result<Vec<u8>, Option<u16>> {
+    let window = web_sys::window().ok_or(None)?;
+    let response: Response = JsFuture::from(window.fetch_with_str(url))
+        .await
+        .map_err(|_| None)?
+        .dyn_into()
+        .map_err(|_| None)?;
+
+    if !response.ok() {
+        return Err(Some(response.status()));
+    }
+
+    let buffer = JsFuture::from(response.array_buffer().map_err(|_| None)?)
+        .await
+        .map_err(|_| None)?;
+    Ok(Uint8Array::new(&buffer).to_vec())
+}
+
+/// Resource cache backed by IndexedDB.
+///
+/// Each build gets its own database, named after the hash in the build manifest. The database
+/// of the previous build is deleted once the database of a new build is opened.
+struct Cache {
+    db: IdbDatabase,
+}
+
+impl Cache {
+    /// Returns the cache of the page, opening it on the first call. Returns `None` if caching is
+    /// unavailable.
+    async fn shared() -> Option<Self> {
+        let promise = CACHE.with(|cache| {
+            cache
+                .get_or_init(|| {
+                    wasm_bindgen_futures::future_to_promise(async {
+                        Ok(Self::open()
+                            .await
+                            .map_or(JsValue::UNDEFINED, |cache| cache.db.into()))
+                    })
+                })
+                .clone()
+        });
+        let db = JsFuture::from(promise).await.ok()?;
+        Some(Self {
+            db: db.dyn_into().ok()?,
+        })
+    }
+
+    /// Opens the cache for the current build. Returns `None` if caching is unavailable.
+    async fn open() -> Option<Self> {
+        let manifest = fetch(&format!("{}/{}", RESOURCE_ROOT, MANIFEST_PATH))
+            .await
+            .ok()?;
+        let hash = String::from_utf8(manifest).ok()?;
+        let name = format!("ravia_engine::resources::{}", hash.trim());
+
+        let factory = web_sys::window()?.indexed_db().ok()??;
+        let request = factory.open_with_u32(&name, 1).ok()?;
+
+        let on_upgrade_needed = Closure::once_into_js({
+            let request = request.clone();
+            move || {
+                if let Some(db) = request
+                    .result()
+                    .ok()
+                    .and_then(|db| db.dyn_into::<IdbDatabase>().ok())
+                {
+                    let _ = db.create_object_store(STORE_NAME);
+                }
+            }
+        });
+        request.set_onupgradeneeded(Some(on_upgrade_needed.unchecked_ref()));
+
+        let db = wait(&request).await.ok()?.dyn_into::<IdbDatabase>().ok()?;
+        Self::delete_previous(&factory, &name);
+        Some(Self { db })
+    }
+
+    /// Deletes the database of the build opened before, if it is not the current one, and
+    /// remembers the current one.
+    fn delete_previous(factory: &web_sys::IdbFactory, name: &str) {
+        let Some(storage) = web_sys::window().and_then(|window| window.local_storage().ok()?)
+        else {
+            return;
+        };
+
+        if let Ok(Some(previous)) = storage.get_item(DATABASE_KEY) {
+            if previous != name {
+                log::info!("deleting resource cache of previous build: {}", previous);
+                // the deletion completes once the pages of the previous build close it.
+                let _ = factory.delete_database(&previous);
+            }
+        }
+        let _ = storage.set_item(DATABASE_KEY, name);
+    }
+
+    /// Returns the cached data for the given path, if any.
+    async fn get(&self, path: &str) -> Option<Vec<u8>> {
+        let request = self
+            .db
+            .transaction_with_str(STORE_NAME)
+            .ok()?
+            .object_store(STORE_NAME)
+            .ok()?
+            .get(&JsValue::from_str(path))
+            .ok()?;
+
+        let value = wait(&request).await.ok()?;
+        value
+            .dyn_into::<Uint8Array>()
+            .ok()
+            .map(|array| array.to_vec())
+    }
+
+    /// Stores the data for the given path. Failures are logged and otherwise ignored.
+    async fn put(&self, path: &str, data: &[u8]) {
+        let request = self
+            .db
+            .transaction_with_str_and_mode(STORE_NAME, IdbTransactionMode::Readwrite)
+            .and_then(|tx| tx.object_store(STORE_NAME))
+            .and_then(|store| {
+                store.put_with_key(&Uint8Array::from(data), &JsValue::from_str(path))
+            });
+
+        let result = match request {
+            Ok(request) => wait(&request).await.map(|_| ()),
+            Err(e) => Err(e),
+        };
+        if let Err(e) = result {
+            log::warn!("failed to cache resource {}: {:?}", path, e);
+        }
+    }
+}
+
+/// Waits for an IndexedDB request to complete, returning its result.
+async fn wait(request: &IdbRequest) -> std::result::Result<JsValue, JsValue> {
+    let promise = Promise::new(&mut |resolve, reject| {
+        let on_success = Closure::once_into_js({
+            let request = request.clone();
+            move || {
+                let result = request.result().unwrap_or(JsValue::UNDEFINED);
+                let _ = resolve.call1(&JsValue::NULL, &result);
+            }
+        });
+        let on_error = Closure::once_into_js(move || {
+            let _ = reject.call0(&JsValue::NULL);
+        });
+        request.set_onsuccess(Some(on_success.unchecked_ref()));
+        request.set_onerror(Some(on_error.unchecked_ref()));
+    });
+    JsFuture::from(promise).await
+}
@@ -2,8 +2,14 @@
 mod internal;
 
 pub use internal::{
+    assets::{Assets, Handle},
+    dependencies::{DependencyGraph, Dependent},
     error::{Error, Result},
+    preload::PreloadProgress,
     resource::Resource,
     resource_manager::ResourceManager,
+    retry::RetryPolicy,
     system::system,
 };
+
+pub(crate) use internal::system::restore_world;
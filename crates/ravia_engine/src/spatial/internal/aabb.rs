@@ -0,0 +1,80 @@
+use crate::{ecs, math};
+
+/// An axis-aligned bounding box.
+///
+/// As a component, an [`Aabb`] describes the bounds of an entity in the local space of its
+/// [`crate::graphics::Transform`], to be indexed by the [`super::index::SpatialIndex`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Aabb {
+    pub min: math::Vec3,
+    pub max: math::Vec3,
+}
+
+assert_impl_all!(Aabb: ecs::storage::Component);
+
+impl Aabb {
+    /// Creates a new [`Aabb`] from its minimum and maximum corners.
+    pub fn new(min: math::Vec3, max: math::Vec3) -> Self {
+        Self {
+            min: min.min(max),
+            max: min.max(max),
+        }
+    }
+
+    /// Creates a new [`Aabb`] from its center and half of its size.
+    pub fn from_center_half_extents(center: math::Vec3, half_extents: math::Vec3) -> Self {
+        Self::new(center - half_extents, center + half_extents)
+    }
+
+    /// Creates a new [`Aabb`] enclosing the sphere.
+    pub fn from_sphere(center: math::Vec3, radius: f32) -> Self {
+        Self::from_center_half_extents(center, math::Vec3::splat(radius))
+    }
+
+    /// Returns the center of the box.
+    pub fn center(&self) -> math::Vec3 {
+        (self.min + self.max) / 2.0
+    }
+
+    /// Returns half of the size of the box.
+    pub fn half_extents(&self) -> math::Vec3 {
+        (self.max - self.min) / 2.0
+    }
+
+    /// Returns the box enclosing this box transformed by the matrix.
+    pub fn transformed(&self, matrix: &math::Mat4) -> Self {
+        let center = matrix.transform_point3(self.center());
+        let half_extents = self.half_extents();
+        let half_extents = matrix.x_axis.truncate().abs() * half_extents.x
+            + matrix.y_axis.truncate().abs() * half_extents.y
+            + matrix.z_axis.truncate().abs() * half_extents.z;
+        Self::from_center_half_extents(center, half_extents)
+    }
+
+    /// Returns true if the point is inside the box.
+    pub fn contains_point(&self, point: math::Vec3) -> bool {
+        point.cmpge(self.min).all() && point.cmple(self.max).all()
+    }
+
+    /// Returns true if the boxes overlap.
+    pub fn intersects(&self, other: &Aabb) -> bool {
+        self.min.cmple(other.max).all() && self.max.cmpge(other.min).all()
+    }
+
+    /// Returns true if the box overlaps the sphere.
+    pub fn intersects_sphere(&self, center: math::Vec3, radius: f32) -> bool {
+        center.clamp(self.min, self.max).distance_squared(center) <= radius * radius
+    }
+
+    /// Returns the distance along the ray where it enters the box, or zero if the origin is
+    /// inside the box. The direction is expected to be normalized.
+    pub fn ray_intersection(&self, origin: math::Vec3, direction: math::Vec3) -> Option<f32> {
+        let inv = direction.recip();
+        let t0 = (self.min - origin) * inv;
+        let t1 = (self.max - origin) * inv;
+        // NaN appears when the ray lies on a face of the box, and is ignored by min and max.
+        let near = t0.min(t1).max_element();
+        let far = t0.max(t1).min_element();
+        (near <= far && far >= 0.0).then_some(near.max(0.0))
+    }
+}
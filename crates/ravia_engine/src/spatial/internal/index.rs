@@ -0,0 +1,205 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::{ecs, math};
+
+use super::aabb::Aabb;
+
+/// [`SpatialConfig`] configures the [`SpatialIndex`].
+#[derive(Debug, Clone, Copy)]
+pub struct SpatialConfig {
+    /// Size of the cells of the grid, in world units. It should be around the size of the
+    /// common entities, so that an entity covers only a few cells.
+    pub cell_size: f32,
+    /// Entities covering more cells than this are kept out of the grid, and tested by every
+    /// query instead.
+    pub max_cells_per_entity: usize,
+}
+
+impl Default for SpatialConfig {
+    fn default() -> Self {
+        Self {
+            cell_size: 4.0,
+            max_cells_per_entity: 64,
+        }
+    }
+}
+
+/// A hit of a ray query on the [`SpatialIndex`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RayHit {
+    pub entity: ecs::Entity,
+    /// Distance along the ray where it enters the bounds of the entity.
+    pub distance: f32,
+}
+
+/// [`SpatialIndex`] is a resource indexing the world space bounds of the entities in a uniform
+/// grid, to find the entities in a region without visiting every entity.
+///
+/// Every entity with a [`crate::graphics::Transform`] and either an [`Aabb`] or a
+/// [`crate::graphics::MeshCpuData`] component is indexed, with the bounds transformed into world
+/// space. The index is rebuilt at the start of every frame, so entities moved later in the frame
+/// are found at their previous position until the next frame.
+#[derive(Debug, Default)]
+pub struct SpatialIndex {
+    config: SpatialConfig,
+    bounds: Vec<(ecs::Entity, Aabb)>,
+    cells: HashMap<math::IVec3, Vec<usize>>,
+    /// Entities covering too many cells to be stored in the grid.
+    large: Vec<usize>,
+}
+
+assert_impl_all!(SpatialIndex: ecs::systems::Resource);
+
+impl SpatialIndex {
+    /// Creates a new empty [`SpatialIndex`].
+    pub fn new(config: SpatialConfig) -> Self {
+        Self {
+            config,
+            ..Default::default()
+        }
+    }
+
+    /// Returns the configuration of the index.
+    pub fn config(&self) -> &SpatialConfig {
+        &self.config
+    }
+
+    /// Returns the number of indexed entities.
+    pub fn len(&self) -> usize {
+        self.bounds.len()
+    }
+
+    /// Returns true if no entity is indexed.
+    pub fn is_empty(&self) -> bool {
+        self.bounds.is_empty()
+    }
+
+    /// Returns the world space bounds of the indexed entities.
+    pub fn iter(&self) -> impl Iterator<Item = &(ecs::Entity, Aabb)> {
+        self.bounds.iter()
+    }
+
+    /// Replaces the indexed entities with their world space bounds.
+    pub fn rebuild(&mut self, bounds: impl IntoIterator<Item = (ecs::Entity, Aabb)>) {
+        self.bounds.clear();
+        self.bounds.extend(bounds);
+        self.cells.clear();
+        self.large.clear();
+
+        for (index, (_, aabb)) in self.bounds.iter().enumerate() {
+            let (min, max) = self.cell_range(aabb);
+            if Self::num_cells(min, max) > self.config.max_cells_per_entity as u64 {
+                self.large.push(index);
+                continue;
+            }
+
+            for cell in Self::cells(min, max) {
+                self.cells.entry(cell).or_default().push(index);
+            }
+        }
+    }
+
+    fn cell_range(&self, aabb: &Aabb) -> (math::IVec3, math::IVec3) {
+        let cell_size = self.config.cell_size.max(f32::EPSILON);
+        (
+            (aabb.min / cell_size).floor().as_ivec3(),
+            (aabb.max / cell_size).floor().as_ivec3(),
+        )
+    }
+
+    fn num_cells(min: math::IVec3, max: math::IVec3) -> u64 {
+        let size = (max.as_i64vec3() - min.as_i64vec3() + 1).as_u64vec3();
+        size.x.saturating_mul(size.y).saturating_mul(size.z)
+    }
+
+    fn cells(min: math::IVec3, max: math::IVec3) -> impl Iterator<Item = math::IVec3> {
+        (min.x..=max.x)
+            .flat_map(move |x| (min.y..=max.y).map(move |y| (x, y)))
+            .flat_map(move |(x, y)| (min.z..=max.z).map(move |z| math::ivec3(x, y, z)))
+    }
+
+    /// Returns the indices of the entities which may overlap the box, each once.
+    fn candidates(&self, aabb: &Aabb) -> Box<dyn Iterator<Item = usize> + '_> {
+        let (min, max) = self.cell_range(aabb);
+
+        // large queries, e.g. long rays, visit the occupied cells instead of every covered cell.
+        let cells: Box<dyn Iterator<Item = &Vec<usize>>> =
+            if Self::num_cells(min, max) > self.cells.len() as u64 {
+                Box::new(
+                    self.cells
+                        .iter()
+                        .filter(move |(cell, _)| cell.cmpge(min).all() && cell.cmple(max).all())
+                        .map(|(_, indices)| indices),
+                )
+            } else {
+                Box::new(Self::cells(min, max).filter_map(|cell| self.cells.get(&cell)))
+            };
+
+        let mut visited = HashSet::new();
+        Box::new(
+            cells
+                .flatten()
+                .chain(self.large.iter())
+                .copied()
+                .filter(move |index| visited.insert(*index)),
+        )
+    }
+
+    /// Returns the entities whose bounds overlap the box.
+    pub fn query_aabb(&self, aabb: &Aabb) -> Vec<ecs::Entity> {
+        self.candidates(aabb)
+            .filter(|&index| self.bounds[index].1.intersects(aabb))
+            .map(|index| self.bounds[index].0)
+            .collect()
+    }
+
+    /// Returns the entities whose bounds overlap the sphere.
+    pub fn query_sphere(&self, center: math::Vec3, radius: f32) -> Vec<ecs::Entity> {
+        self.candidates(&Aabb::from_sphere(center, radius))
+            .filter(|&index| self.bounds[index].1.intersects_sphere(center, radius))
+            .map(|index| self.bounds[index].0)
+            .collect()
+    }
+
+    /// Returns the entities whose bounds are hit by the ray within the maximum distance, nearest
+    /// first.
+    pub fn query_ray(
+        &self,
+        origin: math::Vec3,
+        direction: math::Vec3,
+        max_distance: f32,
+    ) -> Vec<RayHit> {
+        let direction = direction.normalize_or_zero();
+        if direction == math::Vec3::ZERO {
+            return vec![];
+        }
+
+        let end = origin + direction * max_distance;
+        let mut hits = self
+            .candidates(&Aabb::new(origin, end))
+            .filter_map(|index| {
+                let (entity, aabb) = &self.bounds[index];
+                aabb.ray_intersection(origin, direction)
+                    .filter(|distance| *distance <= max_distance)
+                    .map(|distance| RayHit {
+                        entity: *entity,
+                        distance,
+                    })
+            })
+            .collect::<Vec<_>>();
+        hits.sort_by(|a, b| a.distance.total_cmp(&b.distance));
+        hits
+    }
+
+    /// Returns the nearest entity whose bounds are hit by the ray within the maximum distance.
+    pub fn raycast(
+        &self,
+        origin: math::Vec3,
+        direction: math::Vec3,
+        max_distance: f32,
+    ) -> Option<RayHit> {
+        self.query_ray(origin, direction, max_distance)
+            .into_iter()
+            .next()
+    }
+}
@@ -0,0 +1,3 @@
+pub mod aabb;
+pub mod index;
+pub mod system;
@@ -0,0 +1,36 @@
+use crate::{
+    ecs::{self, world::SubWorld, IntoQuery},
+    graphics::{MeshCpuData, Transform},
+};
+
+use super::{aabb::Aabb, index::SpatialIndex};
+
+/// Attaches a system of the spatial index.
+pub fn system(builder: &mut ecs::systems::Builder) {
+    builder.add_system(rebuild_spatial_index_system());
+}
+
+#[ecs::system]
+#[read_component(Aabb)]
+#[read_component(MeshCpuData)]
+#[read_component(Transform)]
+fn rebuild_spatial_index(world: &mut SubWorld, #[resource] index: &mut SpatialIndex) {
+    let mut explicit_query = <(ecs::Entity, &Aabb, &Transform)>::query();
+    let mut mesh_query =
+        <(ecs::Entity, &MeshCpuData, &Transform)>::query().filter(!ecs::component::<Aabb>());
+
+    let explicit = explicit_query
+        .iter(world)
+        .map(|(entity, aabb, transform)| (*entity, aabb.transformed(&transform.matrix())));
+
+    // entities with both components are indexed with the explicit bounds.
+    let meshes = mesh_query.iter(world).map(|(entity, data, transform)| {
+        let (min, max) = data.bounds();
+        (
+            *entity,
+            Aabb::new(min, max).transformed(&transform.matrix()),
+        )
+    });
+
+    index.rebuild(explicit.chain(meshes));
+}
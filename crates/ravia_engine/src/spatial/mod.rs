@@ -0,0 +1,8 @@
+// implementation module
+mod internal;
+
+pub use internal::{
+    aabb::Aabb,
+    index::{RayHit, SpatialConfig, SpatialIndex},
+    system::system,
+};
@@ -0,0 +1,187 @@
+use std::sync::Arc;
+
+use crate::{
+    ecs::{self, systems::CommandBuffer, Entity},
+    engine::EngineContext,
+    math,
+    resource::Resource,
+};
+
+/// Spawns the entities of a chunk, returning them so that they are despawned with the chunk.
+pub type ChunkSpawner =
+    Arc<dyn Fn(&mut CommandBuffer, &EngineContext) -> Vec<Entity> + Send + Sync>;
+
+/// Identifies a chunk registered to [`WorldStreaming`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ChunkId(usize);
+
+/// A [`ChunkMember`] component marks an entity spawned by a chunk.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ChunkMember(pub ChunkId);
+
+assert_impl_all!(ChunkMember: ecs::storage::Component);
+
+/// A [`StreamingChunk`] is a part of the world which is loaded when the camera comes close to it,
+/// and unloaded when the camera moves away.
+///
+/// Its resources are requested first, and once all of them are loaded or failed, its entities are
+/// spawned. Entities may then use [`Resource`] components with the same paths, which share the
+/// loaded data.
+#[derive(Clone)]
+pub struct StreamingChunk {
+    /// Center of the bounding sphere of the chunk.
+    pub center: math::Vec3,
+    /// Radius of the bounding sphere of the chunk.
+    pub radius: f32,
+    /// Chunks with higher priority request their resources and spawn first.
+    pub priority: i32,
+    /// Paths of the resources to load before spawning the entities.
+    pub resources: Vec<String>,
+    spawn: ChunkSpawner,
+}
+
+impl std::fmt::Debug for StreamingChunk {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("StreamingChunk")
+            .field("center", &self.center)
+            .field("radius", &self.radius)
+            .field("priority", &self.priority)
+            .field("resources", &self.resources)
+            .finish_non_exhaustive()
+    }
+}
+
+impl StreamingChunk {
+    /// Creates a new [`StreamingChunk`] within the bounding sphere, spawning its entities with the
+    /// given function.
+    pub fn new(
+        center: math::Vec3,
+        radius: f32,
+        spawn: impl Fn(&mut CommandBuffer, &EngineContext) -> Vec<Entity> + Send + Sync + 'static,
+    ) -> Self {
+        Self {
+            center,
+            radius,
+            priority: 0,
+            resources: vec![],
+            spawn: Arc::new(spawn),
+        }
+    }
+
+    /// Specifies the priority of the chunk.
+    pub fn with_priority(mut self, priority: i32) -> Self {
+        self.priority = priority;
+        self
+    }
+
+    /// Specifies the resources to load before spawning the entities.
+    pub fn with_resources(mut self, resources: &[&str]) -> Self {
+        self.resources = resources.iter().map(|path| path.to_string()).collect();
+        self
+    }
+
+    /// Returns the distance from the point to the bounding sphere, or zero if it is inside.
+    pub(super) fn distance(&self, point: math::Vec3) -> f32 {
+        (self.center.distance(point) - self.radius).max(0.0)
+    }
+
+    pub(super) fn spawn(&self, cmd: &mut CommandBuffer, ctx: &EngineContext) -> Vec<Entity> {
+        (self.spawn)(cmd, ctx)
+    }
+}
+
+/// Loading state of a chunk.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChunkState {
+    /// The chunk is out of range.
+    Unloaded,
+    /// The resources of the chunk are loading.
+    Loading,
+    /// The entities of the chunk are spawned.
+    Loaded,
+}
+
+/// [`StreamingConfig`] holds the configuration of world streaming.
+#[derive(Debug, Clone, Copy)]
+pub struct StreamingConfig {
+    /// Distance from the camera to a chunk at which the chunk is loaded.
+    pub load_distance: f32,
+    /// Distance from the camera to a chunk at which the chunk is unloaded. Should be larger than
+    /// the load distance, so that chunks at the border are not reloaded every frame.
+    pub unload_distance: f32,
+    /// Maximum number of chunks spawned per frame, to spread the spawns over frames.
+    pub max_spawns_per_frame: usize,
+}
+
+impl Default for StreamingConfig {
+    fn default() -> Self {
+        Self {
+            load_distance: 100.0,
+            unload_distance: 120.0,
+            max_spawns_per_frame: 1,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub(super) struct ChunkEntry {
+    pub chunk: StreamingChunk,
+    pub state: ChunkState,
+    pub resources: Vec<Resource>,
+    pub entities: Vec<Entity>,
+}
+
+/// The [`WorldStreaming`] resource holds the chunks of the world, which are loaded and unloaded
+/// around the camera.
+#[derive(Debug)]
+pub struct WorldStreaming {
+    pub(super) config: StreamingConfig,
+    pub(super) chunks: Vec<ChunkEntry>,
+}
+
+impl WorldStreaming {
+    /// Creates a new [`WorldStreaming`] with no chunks.
+    pub fn new(config: StreamingConfig) -> Self {
+        Self {
+            config,
+            chunks: vec![],
+        }
+    }
+
+    /// Returns the configuration.
+    pub fn config(&self) -> &StreamingConfig {
+        &self.config
+    }
+
+    /// Registers a chunk, which is loaded once the camera comes in range.
+    pub fn add_chunk(&mut self, chunk: StreamingChunk) -> ChunkId {
+        self.chunks.push(ChunkEntry {
+            chunk,
+            state: ChunkState::Unloaded,
+            resources: vec![],
+            entities: vec![],
+        });
+        ChunkId(self.chunks.len() - 1)
+    }
+
+    /// Returns the chunk with the given id.
+    pub fn chunk(&self, id: ChunkId) -> Option<&StreamingChunk> {
+        self.chunks.get(id.0).map(|entry| &entry.chunk)
+    }
+
+    /// Returns the state of the chunk with the given id.
+    pub fn state(&self, id: ChunkId) -> Option<ChunkState> {
+        self.chunks.get(id.0).map(|entry| entry.state)
+    }
+
+    /// Returns the entities spawned by the chunk with the given id.
+    pub fn entities(&self, id: ChunkId) -> &[Entity] {
+        self.chunks
+            .get(id.0)
+            .map_or(&[], |entry| entry.entities.as_slice())
+    }
+
+    pub(super) fn id(index: usize) -> ChunkId {
+        ChunkId(index)
+    }
+}
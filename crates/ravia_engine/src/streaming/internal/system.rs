@@ -0,0 +1,102 @@
+use crate::{
+    ecs::{self, systems::CommandBuffer, world::SubWorld},
+    engine::EngineContext,
+    graphics::{primary_camera_position, Camera, Overlay, Transform},
+    resource::Resource,
+};
+
+use super::chunk::{ChunkMember, ChunkState, WorldStreaming};
+
+/// Attaches a system of the world streaming engine.
+pub fn system(builder: &mut ecs::systems::Builder) {
+    builder.add_system(stream_chunks_system());
+}
+
+#[ecs::system]
+#[read_component(Camera)]
+#[read_component(Overlay)]
+#[read_component(Transform)]
+fn stream_chunks(
+    world: &mut SubWorld,
+    cmd: &mut CommandBuffer,
+    #[resource] ctx: &EngineContext,
+    #[resource] streaming: &mut WorldStreaming,
+) {
+    let Some(camera_position) = primary_camera_position(world) else {
+        return;
+    };
+
+    let config = streaming.config;
+    let mut order = (0..streaming.chunks.len())
+        .map(|index| {
+            let chunk = &streaming.chunks[index].chunk;
+            (index, chunk.distance(camera_position))
+        })
+        .collect::<Vec<_>>();
+
+    // higher priority first, then nearer first.
+    order.sort_by(|(a, a_distance), (b, b_distance)| {
+        let a_priority = streaming.chunks[*a].chunk.priority;
+        let b_priority = streaming.chunks[*b].chunk.priority;
+        b_priority
+            .cmp(&a_priority)
+            .then(a_distance.total_cmp(b_distance))
+    });
+
+    let mut spawns = 0;
+    for (index, distance) in order {
+        let entry = &mut streaming.chunks[index];
+        match entry.state {
+            ChunkState::Unloaded if distance <= config.load_distance => {
+                entry.resources = entry
+                    .chunk
+                    .resources
+                    .iter()
+                    .map(|path| {
+                        let mut resource = Resource::new(path);
+                        ctx.resource_manager.request(&mut resource);
+                        resource
+                    })
+                    .collect();
+                entry.state = ChunkState::Loading;
+            }
+            ChunkState::Loading | ChunkState::Loaded if distance > config.unload_distance => {
+                for entity in entry.entities.drain(..) {
+                    cmd.remove(entity);
+                }
+                let resources = std::mem::take(&mut entry.resources);
+                entry.state = ChunkState::Unloaded;
+
+                // requests of the same path share their data, which is kept while another chunk
+                // still uses it.
+                for resource in resources {
+                    let shared = streaming.chunks.iter().any(|other| {
+                        matches!(other.state, ChunkState::Loading | ChunkState::Loaded)
+                            && other.resources.iter().any(|r| r.path == resource.path)
+                    });
+                    if !shared {
+                        ctx.resource_manager.evict(&resource);
+                    }
+                }
+            }
+            ChunkState::Loading if spawns < config.max_spawns_per_frame => {
+                let loading = entry
+                    .resources
+                    .iter()
+                    .any(|resource| ctx.resource_manager.is_loading(resource));
+                if loading {
+                    continue;
+                }
+
+                let id = WorldStreaming::id(index);
+                entry.entities = entry.chunk.spawn(cmd, ctx);
+                for entity in &entry.entities {
+                    cmd.add_component(*entity, ChunkMember(id));
+                }
+                entry.state = ChunkState::Loaded;
+                spawns += 1;
+            }
+            _ => {}
+        }
+    }
+}
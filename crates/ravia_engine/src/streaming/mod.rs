@@ -0,0 +1,7 @@
+// implementation module
+mod internal;
+
+pub use internal::{
+    chunk::{ChunkId, ChunkMember, ChunkState, StreamingChunk, StreamingConfig, WorldStreaming},
+    system::system,
+};
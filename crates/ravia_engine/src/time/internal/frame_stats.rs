@@ -0,0 +1,191 @@
+use std::{collections::VecDeque, time::Duration};
+
+/// [`FrameStatsConfig`] holds the configuration of the [`FrameStats`] resource.
+#[derive(Debug, Clone, Copy)]
+pub struct FrameStatsConfig {
+    /// Number of recent frames the statistics are computed over.
+    pub window: usize,
+    /// Frame time above which a frame is flagged as a [`Hitch`].
+    pub hitch_threshold: Duration,
+    /// Number of recent hitches kept by [`FrameStats::hitches`].
+    pub max_hitches: usize,
+}
+
+impl Default for FrameStatsConfig {
+    fn default() -> Self {
+        Self {
+            window: 240,
+            hitch_threshold: Duration::from_millis(50),
+            max_hitches: 16,
+        }
+    }
+}
+
+/// Wall time spent in a span of the engine loop during a frame: `systems` executing the systems,
+/// `render` rendering the frame, and `wait` between the frames, e.g. in the event loop.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FrameSpan {
+    pub name: &'static str,
+    pub duration: Duration,
+}
+
+/// A frame which took longer than [`FrameStatsConfig::hitch_threshold`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct Hitch {
+    /// Index of the frame, as in [`super::time::Time::frames`].
+    pub frame: u64,
+    /// Wall time of the frame.
+    pub duration: Duration,
+    /// Spans of the frame, the longest first.
+    pub spans: Vec<FrameSpan>,
+}
+
+/// [`FrameStats`] records the wall time of the recent frames, to diagnose stutter.
+///
+/// The frame times are kept over a rolling window of [`FrameStatsConfig::window`] frames, and
+/// frames above [`FrameStatsConfig::hitch_threshold`] are flagged as [`Hitch`]es along with the
+/// spans of the engine loop which ran long. The frame times are also drawn as a graph by the
+/// renderer, see [`crate::graphics::FrameStatsOverlayConfig`].
+#[derive(Debug)]
+pub struct FrameStats {
+    config: FrameStatsConfig,
+    frame_times: VecDeque<Duration>,
+    hitches: VecDeque<Hitch>,
+    hitch_count: u64,
+}
+
+impl FrameStats {
+    /// Upper bounds of the buckets of [`FrameStats::histogram`], i.e. 120, 60, 30, 20 and 10
+    /// frames per second, and everything slower.
+    pub const HISTOGRAM_BUCKETS: [Duration; 6] = [
+        Duration::from_micros(8_333),
+        Duration::from_micros(16_667),
+        Duration::from_micros(33_333),
+        Duration::from_micros(50_000),
+        Duration::from_micros(100_000),
+        Duration::MAX,
+    ];
+
+    /// Creates a new [`FrameStats`] with the given configuration.
+    pub fn new(config: FrameStatsConfig) -> Self {
+        Self {
+            config,
+            frame_times: VecDeque::with_capacity(config.window),
+            hitches: VecDeque::with_capacity(config.max_hitches),
+            hitch_count: 0,
+        }
+    }
+
+    /// Returns the configuration.
+    pub fn config(&self) -> &FrameStatsConfig {
+        &self.config
+    }
+
+    /// Returns the times of the frames in the window, the oldest first.
+    pub fn frame_times(&self) -> impl Iterator<Item = Duration> + '_ {
+        self.frame_times.iter().copied()
+    }
+
+    /// Returns the time of the last frame.
+    pub fn last(&self) -> Option<Duration> {
+        self.frame_times.back().copied()
+    }
+
+    /// Returns the average frame time over the window.
+    pub fn average(&self) -> Duration {
+        if self.frame_times.is_empty() {
+            return Duration::ZERO;
+        }
+        self.frame_times.iter().sum::<Duration>() / self.frame_times.len() as u32
+    }
+
+    /// Returns the longest frame time over the window.
+    pub fn max(&self) -> Duration {
+        self.frame_times.iter().copied().max().unwrap_or_default()
+    }
+
+    /// Returns the frame time below which the given fraction of the frames in the window fall,
+    /// e.g. 0.99 for the 99th percentile.
+    pub fn percentile(&self, fraction: f32) -> Duration {
+        if self.frame_times.is_empty() {
+            return Duration::ZERO;
+        }
+        let mut sorted = self.frame_times.iter().copied().collect::<Vec<_>>();
+        sorted.sort_unstable();
+        let index = (fraction.clamp(0.0, 1.0) * (sorted.len() - 1) as f32).round() as usize;
+        sorted[index]
+    }
+
+    /// Returns the number of frames in the window falling into each bucket of
+    /// [`FrameStats::HISTOGRAM_BUCKETS`].
+    pub fn histogram(&self) -> [usize; Self::HISTOGRAM_BUCKETS.len()] {
+        let mut histogram = [0; Self::HISTOGRAM_BUCKETS.len()];
+        for frame_time in &self.frame_times {
+            let bucket = Self::HISTOGRAM_BUCKETS
+                .iter()
+                .position(|bound| frame_time <= bound)
+                .unwrap_or(Self::HISTOGRAM_BUCKETS.len() - 1);
+            histogram[bucket] += 1;
+        }
+        histogram
+    }
+
+    /// Returns the recent hitches, the oldest first.
+    pub fn hitches(&self) -> impl Iterator<Item = &Hitch> {
+        self.hitches.iter()
+    }
+
+    /// Returns the number of hitches since the engine started, including those no longer kept.
+    pub fn hitch_count(&self) -> u64 {
+        self.hitch_count
+    }
+
+    /// Clears the recorded frames and hitches, e.g. after a loading screen.
+    pub fn clear(&mut self) {
+        self.frame_times.clear();
+        self.hitches.clear();
+        self.hitch_count = 0;
+    }
+
+    /// Records the time of a frame, with the spans of the engine loop. Returns the hitch if the
+    /// frame is one.
+    pub(crate) fn record(
+        &mut self,
+        frame: u64,
+        duration: Duration,
+        mut spans: Vec<FrameSpan>,
+    ) -> Option<&Hitch> {
+        if self.config.window == 0 {
+            return None;
+        }
+        if self.frame_times.len() == self.config.window {
+            self.frame_times.pop_front();
+        }
+        self.frame_times.push_back(duration);
+
+        if duration <= self.config.hitch_threshold {
+            return None;
+        }
+
+        self.hitch_count += 1;
+        if self.config.max_hitches == 0 {
+            return None;
+        }
+        if self.hitches.len() == self.config.max_hitches {
+            self.hitches.pop_front();
+        }
+        spans.sort_by_key(|span| std::cmp::Reverse(span.duration));
+        self.hitches.push_back(Hitch {
+            frame,
+            duration,
+            spans,
+        });
+        self.hitches.back()
+    }
+}
+
+impl Default for FrameStats {
+    fn default() -> Self {
+        Self::new(FrameStatsConfig::default())
+    }
+}
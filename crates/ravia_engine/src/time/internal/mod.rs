@@ -1 +1,3 @@
+pub mod frame_stats;
+pub mod system_timings;
 pub mod time;
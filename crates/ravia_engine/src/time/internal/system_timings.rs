@@ -0,0 +1,313 @@
+use core::fmt;
+use std::{
+    collections::VecDeque,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+#[cfg(not(target_arch = "wasm32"))]
+use std::time::Instant;
+#[cfg(target_arch = "wasm32")]
+use web_time::Instant;
+
+use crate::ecs::{
+    self,
+    storage::ComponentTypeId,
+    systems::{
+        CommandBuffer, Executor, ParallelRunnable, ResourceTypeId, Runnable, Step, SystemId,
+        UnsafeResources,
+    },
+    world::{ArchetypeAccess, WorldId},
+};
+
+/// [`SystemTimingsConfig`] holds the configuration of the [`SystemTimings`] resource.
+#[derive(Debug, Clone, Copy)]
+pub struct SystemTimingsConfig {
+    /// Whether the systems are timed. Only effective in native mode.
+    pub enabled: bool,
+    /// Number of recent frames the timings are computed over.
+    pub window: usize,
+    /// Execution time above which a system is logged as slow, if any.
+    pub budget: Option<Duration>,
+}
+
+impl Default for SystemTimingsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            window: 120,
+            budget: None,
+        }
+    }
+}
+
+/// Execution times of a system over the recent frames.
+#[derive(Debug, Clone)]
+pub struct SystemTiming {
+    name: String,
+    samples: VecDeque<Duration>,
+    runs: u64,
+    over_budget: u64,
+    /// Whether the last run exceeded the budget, so that a slow system is logged once until it
+    /// runs within the budget again.
+    slow: bool,
+}
+
+impl SystemTiming {
+    fn new(name: String, window: usize) -> Self {
+        Self {
+            name,
+            samples: VecDeque::with_capacity(window),
+            runs: 0,
+            over_budget: 0,
+            slow: false,
+        }
+    }
+
+    /// Returns the name of the system.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the execution time of the last run.
+    pub fn last(&self) -> Duration {
+        self.samples.back().copied().unwrap_or_default()
+    }
+
+    /// Returns the average execution time over the window.
+    pub fn average(&self) -> Duration {
+        if self.samples.is_empty() {
+            return Duration::ZERO;
+        }
+        self.samples.iter().sum::<Duration>() / self.samples.len() as u32
+    }
+
+    /// Returns the longest execution time over the window.
+    pub fn max(&self) -> Duration {
+        self.samples.iter().copied().max().unwrap_or_default()
+    }
+
+    /// Returns the number of runs since the engine started.
+    pub fn runs(&self) -> u64 {
+        self.runs
+    }
+
+    /// Returns the number of runs exceeding [`SystemTimingsConfig::budget`].
+    pub fn over_budget(&self) -> u64 {
+        self.over_budget
+    }
+}
+
+/// [`SystemTimings`] records the execution time of every scheduled system, to find the systems
+/// making the frames slow without a profiler.
+///
+/// The systems are timed on native platforms, where they run on the ECS thread pool; the
+/// timings stay empty on the web. The resource prints as a table of the systems, the slowest
+/// first:
+///
+/// ```ignore
+/// log::info!("{}", *resources.get::<time::SystemTimings>().unwrap());
+/// ```
+#[derive(Debug)]
+pub struct SystemTimings {
+    config: SystemTimingsConfig,
+    systems: Vec<SystemTiming>,
+    /// Execution time of the last run of every system in nanoseconds, written by the systems.
+    clocks: Vec<Arc<AtomicU64>>,
+}
+
+impl SystemTimings {
+    /// Creates a new [`SystemTimings`] with the given configuration.
+    pub fn new(config: SystemTimingsConfig) -> Self {
+        Self {
+            config,
+            systems: vec![],
+            clocks: vec![],
+        }
+    }
+
+    /// Returns the configuration.
+    pub fn config(&self) -> &SystemTimingsConfig {
+        &self.config
+    }
+
+    /// Returns the timings of the systems, in the order of the schedule.
+    pub fn iter(&self) -> impl Iterator<Item = &SystemTiming> {
+        self.systems.iter()
+    }
+
+    /// Returns the timing of the system with the given name.
+    pub fn get(&self, name: &str) -> Option<&SystemTiming> {
+        self.systems.iter().find(|system| system.name == name)
+    }
+
+    /// Returns the timings of the systems, the slowest on average first.
+    pub fn slowest(&self) -> Vec<&SystemTiming> {
+        let mut systems = self.systems.iter().collect::<Vec<_>>();
+        systems.sort_by_key(|system| std::cmp::Reverse(system.average()));
+        systems
+    }
+
+    /// Returns the total execution time of the systems in the last frame. The systems running
+    /// in parallel are counted separately, so this may exceed the wall time of the frame.
+    pub fn total(&self) -> Duration {
+        self.systems.iter().map(SystemTiming::last).sum()
+    }
+
+    /// Clears the recorded timings, e.g. after a loading screen.
+    pub fn clear(&mut self) {
+        for system in &mut self.systems {
+            system.samples.clear();
+            system.runs = 0;
+            system.over_budget = 0;
+            system.slow = false;
+        }
+    }
+
+    /// Wraps the systems of the schedule to time them.
+    pub(crate) fn instrument(&mut self, schedule: ecs::Schedule) -> ecs::Schedule {
+        if !self.config.enabled || cfg!(target_arch = "wasm32") {
+            return schedule;
+        }
+
+        let steps = schedule
+            .into_vec()
+            .into_iter()
+            .map(|step| match step {
+                Step::Systems(executor) => Step::Systems(Executor::new(
+                    executor
+                        .into_vec()
+                        .into_iter()
+                        .map(|system| Box::new(self.timed(system)) as Box<dyn ParallelRunnable>)
+                        .collect(),
+                )),
+                Step::ThreadLocalSystem(system) => {
+                    Step::ThreadLocalSystem(Box::new(self.timed(system)))
+                }
+                step => step,
+            })
+            .collect::<Vec<_>>();
+        ecs::Schedule::from(steps)
+    }
+
+    fn timed<R: Runnable + ?Sized>(&mut self, system: Box<R>) -> Timed<R> {
+        let name = match system.name() {
+            Some(name) => name.to_string(),
+            None => format!("system #{}", self.systems.len()),
+        };
+        let clock = Arc::new(AtomicU64::new(0));
+        self.systems
+            .push(SystemTiming::new(name, self.config.window));
+        self.clocks.push(clock.clone());
+        Timed { system, clock }
+    }
+
+    /// Records the execution times of the systems after the schedule has been executed. Returns
+    /// the systems which started to exceed the budget.
+    pub(crate) fn record(&mut self) -> Vec<&SystemTiming> {
+        if self.config.window == 0 {
+            return vec![];
+        }
+
+        let budget = self.config.budget;
+        let mut slow = vec![];
+        for (system, clock) in self.systems.iter_mut().zip(&self.clocks) {
+            let duration = Duration::from_nanos(clock.load(Ordering::Relaxed));
+            if system.samples.len() == self.config.window {
+                system.samples.pop_front();
+            }
+            system.samples.push_back(duration);
+            system.runs += 1;
+
+            let over_budget = budget.is_some_and(|budget| duration > budget);
+            if over_budget {
+                system.over_budget += 1;
+            }
+            let became_slow = over_budget && !system.slow;
+            system.slow = over_budget;
+            if became_slow {
+                slow.push(&*system);
+            }
+        }
+        slow
+    }
+}
+
+impl Default for SystemTimings {
+    fn default() -> Self {
+        Self::new(SystemTimingsConfig::default())
+    }
+}
+
+impl fmt::Display for SystemTimings {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let width = self
+            .systems
+            .iter()
+            .map(|system| system.name.len())
+            .max()
+            .unwrap_or(0)
+            .max("system".len());
+
+        writeln!(
+            f,
+            "{:<width$}  {:>10}  {:>10}  {:>10}  {:>6}",
+            "system", "last", "average", "max", "slow"
+        )?;
+        for system in self.slowest() {
+            writeln!(
+                f,
+                "{:<width$}  {:>10}  {:>10}  {:>10}  {:>6}",
+                system.name,
+                format!("{:.2?}", system.last()),
+                format!("{:.2?}", system.average()),
+                format!("{:.2?}", system.max()),
+                system.over_budget,
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// A system timed into [`SystemTimings`].
+struct Timed<R: ?Sized> {
+    system: Box<R>,
+    clock: Arc<AtomicU64>,
+}
+
+impl<R: Runnable + ?Sized> Runnable for Timed<R> {
+    fn name(&self) -> Option<&SystemId> {
+        self.system.name()
+    }
+
+    fn reads(&self) -> (&[ResourceTypeId], &[ComponentTypeId]) {
+        self.system.reads()
+    }
+
+    fn writes(&self) -> (&[ResourceTypeId], &[ComponentTypeId]) {
+        self.system.writes()
+    }
+
+    fn prepare(&mut self, world: &ecs::World) {
+        self.system.prepare(world);
+    }
+
+    fn accesses_archetypes(&self) -> &ArchetypeAccess {
+        self.system.accesses_archetypes()
+    }
+
+    unsafe fn run_unsafe(&mut self, world: &ecs::World, resources: &UnsafeResources) {
+        let start = Instant::now();
+        // the wrapper declares the same accesses as the system, so the schedule keeps it safe.
+        self.system.run_unsafe(world, resources);
+        self.clock
+            .store(start.elapsed().as_nanos() as u64, Ordering::Relaxed);
+    }
+
+    fn command_buffer_mut(&mut self, world: WorldId) -> Option<&mut CommandBuffer> {
+        self.system.command_buffer_mut(world)
+    }
+}
@@ -10,11 +10,13 @@ use web_time::Instant;
 pub struct Timer {
     first_frame: bool,
     frames: u64,
+    paused_at: Option<Instant>,
 
     start_frame: Instant,
     current_frame: Instant,
     time: Duration,
     delta: Duration,
+    fixed_timestep: Option<Duration>,
 }
 
 impl Timer {
@@ -23,13 +25,29 @@ impl Timer {
         Self {
             first_frame: true,
             frames: 0,
+            paused_at: None,
             start_frame: Instant::now(),
             current_frame: Instant::now(),
             time: Duration::ZERO,
             delta: Duration::ZERO,
+            fixed_timestep: None,
         }
     }
 
+    /// Creates a new [`Timer`] which advances by a fixed timestep every frame, regardless of the
+    /// elapsed wall time.
+    pub fn with_fixed_timestep(timestep: Duration) -> Self {
+        Self {
+            fixed_timestep: Some(timestep),
+            ..Self::new()
+        }
+    }
+
+    /// Returns the fixed timestep, if the timer has one.
+    pub fn fixed_timestep(&self) -> Option<Duration> {
+        self.fixed_timestep
+    }
+
     /// Returns the [`Time`] of the current frame.
     pub fn time(&self) -> Time {
         Time {
@@ -43,20 +61,56 @@ impl Timer {
     pub fn start(&mut self) {
         self.first_frame = false;
         self.frames = 0;
+        self.paused_at = None;
         self.start_frame = Instant::now();
         self.current_frame = Instant::now();
         self.time = Duration::ZERO;
         self.delta = Duration::ZERO;
     }
 
+    /// Pauses the time measurement.
+    ///
+    /// While paused, frame ticks do not advance the time and the elapsed wall time is excluded
+    /// from [`Time`].
+    pub fn pause(&mut self) {
+        if self.paused_at.is_none() {
+            self.paused_at = Some(Instant::now());
+        }
+    }
+
+    /// Resumes the time measurement paused by [`Timer::pause`].
+    pub fn resume(&mut self) {
+        if let Some(paused_at) = self.paused_at.take() {
+            let paused = paused_at.elapsed();
+            self.start_frame += paused;
+            self.current_frame += paused;
+        }
+    }
+
+    /// Returns true if the timer is paused.
+    pub fn is_paused(&self) -> bool {
+        self.paused_at.is_some()
+    }
+
     /// Frame tick.
     pub fn frame(&mut self) {
+        if self.is_paused() {
+            self.delta = Duration::ZERO;
+            return;
+        }
+
         if self.first_frame {
             self.start();
             return;
         }
 
         self.frames += 1;
+        if let Some(timestep) = self.fixed_timestep {
+            self.time += timestep;
+            self.delta = timestep;
+            return;
+        }
+
         self.time = self.start_frame.elapsed();
         self.delta = self.current_frame.elapsed();
         self.current_frame = Instant::now();
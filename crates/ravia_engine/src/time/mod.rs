@@ -1,4 +1,8 @@
 // implementation module
 mod internal;
 
-pub use internal::time::{Time, Timer};
+pub use internal::{
+    frame_stats::{FrameSpan, FrameStats, FrameStatsConfig, Hitch},
+    system_timings::{SystemTiming, SystemTimings, SystemTimingsConfig},
+    time::{Time, Timer},
+};
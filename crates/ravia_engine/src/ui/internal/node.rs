@@ -0,0 +1,131 @@
+use crate::{ecs, math};
+
+/// Margins between the edges of a [`UiNode`] and its anchors, in logical pixels.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct UiMargin {
+    pub left: f32,
+    pub top: f32,
+    pub right: f32,
+    pub bottom: f32,
+}
+
+impl UiMargin {
+    /// Creates a [`UiMargin`] with the same margin on every edge.
+    pub fn all(margin: f32) -> Self {
+        Self {
+            left: margin,
+            top: margin,
+            right: margin,
+            bottom: margin,
+        }
+    }
+}
+
+/// A [`UiNode`] component places an entity on the screen relative to its parent node, or to the
+/// screen if it has none.
+///
+/// Anchors are fractions of the parent rect, with (0, 0) at the top-left corner and (1, 1) at the
+/// bottom-right corner. The node spans the anchors, shrunk by its margins, and its size is then
+/// clamped to the size constraints around its center. The resolved rect is written to the
+/// [`UiRect`] component of the entity whenever the screen is resized or a node changes.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct UiNode {
+    pub parent: Option<ecs::Entity>,
+    pub anchor_min: math::Vec2,
+    pub anchor_max: math::Vec2,
+    pub margin: UiMargin,
+    pub min_size: math::Vec2,
+    pub max_size: math::Vec2,
+}
+
+assert_impl_all!(UiNode: ecs::storage::Component);
+
+impl Default for UiNode {
+    fn default() -> Self {
+        Self {
+            parent: None,
+            anchor_min: math::Vec2::ZERO,
+            anchor_max: math::Vec2::ONE,
+            margin: UiMargin::default(),
+            min_size: math::Vec2::ZERO,
+            max_size: math::Vec2::INFINITY,
+        }
+    }
+}
+
+impl UiNode {
+    /// Creates a [`UiNode`] filling its parent.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a [`UiNode`] of a fixed size, whose center is anchored to the given point.
+    pub fn fixed(anchor: math::Vec2, size: math::Vec2) -> Self {
+        Self {
+            anchor_min: anchor,
+            anchor_max: anchor,
+            min_size: size,
+            max_size: size,
+            ..Default::default()
+        }
+    }
+
+    /// Sets the parent node.
+    pub fn with_parent(mut self, parent: ecs::Entity) -> Self {
+        self.parent = Some(parent);
+        self
+    }
+
+    /// Sets the margins.
+    pub fn with_margin(mut self, margin: UiMargin) -> Self {
+        self.margin = margin;
+        self
+    }
+
+    /// Resolves the rect of the node inside the rect of its parent.
+    pub(super) fn resolve(&self, parent: UiRect) -> UiRect {
+        let parent_size = parent.size();
+        let min = parent.min
+            + self.anchor_min * parent_size
+            + math::vec2(self.margin.left, self.margin.top);
+        let max = parent.min + self.anchor_max * parent_size
+            - math::vec2(self.margin.right, self.margin.bottom);
+
+        let center = (min + max) / 2.0;
+        let size = (max - min)
+            .max(math::Vec2::ZERO)
+            .clamp(self.min_size, self.max_size.max(self.min_size));
+
+        UiRect {
+            min: center - size / 2.0,
+            max: center + size / 2.0,
+        }
+    }
+}
+
+/// A [`UiRect`] component holds the rect of a [`UiNode`] resolved by the layout pass, in logical
+/// pixels with the origin at the top-left corner of the screen.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct UiRect {
+    pub min: math::Vec2,
+    pub max: math::Vec2,
+}
+
+assert_impl_all!(UiRect: ecs::storage::Component);
+
+impl UiRect {
+    /// Returns the size of the rect.
+    pub fn size(&self) -> math::Vec2 {
+        self.max - self.min
+    }
+
+    /// Returns the center of the rect.
+    pub fn center(&self) -> math::Vec2 {
+        (self.min + self.max) / 2.0
+    }
+
+    /// Returns true if the point is inside the rect.
+    pub fn contains(&self, point: math::Vec2) -> bool {
+        point.cmpge(self.min).all() && point.cmplt(self.max).all()
+    }
+}
@@ -0,0 +1,89 @@
+use std::collections::HashMap;
+
+use crate::{
+    ecs::{self, systems::CommandBuffer, world::SubWorld, Entity, EntityStore, IntoQuery},
+    math,
+    window::ScreenSize,
+};
+
+use super::node::{UiNode, UiRect};
+
+/// Attaches a system of the UI engine.
+pub fn system(builder: &mut ecs::systems::Builder) {
+    builder.add_system(layout_system(None));
+}
+
+#[ecs::system]
+#[read_component(UiNode)]
+#[write_component(UiRect)]
+fn layout(
+    world: &mut SubWorld,
+    cmd: &mut CommandBuffer,
+    #[resource] screen_size: &ScreenSize,
+    #[state] last_screen_size: &mut Option<math::Vec2>,
+) {
+    let screen_size = screen_size.logical_size();
+    let changed = <&UiNode>::query()
+        .filter(ecs::maybe_changed::<UiNode>())
+        .iter(world)
+        .next()
+        .is_some();
+    if !changed && *last_screen_size == Some(screen_size) {
+        return;
+    }
+    *last_screen_size = Some(screen_size);
+
+    let nodes = <(Entity, &UiNode)>::query()
+        .iter(world)
+        .map(|(entity, node)| (*entity, *node))
+        .collect::<HashMap<_, _>>();
+
+    let screen = UiRect {
+        min: math::Vec2::ZERO,
+        max: screen_size,
+    };
+    let mut rects = HashMap::new();
+    for entity in nodes.keys() {
+        resolve(*entity, &nodes, screen, &mut rects, 0);
+    }
+
+    for (entity, rect) in rects {
+        if let Ok(mut entry) = world.entry_mut(entity) {
+            if let Ok(current) = entry.get_component_mut::<UiRect>() {
+                *current = rect;
+                continue;
+            }
+        }
+        cmd.add_component(entity, rect);
+    }
+}
+
+/// Resolves the rect of a node, resolving its ancestors first.
+///
+/// Nodes whose parent is not a node, or which are nested too deep (e.g. in a cycle), are laid out
+/// relative to the screen.
+fn resolve(
+    entity: Entity,
+    nodes: &HashMap<Entity, UiNode>,
+    screen: UiRect,
+    rects: &mut HashMap<Entity, UiRect>,
+    depth: usize,
+) -> UiRect {
+    const MAX_DEPTH: usize = 64;
+
+    if let Some(rect) = rects.get(&entity) {
+        return *rect;
+    }
+
+    let node = nodes[&entity];
+    let parent = match node.parent {
+        Some(parent) if nodes.contains_key(&parent) && depth < MAX_DEPTH => {
+            resolve(parent, nodes, screen, rects, depth + 1)
+        }
+        _ => screen,
+    };
+
+    let rect = node.resolve(parent);
+    rects.insert(entity, rect);
+    rect
+}
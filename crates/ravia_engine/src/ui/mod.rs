@@ -0,0 +1,7 @@
+// implementation module
+mod internal;
+
+pub use internal::{
+    node::{UiMargin, UiNode, UiRect},
+    system::system,
+};
@@ -0,0 +1,2 @@
+pub mod screen_size;
+pub mod window_commands;
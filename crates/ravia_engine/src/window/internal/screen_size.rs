@@ -0,0 +1,22 @@
+use crate::math;
+
+/// [`ScreenSize`] is a resource describing the size of the window surface.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ScreenSize {
+    /// Size of the surface in physical pixels.
+    pub size: math::UVec2,
+    /// Ratio between the physical and logical pixels, e.g. the device pixel ratio in web platforms.
+    pub scale_factor: f64,
+}
+
+impl ScreenSize {
+    /// Returns the size of the surface in logical pixels.
+    pub fn logical_size(&self) -> math::Vec2 {
+        self.size.as_vec2() / self.scale_factor as f32
+    }
+
+    /// Returns the aspect ratio of the surface.
+    pub fn aspect_ratio(&self) -> f32 {
+        self.size.x as f32 / self.size.y.max(1) as f32
+    }
+}
@@ -0,0 +1,70 @@
+use crate::ecs;
+
+/// A command to be applied to the window.
+#[derive(Debug, Clone, PartialEq)]
+pub enum WindowCommand {
+    SetRelativeMouseMode(bool),
+    SetTitle(String),
+}
+
+/// [`WindowCommands`] is a resource to control the window from the systems.
+///
+/// The window is owned by the engine, so the commands are queued and applied at the end of the
+/// frame.
+#[derive(Debug, Default)]
+pub struct WindowCommands {
+    commands: Vec<WindowCommand>,
+    relative_mouse_mode: bool,
+    title: String,
+}
+
+assert_impl_all!(WindowCommands: ecs::systems::Resource);
+
+impl WindowCommands {
+    /// Creates a new [`WindowCommands`].
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Enables or disables the relative mouse mode.
+    ///
+    /// In relative mode, the cursor is hidden and grabbed by the window, so the mouse movement is
+    /// not limited by the window edges. Use [`crate::event::Event::MouseMotion`] to read the raw
+    /// mouse movement, e.g. for first-person cameras.
+    pub fn set_relative_mouse_mode(&mut self, enabled: bool) {
+        self.relative_mouse_mode = enabled;
+        self.commands
+            .push(WindowCommand::SetRelativeMouseMode(enabled));
+    }
+
+    /// Returns true if the relative mouse mode is requested.
+    pub fn relative_mouse_mode(&self) -> bool {
+        self.relative_mouse_mode
+    }
+
+    /// Sets the title of the window, e.g. to append the name of the current scene.
+    ///
+    /// If [`crate::engine::EngineConfig::title_stats`] is enabled, the frame rate is appended to
+    /// the title.
+    pub fn set_title(&mut self, title: impl Into<String>) {
+        self.title = title.into();
+        self.commands
+            .push(WindowCommand::SetTitle(self.title.clone()));
+    }
+
+    /// Returns the title of the window, without the appended frame rate.
+    pub fn title(&self) -> &str {
+        &self.title
+    }
+
+    /// Sets the initial title, which the window has been created with.
+    pub(crate) fn with_title(mut self, title: &str) -> Self {
+        self.title = title.to_string();
+        self
+    }
+
+    /// Takes the queued commands.
+    pub(crate) fn drain(&mut self) -> impl Iterator<Item = WindowCommand> + '_ {
+        self.commands.drain(..)
+    }
+}
@@ -0,0 +1,6 @@
+// implementation module
+mod internal;
+
+pub use internal::{screen_size::ScreenSize, window_commands::WindowCommands};
+
+pub(crate) use internal::window_commands::WindowCommand;
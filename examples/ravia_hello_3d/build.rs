@@ -3,7 +3,11 @@ use ravia_build::*;
 fn main() -> Result<()> {
     println!("cargo::rerun-if-changed=build.rs");
 
-    build()?;
+    build_with_config(BuildConfig {
+        convert_meshes: true,
+        optimize_meshes: Some(MeshOptimization::default()),
+        ..Default::default()
+    })?;
 
     Ok(())
 }
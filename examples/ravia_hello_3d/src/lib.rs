@@ -24,6 +24,7 @@ pub fn run() {
         window_title: "Hello 3D",
         init_world,
         init_system,
+        preload: &["engine/model/cube.rmesh"],
         ..Default::default()
     });
 }
@@ -32,10 +33,9 @@ pub fn run() {
 struct ExampleMovement {}
 
 fn init_world(world: &mut World, ctx: &EngineContext) {
-    let camera = Camera::perspective_with_defaults(ctx);
-    world.push((camera, Transform::identity(ctx)));
+    world.push(DemoCamera::bundle(ctx, vec3(0.0, 0.0, -5.0), 5.0));
 
-    let cube = Resource::new("engine/model/cube.obj");
+    let cube = Resource::new("engine/model/cube.rmesh");
 
     let mut material = Material::new(
         ctx,
@@ -49,7 +49,7 @@ fn init_world(world: &mut World, ctx: &EngineContext) {
             ]),
     );
     let texture = Texture::default_2d(ctx);
-    material.texture = Some(texture);
+    material.set_texture(ctx, texture);
 
     world.push((cube, material, Transform::identity(ctx), ExampleMovement {}));
 }
@@ -58,10 +58,11 @@ fn init_world(world: &mut World, ctx: &EngineContext) {
         ctx,
         &ShaderConfig::new(include_str!("triangle_tex.wgsl"))
             .with_vertex_type::<Vertex2DTexture>()
-            .with_uniforms(&[UniformType::Texture2D]),
+            .with_uniforms(&[UniformType::Texture2D])
+            .with_depth_test(false),
     );
     let texture = Texture::default_2d(ctx);
-    material.texture = Some(texture);
+    material.set_texture(ctx, texture);
 
     world.push((mesh, material, Transform::identity(ctx)));
 }
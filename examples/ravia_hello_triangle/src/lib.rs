@@ -51,7 +51,9 @@ fn init_world(world: &mut World, ctx: &EngineContext) {
 
     let material = Material::new(
         ctx,
-        &ShaderConfig::new(include_str!("triangle.wgsl")).with_vertex_type::<Vertex2DColor>(),
+        &ShaderConfig::new(include_str!("triangle.wgsl"))
+            .with_vertex_type::<Vertex2DColor>()
+            .with_depth_test(false),
     );
 
     world.push((mesh, material, Transform::identity(ctx)));